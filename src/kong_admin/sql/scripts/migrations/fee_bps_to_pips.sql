@@ -0,0 +1,62 @@
+-- migrates a database created with the *_fee_bps columns to *_fee_pips (1 bps = 100 pips)
+BEGIN;
+
+-- the view selects the old columns, so it is dropped and created again with the new names
+DROP VIEW IF EXISTS public.pools_with_token_symbols;
+
+ALTER TABLE pools RENAME COLUMN lp_fee_bps TO lp_fee_pips;
+ALTER TABLE pools RENAME COLUMN kong_fee_bps TO kong_fee_pips;
+ALTER TABLE pools ALTER COLUMN lp_fee_pips TYPE INT USING lp_fee_pips * 100;
+ALTER TABLE pools ALTER COLUMN kong_fee_pips TYPE INT USING kong_fee_pips * 100;
+
+UPDATE pools
+SET raw_json = (raw_json - 'lp_fee_bps' - 'kong_fee_bps')
+    || jsonb_build_object(
+        'lp_fee_pips', (raw_json->>'lp_fee_bps')::INT * 100,
+        'kong_fee_pips', (raw_json->>'kong_fee_bps')::INT * 100
+    )
+WHERE raw_json ? 'lp_fee_bps';
+
+CREATE OR REPLACE VIEW public.pools_with_token_symbols
+AS SELECT p.pool_id,
+    t0.symbol AS symbol_0,
+    t1.symbol AS symbol_1,
+    p.balance_0,
+    p.lp_fee_0,
+    p.kong_fee_0,
+    p.balance_1,
+    p.lp_fee_1,
+    p.kong_fee_1,
+    p.lp_fee_pips,
+    p.kong_fee_pips,
+    p.on_kong
+   FROM pools p
+     JOIN tokens t0 ON p.token_id_0 = t0.token_id
+     JOIN tokens t1 ON p.token_id_1 = t1.token_id
+  ORDER BY p.pool_id;
+
+-- add pool args and replies are stored as JSON in requests. null fees in the args stay null
+UPDATE requests
+SET request = jsonb_set(
+        request,
+        '{AddPoolArgs}',
+        ((request->'AddPoolArgs') - 'lp_fee_bps' - 'kong_fee_bps')
+            || jsonb_build_object(
+                'lp_fee_pips', (request->'AddPoolArgs'->>'lp_fee_bps')::INT * 100,
+                'kong_fee_pips', (request->'AddPoolArgs'->>'kong_fee_bps')::INT * 100
+            )
+    )
+WHERE request_type = 'add_pool'
+    AND request->'AddPoolArgs' ? 'lp_fee_bps';
+
+UPDATE requests
+SET reply = jsonb_set(
+        reply,
+        '{AddPoolReply}',
+        ((reply->'AddPoolReply') - 'lp_fee_bps')
+            || jsonb_build_object('lp_fee_pips', (reply->'AddPoolReply'->>'lp_fee_bps')::INT * 100)
+    )
+WHERE request_type = 'add_pool'
+    AND reply->'AddPoolReply' ? 'lp_fee_bps';
+
+COMMIT;
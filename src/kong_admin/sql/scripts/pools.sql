@@ -8,8 +8,8 @@ CREATE TABLE pools (
     balance_1 DOUBLE PRECISION NOT NULL,
     lp_fee_1 DOUBLE PRECISION NOT NULL,
     kong_fee_1 DOUBLE PRECISION NOT NULL,
-    lp_fee_bps SMALLINT NOT NULL,
-    kong_fee_bps SMALLINT NOT NULL,
+    lp_fee_pips INT NOT NULL,
+    kong_fee_pips INT NOT NULL,
     lp_token_id INT REFERENCES tokens(token_id) NOT NULL,
     on_kong BOOLEAN NOT NULL,
     tvl DOUBLE PRECISION NOT NULL,
@@ -8,8 +8,8 @@ AS SELECT p.pool_id,
     p.balance_1,
     p.lp_fee_1,
     p.kong_fee_1,
-    p.lp_fee_bps,
-    p.kong_fee_bps,
+    p.lp_fee_pips,
+    p.kong_fee_pips,
     p.on_kong
    FROM pools p
      JOIN tokens t0 ON p.token_id_0 = t0.token_id
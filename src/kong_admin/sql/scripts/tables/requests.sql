@@ -21,8 +21,8 @@ CREATE TABLE add_pool_args (
     amount_1 DOUBLE PRECISION NOT NULL,
     block_index_1 BIGINT,
     tx_hash_1 TEXT,
-    lp_fee_bps SMALLINT,
-    kong_fee_bps SMALLINT,
+    lp_fee_pips INT,
+    kong_fee_pips INT,
     on_kong BOOLEAN
 );
 
@@ -85,7 +85,7 @@ CREATE TABLE add_pool_reply {
     amount_1 DOUBLE PRECISION NOT NULL,
     balance_1 DOUBLE PRECISION NOT NULL,
     add_lp_token_amount DOUBLE PRECISION NOT NULL,
-    lp_fee_bps SMALLINT NOT NULL,
+    lp_fee_pips INT NOT NULL,
     lp_token_symbol TEXT NOT NULL,
     lp_token_supply DOUBLE PRECISION NOT NULL,
     transfer_ids BIGINT[],
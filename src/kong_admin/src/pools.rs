@@ -22,8 +22,8 @@ pub fn serialize_pool(pool: &StablePool) -> serde_json::Value {
             "balance_1": pool.balance_1.to_string(),
             "lp_fee_1": pool.lp_fee_1.to_string(),
             "kong_fee_1": pool.kong_fee_1.to_string(),
-            "lp_fee_bps": pool.lp_fee_bps,
-            "kong_fee_bps": pool.kong_fee_bps,
+            "lp_fee_pips": pool.lp_fee_pips,
+            "kong_fee_pips": pool.kong_fee_pips,
             "lp_token_id": pool.lp_token_id,
             "on_kong": pool.on_kong,
             "tvl": pool.tvl.to_string(),
@@ -43,8 +43,8 @@ pub async fn update_pools_on_database(
     let reader = BufReader::new(file);
     let pools_map: BTreeMap<StablePoolId, StablePool> = serde_json::from_reader(reader)?;
 
-    for v in pools_map.values() {
-        insert_pool_on_database(v, db_client, tokens_map).await?;
+    for v in pools_map.into_values().map(StablePool::migrate_fee_bps) {
+        insert_pool_on_database(&v, db_client, tokens_map).await?;
     }
 
     load_pools_from_database(db_client).await
@@ -79,8 +79,8 @@ pub async fn insert_pool_on_database(
         *decimals_1,
     );
     // pool
-    let lp_fee_bps = v.lp_fee_bps as i16;
-    let kong_fee_bps = v.kong_fee_bps as i16;
+    let lp_fee_pips = v.lp_fee_pips as i32;
+    let kong_fee_pips = v.kong_fee_pips as i32;
     let lp_token_id = v.lp_token_id as i32;
     let on_kong = v.on_kong;
     let tvl = round_f64(v.tvl.0.to_f64().unwrap() / 1_000_000.0, 6); // in USD
@@ -93,7 +93,7 @@ pub async fn insert_pool_on_database(
     db_client
         .execute(
             "INSERT INTO pools 
-                (pool_id, token_id_0, balance_0, lp_fee_0, kong_fee_0, token_id_1, balance_1, lp_fee_1, kong_fee_1, lp_fee_bps, kong_fee_bps, lp_token_id, on_kong, tvl, rolling_24h_volume, rolling_24h_lp_fee, rolling_24h_num_swaps, rolling_24h_apy, raw_json)
+                (pool_id, token_id_0, balance_0, lp_fee_0, kong_fee_0, token_id_1, balance_1, lp_fee_1, kong_fee_1, lp_fee_pips, kong_fee_pips, lp_token_id, on_kong, tvl, rolling_24h_volume, rolling_24h_lp_fee, rolling_24h_num_swaps, rolling_24h_apy, raw_json)
                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
                 ON CONFLICT (pool_id) DO UPDATE SET
                     token_id_0 = $2,
@@ -104,8 +104,8 @@ pub async fn insert_pool_on_database(
                     balance_1 = $7,
                     lp_fee_1 = $8,
                     kong_fee_1 = $9,
-                    lp_fee_bps = $10,
-                    kong_fee_bps = $11,
+                    lp_fee_pips = $10,
+                    kong_fee_pips = $11,
                     lp_token_id = $12,
                     on_kong = $13,
                     tvl = $14,
@@ -114,7 +114,7 @@ pub async fn insert_pool_on_database(
                     rolling_24h_num_swaps = $17,
                     rolling_24h_apy = $18,
                     raw_json = $19",
-            &[&pool_id, &token_id_0, &balance_0, &lp_fee_0, &kong_fee_0, &token_id_1, &balance_1, &lp_fee_1, &kong_fee_1, &lp_fee_bps, &kong_fee_bps, &lp_token_id, &on_kong, &tvl, &rolling_24h_volume, &rolling_24h_lp_fee, &rolling_24h_num_swaps, &rolling_24h_apy, &raw_json],
+            &[&pool_id, &token_id_0, &balance_0, &lp_fee_0, &kong_fee_0, &token_id_1, &balance_1, &lp_fee_1, &kong_fee_1, &lp_fee_pips, &kong_fee_pips, &lp_token_id, &on_kong, &tvl, &rolling_24h_volume, &rolling_24h_lp_fee, &rolling_24h_num_swaps, &rolling_24h_apy, &raw_json],
         )
         .await?;
 
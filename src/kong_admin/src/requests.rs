@@ -43,8 +43,8 @@ pub fn serialize_request(request: &Request) -> serde_json::Value {
                 "token_1": request.token_1,
                 "amount_1": request.amount_1.to_string(),
                 "tx_id_1": serialize_option_tx_id(request.tx_id_1.as_ref()),
-                "lp_fee_bps": request.lp_fee_bps,
-                "kong_fee_bps": request.kong_fee_bps,
+                "lp_fee_pips": request.lp_fee_pips,
+                "kong_fee_pips": request.kong_fee_pips,
                 "on_kong": request.on_kong,
             }
         }),
@@ -106,7 +106,7 @@ pub fn serialize_reply(reply: &Reply) -> serde_json::Value {
                 "amount_1": reply.amount_1.to_string(),
                 "balance_1": reply.balance_1.to_string(),
                 "add_lp_token_amount": reply.add_lp_token_amount.to_string(),
-                "lp_fee_bps": reply.lp_fee_bps,
+                "lp_fee_pips": reply.clone().migrate_fee_bps().lp_fee_pips,
                 "lp_token_symbol": reply.lp_token_symbol,
                 "transfer_ids": reply.transfer_ids.iter().map(|id| json!({
                     "transfer_id": id.transfer_id,
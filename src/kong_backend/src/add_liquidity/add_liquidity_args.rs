@@ -13,4 +13,7 @@ pub struct AddLiquidityArgs {
     pub token_1: String,
     pub amount_1: Nat,
     pub tx_id_1: Option<TxId>,
+    pub fee_tier: Option<u32>, // if specified, the pool of the token pair with this LP fee in pips. otherwise the first pool of the token pair
+    pub deadline_ns: Option<u64>, // if specified, add liquidity fails if the pool is not updated by this time
+    pub min_lp_token_amount: Option<Nat>, // if specified, add liquidity fails if the LP tokens received are less than this
 }
@@ -232,7 +232,7 @@ async fn process_add_liquidity(
         let tok_id_0 = tok_0.token_id();
         let tok_1 = token_1.unwrap();
         let tok_id_1 = tok_1.token_id();
-        match pool_map::get_by_token_ids(tok_id_0, tok_id_1) {
+        match pool_map::get_by_token_ids(tok_id_0, tok_id_1, args.fee_tier) {
             Some(pool) => {
                 if transfer_0.is_err() && tx_id_0.is_none() {
                     transfer_0 = transfer_from_token(
@@ -327,29 +327,37 @@ async fn process_add_liquidity(
     }
 
     // re-calculate with latest pool state and make sure amounts are valid
-    let (pool, amount_0, amount_1, add_lp_token_amount) =
-        match update_liquidity_pool(request_id, user_id, &pool, add_amount_0, add_amount_1, ts) {
-            Ok((pool, amount_0, amount_1, add_lp_token_amount)) => (pool, amount_0, amount_1, add_lp_token_amount),
-            Err(e) => {
-                // LP amounts are incorrect. return token_0 and token_1 back to user
-                return_tokens(
-                    request_id,
-                    user_id,
-                    &caller_id,
-                    Some(pool.pool_id),
-                    token_0,
-                    &transfer_0,
-                    add_amount_0,
-                    token_1,
-                    &transfer_1,
-                    add_amount_1,
-                    &mut transfer_ids,
-                    ts,
-                )
-                .await;
-                return Err(format!("Req #{} failed. {}", request_id, e));
-            }
-        };
+    let (pool, amount_0, amount_1, add_lp_token_amount) = match update_liquidity_pool(
+        request_id,
+        user_id,
+        &pool,
+        add_amount_0,
+        add_amount_1,
+        args.min_lp_token_amount.as_ref(),
+        args.deadline_ns,
+        ts,
+    ) {
+        Ok((pool, amount_0, amount_1, add_lp_token_amount)) => (pool, amount_0, amount_1, add_lp_token_amount),
+        Err(e) => {
+            // LP amounts are incorrect. return token_0 and token_1 back to user
+            return_tokens(
+                request_id,
+                user_id,
+                &caller_id,
+                Some(pool.pool_id),
+                token_0,
+                &transfer_0,
+                add_amount_0,
+                token_1,
+                &transfer_1,
+                add_amount_1,
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
 
     // successful, add tx and update request with reply
     let add_liquidity_tx = AddLiquidityTx::new_success(
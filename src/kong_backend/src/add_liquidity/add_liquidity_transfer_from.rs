@@ -6,19 +6,23 @@ use super::add_liquidity_args::AddLiquidityArgs;
 use super::add_liquidity_reply::AddLiquidityReply;
 use super::add_liquidity_reply_helpers::{create_add_liquidity_reply_failed, create_add_liquidity_reply_with_tx_id};
 
-use crate::helpers::nat_helpers::{
-    nat_add, nat_divide, nat_is_zero, nat_multiply, nat_sqrt, nat_subtract, nat_to_decimal_precision, nat_zero,
-};
+use crate::add_pool::add_pool::initial_lp_token_amount;
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_to_decimal_precision, nat_zero};
 use crate::ic::{
     address::Address,
-    get_time::get_time,
+    get_time::{get_time, is_expired},
     id::caller_id,
+    logging::error_log,
     transfer::{icrc1_transfer, icrc2_transfer_from},
 };
 use crate::stable_claim::{claim_map, stable_claim::StableClaim};
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
 use crate::stable_lp_token::{lp_token_map, stable_lp_token::StableLPToken};
-use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_pool::{
+    pool_map,
+    stable_pool::{PoolType, StablePool},
+};
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
@@ -29,20 +33,29 @@ use crate::stable_user::user_map;
 pub async fn add_liquidity_transfer_from(args: AddLiquidityArgs) -> Result<AddLiquidityReply, String> {
     let (user_id, pool, add_amount_0, add_amount_1) = check_arguments(&args).await?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddLiquidity(args.clone()), ts));
 
-    let result = process_add_liquidity(request_id, user_id, &pool, &add_amount_0, &add_amount_1, ts)
-        .await
-        .map_or_else(
-            |e| {
-                request_map::update_status(request_id, StatusCode::Failed, Some(&e));
-                Err(e)
-            },
-            |reply| {
-                request_map::update_status(request_id, StatusCode::Success, None);
-                Ok(reply)
-            },
-        );
+    let result = process_add_liquidity(
+        request_id,
+        user_id,
+        &pool,
+        &add_amount_0,
+        &add_amount_1,
+        args.min_lp_token_amount.as_ref(),
+        args.deadline_ns,
+        ts,
+    )
+    .await
+    .map_or_else(
+        |e| {
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            Err(e)
+        },
+        |reply| {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        },
+    );
 
     request_map::get_by_request_and_user_id(Some(request_id), Some(user_id), None)
         .first()
@@ -57,7 +70,18 @@ pub async fn add_liquidity_transfer_from_async(args: AddLiquidityArgs) -> Result
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddLiquidity(args.clone()), ts));
 
     ic_cdk::spawn(async move {
-        match process_add_liquidity(request_id, user_id, &pool, &add_amount_0, &add_amount_1, ts).await {
+        match process_add_liquidity(
+            request_id,
+            user_id,
+            &pool,
+            &add_amount_0,
+            &add_amount_1,
+            args.min_lp_token_amount.as_ref(),
+            args.deadline_ns,
+            ts,
+        )
+        .await
+        {
             Ok(_) => request_map::update_status(request_id, StatusCode::Success, None),
             Err(e) => request_map::update_status(request_id, StatusCode::Failed, Some(&e)),
         };
@@ -80,9 +104,18 @@ async fn check_arguments(args: &AddLiquidityArgs) -> Result<(u32, StablePool, Na
         return Err("Tx_id_0 and Tx_id_1 not supported".to_string());
     }
 
+    // fail early if the deadline has already passed
+    if is_expired(args.deadline_ns) {
+        return Err("Deadline expired".to_string());
+    }
+
     // add_amount_0 and add_amount_1 are the amounts to be added to the pool with the current state
     // these are the amounts that will be transferred to the pool
-    let (pool, add_amount_0, add_amount_1, _) = calculate_amounts(&args.token_0, &args.amount_0, &args.token_1, &args.amount_1)?;
+    let (pool, add_amount_0, add_amount_1, add_lp_token_amount) =
+        calculate_amounts(&args.token_0, &args.amount_0, &args.token_1, &args.amount_1, args.fee_tier)?;
+
+    // make sure the LP tokens received with the current state meet the minimum
+    check_min_lp_token_amount(&add_lp_token_amount, args.min_lp_token_amount.as_ref())?;
 
     // make sure tokens support ICRC2
     let token_0 = pool.token_0();
@@ -101,9 +134,18 @@ async fn check_arguments(args: &AddLiquidityArgs) -> Result<(u32, StablePool, Na
 /// calculate the LP token amount for the user
 ///
 /// returns (pool, amount_0, amount_1, add_lp_token_amount)
-pub fn calculate_amounts(token_0: &str, amount_0: &Nat, token_1: &str, amount_1: &Nat) -> Result<(StablePool, Nat, Nat, Nat), String> {
+pub fn calculate_amounts(
+    token_0: &str,
+    amount_0: &Nat,
+    token_1: &str,
+    amount_1: &Nat,
+    fee_tier: Option<u32>,
+) -> Result<(StablePool, Nat, Nat, Nat), String> {
     // Pool - make sure pool exists, refresh balances of the pool to make sure we have the latest state
-    let pool = pool_map::get_by_tokens(token_0, token_1)?;
+    let pool = pool_map::get_by_tokens(token_0, token_1, fee_tier)?;
+    if pool.pool_type == PoolType::ConcentratedLiquidity {
+        return Err(format!("Use add_position for concentrated liquidity pool {}", pool.symbol()));
+    }
     // Token0
     let token_0 = pool.token_0();
     // reserve_0 is the total balance of token_0 in the pool = balance_0 + lp_fee_0
@@ -118,11 +160,15 @@ pub fn calculate_amounts(token_0: &str, amount_0: &Nat, token_1: &str, amount_1:
 
     if nat_is_zero(&reserve_0) && nat_is_zero(&reserve_1) {
         // new pool as there are no balances - take user amounts as initial ratio
-        // initialize LP tokens as sqrt(amount_0 * amount_1)
         // convert the amounts to the same decimal precision as the LP token
         let amount_0_in_lp_token_decimals = nat_to_decimal_precision(amount_0, token_0.decimals(), lp_token.decimals());
         let amount_1_in_lp_token_decimals = nat_to_decimal_precision(amount_1, token_1.decimals(), lp_token.decimals());
-        let add_lp_token_amount = nat_sqrt(&nat_multiply(&amount_0_in_lp_token_decimals, &amount_1_in_lp_token_decimals));
+        let add_lp_token_amount = initial_lp_token_amount(
+            &amount_0_in_lp_token_decimals,
+            &amount_1_in_lp_token_decimals,
+            pool.get_amp(),
+            pool.get_weights(),
+        )?;
         return Ok((pool, amount_0.clone(), amount_1.clone(), add_lp_token_amount));
     }
 
@@ -184,12 +230,15 @@ pub fn calculate_amounts(token_0: &str, amount_0: &Nat, token_1: &str, amount_1:
     Err("Incorrect ratio of amount_0 and amount_1".to_string())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_add_liquidity(
     request_id: u64,
     user_id: u32,
     pool: &StablePool,
     add_amount_0: &Nat,
     add_amount_1: &Nat,
+    min_lp_token_amount: Option<&Nat>,
+    deadline_ns: Option<u64>,
     ts: u64,
 ) -> Result<AddLiquidityReply, String> {
     // Token0
@@ -245,25 +294,33 @@ async fn process_add_liquidity(
     };
 
     // re-calculate with latest pool state and make sure amounts are valid
-    let (pool, amount_0, amount_1, add_lp_token_amount) =
-        match update_liquidity_pool(request_id, user_id, pool, add_amount_0, add_amount_1, ts) {
-            Ok((pool, amount_0, amount_1, add_lp_token_amount)) => (pool, amount_0, amount_1, add_lp_token_amount),
-            Err(e) => {
-                // LP amounts are incorrect. return token_0 and token_1 back to user
-                return_tokens(
-                    request_id,
-                    user_id,
-                    &caller_id,
-                    pool,
-                    Some(add_amount_0),
-                    Some(add_amount_1),
-                    &mut transfer_ids,
-                    ts,
-                )
-                .await;
-                return Err(format!("Req #{} failed. {}", request_id, e));
-            }
-        };
+    let (pool, amount_0, amount_1, add_lp_token_amount) = match update_liquidity_pool(
+        request_id,
+        user_id,
+        pool,
+        add_amount_0,
+        add_amount_1,
+        min_lp_token_amount,
+        deadline_ns,
+        ts,
+    ) {
+        Ok((pool, amount_0, amount_1, add_lp_token_amount)) => (pool, amount_0, amount_1, add_lp_token_amount),
+        Err(e) => {
+            // LP amounts are incorrect. return token_0 and token_1 back to user
+            return_tokens(
+                request_id,
+                user_id,
+                &caller_id,
+                pool,
+                Some(add_amount_0),
+                Some(add_amount_1),
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
 
     // succcesful, add tx and update request with reply
     let add_liquidity_tx = AddLiquidityTx::new_success(
@@ -335,14 +392,24 @@ pub async fn transfer_from_token(
 
 /// update the liquidity pool with the new liquidity amounts
 /// ensure we have the latest state of the pool before adding the new amounts
+/// fails if deadline_ns has passed or the LP tokens received are less than min_lp_token_amount so the caller returns the tokens to the user
+#[allow(clippy::too_many_arguments)]
 pub fn update_liquidity_pool(
     request_id: u64,
     user_id: u32,
     pool: &StablePool,
     add_amount_0: &Nat,
     add_amount_1: &Nat,
+    min_lp_token_amount: Option<&Nat>,
+    deadline_ns: Option<u64>,
     ts: u64,
 ) -> Result<(StablePool, Nat, Nat, Nat), String> {
+    if is_expired(deadline_ns) {
+        let e = "Deadline expired".to_string();
+        request_map::update_status(request_id, StatusCode::DeadlineExpired, Some(&e));
+        return Err(e);
+    }
+
     request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
 
     let token_0 = pool.token_0().address_with_chain();
@@ -350,10 +417,15 @@ pub fn update_liquidity_pool(
     // re-calculate the amounts to be added to the pool with new state (after token_0 and token_1 transfers)
     // add_amount_0 and add_amount_1 are the transferred amounts from the initial calculations
     // amount_0, amount_1 and add_lp_token_amount will be the actual amounts to be added to the pool
-    match calculate_amounts(&token_0, add_amount_0, &token_1, add_amount_1) {
+    match calculate_amounts(&token_0, add_amount_0, &token_1, add_amount_1, pool.fee_tier) {
         Ok((mut pool, amount_0, amount_1, add_lp_token_amount)) => {
             request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
 
+            if let Err(e) = check_min_lp_token_amount(&add_lp_token_amount, min_lp_token_amount) {
+                request_map::update_status(request_id, StatusCode::LPTokenAmountBelowMinimum, Some(&e));
+                return Err(e);
+            }
+
             request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
 
             pool.balance_0 = nat_add(&pool.balance_0, &amount_0);
@@ -374,6 +446,17 @@ pub fn update_liquidity_pool(
     }
 }
 
+/// make sure add_lp_token_amount is at least min_lp_token_amount if specified
+fn check_min_lp_token_amount(add_lp_token_amount: &Nat, min_lp_token_amount: Option<&Nat>) -> Result<(), String> {
+    match min_lp_token_amount {
+        Some(min_lp_token_amount) if add_lp_token_amount < min_lp_token_amount => Err(format!(
+            "LP token amount {} below minimum {}",
+            add_lp_token_amount, min_lp_token_amount
+        )),
+        _ => Ok(()),
+    }
+}
+
 /// update the user's LP token amount
 /// ensure we have the latest state of the LP token before adding the new amounts
 fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token_amount: &Nat, ts: u64) {
@@ -389,19 +472,32 @@ fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token
                 ..lp_token.clone()
             };
             lp_token_map::update(&new_user_lp_token);
+            insert_mint_block(request_id, lp_token_id, user_id, add_lp_token_amount, ts);
             request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
         }
         None => {
             // new entry
             let new_user_lp_token = StableLPToken::new(user_id, lp_token_id, add_lp_token_amount.clone(), ts);
             match lp_token_map::insert(&new_user_lp_token) {
-                Ok(_) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None),
-                Err(e) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e)),
+                Ok(_) => {
+                    insert_mint_block(request_id, lp_token_id, user_id, add_lp_token_amount, ts);
+                    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
+                }
+                Err(e) => {
+                    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e));
+                }
             };
         }
     }
 }
 
+/// the tokens are already transferred and the LP tokens minted, so a block that can not be recorded is logged instead of failing the request
+fn insert_mint_block(request_id: u64, lp_token_id: u32, user_id: u32, add_lp_token_amount: &Nat, ts: u64) {
+    if let Err(e) = lp_block_map::insert_mint(lp_token_id, user_id, add_lp_token_amount, ts) {
+        error_log(&format!("Req #{} failed to record LP mint block. {}", request_id, e));
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn return_tokens(
     request_id: u64,
@@ -452,7 +548,7 @@ async fn return_tokens(
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn return_token(
+pub async fn return_token(
     request_id: u64,
     user_id: u32,
     to_principal_id: &Account,
@@ -511,14 +607,26 @@ async fn return_token(
 }
 
 pub fn archive_to_kong_data(request: &StableRequest) {
-    request_map::archive_request_to_kong_data(request.request_id);
-    if let Reply::AddLiquidity(reply) = &request.reply {
-        for claim_id in reply.claim_ids.iter() {
-            claim_map::archive_claim_to_kong_data(*claim_id);
+    match &request.reply {
+        Reply::AddLiquidity(reply) => {
+            request_map::archive_request_and_reply_to_kong_data(request.request_id, &reply.claim_ids, &reply.transfer_ids, &[reply.tx_id])
         }
-        for transfer_id_reply in reply.transfer_ids.iter() {
-            transfer_map::archive_transfer_to_kong_data(transfer_id_reply.transfer_id);
-        }
-        tx_map::archive_tx_to_kong_data(reply.tx_id);
-    };
+        _ => request_map::archive_request_to_kong_data(request.request_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_min_lp_token_amount() {
+        let add_lp_token_amount = Nat::from(1_000_u32);
+        assert!(check_min_lp_token_amount(&add_lp_token_amount, None).is_ok());
+        assert!(check_min_lp_token_amount(&add_lp_token_amount, Some(&Nat::from(1_000_u32))).is_ok());
+        assert_eq!(
+            check_min_lp_token_amount(&add_lp_token_amount, Some(&Nat::from(1_001_u32))),
+            Err("LP token amount 1_000 below minimum 1_001".to_string())
+        );
+    }
 }
@@ -6,18 +6,23 @@ use super::add_liquidity_amounts_reply::AddLiquidityAmountsReply;
 use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_to_decimal_precision};
 use crate::ic::guards::not_in_maintenance_mode;
 use crate::stable_lp_token::lp_token_map;
-use crate::stable_pool::pool_map;
+use crate::stable_pool::{pool_map, stable_pool::PoolType};
 use crate::stable_token::token::Token;
 
 /// Add liquidity to a pool
 ///
-/// Given an amount of one of the tokens, calculate the amount of the other token to maintain a constant K
+/// Given an amount of one of the tokens, calculate the amount of the other token to maintain the pool ratio
+/// Liquidity is added in proportion to the reserves so the price of the pool does not change. This holds for both
+/// constant product and StableSwap pools as both invariants scale linearly with the reserves
 ///
 /// The output of amount_0 and amount_1 should be passed to add_liquidity() to execute the actual transaction
 /// Also calculate the amount of LP token user will receive
 #[query(guard = "not_in_maintenance_mode")]
-fn add_liquidity_amounts(token_0: String, amount: Nat, token_1: String) -> Result<AddLiquidityAmountsReply, String> {
-    if let Ok(pool) = pool_map::get_by_tokens(&token_0, &token_1) {
+fn add_liquidity_amounts(token_0: String, amount: Nat, token_1: String, fee_tier: Option<u32>) -> Result<AddLiquidityAmountsReply, String> {
+    if let Ok(pool) = pool_map::get_by_tokens(&token_0, &token_1, fee_tier) {
+        if pool.pool_type == PoolType::ConcentratedLiquidity {
+            return Err(format!("Use add_position for concentrated liquidity pool {}", pool.symbol()));
+        }
         // Pool
         let symbol = pool.symbol();
         // Token0
@@ -73,7 +78,10 @@ fn add_liquidity_amounts(token_0: String, amount: Nat, token_1: String) -> Resul
             fee_1,
             add_lp_token_amount,
         });
-    } else if let Ok(pool) = pool_map::get_by_tokens(&token_1, &token_0) {
+    } else if let Ok(pool) = pool_map::get_by_tokens(&token_1, &token_0, fee_tier) {
+        if pool.pool_type == PoolType::ConcentratedLiquidity {
+            return Err(format!("Use add_position for concentrated liquidity pool {}", pool.symbol()));
+        }
         let symbol = pool.symbol();
         // Token0
         let token_0 = pool.token_0();
@@ -2,13 +2,16 @@ use candid::Nat;
 use ic_cdk::update;
 use icrc_ledger_types::icrc1::account::Account;
 
-use super::add_pool_args::AddPoolArgs;
+use super::add_pool_args::{AddPoolArgs, LBPArgs};
 use super::add_pool_reply::AddPoolReply;
 use super::add_pool_reply_helpers::{create_add_pool_reply_failed, create_add_pool_reply_with_tx_id};
 
 use crate::add_token::add_token::{add_ic_token, add_lp_token};
 use crate::chains::chains::{IC_CHAIN, LP_CHAIN};
+use crate::helpers::concentrated_liquidity_helpers::{sqrt_price_from_amounts, tick_at_sqrt_price, MAX_TICK, MIN_TICK};
 use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_multiply, nat_sqrt, nat_subtract, nat_to_decimal_precision, nat_zero};
+use crate::helpers::stableswap_helpers::stableswap_d;
+use crate::helpers::weighted_helpers::weighted_invariant;
 use crate::ic::{
     address::Address,
     ckusdt::is_ckusdt,
@@ -16,15 +19,20 @@ use crate::ic::{
     guards::not_in_maintenance_mode,
     icp::is_icp,
     id::{caller_id, is_caller_controller},
+    logging::error_log,
     transfer::{icrc1_transfer, icrc2_transfer_from},
     verify::verify_transfer,
 };
 use crate::stable_claim::{claim_map, stable_claim::StableClaim};
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
 use crate::stable_lp_token::lp_token_map;
 use crate::stable_lp_token::stable_lp_token::StableLPToken;
+use crate::stable_pool::concentrated_liquidity::{full_range_ticks, mint_position};
 use crate::stable_pool::pool_map;
-use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_pool::stable_pool::{
+    PoolType, StablePool, DEFAULT_TICK_SPACING, MAX_AMP, MAX_LP_FEE_PIPS, MAX_TICK_SPACING, MAX_WEIGHT, MIN_AMP, MIN_WEIGHT, PIPS_PER_BPS,
+};
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
 use crate::stable_token::lp_token::LP_DECIMALS;
 use crate::stable_token::stable_token::StableToken;
@@ -54,8 +62,9 @@ enum TokenIndex {
 /// * `Err(String)` - An error message if the operation fails.
 #[update(guard = "not_in_maintenance_mode")]
 pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
-    let (user_id, token_0, add_amount_0, tx_id_0, token_1, add_amount_1, tx_id_1, lp_fee_bps, kong_fee_bps, add_lp_token_amount, on_kong) =
-        check_arguments(&args).await?;
+    let (amp, tick_spacing, weight_0, lbp) = check_pool_type(&args)?;
+    let (user_id, token_0, add_amount_0, tx_id_0, token_1, add_amount_1, tx_id_1, lp_fee_pips, kong_fee_pips, add_lp_token_amount, on_kong) =
+        check_arguments(&args, amp, tick_spacing, weight_0).await?;
     let ts = get_time();
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddPool(args), ts));
 
@@ -68,10 +77,14 @@ pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
         &token_1,
         &add_amount_1,
         tx_id_1.as_ref(),
-        lp_fee_bps,
-        kong_fee_bps,
+        lp_fee_pips,
+        kong_fee_pips,
         &add_lp_token_amount,
         on_kong,
+        amp,
+        tick_spacing,
+        weight_0,
+        lbp.as_ref(),
         ts,
     )
     .await
@@ -98,10 +111,13 @@ pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
 /// # Arguments
 ///
 /// * `args` - The arguments for adding a pool.
+/// * `amp` - The StableSwap amplification coefficient or None for constant product pools.
+/// * `tick_spacing` - The tick spacing for concentrated liquidity pools or None for other pools.
+/// * `weight_0` - The weight of token_0 in percent for weighted pools or None for other pools.
 ///
 /// # Returns
 ///
-/// * `Ok((user_id, token_0, amount_0, tx_id_0, token_1, add_amount_1, tx_id_1, lp_fee_bps, on_kong))`
+/// * `Ok((user_id, token_0, amount_0, tx_id_0, token_1, add_amount_1, tx_id_1, lp_fee_pips, on_kong))`
 /// *   `user_id` - The user id.
 /// *   `token_0` - The first token.
 /// *   `amount_0` - The amount of the first token.
@@ -109,30 +125,58 @@ pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
 /// *   `token_1` - The second token.
 /// *   `add_amount_1` - The amount of the second token.
 /// *   `tx_id_1` - The transaction id of the second token for icrc1_transfer.
-/// *   `lp_fee_bps` - The liquidity pool fee basis points.
-/// *   `kong_fee_bps` - The liquidity pool Kong fee basis points.
+/// *   `lp_fee_pips` - The liquidity pool fee in pips.
+/// *   `kong_fee_pips` - The liquidity pool Kong fee in pips.
 /// *   `add_lp_token_amount` - The amount of LP token to be added to the pool.
 /// *   `on_kong` - Whether the pool is on Kong.
 /// * `Err(String)` - An error message if the operation fails.
 async fn check_arguments(
     args: &AddPoolArgs,
-) -> Result<(u32, StableToken, Nat, Option<Nat>, StableToken, Nat, Option<Nat>, u8, u8, Nat, bool), String> {
+    amp: Option<u64>,
+    tick_spacing: Option<u32>,
+    weight_0: Option<u8>,
+) -> Result<
+    (
+        u32,
+        StableToken,
+        Nat,
+        Option<Nat>,
+        StableToken,
+        Nat,
+        Option<Nat>,
+        u32,
+        u32,
+        Nat,
+        bool,
+    ),
+    String,
+> {
     if nat_is_zero(&args.amount_0) || nat_is_zero(&args.amount_1) {
         return Err("Invalid zero amounts".to_string());
     }
 
-    // can overwrite lp_fee_bps
-    let default_lp_fee_bps = kong_settings_map::get().default_lp_fee_bps;
-    let lp_fee_bps = args.lp_fee_bps.unwrap_or(default_lp_fee_bps);
-
-    let default_kong_fee_bps = kong_settings_map::get().default_kong_fee_bps;
-    // only controllers can set kong_fee_bps otherwise use default
-    let kong_fee_bps = match is_caller_controller() {
-        true => args.kong_fee_bps.unwrap_or(default_kong_fee_bps),
-        false => default_kong_fee_bps,
+    // can overwrite lp_fee_pips
+    let default_lp_fee_pips = kong_settings_map::get().default_lp_fee_pips;
+    let lp_fee_pips = args
+        .lp_fee_pips
+        .or(args.lp_fee_bps.map(|lp_fee_bps| lp_fee_bps as u32 * PIPS_PER_BPS))
+        .unwrap_or(default_lp_fee_pips);
+
+    let default_kong_fee_pips = kong_settings_map::get().default_kong_fee_pips;
+    // only controllers can set kong_fee_pips otherwise use default
+    let kong_fee_pips = match is_caller_controller() {
+        true => args
+            .kong_fee_pips
+            .or(args.kong_fee_bps.map(|kong_fee_bps| kong_fee_bps as u32 * PIPS_PER_BPS))
+            .unwrap_or(default_kong_fee_pips),
+        false => default_kong_fee_pips,
     };
 
-    if kong_fee_bps > lp_fee_bps {
+    if lp_fee_pips > MAX_LP_FEE_PIPS {
+        return Err(format!("LP fee cannot be greater than {} pips", MAX_LP_FEE_PIPS));
+    }
+
+    if kong_fee_pips > lp_fee_pips {
         return Err("Kong fee cannot be greater than LP fee".to_string());
     }
 
@@ -182,18 +226,28 @@ async fn check_arguments(
         }
     };
 
+    // pools added to a token pair that already has a pool are qualified by their fee tier
+    let fee_tier = pool_map::exists(&token_0, &token_1).then_some(lp_fee_pips);
+
     // make sure LP token does not already exist
-    let lp_token_address = token::address(&token_0, &token_1);
+    let lp_token_address = token::address(&token_0, &token_1, fee_tier);
     if token_map::exists(&lp_token_address) {
-        return Err(format!("LP token {} already exists", token::symbol(&token_0, &token_1)));
+        return Err(format!("LP token {} already exists", token::symbol(&token_0, &token_1, fee_tier)));
     }
 
-    // make sure pool does not already exist
-    if pool_map::exists(&token_0, &token_1) {
-        return Err(format!("Pool {} already exists", pool_map::symbol(&token_0, &token_1)));
+    // make sure pool does not already exist with the same fee tier
+    if pool_map::exists_fee_tier(&token_0, &token_1, lp_fee_pips) {
+        return Err(format!(
+            "Pool {} already exists",
+            pool_map::symbol(&token_0, &token_1, Some(lp_fee_pips))
+        ));
     }
 
-    let (add_amount_0, add_amount_1, add_lp_token_amount) = calculate_amounts(&token_0, &args.amount_0, &token_1, &args.amount_1)?;
+    // concentrated liquidity pools start with a position instead of LP tokens
+    let (add_amount_0, add_amount_1, add_lp_token_amount) = match tick_spacing {
+        Some(_) => (args.amount_0.clone(), args.amount_1.clone(), nat_zero()),
+        None => calculate_amounts(&token_0, &args.amount_0, &token_1, &args.amount_1, amp, weights(weight_0))?,
+    };
 
     // make sure user is registered, if not create a new user
     let user_id = user_map::insert(None)?;
@@ -206,24 +260,110 @@ async fn check_arguments(
         token_1,
         add_amount_1,
         tx_id_1,
-        lp_fee_bps,
-        kong_fee_bps,
+        lp_fee_pips,
+        kong_fee_pips,
         add_lp_token_amount,
         on_kong,
     ))
 }
 
-pub fn calculate_amounts(token_0: &StableToken, amount_0: &Nat, token_1: &StableToken, amount_1: &Nat) -> Result<(Nat, Nat, Nat), String> {
+pub fn calculate_amounts(
+    token_0: &StableToken,
+    amount_0: &Nat,
+    token_1: &StableToken,
+    amount_1: &Nat,
+    amp: Option<u64>,
+    weights: Option<(u32, u32)>,
+) -> Result<(Nat, Nat, Nat), String> {
     // new pool as there are no balances - take user amounts as initial ratio
-    // initialize LP tokens as sqrt(amount_0 * amount_1)
     // convert the amounts to the same decimal precision as the LP token
     let amount_0_in_lp_token_decimals = nat_to_decimal_precision(amount_0, token_0.decimals(), LP_DECIMALS);
     let amount_1_in_lp_token_decimals = nat_to_decimal_precision(amount_1, token_1.decimals(), LP_DECIMALS);
-    let add_lp_token_amount = nat_sqrt(&nat_multiply(&amount_0_in_lp_token_decimals, &amount_1_in_lp_token_decimals));
+    let add_lp_token_amount = initial_lp_token_amount(&amount_0_in_lp_token_decimals, &amount_1_in_lp_token_decimals, amp, weights)?;
 
     Ok((amount_0.clone(), amount_1.clone(), add_lp_token_amount))
 }
 
+/// LP tokens of the first deposit to a pool. amounts must be in the LP token decimal precision
+/// - constant product pools: sqrt(amount_0 * amount_1)
+/// - StableSwap pools: the invariant D, which is close to amount_0 + amount_1 for pegged tokens
+/// - weighted pools: amount_0^weight_0 * amount_1^weight_1 with the weights as fractions
+pub fn initial_lp_token_amount(amount_0: &Nat, amount_1: &Nat, amp: Option<u64>, weights: Option<(u32, u32)>) -> Result<Nat, String> {
+    match (amp, weights) {
+        (Some(amp), _) => stableswap_d(amount_0, amount_1, amp).ok_or("Invalid LP token amount".to_string()),
+        (_, Some((weight_0, weight_1))) => {
+            weighted_invariant(amount_0, amount_1, weight_0, weight_1).ok_or("Invalid LP token amount".to_string())
+        }
+        (None, None) => Ok(nat_sqrt(&nat_multiply(amount_0, amount_1))),
+    }
+}
+
+// weights of token_0 and token_1 in percent from the weight of token_0
+fn weights(weight_0: Option<u8>) -> Option<(u32, u32)> {
+    weight_0.map(|weight_0| (weight_0 as u32, 100 - weight_0 as u32))
+}
+
+// amplification coefficient, tick spacing, weight of token_0 and weight schedule of a new pool
+type PoolTypeParams = (Option<u64>, Option<u32>, Option<u8>, Option<LBPArgs>);
+
+/// returns the amplification coefficient for StableSwap pools, the tick spacing for concentrated liquidity pools,
+/// the weight of token_0 for weighted pools and the weight of token_0 and weight schedule for liquidity bootstrapping pools
+fn check_pool_type(args: &AddPoolArgs) -> Result<PoolTypeParams, String> {
+    let pool_type = args.pool_type.as_ref().unwrap_or(&PoolType::ConstantProduct);
+    if args.amp.is_some() && *pool_type != PoolType::StableSwap {
+        return Err("Amplification coefficient is only for StableSwap pools".to_string());
+    }
+    if args.tick_spacing.is_some() && *pool_type != PoolType::ConcentratedLiquidity {
+        return Err("Tick spacing is only for concentrated liquidity pools".to_string());
+    }
+    if args.weight_0.is_some() && *pool_type != PoolType::Weighted && *pool_type != PoolType::LiquidityBootstrapping {
+        return Err("Weights are only for weighted and liquidity bootstrapping pools".to_string());
+    }
+    if args.lbp.is_some() && *pool_type != PoolType::LiquidityBootstrapping {
+        return Err("Weight schedule is only for liquidity bootstrapping pools".to_string());
+    }
+    match pool_type {
+        PoolType::ConstantProduct => Ok((None, None, None, None)),
+        PoolType::StableSwap => {
+            let amp = args.amp.ok_or("Amplification coefficient is required for StableSwap pools")?;
+            if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+                return Err(format!("Amplification coefficient must be between {} and {}", MIN_AMP, MAX_AMP));
+            }
+            Ok((Some(amp), None, None, None))
+        }
+        PoolType::ConcentratedLiquidity => {
+            let tick_spacing = args.tick_spacing.unwrap_or(DEFAULT_TICK_SPACING);
+            if !(1..=MAX_TICK_SPACING).contains(&tick_spacing) {
+                return Err(format!("Tick spacing must be between 1 and {}", MAX_TICK_SPACING));
+            }
+            Ok((None, Some(tick_spacing), None, None))
+        }
+        PoolType::Weighted => {
+            let weight_0 = args.weight_0.ok_or("Weight of token_0 is required for weighted pools")?;
+            if !(MIN_WEIGHT..=MAX_WEIGHT).contains(&weight_0) {
+                return Err(format!("Weight of token_0 must be between {} and {}", MIN_WEIGHT, MAX_WEIGHT));
+            }
+            Ok((None, None, Some(weight_0), None))
+        }
+        PoolType::LiquidityBootstrapping => {
+            let weight_0 = args
+                .weight_0
+                .ok_or("Start weight of token_0 is required for liquidity bootstrapping pools")?;
+            let lbp = args
+                .lbp
+                .clone()
+                .ok_or("Weight schedule is required for liquidity bootstrapping pools")?;
+            if !(MIN_WEIGHT..=MAX_WEIGHT).contains(&weight_0) || !(MIN_WEIGHT..=MAX_WEIGHT).contains(&lbp.weight_0_end) {
+                return Err(format!("Weights of token_0 must be between {} and {}", MIN_WEIGHT, MAX_WEIGHT));
+            }
+            if lbp.end_ts <= lbp.start_ts || lbp.end_ts <= get_time() {
+                return Err("End of the sale must be in the future and after the start".to_string());
+            }
+            Ok((None, None, Some(weight_0), Some(lbp)))
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn process_add_pool(
     request_id: u64,
@@ -234,10 +374,14 @@ async fn process_add_pool(
     token_1: &StableToken,
     amount_1: &Nat,
     tx_id_1: Option<&Nat>,
-    lp_fee_bps: u8,
-    kong_fee_bps: u8,
+    lp_fee_pips: u32,
+    kong_fee_pips: u32,
     add_lp_token_amount: &Nat,
     on_kong: bool,
+    amp: Option<u64>,
+    tick_spacing: Option<u32>,
+    weight_0: Option<u8>,
+    lbp: Option<&LBPArgs>,
     ts: u64,
 ) -> Result<AddPoolReply, String> {
     let caller_id = caller_id();
@@ -311,7 +455,8 @@ async fn process_add_pool(
 
     // add LP token
     request_map::update_status(request_id, StatusCode::AddLPToken, None);
-    let lp_token = match add_lp_token(token_0, token_1, on_kong) {
+    let fee_tier = pool_map::exists(token_0, token_1).then_some(lp_fee_pips);
+    let lp_token = match add_lp_token(token_0, token_1, fee_tier, on_kong) {
         Ok(lp_token) => {
             request_map::update_status(request_id, StatusCode::AddLPTokenSuccess, None);
             lp_token
@@ -342,10 +487,17 @@ async fn process_add_pool(
     let pool = match add_new_pool(
         token_0.token_id(),
         token_1.token_id(),
-        lp_fee_bps,
-        kong_fee_bps,
+        lp_fee_pips,
+        kong_fee_pips,
         lp_token.token_id(),
+        fee_tier,
         on_kong,
+        amp,
+        tick_spacing,
+        weight_0,
+        lbp,
+        amount_0,
+        amount_1,
     ) {
         Ok(pool) => {
             request_map::update_status(request_id, StatusCode::AddPoolSuccess, None);
@@ -513,6 +665,21 @@ fn update_liquidity_pool(
         ..pool.clone()
     };
     update_pool.update_tvl();
+
+    // concentrated liquidity pools start with a full range position instead of LP tokens
+    // any rounding dust of the amounts not used by the position stays in the pool
+    if update_pool.pool_type == PoolType::ConcentratedLiquidity {
+        request_map::update_status(request_id, StatusCode::AddPosition, None);
+        let (tick_lower, tick_upper) = full_range_ticks(update_pool.tick_spacing);
+        match mint_position(&mut update_pool, user_id, tick_lower, tick_upper, amount_0, amount_1, ts) {
+            Ok(_) => request_map::update_status(request_id, StatusCode::AddPositionSuccess, None),
+            Err(e) => request_map::update_status(request_id, StatusCode::AddPositionFailed, Some(&e)),
+        };
+        pool_map::update(&update_pool);
+        request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+        return;
+    }
+
     pool_map::update(&update_pool);
     request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
 
@@ -533,19 +700,32 @@ fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token
                 ..lp_token.clone()
             };
             lp_token_map::update(&new_user_lp_token);
+            insert_mint_block(request_id, lp_token_id, user_id, add_lp_token_amount, ts);
             request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
         }
         None => {
             // new entry
             let new_user_lp_token = StableLPToken::new(user_id, lp_token_id, add_lp_token_amount.clone(), ts);
             match lp_token_map::insert(&new_user_lp_token) {
-                Ok(_) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None),
-                Err(e) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e)),
+                Ok(_) => {
+                    insert_mint_block(request_id, lp_token_id, user_id, add_lp_token_amount, ts);
+                    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
+                }
+                Err(e) => {
+                    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e));
+                }
             };
         }
     }
 }
 
+/// the tokens are already transferred and the LP tokens minted, so a block that can not be recorded is logged instead of failing the request
+fn insert_mint_block(request_id: u64, lp_token_id: u32, user_id: u32, add_lp_token_amount: &Nat, ts: u64) {
+    if let Err(e) = lp_block_map::insert_mint(lp_token_id, user_id, add_lp_token_amount, ts) {
+        error_log(&format!("Req #{} failed to record LP mint block. {}", request_id, e));
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn return_tokens(
     request_id: u64,
@@ -673,28 +853,75 @@ async fn return_token(
 }
 
 // add_pool() taken
+#[allow(clippy::too_many_arguments)]
 fn add_new_pool(
     token_id_0: u32,
     token_id_1: u32,
-    lp_fee_bps: u8,
-    kong_fee_bps: u8,
+    lp_fee_pips: u32,
+    kong_fee_pips: u32,
     lp_token_id: u32,
+    fee_tier: Option<u32>,
     on_kong: bool,
+    amp: Option<u64>,
+    tick_spacing: Option<u32>,
+    weight_0: Option<u8>,
+    lbp: Option<&LBPArgs>,
+    amount_0: &Nat,
+    amount_1: &Nat,
 ) -> Result<StablePool, String> {
-    let pool = StablePool::new(token_id_0, token_id_1, lp_fee_bps, kong_fee_bps, lp_token_id, on_kong);
+    let pool = StablePool {
+        fee_tier,
+        ..StablePool::new(token_id_0, token_id_1, lp_fee_pips, kong_fee_pips, lp_token_id, on_kong)
+    };
+    let pool = match (amp, tick_spacing, weight_0) {
+        (Some(amp), _, _) => StablePool {
+            pool_type: PoolType::StableSwap,
+            amp_initial: amp,
+            amp_future: amp,
+            ..pool
+        },
+        (_, Some(tick_spacing), _) => {
+            // initial price is the ratio of the amounts
+            let sqrt_price = sqrt_price_from_amounts(amount_0, amount_1).ok_or("Invalid initial price")?;
+            let tick = tick_at_sqrt_price(&sqrt_price);
+            if !(MIN_TICK..MAX_TICK).contains(&tick) {
+                Err("Initial price out of range")?
+            }
+            StablePool {
+                pool_type: PoolType::ConcentratedLiquidity,
+                tick_spacing,
+                sqrt_price,
+                tick,
+                ..pool
+            }
+        }
+        (_, _, Some(weight_0)) => match lbp {
+            Some(lbp) => StablePool {
+                pool_type: PoolType::LiquidityBootstrapping,
+                weight_0,
+                weight_0_end: lbp.weight_0_end,
+                weight_start_ts: lbp.start_ts,
+                weight_end_ts: lbp.end_ts,
+                buy_only: lbp.buy_only.unwrap_or(false),
+                ..pool
+            },
+            None => StablePool {
+                pool_type: PoolType::Weighted,
+                weight_0,
+                ..pool
+            },
+        },
+        (None, None, None) => pool,
+    };
     let pool_id = pool_map::insert(&pool)?;
     pool_map::get_by_pool_id(pool_id).ok_or_else(|| "Failed to add pool".to_string())
 }
 
 pub fn archive_to_kong_data(request: &StableRequest) {
-    request_map::archive_request_to_kong_data(request.request_id);
-    if let Reply::AddPool(reply) = &request.reply {
-        for claim_id in reply.claim_ids.iter() {
-            claim_map::archive_claim_to_kong_data(*claim_id);
+    match &request.reply {
+        Reply::AddPool(reply) => {
+            request_map::archive_request_and_reply_to_kong_data(request.request_id, &reply.claim_ids, &reply.transfer_ids, &[reply.tx_id])
         }
-        for transfer_id_reply in reply.transfer_ids.iter() {
-            transfer_map::archive_transfer_to_kong_data(transfer_id_reply.transfer_id);
-        }
-        tx_map::archive_tx_to_kong_data(reply.tx_id);
-    };
+        _ => request_map::archive_request_to_kong_data(request.request_id),
+    }
 }
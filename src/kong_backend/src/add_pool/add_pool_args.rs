@@ -1,6 +1,7 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::stable_pool::stable_pool::PoolType;
 use crate::stable_transfer::tx_id::TxId;
 
 /// Data structure for the arguments of the `add_pool` function.
@@ -13,7 +14,24 @@ pub struct AddPoolArgs {
     pub token_1: String,
     pub amount_1: Nat,
     pub tx_id_1: Option<TxId>,
-    pub lp_fee_bps: Option<u8>,
-    pub kong_fee_bps: Option<u8>,
+    pub lp_fee_pips: Option<u32>,   // LP fee in pips (1/100 of a basis point). defaults to 3_000 (0.3%)
+    pub kong_fee_pips: Option<u32>, // Kong's share of the LP fee in pips. only for controllers
+    pub lp_fee_bps: Option<u8>,     // deprecated. LP fee in basis points if lp_fee_pips is not specified
+    pub kong_fee_bps: Option<u8>,   // deprecated. Kong's fee in basis points if kong_fee_pips is not specified
     pub on_kong: Option<bool>,
+    pub pool_type: Option<PoolType>, // defaults to ConstantProduct
+    pub amp: Option<u64>,            // StableSwap amplification coefficient A. required for StableSwap pools
+    pub tick_spacing: Option<u32>,   // concentrated liquidity tick spacing. defaults to 60
+    pub weight_0: Option<u8>,        // weight of token_0 in percent. required for Weighted pools and the start weight of LBPs
+    pub lbp: Option<LBPArgs>,        // weight schedule. required for LiquidityBootstrapping pools
+}
+
+/// Weight schedule of a liquidity bootstrapping pool (LBP)
+/// weight of token_0 shifts linearly from weight_0 at start_ts to weight_0_end at end_ts
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LBPArgs {
+    pub weight_0_end: u8,
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub buy_only: Option<bool>, // only swaps buying token_0 are allowed until end_ts. defaults to false
 }
@@ -1,5 +1,6 @@
 use crate::helpers::nat_helpers::nat_zero;
 use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::pips_to_bps;
 use crate::stable_token::token::Token;
 use crate::stable_tx::add_pool_tx::AddPoolTx;
 use crate::stable_tx::status_tx::StatusTx;
@@ -15,7 +16,7 @@ pub fn create_add_pool_reply(add_pool_tx: &AddPoolTx) -> AddPoolReply {
 }
 
 pub fn create_add_pool_reply_with_tx_id(tx_id: u64, add_pool_tx: &AddPoolTx) -> AddPoolReply {
-    let (symbol, chain_0, symbol_0, balance_0, chain_1, symbol_1, balance_1, lp_fee_bps, lp_token_symbol) =
+    let (symbol, chain_0, symbol_0, balance_0, chain_1, symbol_1, balance_1, lp_fee_pips, lp_token_symbol) =
         pool_map::get_by_pool_id(add_pool_tx.pool_id).map_or_else(
             || {
                 (
@@ -39,7 +40,7 @@ pub fn create_add_pool_reply_with_tx_id(tx_id: u64, add_pool_tx: &AddPoolTx) ->
                     pool.chain_1(),
                     pool.symbol_1(),
                     pool.balance_1.clone(),
-                    pool.lp_fee_bps,
+                    pool.lp_fee_pips,
                     pool.lp_token().symbol().to_string(),
                 )
             },
@@ -57,7 +58,8 @@ pub fn create_add_pool_reply_with_tx_id(tx_id: u64, add_pool_tx: &AddPoolTx) ->
         symbol_1,
         amount_1: add_pool_tx.amount_1.clone(),
         balance_1,
-        lp_fee_bps,
+        lp_fee_pips,
+        lp_fee_bps: pips_to_bps(lp_fee_pips),
         add_lp_token_amount: add_pool_tx.add_lp_token_amount.clone(),
         lp_token_symbol,
         transfer_ids: to_transfer_ids(&add_pool_tx.transfer_ids),
@@ -93,6 +95,7 @@ pub fn create_add_pool_reply_failed(
         amount_1: nat_zero(),
         balance_1: nat_zero(),
         add_lp_token_amount: nat_zero(),
+        lp_fee_pips: 0,
         lp_fee_bps: 0,
         lp_token_symbol: "LP token not added".to_string(),
         transfer_ids: to_transfer_ids(transfer_ids),
@@ -82,8 +82,8 @@ pub async fn add_ic_token(token: &str, on_kong: bool) -> Result<StableToken, Str
     token_map::get_by_token_id(token_id).ok_or_else(|| format!("Failed to add token {}", token))
 }
 
-pub fn add_lp_token(token_0: &StableToken, token_1: &StableToken, on_kong: bool) -> Result<StableToken, String> {
-    let lp_token = StableToken::LP(LPToken::new(token_0, token_1, on_kong));
+pub fn add_lp_token(token_0: &StableToken, token_1: &StableToken, fee_tier: Option<u32>, on_kong: bool) -> Result<StableToken, String> {
+    let lp_token = StableToken::LP(LPToken::new(token_0, token_1, fee_tier, on_kong));
     let token_id = token_map::insert(&lp_token)?;
     token_map::get_by_token_id(token_id).ok_or_else(|| "Failed to add LP token".to_string())
 }
@@ -0,0 +1,293 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::batch_args::{BatchArgs, BatchOperation};
+use super::batch_operations::{add_balance, check_operation, execute_operation, operation_inputs, operation_outputs, BatchBalances};
+use super::batch_reply::{BatchOperationReply, BatchReply};
+use super::batch_reply_helpers::create_batch_reply;
+
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::address::Address;
+use crate::ic::get_time::{get_time, is_expired};
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller_id;
+use crate::ic::transfer::icrc1_transfer;
+use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+use crate::stable_tx::status_tx::StatusTx;
+use crate::stable_user::user_map;
+use crate::swap::return_pay_token::record_dust;
+use crate::swap::swap_transfer_from::transfer_from_token;
+
+const MAX_BATCH_OPERATIONS: usize = 10;
+
+/// Run several operations in one call under one request_id
+///
+/// - before calling batch, the user must icrc2_approve amount + gas of each token not received by a previous operation
+///
+/// Arguments: BatchArgs
+///  operations: AddLiquidity, RemoveLiquidity or Swap with the arguments of the Request variants
+///   - an operation takes its tokens from the batch balances, the tokens received by previous operations
+///   - an amount of zero takes the whole batch balance of the token, ie. swap everything received by a remove liquidity
+///   - tokens not received by a previous operation are transferred from the user once, for all the operations
+///
+/// Returns: BatchReply
+///  operations: reply of each operation. the operations do not have transfers of their own
+///  transfer_ids: the transfers from the user and the payouts of the remaining batch balances
+///
+/// Steps:
+/// 1. transfer_from_token() - transfer the tokens not received by a previous operation
+/// 2. execute_operation() - run the operations in sequence with the batch balances, without any awaits in between
+/// 3. send_balances() - send the remaining batch balances to the user
+///
+/// If an operation fails, the operations before it stay done and the batch balances are refunded as claims
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn batch(args: BatchArgs) -> Result<BatchReply, String> {
+    let (user_id, transfer_from_amounts) = check_arguments(&args)?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::Batch(args.clone()), ts));
+
+    let result = process_batch(
+        request_id,
+        user_id,
+        &caller_id(),
+        &args.operations,
+        &transfer_from_amounts,
+        args.deadline_ns,
+        ts,
+    )
+    .await
+    .map_or_else(
+        |e| {
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            Err(e)
+        },
+        |reply| {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        },
+    );
+
+    request_map::get_by_request_and_user_id(Some(request_id), Some(user_id), None)
+        .first()
+        .map(archive_to_kong_data);
+
+    result
+}
+
+/// returns (user_id, transfer_from_amounts) where transfer_from_amounts are the tokens not received by a previous operation
+fn check_arguments(args: &BatchArgs) -> Result<(u32, Vec<(StableToken, Nat)>), String> {
+    if args.operations.is_empty() {
+        return Err("No operations".to_string());
+    }
+    if args.operations.len() > MAX_BATCH_OPERATIONS {
+        return Err(format!("Maximum of {} operations", MAX_BATCH_OPERATIONS));
+    }
+
+    // fail early if the deadline has already passed
+    if is_expired(args.deadline_ns) {
+        return Err("Deadline expired".to_string());
+    }
+
+    // tokens received by previous operations are taken from the batch balances, other tokens are transferred from the user
+    let mut received_token_ids = BTreeSet::new();
+    let mut transfer_from_amounts: BTreeMap<u32, (StableToken, Nat)> = BTreeMap::new();
+    for (i, operation) in args.operations.iter().enumerate() {
+        check_operation(operation).map_err(|e| format!("Operation #{}. {}", i + 1, e))?;
+        for (token, amount) in operation_inputs(operation).map_err(|e| format!("Operation #{}. {}", i + 1, e))? {
+            if received_token_ids.contains(&token.token_id()) {
+                continue;
+            }
+            if nat_is_zero(&amount) {
+                return Err(format!(
+                    "Operation #{}. Amount of {} required as it is not received by a previous operation",
+                    i + 1,
+                    token.symbol()
+                ));
+            }
+            if !token.is_icrc2() {
+                return Err(format!("Operation #{}. {} must support ICRC2", i + 1, token.symbol()));
+            }
+            transfer_from_amounts
+                .entry(token.token_id())
+                .and_modify(|(_, total)| *total = nat_add(total, &amount))
+                .or_insert((token, amount));
+        }
+        received_token_ids.extend(operation_outputs(operation).map_err(|e| format!("Operation #{}. {}", i + 1, e))?);
+    }
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    Ok((user_id, transfer_from_amounts.into_values().collect()))
+}
+
+async fn process_batch(
+    request_id: u64,
+    user_id: u32,
+    caller_id: &Account,
+    operations: &[BatchOperation],
+    transfer_from_amounts: &[(StableToken, Nat)],
+    deadline_ns: Option<u64>,
+    ts: u64,
+) -> Result<BatchReply, String> {
+    let kong_backend = kong_settings_map::get().kong_backend_account;
+    let mut balances = BatchBalances::new();
+    let mut transfer_ids = Vec::new();
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // transfer_from each token once for all the operations
+    for (token, amount) in transfer_from_amounts {
+        if let Err(e) = transfer_from_token(request_id, caller_id, token, amount, &kong_backend, &mut transfer_ids, ts).await {
+            let e = format!("{} transfer_from failed. {}", token.symbol(), e);
+            refund_balances(request_id, user_id, caller_id, &balances, Vec::new(), &transfer_ids, ts);
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+        add_balance(&mut balances, token.token_id(), amount);
+    }
+
+    // make sure the request has not passed its deadline before any operations are run
+    if is_expired(deadline_ns) {
+        request_map::update_status(request_id, StatusCode::DeadlineExpired, None);
+        refund_balances(request_id, user_id, caller_id, &balances, Vec::new(), &transfer_ids, ts);
+        return Err(format!("Req #{} failed. Deadline expired", request_id));
+    }
+
+    // run the operations in sequence. there are no awaits so each operation sees the pools as left by the previous ones
+    let mut operation_replies = Vec::new();
+    for (i, operation) in operations.iter().enumerate() {
+        match execute_operation(request_id, user_id, operation, &mut balances, ts) {
+            Ok(reply) => operation_replies.push(reply),
+            Err(e) => {
+                refund_balances(request_id, user_id, caller_id, &balances, operation_replies, &transfer_ids, ts);
+                return Err(format!("Req #{} failed. Operation #{} failed. {}", request_id, i + 1, e));
+            }
+        }
+    }
+
+    // successful, send the remaining batch balances to the user and update request with reply
+    let mut claim_ids = Vec::new();
+    send_balances(request_id, user_id, caller_id, &balances, &mut transfer_ids, &mut claim_ids, ts).await;
+
+    let reply = create_batch_reply(request_id, StatusTx::Success, operation_replies, &transfer_ids, &claim_ids, ts);
+    request_map::update_reply(request_id, Reply::Batch(reply.clone()));
+
+    Ok(reply)
+}
+
+/// send the batch balances to the user. any failures to send tokens will be saved as claims
+/// balances not enough to pay for the gas are kept by Kong and recorded on the request
+async fn send_balances(
+    request_id: u64,
+    user_id: u32,
+    to_principal_id: &Account,
+    balances: &BatchBalances,
+    transfer_ids: &mut Vec<u64>,
+    claim_ids: &mut Vec<u64>,
+    ts: u64,
+) {
+    for (token_id, amount) in balances {
+        let Some(token) = token_map::get_by_token_id(*token_id) else {
+            continue;
+        };
+        if nat_is_zero(amount) || record_dust(request_id, StatusCode::SendReceiveTokenFailed, &token, amount) {
+            continue;
+        }
+        let amount_with_gas = nat_subtract(amount, &token.fee()).unwrap_or(nat_zero());
+
+        request_map::update_status(request_id, StatusCode::SendReceiveToken, None);
+
+        match icrc1_transfer(&amount_with_gas, to_principal_id, &token, None).await {
+            Ok(block_id) => {
+                let transfer_id = transfer_map::insert(&StableTransfer {
+                    transfer_id: 0,
+                    request_id,
+                    is_send: false,
+                    amount: amount_with_gas,
+                    token_id: *token_id,
+                    tx_id: TxId::BlockIndex(block_id),
+                    ts,
+                });
+                transfer_ids.push(transfer_id);
+                request_map::update_status(request_id, StatusCode::SendReceiveTokenSuccess, None);
+            }
+            Err(e) => {
+                let message = match save_claim(request_id, user_id, to_principal_id, *token_id, amount, ts) {
+                    Ok(claim_id) => {
+                        claim_ids.push(claim_id);
+                        format!("Saved as claim #{}. {}", claim_id, e)
+                    }
+                    Err(e) => format!("Failed to save claim. {}", e),
+                };
+                request_map::update_status(request_id, StatusCode::SendReceiveTokenFailed, Some(&message));
+            }
+        }
+    }
+}
+
+/// refund the batch balances to the user as claims and update request with the failed reply
+fn refund_balances(
+    request_id: u64,
+    user_id: u32,
+    to_principal_id: &Account,
+    balances: &BatchBalances,
+    operation_replies: Vec<BatchOperationReply>,
+    transfer_ids: &[u64],
+    ts: u64,
+) {
+    let mut claim_ids = Vec::new();
+    for (token_id, amount) in balances.iter().filter(|(_, amount)| !nat_is_zero(amount)) {
+        request_map::update_status(request_id, StatusCode::ReturnPayToken, None);
+        match save_claim(request_id, user_id, to_principal_id, *token_id, amount, ts) {
+            Ok(claim_id) => {
+                claim_ids.push(claim_id);
+                let message = format!("Saved as claim #{}", claim_id);
+                request_map::update_status(request_id, StatusCode::ReturnPayTokenSuccess, Some(&message));
+            }
+            Err(e) => {
+                let message = format!("Failed to save claim. {}", e);
+                request_map::update_status(request_id, StatusCode::ReturnPayTokenFailed, Some(&message));
+            }
+        }
+    }
+
+    let reply = create_batch_reply(request_id, StatusTx::Failed, operation_replies, transfer_ids, &claim_ids, ts);
+    request_map::update_reply(request_id, Reply::Batch(reply));
+}
+
+fn save_claim(request_id: u64, user_id: u32, to_principal_id: &Account, token_id: u32, amount: &Nat, ts: u64) -> Result<u64, String> {
+    claim_map::insert(&StableClaim::new(
+        user_id,
+        token_id,
+        amount,
+        Some(request_id),
+        Some(Address::PrincipalId(*to_principal_id)),
+        ts,
+    ))
+}
+
+pub fn archive_to_kong_data(request: &StableRequest) {
+    match &request.reply {
+        Reply::Batch(reply) => {
+            let tx_ids: Vec<u64> = reply
+                .operations
+                .iter()
+                .flat_map(|operation_reply| match operation_reply {
+                    BatchOperationReply::AddLiquidity(reply) => vec![reply.tx_id],
+                    BatchOperationReply::RemoveLiquidity(reply) => std::iter::once(reply.tx_id)
+                        .chain(reply.swap.as_ref().map(|swap| swap.tx_id))
+                        .collect(),
+                    BatchOperationReply::Swap(reply) => vec![reply.tx_id],
+                })
+                .collect();
+            request_map::archive_request_and_reply_to_kong_data(request.request_id, &reply.claim_ids, &reply.transfer_ids, &tx_ids);
+        }
+        _ => request_map::archive_request_to_kong_data(request.request_id),
+    }
+}
@@ -0,0 +1,356 @@
+use candid::Nat;
+use std::collections::BTreeMap;
+
+use super::batch_args::BatchOperation;
+use super::batch_reply::BatchOperationReply;
+
+use crate::add_liquidity::add_liquidity_args::AddLiquidityArgs;
+use crate::add_liquidity::add_liquidity_reply_helpers::create_add_liquidity_reply_with_tx_id;
+use crate::add_liquidity::add_liquidity_transfer_from::update_liquidity_pool;
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::remove_liquidity::remove_liquidity::remove_liquidity_without_payout;
+use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_tx::{add_liquidity_tx::AddLiquidityTx, stable_tx::StableTx, swap_tx::SwapTx, tx_map};
+use crate::swap::swap_args::SwapArgs;
+use crate::swap::swap_reply_helpers::create_swap_reply_with_tx_id;
+use crate::swap::update_liquidity_pool::update_liquidity_pool_single_pool;
+
+/// balances of a batch by token_id. the tokens Kong holds for the user between the operations
+pub type BatchBalances = BTreeMap<u32, Nat>;
+
+/// make sure the operation does not use arguments that are not supported in a batch
+pub fn check_operation(operation: &BatchOperation) -> Result<(), String> {
+    match operation {
+        BatchOperation::AddLiquidity(args) => {
+            if args.tx_id_0.is_some() || args.tx_id_1.is_some() {
+                return Err("Tx_id_0 and Tx_id_1 not supported".to_string());
+            }
+        }
+        BatchOperation::RemoveLiquidity(args) => {
+            if nat_is_zero(&args.remove_lp_token_amount) {
+                return Err("Invalid zero LP token amount".to_string());
+            }
+        }
+        BatchOperation::Swap(args) => {
+            if args.pay_tx_id.is_some() {
+                return Err("Pay tx_id not supported".to_string());
+            }
+            if args.receive_address.is_some() {
+                return Err("Receive address not supported in batch".to_string());
+            }
+            if args.max_pay_amount.is_some() {
+                return Err("Exact-output swaps not supported in batch".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// tokens and amounts the operation takes from the batch balances. an amount of zero takes the whole balance
+pub fn operation_inputs(operation: &BatchOperation) -> Result<Vec<(StableToken, Nat)>, String> {
+    match operation {
+        BatchOperation::AddLiquidity(args) => {
+            let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1, args.fee_tier)?;
+            Ok(vec![
+                (pool.token_0(), args.amount_0.clone()),
+                (pool.token_1(), args.amount_1.clone()),
+            ])
+        }
+        // LP tokens are taken from the user's LP balance
+        BatchOperation::RemoveLiquidity(_) => Ok(Vec::new()),
+        BatchOperation::Swap(args) => {
+            let pay_token = token_map::get_by_token(&args.pay_token)?;
+            let receive_token = token_map::get_by_token(&args.receive_token)?;
+            // fail early if there is no pool to swap through
+            get_swap_pool(&pay_token, &receive_token)?;
+            Ok(vec![(pay_token, args.pay_amount.clone())])
+        }
+    }
+}
+
+/// token_ids the operation adds to the batch balances
+pub fn operation_outputs(operation: &BatchOperation) -> Result<Vec<u32>, String> {
+    match operation {
+        // LP tokens go to the user's LP balance. any amounts not used by the pool ratio stay in the batch balances
+        BatchOperation::AddLiquidity(_) => Ok(Vec::new()),
+        BatchOperation::RemoveLiquidity(args) => match &args.receive_token {
+            Some(receive_token) => Ok(vec![token_map::get_by_token(receive_token)?.token_id()]),
+            None => {
+                let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1, args.fee_tier)?;
+                Ok(vec![pool.token_id_0, pool.token_id_1])
+            }
+        },
+        BatchOperation::Swap(args) => Ok(vec![token_map::get_by_token(&args.receive_token)?.token_id()]),
+    }
+}
+
+/// run the operation with the batch balances. no transfers are done, the outputs are added to the batch balances
+/// if the operation fails, the batch balances are unchanged
+pub fn execute_operation(
+    request_id: u64,
+    user_id: u32,
+    operation: &BatchOperation,
+    balances: &mut BatchBalances,
+    ts: u64,
+) -> Result<BatchOperationReply, String> {
+    match operation {
+        BatchOperation::AddLiquidity(args) => add_liquidity(request_id, user_id, args, balances, ts),
+        BatchOperation::RemoveLiquidity(args) => remove_liquidity(request_id, user_id, args, balances, ts),
+        BatchOperation::Swap(args) => swap(request_id, user_id, args, balances, ts),
+    }
+}
+
+fn add_liquidity(
+    request_id: u64,
+    user_id: u32,
+    args: &AddLiquidityArgs,
+    balances: &mut BatchBalances,
+    ts: u64,
+) -> Result<BatchOperationReply, String> {
+    let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1, args.fee_tier)?;
+    let add_amount_0 = get_amount(balances, &pool.token_0(), &args.amount_0)?;
+    let add_amount_1 = get_amount(balances, &pool.token_1(), &args.amount_1)?;
+
+    let (pool, amount_0, amount_1, add_lp_token_amount) = update_liquidity_pool(
+        request_id,
+        user_id,
+        &pool,
+        &add_amount_0,
+        &add_amount_1,
+        args.min_lp_token_amount.as_ref(),
+        args.deadline_ns,
+        ts,
+    )?;
+    // only the amounts used by the pool ratio are taken, the rest stays in the batch balances
+    sub_balance(balances, pool.token_id_0, &amount_0);
+    sub_balance(balances, pool.token_id_1, &amount_1);
+
+    let add_liquidity_tx = AddLiquidityTx::new_success(
+        pool.pool_id,
+        user_id,
+        request_id,
+        &amount_0,
+        &amount_1,
+        &add_lp_token_amount,
+        &[],
+        &[],
+        ts,
+    );
+    let tx_id = tx_map::insert(&StableTx::AddLiquidity(add_liquidity_tx.clone()));
+
+    Ok(BatchOperationReply::AddLiquidity(create_add_liquidity_reply_with_tx_id(
+        tx_id,
+        &add_liquidity_tx,
+    )))
+}
+
+fn remove_liquidity(
+    request_id: u64,
+    user_id: u32,
+    args: &RemoveLiquidityArgs,
+    balances: &mut BatchBalances,
+    ts: u64,
+) -> Result<BatchOperationReply, String> {
+    let (reply, payouts) = remove_liquidity_without_payout(request_id, user_id, args, ts)?;
+    for (token_id, amount) in payouts {
+        add_balance(balances, token_id, &amount);
+    }
+
+    Ok(BatchOperationReply::RemoveLiquidity(reply))
+}
+
+fn swap(request_id: u64, user_id: u32, args: &SwapArgs, balances: &mut BatchBalances, ts: u64) -> Result<BatchOperationReply, String> {
+    let pay_token = token_map::get_by_token(&args.pay_token)?;
+    let receive_token = token_map::get_by_token(&args.receive_token)?;
+    let pool = get_swap_pool(&pay_token, &receive_token)?;
+    let pay_amount = get_amount(balances, &pay_token, &args.pay_amount)?;
+    // use specified max slippage or use default
+    let max_slippage = args.max_slippage.unwrap_or(kong_settings_map::get().default_max_slippage);
+
+    // swap through the pool without a gas fee as the receive amount stays in the batch balances. gas is only taken once when the balances are sent
+    let (receive_amount, mid_price, price, slippage, swaps) = update_liquidity_pool_single_pool(
+        request_id,
+        &pool,
+        &pay_token,
+        &pay_amount,
+        &receive_token,
+        args.receive_amount.as_ref(),
+        max_slippage,
+        args.deadline_ns,
+    )?;
+    sub_balance(balances, pay_token.token_id(), &pay_amount);
+    add_balance(balances, receive_token.token_id(), &receive_amount);
+
+    let swap_tx = SwapTx::new_success(
+        user_id,
+        request_id,
+        pay_token.token_id(),
+        &pay_amount,
+        receive_token.token_id(),
+        &receive_amount,
+        mid_price,
+        price,
+        slippage,
+        &swaps,
+        &[],
+        &[],
+        ts,
+    );
+    let tx_id = tx_map::insert(&StableTx::Swap(swap_tx.clone()));
+
+    Ok(BatchOperationReply::Swap(create_swap_reply_with_tx_id(tx_id, &swap_tx)))
+}
+
+/// pool of the pair to swap through. multi-hop swaps are done as consecutive Swap operations
+fn get_swap_pool(pay_token: &StableToken, receive_token: &StableToken) -> Result<StablePool, String> {
+    pool_map::get_by_token_ids(pay_token.token_id(), receive_token.token_id(), None)
+        .or_else(|| pool_map::get_by_token_ids(receive_token.token_id(), pay_token.token_id(), None))
+        .ok_or_else(|| format!("Pool {} not found", pool_map::symbol(pay_token, receive_token, None)))
+}
+
+/// amount of token to take from the batch balances. an amount of zero takes the whole balance
+fn get_amount(balances: &BatchBalances, token: &StableToken, amount: &Nat) -> Result<Nat, String> {
+    let balance = balances.get(&token.token_id()).cloned().unwrap_or(nat_zero());
+    let amount = if nat_is_zero(amount) { balance.clone() } else { amount.clone() };
+    if nat_is_zero(&amount) {
+        return Err(format!("No {} received by previous operations", token.symbol()));
+    }
+    if amount > balance {
+        return Err(format!(
+            "Insufficient {}. {} available, {} required",
+            token.symbol(),
+            balance,
+            amount
+        ));
+    }
+    Ok(amount)
+}
+
+pub fn add_balance(balances: &mut BatchBalances, token_id: u32, amount: &Nat) {
+    let balance = balances.entry(token_id).or_insert(nat_zero());
+    *balance = nat_add(balance, amount);
+}
+
+fn sub_balance(balances: &mut BatchBalances, token_id: u32, amount: &Nat) {
+    let balance = balances.entry(token_id).or_insert(nat_zero());
+    *balance = nat_subtract(balance, amount).unwrap_or(nat_zero());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::{insert_pool, insert_token, new_pool};
+
+    // TOKEN_0/TOKEN_1 pool, TOKEN_2 has no pool
+    fn setup_pool() -> StablePool {
+        insert_token(1, "TOKEN_0");
+        insert_token(2, "TOKEN_1");
+        insert_token(3, "TOKEN_2");
+        let pool = new_pool(1, 1, 2, 1_000_000_000, 4_000_000_000);
+        insert_pool(&pool);
+        pool
+    }
+
+    fn swap_args(pay_token: &str, pay_amount: u64, receive_token: &str) -> SwapArgs {
+        SwapArgs {
+            pay_token: pay_token.to_string(),
+            pay_amount: Nat::from(pay_amount),
+            pay_tx_id: None,
+            receive_token: receive_token.to_string(),
+            receive_amount: None,
+            receive_address: None,
+            max_slippage: None,
+            referred_by: None,
+            max_pay_amount: None,
+            deadline_ns: None,
+        }
+    }
+
+    // a failed operation must leave the batch balances and the pools as they were so they can be refunded
+    fn assert_rollback(operation: &BatchOperation, balances: &BatchBalances, pool: &StablePool) -> String {
+        let mut after = balances.clone();
+        let e = execute_operation(1, 1, operation, &mut after, 0).unwrap_err();
+        assert_eq!(&after, balances);
+        let pool_after = pool_map::get_by_pool_id(pool.pool_id).unwrap();
+        assert_eq!(pool_after.balance_0, pool.balance_0);
+        assert_eq!(pool_after.balance_1, pool.balance_1);
+        e
+    }
+
+    #[test]
+    fn test_get_amount() {
+        let token = insert_token(1, "TOKEN_0");
+        let mut balances = BatchBalances::new();
+        // nothing received yet
+        assert!(get_amount(&balances, &token, &nat_zero()).is_err());
+
+        add_balance(&mut balances, 1, &Nat::from(1_000_u32));
+        // zero takes the whole balance
+        assert_eq!(get_amount(&balances, &token, &nat_zero()).unwrap(), Nat::from(1_000_u32));
+        assert_eq!(get_amount(&balances, &token, &Nat::from(400_u32)).unwrap(), Nat::from(400_u32));
+        assert!(get_amount(&balances, &token, &Nat::from(1_001_u32)).is_err());
+    }
+
+    #[test]
+    fn test_add_sub_balance() {
+        let mut balances = BatchBalances::new();
+        add_balance(&mut balances, 1, &Nat::from(1_000_u32));
+        add_balance(&mut balances, 1, &Nat::from(500_u32));
+        sub_balance(&mut balances, 1, &Nat::from(300_u32));
+        assert_eq!(balances.get(&1), Some(&Nat::from(1_200_u32)));
+        // never goes below zero
+        sub_balance(&mut balances, 2, &Nat::from(300_u32));
+        assert_eq!(balances.get(&2), Some(&nat_zero()));
+    }
+
+    #[test]
+    fn test_swap_insufficient_balance_rollback() {
+        let pool = setup_pool();
+        let mut balances = BatchBalances::new();
+        add_balance(&mut balances, 1, &Nat::from(1_000_u32));
+
+        let e = assert_rollback(&BatchOperation::Swap(swap_args("TOKEN_0", 1_001, "TOKEN_1")), &balances, &pool);
+        assert!(e.contains("Insufficient"));
+        // swaps the reverse of the pair through the same pool
+        let e = assert_rollback(&BatchOperation::Swap(swap_args("TOKEN_1", 0, "TOKEN_0")), &balances, &pool);
+        assert!(e.contains("No TOKEN_1"));
+    }
+
+    #[test]
+    fn test_swap_no_pool_rollback() {
+        let pool = setup_pool();
+        let mut balances = BatchBalances::new();
+        add_balance(&mut balances, 1, &Nat::from(1_000_u32));
+
+        // swaps only go through the pool of the pair
+        let e = assert_rollback(&BatchOperation::Swap(swap_args("TOKEN_0", 0, "TOKEN_2")), &balances, &pool);
+        assert!(e.contains("not found"));
+        assert!(operation_inputs(&BatchOperation::Swap(swap_args("TOKEN_0", 0, "TOKEN_2"))).is_err());
+    }
+
+    #[test]
+    fn test_add_liquidity_insufficient_balance_rollback() {
+        let pool = setup_pool();
+        let mut balances = BatchBalances::new();
+        add_balance(&mut balances, 1, &Nat::from(1_000_u32));
+        add_balance(&mut balances, 2, &Nat::from(4_000_u32));
+
+        let args = AddLiquidityArgs {
+            token_0: "TOKEN_0".to_string(),
+            amount_0: nat_zero(),
+            tx_id_0: None,
+            token_1: "TOKEN_1".to_string(),
+            amount_1: Nat::from(4_001_u32),
+            tx_id_1: None,
+            fee_tier: None,
+            deadline_ns: None,
+            min_lp_token_amount: None,
+        };
+        let e = assert_rollback(&BatchOperation::AddLiquidity(args), &balances, &pool);
+        assert!(e.contains("Insufficient TOKEN_1"));
+    }
+}
@@ -0,0 +1,22 @@
+use super::batch_reply::{BatchOperationReply, BatchReply};
+
+use crate::stable_tx::status_tx::StatusTx;
+use crate::transfers::transfer_reply_helpers::to_transfer_ids;
+
+pub fn create_batch_reply(
+    request_id: u64,
+    status: StatusTx,
+    operations: Vec<BatchOperationReply>,
+    transfer_ids: &[u64],
+    claim_ids: &[u64],
+    ts: u64,
+) -> BatchReply {
+    BatchReply {
+        request_id,
+        status: status.to_string(),
+        operations,
+        transfer_ids: to_transfer_ids(transfer_ids),
+        claim_ids: claim_ids.to_vec(),
+        ts,
+    }
+}
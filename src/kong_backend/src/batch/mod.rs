@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+pub mod batch;
+pub mod batch_args;
+pub mod batch_operations;
+pub mod batch_reply;
+pub mod batch_reply_helpers;
@@ -10,7 +10,8 @@ use serde::Deserialize;
 use std::time::Duration;
 
 use super::stable_memory::{
-    CLAIMS_TIMER_ID, REQUEST_MAP_ARCHIVE_TIMER_ID, STATS_TIMER_ID, TRANSFER_MAP_ARCHIVE_TIMER_ID, TX_MAP_ARCHIVE_TIMER_ID,
+    BUYBACK_TIMER_ID, CLAIMS_TIMER_ID, DCA_TIMER_ID, ORDERS_TIMER_ID, REQUEST_MAP_ARCHIVE_TIMER_ID, STATS_TIMER_ID,
+    TRANSFER_MAP_ARCHIVE_TIMER_ID, TX_MAP_ARCHIVE_TIMER_ID,
 };
 use super::{APP_NAME, APP_VERSION};
 
@@ -21,14 +22,24 @@ use crate::add_pool::add_pool_args::AddPoolArgs;
 use crate::add_pool::add_pool_reply::AddPoolReply;
 use crate::add_token::add_token_args::AddTokenArgs;
 use crate::add_token::add_token_reply::AddTokenReply;
+use crate::batch::batch_args::BatchArgs;
+use crate::batch::batch_reply::BatchReply;
 use crate::claims::claims::process_claims;
+use crate::dca::process_dcas::process_dcas;
 use crate::ic::canister_address::KONG_BACKEND;
+use crate::ic::get_time::get_time;
 use crate::ic::logging::info_log;
+use crate::orders::process_orders::process_orders;
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_archive::archive_lp_block_map;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_history::lp_history_map;
+use crate::stable_pool::liquidity_bootstrapping::end_liquidity_bootstrapping;
 use crate::stable_pool::pool_stats::update_pool_stats;
 use crate::stable_request::request_archive::archive_request_map;
 use crate::stable_transfer::transfer_archive::archive_transfer_map;
 use crate::stable_tx::tx_archive::archive_tx_map;
+use crate::treasury::process_buybacks::process_buybacks;
 
 #[init]
 async fn init() {
@@ -42,18 +53,44 @@ async fn init() {
     });
     CLAIMS_TIMER_ID.with(|cell| cell.set(timer_id));
 
-    // start the background timer to process stats
+    // start the background timer to process stats and end liquidity bootstrapping sales
     let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().stats_interval_secs), || {
         ic_cdk::spawn(async {
             update_pool_stats();
+            end_liquidity_bootstrapping();
         });
     });
     STATS_TIMER_ID.with(|cell| cell.set(timer_id));
 
+    // start the background timer to process limit orders
+    let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().orders_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_orders().await;
+        });
+    });
+    ORDERS_TIMER_ID.with(|cell| cell.set(timer_id));
+
+    // start the background timer to process DCA schedules
+    let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().dca_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_dcas().await;
+        });
+    });
+    DCA_TIMER_ID.with(|cell| cell.set(timer_id));
+
+    // start the background timer to buy back and burn Kong fees
+    let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().buyback_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_buybacks().await;
+        });
+    });
+    BUYBACK_TIMER_ID.with(|cell| cell.set(timer_id));
+
     // start the background timer to archive tx map
     let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().txs_archive_interval_secs), || {
         ic_cdk::spawn(async {
             archive_tx_map(); // archive transaction map
+            archive_lp_block_map(); // archive LP block log
         });
     });
     TX_MAP_ARCHIVE_TIMER_ID.with(|cell| cell.set(timer_id));
@@ -88,6 +125,15 @@ fn pre_upgrade() {
     // clear the background timer for processing stats
     STATS_TIMER_ID.with(|cell| clear_timer(cell.get()));
 
+    // clear the background timer for processing limit orders
+    ORDERS_TIMER_ID.with(|cell| clear_timer(cell.get()));
+
+    // clear the background timer for processing DCA schedules
+    DCA_TIMER_ID.with(|cell| clear_timer(cell.get()));
+
+    // clear the background timer for buying back Kong fees
+    BUYBACK_TIMER_ID.with(|cell| clear_timer(cell.get()));
+
     // clear the background timer for archiving tx map
     TX_MAP_ARCHIVE_TIMER_ID.with(|cell| clear_timer(cell.get()));
 
@@ -100,6 +146,23 @@ fn pre_upgrade() {
 
 #[post_upgrade]
 async fn post_upgrade() {
+    // record the LP balances from before the LP block log as mint blocks
+    let num_blocks = lp_block_map::backfill_mints(get_time());
+    if num_blocks > 0 {
+        info_log(&format!("Backfilled {} LP mint blocks", num_blocks));
+    }
+
+    // record the liquidity txs from before the LP histories of lp_performance()
+    let num_histories = lp_history_map::backfill();
+    if num_histories > 0 {
+        info_log(&format!("Backfilled {} LP histories", num_histories));
+    }
+
+    // certified data is cleared on upgrade, certify the tip of the LP block log again
+    if let Some(tip) = lp_block_map::get_tip() {
+        lp_block_map::set_certified_data(&tip);
+    }
+
     // start the background timer to process claims
     let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().claims_interval_secs), || {
         ic_cdk::spawn(async {
@@ -108,18 +171,44 @@ async fn post_upgrade() {
     });
     CLAIMS_TIMER_ID.with(|cell| cell.set(timer_id));
 
-    // start the background timer to process stats
+    // start the background timer to process stats and end liquidity bootstrapping sales
     let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().stats_interval_secs), || {
         ic_cdk::spawn(async {
             update_pool_stats();
+            end_liquidity_bootstrapping();
         });
     });
     STATS_TIMER_ID.with(|cell| cell.set(timer_id));
 
+    // start the background timer to process limit orders
+    let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().orders_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_orders().await;
+        });
+    });
+    ORDERS_TIMER_ID.with(|cell| cell.set(timer_id));
+
+    // start the background timer to process DCA schedules
+    let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().dca_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_dcas().await;
+        });
+    });
+    DCA_TIMER_ID.with(|cell| cell.set(timer_id));
+
+    // start the background timer to buy back and burn Kong fees
+    let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().buyback_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_buybacks().await;
+        });
+    });
+    BUYBACK_TIMER_ID.with(|cell| cell.set(timer_id));
+
     // start the background timer to archive tx map
     let timer_id = set_timer_interval(Duration::from_secs(kong_settings_map::get().txs_archive_interval_secs), || {
         ic_cdk::spawn(async {
             archive_tx_map();
+            archive_lp_block_map();
         });
     });
     TX_MAP_ARCHIVE_TIMER_ID.with(|cell| cell.set(timer_id));
@@ -164,6 +253,10 @@ fn icrc10_supported_standards() -> Vec<SupportedStandard> {
             url: "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-10/ICRC-10.md".to_string(),
             name: "ICRC-10".to_string(),
         },
+        SupportedStandard {
+            url: "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-3/README.md".to_string(),
+            name: "ICRC-3".to_string(),
+        },
         SupportedStandard {
             url: "https://github.com/dfinity/wg-identity-authentication/blob/main/topics/ICRC-21/icrc_21_consent_msg.md".to_string(),
             name: "ICRC-21".to_string(),
@@ -175,6 +268,25 @@ fn icrc10_supported_standards() -> Vec<SupportedStandard> {
     ]
 }
 
+/// standards of the LP ledger. the ICRC-1 and ICRC-2 endpoints are prefixed with lp_ and take the LP token as their first argument
+#[query]
+fn icrc1_supported_standards() -> Vec<SupportedStandard> {
+    vec![
+        SupportedStandard {
+            url: "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-1/README.md".to_string(),
+            name: "ICRC-1".to_string(),
+        },
+        SupportedStandard {
+            url: "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-2/README.md".to_string(),
+            name: "ICRC-2".to_string(),
+        },
+        SupportedStandard {
+            url: "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-3/README.md".to_string(),
+            name: "ICRC-3".to_string(),
+        },
+    ]
+}
+
 #[update]
 fn icrc21_canister_call_consent_message(consent_msg_request: ConsentMessageRequest) -> Result<ConsentInfo, ErrorInfo> {
     let metadata = ConsentMessageMetadata {
@@ -12,12 +12,14 @@ use crate::ic::{
 };
 use crate::stable_claim::claim_map;
 use crate::stable_claim::stable_claim::{ClaimStatus, StableClaim};
+use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_memory::CLAIM_MAP;
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
 use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
 use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
 use crate::stable_user::stable_user::CLAIMS_TIMER_USER_ID;
 use crate::transfers::transfer_reply_helpers::to_transfer_ids;
+use crate::treasury::process_buybacks::is_burn_claim;
 
 /// send out outstanding claims
 pub async fn process_claims() {
@@ -68,7 +70,8 @@ pub async fn process_claims() {
             // create new request with CLAIMS_TIMER_USER_ID as user_id
             let request_id = request_map::insert(&StableRequest::new(CLAIMS_TIMER_USER_ID, &Request::Claim(claim.claim_id), ts));
 
-            match process_claim(request_id, claim.claim_id, &token, &claim.amount, to_address, ts).await {
+            let burn = is_burn_claim(claim);
+            match process_claim(request_id, claim.claim_id, &token, &claim.amount, to_address, burn, ts).await {
                 Ok(_) => {
                     consecutive_errors = 0;
                 }
@@ -85,22 +88,25 @@ pub async fn process_claims() {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_claim(
     request_id: u64,
     claim_id: u64,
     token: &StableToken,
     amount: &Nat,
     to_address: &Address,
+    burn: bool,
     ts: u64,
 ) -> Result<ClaimReply, String> {
     let chain = token.chain();
     let symbol = token.symbol();
+    let fee = if burn { nat_zero() } else { token.fee() };
 
     let mut transfer_ids = Vec::new();
 
     request_map::update_status(request_id, StatusCode::Start, None);
 
-    let reply = match send_claim(request_id, claim_id, token, amount, to_address, &mut transfer_ids, ts).await {
+    let reply = match send_claim(request_id, claim_id, token, amount, to_address, burn, &mut transfer_ids, ts).await {
         Ok(_) => {
             request_map::update_status(request_id, StatusCode::Success, None);
 
@@ -110,7 +116,7 @@ async fn process_claim(
                 chain: chain.to_string(),
                 symbol: symbol.to_string(),
                 amount: amount.clone(),
-                fee: token.fee(),
+                fee: fee.clone(),
                 to_address: to_address.to_string(),
                 transfer_ids: to_transfer_ids(&transfer_ids),
                 ts,
@@ -125,7 +131,7 @@ async fn process_claim(
                 chain: chain.to_string(),
                 symbol: symbol.to_string(),
                 amount: amount.clone(),
-                fee: token.fee(),
+                fee: fee.clone(),
                 to_address: to_address.to_string(),
                 transfer_ids: to_transfer_ids(&transfer_ids),
                 ts,
@@ -138,12 +144,14 @@ async fn process_claim(
     Ok(reply)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn send_claim(
     request_id: u64,
     claim_id: u64,
     token: &StableToken,
     amount: &Nat,
     to_address: &Address,
+    burn: bool,
     transfer_ids: &mut Vec<u64>,
     ts: u64,
 ) -> Result<(), String> {
@@ -152,7 +160,7 @@ async fn send_claim(
 
     request_map::update_status(request_id, StatusCode::ClaimToken, None);
 
-    let amount_with_gas = nat_subtract(amount, &token.fee()).unwrap_or(nat_zero());
+    let amount_with_gas = claim_amount_with_gas(amount, token, burn);
     match match to_address {
         AccountId(to_account_id) => icp_transfer(&amount_with_gas, to_account_id, token, None).await,
         PrincipalId(to_principal_id) => icrc1_transfer(&amount_with_gas, to_principal_id, token, None).await,
@@ -171,6 +179,10 @@ async fn send_claim(
 
             // claim successful. update claim status
             claim_map::update_claimed_status(claim_id, request_id, transfer_id);
+            if burn {
+                // buyback burned once its claim is sent to the minting account
+                kong_settings_map::add_buyback_burned(amount, ts);
+            }
 
             request_map::update_status(request_id, StatusCode::ClaimTokenSuccess, None);
 
@@ -192,3 +204,38 @@ async fn send_claim(
         }
     }
 }
+
+/// amount sent for a claim. burns to the minting account pay no fee to the ledger so the whole claim is burned
+fn claim_amount_with_gas(amount: &Nat, token: &StableToken, burn: bool) -> Nat {
+    if burn {
+        amount.clone()
+    } else {
+        nat_subtract(amount, &token.fee()).unwrap_or(nat_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::ic_token;
+    use crate::stable_token::ic_token::ICToken;
+
+    #[test]
+    fn test_claim_amount_with_gas() {
+        let token = StableToken::IC(ICToken {
+            fee: Nat::from(10_000_u32),
+            ..ic_token(1, "KONG")
+        });
+        assert_eq!(
+            claim_amount_with_gas(&Nat::from(1_000_000_u32), &token, false),
+            Nat::from(990_000_u32)
+        );
+        assert_eq!(claim_amount_with_gas(&Nat::from(5_000_u32), &token, false), nat_zero());
+        // burns pay no fee
+        assert_eq!(
+            claim_amount_with_gas(&Nat::from(1_000_000_u32), &token, true),
+            Nat::from(1_000_000_u32)
+        );
+    }
+}
@@ -24,7 +24,7 @@ fn update_kong_settings(kong_settings: String) -> Result<String, String> {
     };
 
     KONG_SETTINGS.with(|s| {
-        _ = s.borrow_mut().set(kong_settings);
+        _ = s.borrow_mut().set(kong_settings.migrate_fee_bps().clamp_max_swap_hops());
     });
 
     Ok("Kong settings updated".to_string())
@@ -42,8 +42,9 @@ fn set_kong_settings(update_settings: String) -> Result<String, String> {
     let mut kong_settings_value = kong_settings_value;
     json_helpers::merge(&mut kong_settings_value, &updates);
 
-    let kong_settings: StableKongSettings =
-        serde_json::from_value(kong_settings_value).map_err(|e| format!("Failed to parse updated Kong settings: {}", e))?;
+    let kong_settings = serde_json::from_value::<StableKongSettings>(kong_settings_value)
+        .map_err(|e| format!("Failed to parse updated Kong settings: {}", e))?
+        .clamp_max_swap_hops();
 
     KONG_SETTINGS.with(|m| {
         m.borrow_mut()
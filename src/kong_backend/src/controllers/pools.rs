@@ -4,17 +4,21 @@ use icrc_ledger_types::icrc1::account::Account;
 use std::collections::BTreeMap;
 
 use crate::helpers::nat_helpers::nat_zero;
+use crate::ic::get_time::get_time;
 use crate::ic::guards::caller_is_kingkong;
 use crate::remove_liquidity::remove_liquidity::remove_liquidity_from_pool;
 use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
 use crate::stable_lp_token::lp_token_map;
 use crate::stable_memory::{LP_TOKEN_MAP, POOL_MAP, USER_MAP};
 use crate::stable_pool::pool_map;
-use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
+use crate::stable_pool::stable_pool::{StablePool, StablePoolId, MAX_AMP, MAX_LP_FEE_PIPS, MIN_AMP};
 use crate::stable_token::token::Token;
 use crate::stable_user::stable_user::StableUserId;
 
 const MAX_POOLS: usize = 1_000;
+// StableSwap amplification coefficient ramps must take at least a day and change A by at most 10x
+const MIN_AMP_RAMP_NS: u64 = 86_400_000_000_000;
+const MAX_AMP_CHANGE: u64 = 10;
 
 /// serializes POOL_MAP for backup
 #[query(hidden = true, guard = "caller_is_kingkong")]
@@ -47,13 +51,101 @@ fn update_pools(tokens: String) -> Result<String, String> {
     POOL_MAP.with(|user_map| {
         let mut map = user_map.borrow_mut();
         for (k, v) in pools {
-            map.insert(k, v);
+            map.insert(k, v.migrate_fee_bps());
         }
     });
 
     Ok("Pools updated".to_string())
 }
 
+/// ramp the StableSwap amplification coefficient A of a pool linearly from its current value to future_amp by future_ts
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn ramp_amp(symbol: String, future_amp: u64, future_ts: u64) -> Result<String, String> {
+    let pool = pool_map::get_by_token(&symbol)?;
+    let ts = get_time();
+    let amp = pool.get_amp_at(ts).ok_or(format!("Pool {} is not a StableSwap pool", symbol))?;
+    if !(MIN_AMP..=MAX_AMP).contains(&future_amp) {
+        return Err(format!("Amplification coefficient must be between {} and {}", MIN_AMP, MAX_AMP));
+    }
+    if future_ts < ts + MIN_AMP_RAMP_NS {
+        return Err("Ramp must take at least 1 day".to_string());
+    }
+    if future_amp > amp * MAX_AMP_CHANGE || future_amp * MAX_AMP_CHANGE < amp {
+        return Err(format!("Amplification coefficient can change by at most {}x", MAX_AMP_CHANGE));
+    }
+
+    pool_map::update(&StablePool {
+        amp_initial: amp,
+        amp_initial_ts: ts,
+        amp_future: future_amp,
+        amp_future_ts: future_ts,
+        ..pool
+    });
+
+    Ok(format!("Pool {} A ramping from {} to {}", symbol, amp, future_amp))
+}
+
+/// stop ramping the StableSwap amplification coefficient A of a pool at its current value
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn stop_ramp_amp(symbol: String) -> Result<String, String> {
+    let pool = pool_map::get_by_token(&symbol)?;
+    let ts = get_time();
+    let amp = pool.get_amp_at(ts).ok_or(format!("Pool {} is not a StableSwap pool", symbol))?;
+
+    pool_map::update(&StablePool {
+        amp_initial: amp,
+        amp_initial_ts: ts,
+        amp_future: amp,
+        amp_future_ts: ts,
+        ..pool
+    });
+
+    Ok(format!("Pool {} A stopped at {}", symbol, amp))
+}
+
+/// set the dynamic LP fee of a pool. the LP fee rises with the volatility of the pool's price measured over volatility_window_secs
+/// from min_lp_fee_pips by volatility_fee_pct percent of the volatility in pips, up to max_lp_fee_pips
+/// dynamic_fee = false uses the static lp_fee_pips again
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_dynamic_fee(
+    symbol: String,
+    dynamic_fee: bool,
+    min_lp_fee_pips: u32,
+    max_lp_fee_pips: u32,
+    volatility_fee_pct: u32,
+    volatility_window_secs: u64,
+) -> Result<String, String> {
+    let pool = pool_map::get_by_token(&symbol)?;
+    if dynamic_fee {
+        if min_lp_fee_pips > max_lp_fee_pips {
+            return Err("Min LP fee cannot be greater than max LP fee".to_string());
+        }
+        if max_lp_fee_pips > MAX_LP_FEE_PIPS {
+            return Err(format!("LP fee cannot be greater than {} pips", MAX_LP_FEE_PIPS));
+        }
+        if volatility_window_secs == 0 {
+            return Err("Volatility window must be greater than zero".to_string());
+        }
+    }
+
+    pool_map::update(&StablePool {
+        dynamic_fee,
+        min_lp_fee_pips,
+        max_lp_fee_pips,
+        volatility_fee_pct,
+        volatility_window_secs,
+        ..pool
+    });
+
+    match dynamic_fee {
+        true => Ok(format!(
+            "Pool {} dynamic LP fee between {} and {} pips",
+            symbol, min_lp_fee_pips, max_lp_fee_pips
+        )),
+        false => Ok(format!("Pool {} static LP fee of {} pips", symbol, pool.lp_fee_pips)),
+    }
+}
+
 /// remove pool, LP token and all LP positions
 #[update(hidden = true, guard = "caller_is_kingkong")]
 fn remove_pool(symbol: String) -> Result<String, String> {
@@ -99,7 +191,14 @@ async fn remove_lps_from_pool(symbol: String) -> Result<String, String> {
         let args = RemoveLiquidityArgs {
             token_0: token_0.clone(),
             token_1: token_1.clone(),
+            fee_tier: pool.fee_tier,
             remove_lp_token_amount,
+            deadline_ns: None,
+            min_amount_0: None,
+            min_amount_1: None,
+            receive_token: None,
+            max_slippage: None,
+            min_receive_amount: None,
         };
         match Principal::from_text(principal_id) {
             Ok(principal) => {
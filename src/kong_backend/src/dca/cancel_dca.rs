@@ -0,0 +1,18 @@
+use ic_cdk::update;
+
+use super::dca_reply::DcaReply;
+use super::dca_reply_helpers::to_dca_reply;
+use super::pause_dca::get_user_dca;
+
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::stable_dca::{dca_map, stable_dca::DcaStatus};
+
+/// cancel an active or paused DCA schedule. no further swaps are executed
+/// as the pay token is transferred at every swap, there is nothing to return. the user may revoke the remaining ICRC-2 approval
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+pub fn cancel_dca(dca_id: u64) -> Result<DcaReply, String> {
+    let dca = get_user_dca(dca_id)?;
+    let dca = dca_map::update_status(dca.dca_id, &[DcaStatus::Active, DcaStatus::Paused], DcaStatus::Cancelled)
+        .ok_or_else(|| format!("DCA #{} is {}", dca_id, dca.status))?;
+    Ok(to_dca_reply(&dca))
+}
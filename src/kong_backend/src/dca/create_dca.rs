@@ -0,0 +1,81 @@
+use ic_cdk::update;
+
+use super::dca_args::DcaArgs;
+use super::dca_reply::DcaReply;
+use super::dca_reply_helpers::to_dca_reply;
+
+use crate::helpers::nat_helpers::nat_is_zero;
+use crate::ic::address::Address;
+use crate::ic::address_helpers::get_address;
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::ic::id::caller_id;
+use crate::stable_dca::{
+    dca_map,
+    stable_dca::{DcaFailurePolicy, StableDca},
+};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_user::user_map;
+use crate::swap::swap_amounts::swap_mid_price;
+
+/// create a recurring swap schedule. swaps pay_amount of pay_token for receive_token every interval_secs, num_swaps times
+/// - before calling create_dca(), the user must icrc2_approve (pay_amount+gas) * num_swaps of pay_token
+/// - swaps are executed by the background DCA timer. each swap is a separate swap request and tx
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+pub fn create_dca(args: DcaArgs) -> Result<DcaReply, String> {
+    let pay_token = token_map::get_by_token(&args.pay_token)?;
+    let receive_token = token_map::get_by_token(&args.receive_token)?;
+    if pay_token.token_id() == receive_token.token_id() {
+        return Err("Pay and receive tokens must be different".to_string());
+    }
+    if !pay_token.is_icrc2() {
+        return Err("Pay token must support ICRC2".to_string());
+    }
+    if nat_is_zero(&args.pay_amount) {
+        return Err("Pay amount is zero".to_string());
+    }
+    if args.num_swaps == 0 {
+        return Err("Number of swaps is zero".to_string());
+    }
+    let kong_settings = kong_settings_map::get();
+    // swaps can not be executed more often than the DCA timer runs
+    if args.interval_secs < kong_settings.dca_interval_secs {
+        return Err(format!("Interval must be at least {} seconds", kong_settings.dca_interval_secs));
+    }
+    // use specified max slippage or use default
+    let max_slippage = args.max_slippage.unwrap_or(kong_settings.default_max_slippage);
+    if !max_slippage.is_finite() || max_slippage < 0.0 {
+        return Err("Invalid max slippage".to_string());
+    }
+    // use specified address or default to caller's principal id
+    let to_address = match args.receive_address {
+        Some(ref address) => get_address(address).ok_or("Invalid receive address")?,
+        None => Address::PrincipalId(caller_id()),
+    };
+
+    // make sure there is a route between the tokens
+    swap_mid_price(&pay_token, &receive_token)?;
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    let ts = get_time();
+    let dca = StableDca::new(
+        user_id,
+        pay_token.token_id(),
+        &args.pay_amount,
+        receive_token.token_id(),
+        max_slippage,
+        &caller_id(),
+        &to_address,
+        args.interval_secs,
+        args.num_swaps,
+        args.on_failure.as_ref().unwrap_or(&DcaFailurePolicy::Skip),
+        args.start_at.map_or(ts, |start_at| start_at.max(ts)),
+        ts,
+    );
+    let dca_id = dca_map::insert(&dca);
+
+    Ok(to_dca_reply(&StableDca { dca_id, ..dca }))
+}
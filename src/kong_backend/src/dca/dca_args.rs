@@ -0,0 +1,18 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use crate::stable_dca::stable_dca::DcaFailurePolicy;
+
+/// Data structure for the arguments of the `create_dca` function.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct DcaArgs {
+    pub pay_token: String,
+    pub pay_amount: Nat, // pay amount of each swap
+    pub receive_token: String,
+    pub receive_address: Option<String>,
+    pub max_slippage: Option<f64>, // max slippage of each swap
+    pub interval_secs: u64,
+    pub num_swaps: u32,
+    pub start_at: Option<u64>,                // nanoseconds since the Unix epoch of the first swap. defaults to now
+    pub on_failure: Option<DcaFailurePolicy>, // defaults to Skip
+}
@@ -0,0 +1,27 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use crate::stable_dca::stable_dca::DcaFailurePolicy;
+
+/// Data structure for the reply of the `create_dca`, `pause_dca`, `resume_dca`, `cancel_dca` and `dcas` functions.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct DcaReply {
+    pub dca_id: u64,
+    pub status: String,
+    pub pay_chain: String,
+    pub pay_symbol: String,
+    pub pay_amount: Nat,
+    pub receive_chain: String,
+    pub receive_symbol: String,
+    pub max_slippage: f64,
+    pub to_address: String,
+    pub interval_secs: u64,
+    pub num_swaps: u32,
+    pub swaps_done: u32,
+    pub swaps_skipped: u32,
+    pub on_failure: DcaFailurePolicy,
+    pub next_swap_at: u64,
+    pub request_ids: Vec<u64>,
+    pub tx_ids: Vec<u64>,
+    pub ts: u64,
+}
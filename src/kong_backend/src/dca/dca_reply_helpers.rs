@@ -0,0 +1,36 @@
+use super::dca_reply::DcaReply;
+
+use crate::stable_dca::stable_dca::StableDca;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+
+pub fn to_dca_reply(dca: &StableDca) -> DcaReply {
+    let (pay_chain, pay_symbol) = token_map::get_by_token_id(dca.pay_token_id).map_or_else(
+        || ("Pay chain not found".to_string(), "Pay symbol not found".to_string()),
+        |token| (token.chain().to_string(), token.symbol().to_string()),
+    );
+    let (receive_chain, receive_symbol) = token_map::get_by_token_id(dca.receive_token_id).map_or_else(
+        || ("Receive chain not found".to_string(), "Receive symbol not found".to_string()),
+        |token| (token.chain().to_string(), token.symbol().to_string()),
+    );
+    DcaReply {
+        dca_id: dca.dca_id,
+        status: dca.status.to_string(),
+        pay_chain,
+        pay_symbol,
+        pay_amount: dca.pay_amount.clone(),
+        receive_chain,
+        receive_symbol,
+        max_slippage: dca.max_slippage,
+        to_address: dca.to_address.to_string(),
+        interval_secs: dca.interval_secs,
+        num_swaps: dca.num_swaps,
+        swaps_done: dca.swaps_done,
+        swaps_skipped: dca.swaps_skipped,
+        on_failure: dca.on_failure.clone(),
+        next_swap_at: dca.next_swap_at,
+        request_ids: dca.request_ids.clone(),
+        tx_ids: dca.tx_ids.clone(),
+        ts: dca.ts,
+    }
+}
@@ -0,0 +1,19 @@
+use ic_cdk::query;
+
+use super::dca_reply::DcaReply;
+use super::dca_reply_helpers::to_dca_reply;
+
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::stable_dca::dca_map;
+use crate::stable_user::user_map;
+
+/// returns a specific DCA schedule or all DCA schedules of the user
+#[query(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn dcas(dca_id: Option<u64>) -> Result<Vec<DcaReply>, String> {
+    let user_id = match user_map::get_by_caller() {
+        Ok(Some(caller)) => caller.user_id,
+        Ok(None) | Err(_) => return Ok(Vec::new()),
+    };
+    let dcas = dca_map::get_by_dca_and_user_id(dca_id, user_id).iter().map(to_dca_reply).collect();
+    Ok(dcas)
+}
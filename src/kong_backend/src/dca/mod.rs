@@ -0,0 +1,8 @@
+pub mod cancel_dca;
+pub mod create_dca;
+pub mod dca_args;
+pub mod dca_reply;
+pub mod dca_reply_helpers;
+pub mod dcas;
+pub mod pause_dca;
+pub mod process_dcas;
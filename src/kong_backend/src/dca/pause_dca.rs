@@ -0,0 +1,41 @@
+use ic_cdk::update;
+
+use super::dca_reply::DcaReply;
+use super::dca_reply_helpers::to_dca_reply;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::stable_dca::{
+    dca_map,
+    stable_dca::{DcaStatus, StableDca},
+};
+use crate::stable_user::user_map;
+
+/// pause an active DCA schedule
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+pub fn pause_dca(dca_id: u64) -> Result<DcaReply, String> {
+    let dca = get_user_dca(dca_id)?;
+    let dca = dca_map::update_status(dca.dca_id, &[DcaStatus::Active], DcaStatus::Paused)
+        .ok_or_else(|| format!("DCA #{} is {}", dca_id, dca.status))?;
+    Ok(to_dca_reply(&dca))
+}
+
+/// resume a paused DCA schedule. if the next swap is overdue, it is executed on the next timer run
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+pub fn resume_dca(dca_id: u64) -> Result<DcaReply, String> {
+    let dca = get_user_dca(dca_id)?;
+    let mut dca = dca_map::update_status(dca.dca_id, &[DcaStatus::Paused], DcaStatus::Active)
+        .ok_or_else(|| format!("DCA #{} is {}", dca_id, dca.status))?;
+    // don't execute missed swaps, only the next one
+    dca.next_swap_at = dca.next_swap_at.max(get_time());
+    dca_map::update(&dca);
+    Ok(to_dca_reply(&dca))
+}
+
+/// get DCA schedule of the caller
+pub fn get_user_dca(dca_id: u64) -> Result<StableDca, String> {
+    let user_id = user_map::get_by_caller()?.ok_or("DCA not found")?.user_id;
+    dca_map::get_by_dca_id(dca_id)
+        .filter(|dca| dca.user_id == user_id)
+        .ok_or("DCA not found".to_string())
+}
@@ -0,0 +1,214 @@
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode, logging::error_log};
+use crate::stable_dca::{
+    dca_map,
+    stable_dca::{DcaFailurePolicy, DcaStatus, StableDca},
+};
+use crate::stable_request::{request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{token::Token, token_map};
+use crate::swap::archive_to_kong_data::archive_to_kong_data;
+use crate::swap::swap_args::SwapArgs;
+use crate::swap::swap_transfer_from::process_swap;
+
+/// execute the due swaps of active DCA schedules
+pub async fn process_dcas() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let ts = get_time();
+    for dca in dca_map::get_due(ts) {
+        if let Err(e) = execute_dca(&dca, ts).await {
+            error_log(&format!("Error executing DCA #{}: {}", dca.dca_id, e));
+        }
+    }
+}
+
+/// execute the next swap of the schedule through the swap path with the user's ICRC-2 approval
+async fn execute_dca(dca: &StableDca, ts: u64) -> Result<(), String> {
+    // set the schedule to executing so the swap can not be executed twice or the schedule changed while swapping
+    let mut dca = dca_map::update_status(dca.dca_id, &[DcaStatus::Active], DcaStatus::Executing)
+        .ok_or_else(|| format!("DCA #{} is not active", dca.dca_id))?;
+
+    let (pay_token, receive_token) = match (
+        token_map::get_by_token_id(dca.pay_token_id),
+        token_map::get_by_token_id(dca.receive_token_id),
+    ) {
+        (Some(pay_token), Some(receive_token)) => (pay_token, receive_token),
+        _ => {
+            dca_map::update_status(dca.dca_id, &[DcaStatus::Executing], DcaStatus::Active);
+            return Err("Token not found".to_string());
+        }
+    };
+
+    let args = SwapArgs {
+        pay_token: pay_token.address_with_chain(),
+        pay_amount: dca.pay_amount.clone(),
+        pay_tx_id: None,
+        receive_token: receive_token.address_with_chain(),
+        receive_amount: None,
+        receive_address: Some(dca.to_address.to_string()),
+        max_slippage: Some(dca.max_slippage),
+        referred_by: None,
+        max_pay_amount: None,
+        deadline_ns: None,
+    };
+    let request_id = request_map::insert(&StableRequest::new(dca.user_id, &Request::Swap(args), ts));
+    dca.request_ids.push(request_id);
+
+    let result = process_swap(
+        request_id,
+        dca.user_id,
+        &dca.from_principal_id,
+        &pay_token,
+        &dca.pay_amount,
+        &receive_token,
+        None,
+        false,
+        dca.max_slippage,
+        None,
+        &dca.to_address,
+        ts,
+    )
+    .await;
+    match result {
+        Ok(_) => request_map::update_status(request_id, StatusCode::Success, None),
+        Err(ref e) => request_map::update_status(request_id, StatusCode::Failed, Some(e)),
+    };
+    if let Some(request) = request_map::get_by_request_and_user_id(Some(request_id), Some(dca.user_id), None).first() {
+        archive_to_kong_data(request);
+    }
+
+    update_schedule(&mut dca, result.as_ref().ok().map(|reply| reply.tx_id), ts);
+    dca_map::update(&dca);
+
+    result.map(|_| ())
+}
+
+/// update the schedule with the swap tx or apply the failure policy if the swap failed
+fn update_schedule(dca: &mut StableDca, tx_id: Option<u64>, ts: u64) {
+    let mut status = DcaStatus::Active;
+    match tx_id {
+        Some(tx_id) => {
+            dca.tx_ids.push(tx_id);
+            next_swap(dca, ts);
+        }
+        None => match dca.on_failure {
+            // retry on the next timer run
+            DcaFailurePolicy::Retry(max_retries) if dca.retries < max_retries => dca.retries += 1,
+            // swap is retried when the schedule is resumed
+            DcaFailurePolicy::Pause => status = DcaStatus::Paused,
+            _ => {
+                dca.swaps_skipped += 1;
+                next_swap(dca, ts);
+            }
+        },
+    }
+    dca.status = if dca.swaps_done >= dca.num_swaps {
+        DcaStatus::Completed
+    } else {
+        status
+    };
+}
+
+/// count the current swap as done and schedule the next one. missed intervals are not caught up
+fn next_swap(dca: &mut StableDca, ts: u64) {
+    let interval_ns = dca.interval_secs * 1_000_000_000;
+    dca.swaps_done += 1;
+    dca.retries = 0;
+    dca.next_swap_at += interval_ns;
+    if dca.next_swap_at <= ts {
+        dca.next_swap_at = ts + interval_ns;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use candid::{Nat, Principal};
+    use icrc_ledger_types::icrc1::account::Account;
+
+    use crate::ic::address::Address;
+
+    const INTERVAL_NS: u64 = 60 * 1_000_000_000;
+
+    fn dca(num_swaps: u32, on_failure: DcaFailurePolicy) -> StableDca {
+        let account = Account::from(Principal::anonymous());
+        StableDca {
+            status: DcaStatus::Executing,
+            ..StableDca::new(
+                1,
+                1,
+                &Nat::from(1_000_u32),
+                2,
+                2.0,
+                &account,
+                &Address::PrincipalId(account),
+                60,
+                num_swaps,
+                &on_failure,
+                INTERVAL_NS,
+                0,
+            )
+        }
+    }
+
+    #[test]
+    fn test_update_schedule_success() {
+        let mut dca = dca(2, DcaFailurePolicy::Skip);
+        update_schedule(&mut dca, Some(7), INTERVAL_NS);
+        assert_eq!(dca.status, DcaStatus::Active);
+        assert_eq!(dca.swaps_done, 1);
+        assert_eq!(dca.tx_ids, vec![7]);
+        assert_eq!(dca.next_swap_at, 2 * INTERVAL_NS);
+
+        update_schedule(&mut dca, Some(8), 2 * INTERVAL_NS);
+        assert_eq!(dca.status, DcaStatus::Completed);
+        assert_eq!(dca.swaps_done, 2);
+    }
+
+    #[test]
+    fn test_update_schedule_missed_intervals() {
+        let mut dca = dca(5, DcaFailurePolicy::Skip);
+        // timer ran late. missed intervals are not caught up
+        update_schedule(&mut dca, Some(7), 4 * INTERVAL_NS + 1);
+        assert_eq!(dca.swaps_done, 1);
+        assert_eq!(dca.next_swap_at, 5 * INTERVAL_NS + 1);
+    }
+
+    #[test]
+    fn test_update_schedule_skip() {
+        let mut dca = dca(2, DcaFailurePolicy::Skip);
+        update_schedule(&mut dca, None, INTERVAL_NS);
+        assert_eq!(dca.status, DcaStatus::Active);
+        assert_eq!(dca.swaps_done, 1);
+        assert_eq!(dca.swaps_skipped, 1);
+        assert!(dca.tx_ids.is_empty());
+        assert_eq!(dca.next_swap_at, 2 * INTERVAL_NS);
+    }
+
+    #[test]
+    fn test_update_schedule_retry() {
+        let mut dca = dca(2, DcaFailurePolicy::Retry(2));
+        update_schedule(&mut dca, None, INTERVAL_NS);
+        update_schedule(&mut dca, None, INTERVAL_NS);
+        assert_eq!(dca.retries, 2);
+        assert_eq!(dca.swaps_done, 0);
+        assert_eq!(dca.next_swap_at, INTERVAL_NS);
+        // out of retries, the swap is skipped
+        update_schedule(&mut dca, None, INTERVAL_NS);
+        assert_eq!(dca.retries, 0);
+        assert_eq!(dca.swaps_done, 1);
+        assert_eq!(dca.swaps_skipped, 1);
+        assert_eq!(dca.status, DcaStatus::Active);
+    }
+
+    #[test]
+    fn test_update_schedule_pause() {
+        let mut dca = dca(2, DcaFailurePolicy::Pause);
+        update_schedule(&mut dca, None, INTERVAL_NS);
+        assert_eq!(dca.status, DcaStatus::Paused);
+        assert_eq!(dca.swaps_done, 0);
+        assert_eq!(dca.next_swap_at, INTERVAL_NS);
+    }
+}
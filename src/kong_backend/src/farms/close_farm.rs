@@ -0,0 +1,79 @@
+use ic_cdk::update;
+
+use super::farm_reply::FarmReply;
+use super::farm_reply_helpers::to_farm_reply;
+
+use crate::helpers::nat_helpers::{nat_add, nat_subtract, nat_zero};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::caller_is_kingkong;
+use crate::ic::id::caller_id;
+use crate::ic::transfer::icrc1_transfer;
+use crate::stable_farm::{farm_map, stable_farm::StableFarm};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+use crate::stable_user::user_map;
+
+/// close a farm after end_at and return its remaining budget less the gas fee to the caller
+/// - the remaining budget is what was never distributed, including the rewards of periods nothing was staked.
+///   rewards already distributed stay in the farm to be harvested
+#[update(hidden = true, guard = "caller_is_kingkong")]
+async fn close_farm(farm_id: u64) -> Result<FarmReply, String> {
+    let ts = get_time();
+    let mut farm = farm_map::get_by_farm_id(farm_id).ok_or("Farm not found")?;
+    let reward_token = token_map::get_by_token_id(farm.reward_token_id).ok_or("Reward token not found")?;
+    let remaining_budget = farm.close(ts)?;
+    if remaining_budget <= reward_token.fee() {
+        return Err(format!(
+            "Remaining budget {} not enough to pay the gas fee {}",
+            remaining_budget,
+            reward_token.fee()
+        ));
+    }
+
+    let user_id = user_map::insert(None)?;
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::CloseFarm(farm_id), ts));
+
+    // take the remaining budget out of the farm before the transfer so it can not be returned twice
+    farm_map::update(&farm);
+
+    request_map::update_status(request_id, StatusCode::ReturnRewardBudget, None);
+    let amount_with_gas = nat_subtract(&remaining_budget, &reward_token.fee()).unwrap_or(nat_zero());
+    match icrc1_transfer(&amount_with_gas, &caller_id(), &reward_token, None).await {
+        Ok(block_id) => {
+            let transfer_id = transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: false,
+                amount: amount_with_gas,
+                token_id: reward_token.token_id(),
+                tx_id: TxId::BlockIndex(block_id),
+                ts,
+            });
+            request_map::update_status(request_id, StatusCode::ReturnRewardBudgetSuccess, None);
+            transfer_map::archive_transfer_to_kong_data(transfer_id);
+        }
+        Err(e) => {
+            // add back with the latest state of the farm
+            if let Some(farm) = farm_map::get_by_farm_id(farm_id) {
+                farm_map::update(&StableFarm {
+                    remaining_budget: nat_add(&farm.remaining_budget, &remaining_budget),
+                    ..farm
+                });
+            }
+            let e = format!("Remaining budget transfer failed: {}", e);
+            request_map::update_status(request_id, StatusCode::ReturnRewardBudgetFailed, Some(&e));
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            request_map::archive_request_to_kong_data(request_id);
+            return Err(e);
+        }
+    }
+
+    let farm = farm_map::get_by_farm_id(farm_id).ok_or("Farm not found")?;
+    let reply = to_farm_reply(&farm, None);
+    request_map::update_reply(request_id, Reply::Farm(reply.clone()));
+    request_map::update_status(request_id, StatusCode::Success, None);
+    request_map::archive_request_to_kong_data(request_id);
+
+    Ok(reply)
+}
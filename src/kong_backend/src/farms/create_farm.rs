@@ -0,0 +1,87 @@
+use ic_cdk::update;
+
+use super::create_farm_args::CreateFarmArgs;
+use super::farm_reply::FarmReply;
+use super::farm_reply_helpers::to_farm_reply;
+
+use crate::helpers::nat_helpers::nat_is_zero;
+use crate::ic::get_time::get_time;
+use crate::ic::guards::caller_is_kingkong;
+use crate::ic::id::caller_id;
+use crate::ic::transfer::icrc2_transfer_from;
+use crate::lp_ledger::lp_ledger::get_lp_token;
+use crate::stable_farm::{farm_map, stable_farm::StableFarm};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+use crate::stable_user::user_map;
+
+/// create a liquidity mining farm. stakers of lp_token earn reward_rate of reward_token per second until end_at
+/// - reward_budget of reward_token is transferred from the caller with icrc2_transfer_from, so the caller must
+///   icrc2_approve reward_budget plus the gas fee first. emissions stop once the budget is distributed
+#[update(hidden = true, guard = "caller_is_kingkong")]
+async fn create_farm(args: CreateFarmArgs) -> Result<FarmReply, String> {
+    let lp_token = get_lp_token(&args.lp_token)?;
+    let reward_token = token_map::get_by_token(&args.reward_token)?;
+    if let StableToken::LP(_) = reward_token {
+        return Err("Reward token can not be an LP token".to_string());
+    }
+    if nat_is_zero(&args.reward_rate) {
+        return Err("Reward rate is zero".to_string());
+    }
+    if nat_is_zero(&args.reward_budget) {
+        return Err("Reward budget is zero".to_string());
+    }
+    let ts = get_time();
+    let start_ts = args.start_at.map_or(ts, |start_at| start_at.max(ts));
+    if args.end_at <= start_ts {
+        return Err("End time must be after start time".to_string());
+    }
+
+    let user_id = user_map::insert(None)?;
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::CreateFarm(args.clone()), ts));
+
+    // fund the farm before it is created so rewards are always backed by tokens held by Kong
+    request_map::update_status(request_id, StatusCode::ReceiveRewardBudget, None);
+    let kong_backend = kong_settings_map::get().kong_backend_account;
+    let block_id = match icrc2_transfer_from(&reward_token, &args.reward_budget, &caller_id(), &kong_backend).await {
+        Ok(block_id) => block_id,
+        Err(e) => {
+            let e = format!("Reward budget transfer failed: {}", e);
+            request_map::update_status(request_id, StatusCode::ReceiveRewardBudgetFailed, Some(&e));
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            request_map::archive_request_to_kong_data(request_id);
+            return Err(e);
+        }
+    };
+    let transfer_id = transfer_map::insert(&StableTransfer {
+        transfer_id: 0,
+        request_id,
+        is_send: true,
+        amount: args.reward_budget.clone(),
+        token_id: reward_token.token_id(),
+        tx_id: TxId::BlockIndex(block_id),
+        ts,
+    });
+    request_map::update_status(request_id, StatusCode::ReceiveRewardBudgetSuccess, None);
+
+    let farm = StableFarm::new(
+        lp_token.token_id(),
+        reward_token.token_id(),
+        &args.reward_rate,
+        &args.reward_budget,
+        start_ts,
+        args.end_at,
+        ts,
+    );
+    let farm_id = farm_map::insert(&farm);
+
+    let reply = to_farm_reply(&StableFarm { farm_id, ..farm }, None);
+    request_map::update_reply(request_id, Reply::Farm(reply.clone()));
+    request_map::update_status(request_id, StatusCode::Success, None);
+    request_map::archive_request_to_kong_data(request_id);
+    transfer_map::archive_transfer_to_kong_data(transfer_id);
+
+    Ok(reply)
+}
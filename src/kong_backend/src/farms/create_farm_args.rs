@@ -0,0 +1,14 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `create_farm` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFarmArgs {
+    pub lp_token: String,      // LP token symbol or address. ie. "ckBTC_ckUSDT" or "LP.ckBTC_ckUSDT"
+    pub reward_token: String,  // reward token symbol or address
+    pub reward_rate: Nat,      // reward token amount per second shared by all stakers
+    pub reward_budget: Nat,    // reward tokens transferred from the caller with icrc2_transfer_from. emissions stop once distributed
+    pub start_at: Option<u64>, // nanoseconds since the Unix epoch rewards start. defaults to now
+    pub end_at: u64,           // nanoseconds since the Unix epoch rewards end
+}
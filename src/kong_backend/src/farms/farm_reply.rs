@@ -0,0 +1,22 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the reply of the `create_farm`, `close_farm`, `stake`, `unstake` and `farms` functions.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct FarmReply {
+    pub farm_id: u64,
+    pub lp_token_symbol: String,
+    pub reward_chain: String,
+    pub reward_symbol: String,
+    pub reward_rate: Nat,
+    pub reward_budget: Nat,
+    pub remaining_budget: Nat, // reward tokens not distributed yet
+    pub start_at: u64,
+    pub end_at: u64,
+    pub total_staked: Nat,
+    pub total_harvested: Nat,
+    pub staked: Nat,         // LP tokens staked by the caller
+    pub pending_reward: Nat, // rewards of the caller not harvested yet
+    pub ts: u64,
+}
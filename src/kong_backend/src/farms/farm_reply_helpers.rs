@@ -0,0 +1,33 @@
+use super::farm_reply::FarmReply;
+
+use crate::helpers::nat_helpers::nat_zero;
+use crate::stable_farm::stable_farm::StableFarm;
+use crate::stable_farm_stake::stable_farm_stake::StableFarmStake;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+
+/// farm must be accrued to ts for pending_reward to be current
+pub fn to_farm_reply(farm: &StableFarm, stake: Option<&StableFarmStake>) -> FarmReply {
+    let lp_token_symbol =
+        token_map::get_by_token_id(farm.lp_token_id).map_or_else(|| "LP token not found".to_string(), |token| token.symbol());
+    let (reward_chain, reward_symbol) = token_map::get_by_token_id(farm.reward_token_id).map_or_else(
+        || ("Reward chain not found".to_string(), "Reward symbol not found".to_string()),
+        |token| (token.chain().to_string(), token.symbol().to_string()),
+    );
+    FarmReply {
+        farm_id: farm.farm_id,
+        lp_token_symbol,
+        reward_chain,
+        reward_symbol,
+        reward_rate: farm.reward_rate.clone(),
+        reward_budget: farm.reward_budget.clone(),
+        remaining_budget: farm.remaining_budget.clone(),
+        start_at: farm.start_ts,
+        end_at: farm.end_ts,
+        total_staked: farm.total_staked.clone(),
+        total_harvested: farm.total_harvested.clone(),
+        staked: stake.map_or_else(nat_zero, |stake| stake.amount.clone()),
+        pending_reward: stake.map_or_else(nat_zero, |stake| stake.pending_reward(farm)),
+        ts: farm.ts,
+    }
+}
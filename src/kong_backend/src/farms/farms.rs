@@ -0,0 +1,26 @@
+use ic_cdk::query;
+
+use super::farm_reply::FarmReply;
+use super::farm_reply_helpers::to_farm_reply;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_farm::farm_map;
+use crate::stable_farm_stake::farm_stake_map;
+use crate::stable_user::user_map;
+
+/// returns a specific farm or all farms with the caller's stake and pending rewards
+#[query(guard = "not_in_maintenance_mode")]
+fn farms(farm_id: Option<u64>) -> Result<Vec<FarmReply>, String> {
+    let ts = get_time();
+    let user_id = user_map::get_by_caller().ok().flatten().map(|user| user.user_id);
+    let farms = farm_map::get(farm_id)
+        .into_iter()
+        .map(|mut farm| {
+            farm.accrue(ts);
+            let stake = user_id.and_then(|user_id| farm_stake_map::get_by_farm_and_user_id(farm.farm_id, user_id));
+            to_farm_reply(&farm, stake.as_ref())
+        })
+        .collect();
+    Ok(farms)
+}
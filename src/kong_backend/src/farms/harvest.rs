@@ -0,0 +1,61 @@
+use ic_cdk::update;
+
+use super::harvest_reply::HarvestReply;
+
+use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::ic::address::Address;
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::ic::id::caller_id;
+use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_farm::farm_map;
+use crate::stable_farm_stake::farm_stake_map;
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_user::user_map;
+
+/// harvest the rewards of the caller from a farm
+/// - rewards are saved as a claim and paid out by the claims timer, so failed payouts are retried
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn harvest(farm_id: u64) -> Result<HarvestReply, String> {
+    let ts = get_time();
+    let mut farm = farm_map::get_by_farm_id(farm_id).ok_or("Farm not found")?;
+    let user_id = user_map::get_by_caller()?.ok_or("Stake not found")?.user_id;
+    let mut stake = farm_stake_map::get_by_farm_and_user_id(farm.farm_id, user_id).ok_or("Stake not found")?;
+    let reward_token = token_map::get_by_token_id(farm.reward_token_id).ok_or("Reward token not found")?;
+
+    farm.accrue(ts);
+    let amount = stake.amount.clone();
+    stake.settle(&farm, &amount, ts);
+    let reward = stake.unharvested_reward.clone();
+    // the claim pays out the reward less the gas fee
+    if reward <= reward_token.fee() {
+        return Err(format!("Rewards {} not enough to pay the gas fee {}", reward, reward_token.fee()));
+    }
+    // accrue() never distributes more than the budget, so this only guards against accounting errors
+    let total_harvested = nat_add(&farm.total_harvested, &reward);
+    if total_harvested > farm.reward_budget {
+        return Err("Rewards exceed the farm's reward budget".to_string());
+    }
+
+    let claim_id = claim_map::insert(&StableClaim::new(
+        user_id,
+        farm.reward_token_id,
+        &reward,
+        None,
+        Some(Address::PrincipalId(caller_id())),
+        ts,
+    ))?;
+    stake.unharvested_reward = nat_zero();
+    farm_stake_map::upsert(&stake);
+    farm.total_harvested = total_harvested;
+    farm_map::update(&farm);
+
+    Ok(HarvestReply {
+        farm_id,
+        reward_chain: reward_token.chain(),
+        reward_symbol: reward_token.symbol(),
+        amount: reward,
+        claim_id,
+        ts,
+    })
+}
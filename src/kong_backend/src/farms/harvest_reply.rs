@@ -0,0 +1,13 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the reply of the `harvest` function.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct HarvestReply {
+    pub farm_id: u64,
+    pub reward_chain: String,
+    pub reward_symbol: String,
+    pub amount: Nat,
+    pub claim_id: u64, // rewards are paid out by the claims timer
+    pub ts: u64,
+}
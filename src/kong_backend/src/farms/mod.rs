@@ -0,0 +1,11 @@
+pub mod close_farm;
+pub mod create_farm;
+pub mod create_farm_args;
+pub mod farm_reply;
+pub mod farm_reply_helpers;
+#[allow(clippy::module_inception)]
+pub mod farms;
+pub mod harvest;
+pub mod harvest_reply;
+pub mod stake;
+pub mod stake_args;
@@ -0,0 +1,82 @@
+use ic_cdk::update;
+
+use super::farm_reply::FarmReply;
+use super::farm_reply_helpers::to_farm_reply;
+use super::stake_args::StakeArgs;
+
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::ic::id::kong_account;
+use crate::stable_farm::farm_map;
+use crate::stable_farm_stake::{farm_stake_map, stable_farm_stake::StableFarmStake};
+use crate::stable_lp_block::stable_lp_block::LPBlockTxArgs;
+use crate::stable_lp_token::transfer::transfer;
+use crate::stable_user::user_map;
+
+/// stake LP tokens of the caller in a farm. staked LP tokens are held in Kong's account until unstaked
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn stake(args: StakeArgs) -> Result<FarmReply, String> {
+    let ts = get_time();
+    let mut farm = farm_map::get_by_farm_id(args.farm_id).ok_or("Farm not found")?;
+    if ts >= farm.end_ts {
+        return Err(format!("Farm #{} has ended", farm.farm_id));
+    }
+    if nat_is_zero(&args.amount) {
+        return Err("Amount is zero".to_string());
+    }
+    let user_id = user_map::get_by_caller()?.ok_or("Not enough LP token")?.user_id;
+
+    transfer(
+        farm.lp_token_id,
+        user_id,
+        farm_escrow_user_id()?,
+        &args.amount,
+        &LPBlockTxArgs::default(),
+    )?;
+
+    farm.accrue(ts);
+    let mut stake =
+        farm_stake_map::get_by_farm_and_user_id(farm.farm_id, user_id).unwrap_or_else(|| StableFarmStake::new(farm.farm_id, user_id, ts));
+    let amount = nat_add(&stake.amount, &args.amount);
+    stake.settle(&farm, &amount, ts);
+    stake.stake_id = farm_stake_map::upsert(&stake);
+    farm.total_staked = nat_add(&farm.total_staked, &args.amount);
+    farm_map::update(&farm);
+
+    Ok(to_farm_reply(&farm, Some(&stake)))
+}
+
+/// unstake LP tokens of the caller from a farm. unharvested rewards stay in the farm to be harvested
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn unstake(args: StakeArgs) -> Result<FarmReply, String> {
+    let ts = get_time();
+    let mut farm = farm_map::get_by_farm_id(args.farm_id).ok_or("Farm not found")?;
+    if nat_is_zero(&args.amount) {
+        return Err("Amount is zero".to_string());
+    }
+    let user_id = user_map::get_by_caller()?.ok_or("Stake not found")?.user_id;
+    let mut stake = farm_stake_map::get_by_farm_and_user_id(farm.farm_id, user_id).ok_or("Stake not found")?;
+    let amount = nat_subtract(&stake.amount, &args.amount).ok_or(format!("Not enough LP token staked. {} staked", stake.amount))?;
+
+    transfer(
+        farm.lp_token_id,
+        farm_escrow_user_id()?,
+        user_id,
+        &args.amount,
+        &LPBlockTxArgs::default(),
+    )?;
+
+    farm.accrue(ts);
+    stake.settle(&farm, &amount, ts);
+    farm_stake_map::upsert(&stake);
+    farm.total_staked = nat_subtract(&farm.total_staked, &args.amount).ok_or("Invalid farm total staked")?;
+    farm_map::update(&farm);
+
+    Ok(to_farm_reply(&farm, Some(&stake)))
+}
+
+/// user_id of Kong's account which holds the staked LP tokens
+fn farm_escrow_user_id() -> Result<u32, String> {
+    user_map::insert_by_account(&kong_account())
+}
@@ -0,0 +1,9 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `stake` and `unstake` functions.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StakeArgs {
+    pub farm_id: u64,
+    pub amount: Nat, // LP token amount
+}
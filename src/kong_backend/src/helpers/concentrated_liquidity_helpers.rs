@@ -0,0 +1,358 @@
+use candid::Nat;
+use num::Integer;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::helpers::nat_helpers::nat_to_biguint;
+
+// concentrated liquidity (Uniswap v3) math for 2 tokens
+// price is token_1 per token_0 in raw token units and is stored as sqrt(price) * 2^96
+// each tick is a 0.01% price step, price = 1.0001^tick
+
+pub const MIN_TICK: i32 = -887_272;
+pub const MAX_TICK: i32 = 887_272;
+
+const Q96_BITS: u32 = 96;
+const Q128_BITS: u32 = 128;
+
+fn q96() -> BigUint {
+    BigUint::one() << Q96_BITS
+}
+
+fn div_ceil(numerator: &BigUint, denominator: &BigUint) -> BigUint {
+    numerator.div_ceil(denominator)
+}
+
+/// sqrt(1.0001^tick) * 2^96, rounded up
+pub fn sqrt_price_at_tick(tick: i32) -> Nat {
+    Nat::from(sqrt_price_at_tick_biguint(tick))
+}
+
+fn sqrt_price_at_tick_biguint(tick: i32) -> BigUint {
+    let tick = tick.clamp(MIN_TICK, MAX_TICK);
+    let abs_tick = tick.unsigned_abs();
+
+    // sqrt(1.0001)^abs_tick in Q128.128 by binary exponentiation. |tick| < 2^20 so at most 20 squarings
+    let one = BigUint::one() << Q128_BITS;
+    let mut base = (BigUint::from(10_001_u32) * (BigUint::one() << (2 * Q128_BITS)) / BigUint::from(10_000_u32)).sqrt();
+    let mut ratio = one.clone();
+    let mut exponent = abs_tick;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            ratio = (&ratio * &base) >> Q128_BITS;
+        }
+        base = (&base * &base) >> Q128_BITS;
+        exponent >>= 1;
+    }
+    if tick < 0 {
+        ratio = (BigUint::one() << (2 * Q128_BITS)) / ratio;
+    }
+
+    // Q128.128 to Q.96 rounding up so tick_at_sqrt_price() of the result is always tick
+    div_ceil(&ratio, &(BigUint::one() << (Q128_BITS - Q96_BITS)))
+}
+
+/// greatest tick with sqrt_price_at_tick(tick) <= sqrt_price
+pub fn tick_at_sqrt_price(sqrt_price: &Nat) -> i32 {
+    let sqrt_price = nat_to_biguint(sqrt_price);
+    // estimate with floating point then step to the exact tick
+    let price = (sqrt_price.to_f64().unwrap_or(0_f64) / 2_f64.powi(Q96_BITS as i32)).powi(2);
+    let estimate = if price > 0_f64 {
+        (price.ln() / 1.0001_f64.ln()).floor()
+    } else {
+        MIN_TICK as f64
+    };
+    let mut tick = (estimate as i64).clamp(MIN_TICK as i64, MAX_TICK as i64) as i32;
+    while tick > MIN_TICK && sqrt_price_at_tick_biguint(tick) > sqrt_price {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && sqrt_price_at_tick_biguint(tick + 1) <= sqrt_price {
+        tick += 1;
+    }
+    tick
+}
+
+/// sqrt(amount_1 / amount_0) * 2^96. used as the initial price of a pool
+pub fn sqrt_price_from_amounts(amount_0: &Nat, amount_1: &Nat) -> Option<Nat> {
+    let amount_0 = nat_to_biguint(amount_0);
+    if amount_0.is_zero() {
+        None?
+    }
+    let sqrt_price = ((nat_to_biguint(amount_1) << (2 * Q96_BITS)) / amount_0).sqrt();
+    if sqrt_price.is_zero() {
+        None?
+    }
+    Some(Nat::from(sqrt_price))
+}
+
+// amount_0 = L * (sqrt_price_b - sqrt_price_a) / (sqrt_price_a * sqrt_price_b)
+fn amount_0_delta_biguint(sqrt_price_a: &BigUint, sqrt_price_b: &BigUint, liquidity: &BigUint, round_up: bool) -> BigUint {
+    let (sqrt_price_a, sqrt_price_b) = if sqrt_price_a > sqrt_price_b {
+        (sqrt_price_b, sqrt_price_a)
+    } else {
+        (sqrt_price_a, sqrt_price_b)
+    };
+    if sqrt_price_a.is_zero() {
+        return BigUint::zero();
+    }
+    let numerator = (liquidity << Q96_BITS) * (sqrt_price_b - sqrt_price_a);
+    if round_up {
+        div_ceil(&div_ceil(&numerator, sqrt_price_b), sqrt_price_a)
+    } else {
+        numerator / sqrt_price_b / sqrt_price_a
+    }
+}
+
+// amount_1 = L * (sqrt_price_b - sqrt_price_a)
+fn amount_1_delta_biguint(sqrt_price_a: &BigUint, sqrt_price_b: &BigUint, liquidity: &BigUint, round_up: bool) -> BigUint {
+    let (sqrt_price_a, sqrt_price_b) = if sqrt_price_a > sqrt_price_b {
+        (sqrt_price_b, sqrt_price_a)
+    } else {
+        (sqrt_price_a, sqrt_price_b)
+    };
+    let numerator = liquidity * (sqrt_price_b - sqrt_price_a);
+    if round_up {
+        div_ceil(&numerator, &q96())
+    } else {
+        numerator >> Q96_BITS
+    }
+}
+
+/// amounts of token_0 and token_1 for liquidity between sqrt_price_a and sqrt_price_b at sqrt_price
+pub fn amounts_for_liquidity(sqrt_price: &Nat, sqrt_price_a: &Nat, sqrt_price_b: &Nat, liquidity: &Nat, round_up: bool) -> (Nat, Nat) {
+    let sqrt_price = nat_to_biguint(sqrt_price);
+    let sqrt_price_a = nat_to_biguint(sqrt_price_a);
+    let sqrt_price_b = nat_to_biguint(sqrt_price_b);
+    let liquidity = nat_to_biguint(liquidity);
+    let (amount_0, amount_1) = if sqrt_price <= sqrt_price_a {
+        // price below the range, all in token_0
+        (
+            amount_0_delta_biguint(&sqrt_price_a, &sqrt_price_b, &liquidity, round_up),
+            BigUint::zero(),
+        )
+    } else if sqrt_price < sqrt_price_b {
+        (
+            amount_0_delta_biguint(&sqrt_price, &sqrt_price_b, &liquidity, round_up),
+            amount_1_delta_biguint(&sqrt_price_a, &sqrt_price, &liquidity, round_up),
+        )
+    } else {
+        // price above the range, all in token_1
+        (
+            BigUint::zero(),
+            amount_1_delta_biguint(&sqrt_price_a, &sqrt_price_b, &liquidity, round_up),
+        )
+    };
+    (Nat::from(amount_0), Nat::from(amount_1))
+}
+
+/// maximum liquidity between sqrt_price_a and sqrt_price_b at sqrt_price that amount_0 and amount_1 can provide
+pub fn liquidity_for_amounts(sqrt_price: &Nat, sqrt_price_a: &Nat, sqrt_price_b: &Nat, amount_0: &Nat, amount_1: &Nat) -> Nat {
+    let sqrt_price = nat_to_biguint(sqrt_price);
+    let sqrt_price_a = nat_to_biguint(sqrt_price_a);
+    let sqrt_price_b = nat_to_biguint(sqrt_price_b);
+    let amount_0 = nat_to_biguint(amount_0);
+    let amount_1 = nat_to_biguint(amount_1);
+    if sqrt_price_a >= sqrt_price_b {
+        return Nat::from(0_u128);
+    }
+
+    // L = amount_0 * sqrt_price_a * sqrt_price_b / (sqrt_price_b - sqrt_price_a)
+    let liquidity_0 = |sqrt_price_a: &BigUint| {
+        let intermediate = (sqrt_price_a * &sqrt_price_b) >> Q96_BITS;
+        &amount_0 * intermediate / (&sqrt_price_b - sqrt_price_a)
+    };
+    // L = amount_1 / (sqrt_price_b - sqrt_price_a)
+    let liquidity_1 = |sqrt_price_b: &BigUint| (&amount_1 << Q96_BITS) / (sqrt_price_b - &sqrt_price_a);
+
+    let liquidity = if sqrt_price <= sqrt_price_a {
+        liquidity_0(&sqrt_price_a)
+    } else if sqrt_price < sqrt_price_b {
+        std::cmp::min(liquidity_0(&sqrt_price), liquidity_1(&sqrt_price))
+    } else {
+        liquidity_1(&sqrt_price_b)
+    };
+    Nat::from(liquidity)
+}
+
+// next sqrt price after adding or removing amount of token_0, rounded up
+fn next_sqrt_price_from_amount_0(sqrt_price: &BigUint, liquidity: &BigUint, amount: &BigUint, add: bool) -> Option<BigUint> {
+    if amount.is_zero() {
+        return Some(sqrt_price.clone());
+    }
+    let numerator = liquidity << Q96_BITS;
+    let product = amount * sqrt_price;
+    let denominator = if add {
+        &numerator + product
+    } else {
+        if numerator <= product {
+            None?
+        }
+        &numerator - product
+    };
+    Some(div_ceil(&(numerator * sqrt_price), &denominator))
+}
+
+// next sqrt price after adding or removing amount of token_1, rounded down
+fn next_sqrt_price_from_amount_1(sqrt_price: &BigUint, liquidity: &BigUint, amount: &BigUint, add: bool) -> Option<BigUint> {
+    if liquidity.is_zero() {
+        None?
+    }
+    if add {
+        Some(sqrt_price + (amount << Q96_BITS) / liquidity)
+    } else {
+        let quotient = div_ceil(&(amount << Q96_BITS), liquidity);
+        if *sqrt_price <= quotient {
+            None?
+        }
+        Some(sqrt_price - quotient)
+    }
+}
+
+/// swap within a single tick range towards sqrt_price_target. no fees are taken as Kong takes the LP fee from the amount out
+/// amount_remaining is the amount in if exact_input, otherwise the amount out
+/// returns (next sqrt price, amount in, amount out)
+pub fn swap_step(
+    sqrt_price: &Nat,
+    sqrt_price_target: &Nat,
+    liquidity: &Nat,
+    amount_remaining: &Nat,
+    exact_input: bool,
+) -> Option<(Nat, Nat, Nat)> {
+    let sqrt_price = nat_to_biguint(sqrt_price);
+    let sqrt_price_target = nat_to_biguint(sqrt_price_target);
+    let liquidity = nat_to_biguint(liquidity);
+    let amount_remaining = nat_to_biguint(amount_remaining);
+    // paying token_0 moves the price down
+    let zero_for_one = sqrt_price >= sqrt_price_target;
+
+    let next_sqrt_price = if exact_input {
+        let amount_in_max = if zero_for_one {
+            amount_0_delta_biguint(&sqrt_price_target, &sqrt_price, &liquidity, true)
+        } else {
+            amount_1_delta_biguint(&sqrt_price, &sqrt_price_target, &liquidity, true)
+        };
+        if amount_remaining >= amount_in_max {
+            sqrt_price_target.clone()
+        } else if zero_for_one {
+            next_sqrt_price_from_amount_0(&sqrt_price, &liquidity, &amount_remaining, true)?
+        } else {
+            next_sqrt_price_from_amount_1(&sqrt_price, &liquidity, &amount_remaining, true)?
+        }
+    } else {
+        let amount_out_max = if zero_for_one {
+            amount_1_delta_biguint(&sqrt_price_target, &sqrt_price, &liquidity, false)
+        } else {
+            amount_0_delta_biguint(&sqrt_price, &sqrt_price_target, &liquidity, false)
+        };
+        if amount_remaining >= amount_out_max {
+            sqrt_price_target.clone()
+        } else if zero_for_one {
+            next_sqrt_price_from_amount_1(&sqrt_price, &liquidity, &amount_remaining, false)?
+        } else {
+            next_sqrt_price_from_amount_0(&sqrt_price, &liquidity, &amount_remaining, false)?
+        }
+    };
+
+    let (mut amount_in, mut amount_out) = if zero_for_one {
+        (
+            amount_0_delta_biguint(&next_sqrt_price, &sqrt_price, &liquidity, true),
+            amount_1_delta_biguint(&next_sqrt_price, &sqrt_price, &liquidity, false),
+        )
+    } else {
+        (
+            amount_1_delta_biguint(&sqrt_price, &next_sqrt_price, &liquidity, true),
+            amount_0_delta_biguint(&sqrt_price, &next_sqrt_price, &liquidity, false),
+        )
+    };
+
+    if exact_input && next_sqrt_price != sqrt_price_target {
+        // the target was not reached so all of the amount in is used. rounding dust stays in the pool
+        amount_in = amount_remaining;
+    } else if !exact_input && amount_out > amount_remaining {
+        amount_out = amount_remaining;
+    }
+
+    Some((Nat::from(next_sqrt_price), Nat::from(amount_in), Nat::from(amount_out)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_price_at_tick() {
+        assert_eq!(sqrt_price_at_tick(0), Nat::from(q96()));
+
+        // price = 1.0001^tick
+        let sqrt_price = sqrt_price_at_tick(10_000).0.to_f64().unwrap() / 2_f64.powi(96);
+        assert!((sqrt_price * sqrt_price - 1.0001_f64.powi(10_000)).abs() < 1e-9);
+        let sqrt_price = sqrt_price_at_tick(-10_000).0.to_f64().unwrap() / 2_f64.powi(96);
+        assert!((sqrt_price * sqrt_price - 1.0001_f64.powi(-10_000)).abs() < 1e-12);
+
+        assert!(sqrt_price_at_tick(MIN_TICK) > 0_u128);
+        assert!(sqrt_price_at_tick(-1) < sqrt_price_at_tick(0));
+        assert!(sqrt_price_at_tick(MAX_TICK - 1) < sqrt_price_at_tick(MAX_TICK));
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_price() {
+        for tick in [MIN_TICK, -200_000, -60, -1, 0, 1, 59, 200_000, MAX_TICK] {
+            let sqrt_price = sqrt_price_at_tick(tick);
+            assert_eq!(tick_at_sqrt_price(&sqrt_price), tick);
+            if tick < MAX_TICK {
+                // any price up to the next tick is still in tick
+                let next_sqrt_price = sqrt_price_at_tick(tick + 1) - Nat::from(1_u8);
+                assert_eq!(tick_at_sqrt_price(&next_sqrt_price), tick);
+            }
+        }
+    }
+
+    #[test]
+    fn test_liquidity_amounts() {
+        let sqrt_price = sqrt_price_from_amounts(&Nat::from(1_000_000_u128), &Nat::from(4_000_000_u128)).unwrap();
+        assert_eq!(sqrt_price, Nat::from(q96() * 2_u8));
+
+        let sqrt_price_a = sqrt_price_at_tick(-6_000);
+        let sqrt_price_b = sqrt_price_at_tick(24_000);
+        let amount_0 = Nat::from(1_000_000_000_u128);
+        let amount_1 = Nat::from(1_000_000_000_u128);
+        let liquidity = liquidity_for_amounts(&sqrt_price, &sqrt_price_a, &sqrt_price_b, &amount_0, &amount_1);
+        assert!(liquidity > 0_u128);
+
+        // the amounts for the liquidity never exceed the amounts provided and one of them is used up
+        let (used_0, used_1) = amounts_for_liquidity(&sqrt_price, &sqrt_price_a, &sqrt_price_b, &liquidity, true);
+        assert!(used_0 <= amount_0 && used_1 <= amount_1);
+        assert!(used_0 > 999_990_000_u128 || used_1 > 999_990_000_u128);
+
+        // price below the range is all token_0, above is all token_1
+        let (below_0, below_1) = amounts_for_liquidity(&sqrt_price_at_tick(-7_000), &sqrt_price_a, &sqrt_price_b, &liquidity, false);
+        assert!(below_0 > 0_u128 && below_1 == 0_u128);
+        let (above_0, above_1) = amounts_for_liquidity(&sqrt_price_at_tick(25_000), &sqrt_price_a, &sqrt_price_b, &liquidity, false);
+        assert!(above_0 == 0_u128 && above_1 > 0_u128);
+    }
+
+    #[test]
+    fn test_swap_step() {
+        let sqrt_price = sqrt_price_at_tick(0);
+        let liquidity = Nat::from(1_000_000_000_000_u128);
+        let amount_in = Nat::from(1_000_000_u128);
+
+        // exact input of token_0 that does not reach the target
+        let (next_sqrt_price, used_in, amount_out) =
+            swap_step(&sqrt_price, &sqrt_price_at_tick(-1_000), &liquidity, &amount_in, true).unwrap();
+        assert!(next_sqrt_price < sqrt_price);
+        assert_eq!(used_in, amount_in);
+        assert!(amount_out < amount_in && amount_out > 999_000_u128);
+
+        // exact output of the same amount needs at least the same amount in
+        let (_, exact_out_in, exact_out) = swap_step(&sqrt_price, &sqrt_price_at_tick(-1_000), &liquidity, &amount_out, false).unwrap();
+        assert_eq!(exact_out, amount_out);
+        assert!(exact_out_in <= amount_in);
+
+        // a large amount stops at the target
+        let target = sqrt_price_at_tick(10);
+        let (next_sqrt_price, used_in, _) = swap_step(&sqrt_price, &target, &liquidity, &Nat::from(u64::MAX), true).unwrap();
+        assert_eq!(next_sqrt_price, target);
+        assert!(used_in < u64::MAX);
+    }
+}
@@ -1,3 +1,8 @@
+pub mod concentrated_liquidity_helpers;
 pub mod json_helpers;
 pub mod math_helpers;
 pub mod nat_helpers;
+pub mod stableswap_helpers;
+#[cfg(test)]
+pub mod test_fixtures;
+pub mod weighted_helpers;
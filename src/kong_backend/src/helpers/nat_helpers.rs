@@ -36,7 +36,6 @@ pub fn nat_to_f64(n: &Nat) -> Option<f64> {
     n.0.to_f64()
 }
 
-#[allow(dead_code)]
 pub fn nat_10pow(n: u8) -> Nat {
     Nat::from(10_u128.pow(n as u32))
 }
@@ -65,6 +64,17 @@ pub fn nat_to_decimal_precision(n: &Nat, from_decimal_precision: u8, to_decimal_
     }
 }
 
+// same as nat_to_decimal_precision() but rounds up when converting to a lower decimal precision
+pub fn nat_to_decimal_precision_ceil(n: &Nat, from_decimal_precision: u8, to_decimal_precision: u8) -> Nat {
+    match from_decimal_precision.cmp(&to_decimal_precision) {
+        Ordering::Greater => {
+            let decimal_diff = from_decimal_precision - to_decimal_precision;
+            nat_divide_ceil(n, &Nat::from(10_u128.pow(decimal_diff as u32))).unwrap_or(nat_zero())
+        }
+        _ => nat_to_decimal_precision(n, from_decimal_precision, to_decimal_precision),
+    }
+}
+
 // both Nat must have the same decimal precision
 pub fn nat_add(n1: &Nat, n2: &Nat) -> Nat {
     n1.clone() + n2.clone()
@@ -103,6 +113,19 @@ pub fn nat_divide(numerator: &Nat, denominator: &Nat) -> Option<Nat> {
     Some(numerator.clone() / denominator.clone())
 }
 
+// integer division rounded up
+pub fn nat_divide_ceil(numerator: &Nat, denominator: &Nat) -> Option<Nat> {
+    if nat_is_zero(denominator) {
+        None?
+    }
+    let quotient = numerator.clone() / denominator.clone();
+    if nat_multiply(&quotient, denominator) == *numerator {
+        Some(quotient)
+    } else {
+        Some(quotient + 1_u8)
+    }
+}
+
 // division with decimal precision
 pub fn nat_divide_as_f64(numerator: &Nat, denominator: &Nat) -> Option<f64> {
     if nat_is_zero(numerator) {
@@ -219,6 +242,29 @@ mod tests {
         assert_eq!(x, Some(Nat::from(5_u128)));
     }
 
+    #[test]
+    fn test_nat_divide_ceil() {
+        let n1 = Nat::from(5_000_000_000_u128);
+        let n2 = Nat::from(0_u128);
+        let x = nat_divide_ceil(&n1, &n2);
+        assert_eq!(x, None);
+
+        let n1 = Nat::from(5_000_000_000_u128);
+        let n2 = Nat::from(1_000_000_000_u128);
+        let x = nat_divide_ceil(&n1, &n2);
+        assert_eq!(x, Some(Nat::from(5_u128)));
+
+        let n1 = Nat::from(5_000_000_001_u128);
+        let x = nat_divide_ceil(&n1, &n2);
+        assert_eq!(x, Some(Nat::from(6_u128)));
+
+        let n = Nat::from(12_222_222_222_u128);
+        let x = nat_to_decimal_precision_ceil(&n, 18, 8);
+        assert_eq!(x, Nat::from(2_u128));
+        let x = nat_to_decimal_precision_ceil(&n, 8, 10);
+        assert_eq!(x, Nat::from(1_222_222_222_200_u128));
+    }
+
     #[test]
     fn test_nat_divide_f64() {
         let n1 = Nat::from(5_000_000_000_u128);
@@ -0,0 +1,194 @@
+use candid::Nat;
+use num::BigRational;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{CheckedSub, Zero};
+
+use crate::helpers::nat_helpers::{nat_to_bigint, nat_to_biguint};
+
+// StableSwap (Curve) invariant for 2 tokens
+// A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y), n = 2
+// all amounts must be in the same decimal precision
+
+const N_COINS: u32 = 2;
+const MAX_ITERATIONS: usize = 255;
+
+// Ann = A * n^n
+fn ann(amp: u64) -> BigUint {
+    BigUint::from(amp) * BigUint::from(N_COINS.pow(N_COINS))
+}
+
+fn abs_diff(a: &BigUint, b: &BigUint) -> BigUint {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn get_d_biguint(x: &BigUint, y: &BigUint, amp: u64) -> Option<BigUint> {
+    if x.is_zero() || y.is_zero() || amp == 0 {
+        None?
+    }
+
+    let n = BigUint::from(N_COINS);
+    let ann = ann(amp);
+    let s = x + y;
+    let mut d = s.clone();
+    for _ in 0..MAX_ITERATIONS {
+        // d_p = D^3 / (n^n * x * y)
+        let d_p = &d * &d / (x * &n) * &d / (y * &n);
+        let d_prev = d.clone();
+        // D = (Ann * S + D_P * n) * D / ((Ann - 1) * D + (n + 1) * D_P)
+        let numerator = (&ann * &s + &d_p * &n) * &d;
+        let denominator = (&ann - 1_u32) * &d + (&n + 1_u32) * &d_p;
+        d = numerator / denominator;
+        if abs_diff(&d, &d_prev) <= BigUint::from(1_u32) {
+            return Some(d);
+        }
+    }
+    None
+}
+
+fn get_y_biguint(x: &BigUint, d: &BigUint, amp: u64) -> Option<BigUint> {
+    if x.is_zero() || d.is_zero() || amp == 0 {
+        None?
+    }
+
+    let n = BigUint::from(N_COINS);
+    let ann = ann(amp);
+    // c = D^3 / (n^n * x * Ann)
+    let c = d * d / (x * &n) * d / (&ann * &n);
+    // b = x + D / Ann
+    let b = x + d / &ann;
+    let mut y = d.clone();
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y.clone();
+        // y = (y^2 + c) / (2 * y + b - D)
+        let denominator = &y * 2_u32 + &b;
+        if denominator <= *d {
+            None?
+        }
+        y = (&y * &y + &c) / (denominator - d);
+        if abs_diff(&y, &y_prev) <= BigUint::from(1_u32) {
+            return Some(y);
+        }
+    }
+    None
+}
+
+/// invariant D of reserves x and y with amplification coefficient amp
+pub fn stableswap_d(x: &Nat, y: &Nat, amp: u64) -> Option<Nat> {
+    get_d_biguint(&nat_to_biguint(x), &nat_to_biguint(y), amp).map(Nat::from)
+}
+
+/// amount_out received for amount_in before fees
+/// reserve_in, reserve_out and amount_in must be in the same decimal precision
+pub fn stableswap_amount_out(reserve_in: &Nat, reserve_out: &Nat, amount_in: &Nat, amp: u64) -> Option<Nat> {
+    let reserve_in = nat_to_biguint(reserve_in);
+    let reserve_out = nat_to_biguint(reserve_out);
+    let d = get_d_biguint(&reserve_in, &reserve_out, amp)?;
+    let y = get_y_biguint(&(&reserve_in + nat_to_biguint(amount_in)), &d, amp)?;
+    // round down by 1 so the invariant never decreases
+    let amount_out = reserve_out.checked_sub(&y)?.checked_sub(&BigUint::from(1_u32)).unwrap_or_default();
+    Some(Nat::from(amount_out))
+}
+
+/// amount_in needed to receive amount_out before fees. inverse of stableswap_amount_out()
+/// reserve_in, reserve_out and amount_out must be in the same decimal precision
+pub fn stableswap_amount_in(reserve_in: &Nat, reserve_out: &Nat, amount_out: &Nat, amp: u64) -> Option<Nat> {
+    let reserve_in = nat_to_biguint(reserve_in);
+    let reserve_out = nat_to_biguint(reserve_out);
+    let amount_out = nat_to_biguint(amount_out);
+    if amount_out >= reserve_out {
+        None?
+    }
+    let d = get_d_biguint(&reserve_in, &reserve_out, amp)?;
+    let x = get_y_biguint(&(&reserve_out - &amount_out), &d, amp)?;
+    // round up by 1 so the invariant never decreases
+    let mut amount_in = Nat::from(x.checked_sub(&reserve_in)? + 1_u32);
+    // D and y are only accurate to 1 so make sure amount_in is enough to receive amount_out
+    let amount_out = Nat::from(amount_out);
+    let (reserve_in, reserve_out) = (Nat::from(reserve_in), Nat::from(reserve_out));
+    for _ in 0..MAX_ITERATIONS {
+        let received = stableswap_amount_out(&reserve_in, &reserve_out, &amount_in, amp)?;
+        if received >= amount_out {
+            return Some(amount_in);
+        }
+        amount_in += amount_out.clone() - received;
+    }
+    None
+}
+
+/// marginal price of x in y (-dy/dx) at reserves x and y
+/// price = (4 * Ann * x^2 * y^2 + D^3 * y) / (4 * Ann * x^2 * y^2 + D^3 * x)
+pub fn stableswap_price(x: &Nat, y: &Nat, amp: u64) -> Option<BigRational> {
+    let d = stableswap_d(x, y, amp)?;
+    let x = nat_to_bigint(x);
+    let y = nat_to_bigint(y);
+    let d = nat_to_bigint(&d);
+    let ann_x2_y2 = BigInt::from(ann(amp)) * 4 * &x * &x * &y * &y;
+    let d3 = &d * &d * &d;
+    Some(BigRational::new(&ann_x2_y2 + &d3 * y, ann_x2_y2 + d3 * x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_traits::ToPrimitive;
+
+    #[test]
+    fn test_stableswap_d() {
+        // balanced pool, D = x + y
+        let d = stableswap_d(&Nat::from(1_000_000_u128), &Nat::from(1_000_000_u128), 100).unwrap();
+        assert_eq!(d, Nat::from(2_000_000_u128));
+
+        // imbalanced pool, D is between x + y and 2 * sqrt(x * y)
+        let d = stableswap_d(&Nat::from(1_000_000_u128), &Nat::from(4_000_000_u128), 100).unwrap();
+        assert!(d < 5_000_000_u128 && d > 4_000_000_u128);
+
+        assert!(stableswap_d(&Nat::from(0_u128), &Nat::from(1_000_000_u128), 100).is_none());
+    }
+
+    #[test]
+    fn test_stableswap_amount_out() {
+        let reserve = Nat::from(1_000_000_000_000_u128);
+        let amount_in = Nat::from(10_000_000_000_u128); // 1% of the pool
+
+        // high amplification gives almost 1:1
+        let amount_out = stableswap_amount_out(&reserve, &reserve, &amount_in, 1_000).unwrap();
+        assert!(amount_out < amount_in && amount_out > 9_999_000_000_u128);
+
+        // lower amplification gives more slippage but still better than constant product
+        let amount_out_low_amp = stableswap_amount_out(&reserve, &reserve, &amount_in, 1).unwrap();
+        let amount_out_constant_product = amount_in.clone() * reserve.clone() / (reserve.clone() + amount_in.clone());
+        assert!(amount_out_low_amp < amount_out);
+        assert!(amount_out_low_amp > amount_out_constant_product);
+    }
+
+    #[test]
+    fn test_stableswap_amount_in() {
+        let reserve_in = Nat::from(1_000_000_000_000_u128);
+        let reserve_out = Nat::from(800_000_000_000_u128);
+        let amount_out = Nat::from(10_000_000_000_u128);
+
+        let amount_in = stableswap_amount_in(&reserve_in, &reserve_out, &amount_out, 200).unwrap();
+        // paying amount_in receives at least amount_out
+        let received = stableswap_amount_out(&reserve_in, &reserve_out, &amount_in, 200).unwrap();
+        assert!(received >= amount_out);
+        assert!(received < amount_out + Nat::from(10_u128));
+
+        assert!(stableswap_amount_in(&reserve_in, &reserve_out, &reserve_out, 200).is_none());
+    }
+
+    #[test]
+    fn test_stableswap_price() {
+        let price = stableswap_price(&Nat::from(1_000_000_u128), &Nat::from(1_000_000_u128), 100).unwrap();
+        assert_eq!(price.to_f64().unwrap(), 1.0);
+
+        // more x in the pool makes x cheaper, but much less than constant product (0.25)
+        let price = stableswap_price(&Nat::from(2_000_000_u128), &Nat::from(1_000_000_u128), 100).unwrap();
+        let price = price.to_f64().unwrap();
+        assert!(price < 1.0 && price > 0.99);
+    }
+}
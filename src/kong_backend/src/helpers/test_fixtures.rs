@@ -0,0 +1,88 @@
+//! factories for the stable structures used in unit tests
+
+use candid::{Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+
+use crate::stable_lp_token::stable_lp_token::{StableLPToken, StableLPTokenId};
+use crate::stable_memory::{LP_TOKEN_MAP, POOL_MAP, TOKEN_MAP, USER_MAP};
+use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
+use crate::stable_token::ic_token::ICToken;
+use crate::stable_token::lp_token::LPToken;
+use crate::stable_token::stable_token::{StableToken, StableTokenId};
+use crate::stable_user::stable_user::{StableUser, StableUserId};
+
+/// IC token with 8 decimals and a fee of 10
+pub fn ic_token(token_id: u32, symbol: &str) -> ICToken {
+    ICToken {
+        token_id,
+        name: symbol.to_string(),
+        symbol: symbol.to_string(),
+        canister_id: Principal::anonymous(),
+        decimals: 8,
+        fee: Nat::from(10_u32),
+        icrc1: true,
+        icrc2: true,
+        icrc3: false,
+        on_kong: true,
+    }
+}
+
+/// insert ic_token() into TOKEN_MAP
+pub fn insert_token(token_id: u32, symbol: &str) -> StableToken {
+    let token = StableToken::IC(ic_token(token_id, symbol));
+    TOKEN_MAP.with(|m| m.borrow_mut().insert(StableTokenId(token_id), token.clone()));
+    token
+}
+
+/// insert an LP token with 8 decimals into TOKEN_MAP
+pub fn insert_lp_token(token_id: u32, symbol: &str) {
+    let lp_token = StableToken::LP(LPToken {
+        token_id,
+        symbol: symbol.to_string(),
+        address: symbol.to_string(),
+        decimals: 8,
+        on_kong: true,
+    });
+    TOKEN_MAP.with(|m| m.borrow_mut().insert(StableTokenId(token_id), lp_token));
+}
+
+/// pool of token_id_0 and token_id_1 with a 0.3% LP fee and no Kong fee
+pub fn new_pool(pool_id: u32, token_id_0: u32, token_id_1: u32, balance_0: u64, balance_1: u64) -> StablePool {
+    let mut pool = StablePool::new(token_id_0, token_id_1, 3_000, 0, 0, true);
+    pool.pool_id = pool_id;
+    pool.balance_0 = Nat::from(balance_0);
+    pool.balance_1 = Nat::from(balance_1);
+    pool
+}
+
+/// insert pool into POOL_MAP with its pool_id
+pub fn insert_pool(pool: &StablePool) {
+    POOL_MAP.with(|m| m.borrow_mut().insert(StablePoolId(pool.pool_id), pool.clone()));
+}
+
+/// insert a user with the principal and subaccount of account into USER_MAP
+pub fn insert_user(user_id: u32, account: &Account) {
+    let user = StableUser {
+        user_id,
+        principal_id: account.to_string(),
+        user_name: [0; 3],
+        my_referral_code: "".to_string(),
+        referred_by: None,
+        referred_by_expires_at: None,
+        fee_level: 0,
+        fee_level_expires_at: None,
+        campaign1_flags: vec![false, false],
+        last_login_ts: 0,
+        last_swap_ts: 0,
+    };
+    USER_MAP.with(|m| m.borrow_mut().insert(StableUserId(user_id), user));
+}
+
+/// insert or replace the LP token balance lp_token_id of user_id in token_id
+pub fn insert_lp_balance(lp_token_id: u64, user_id: u32, token_id: u32, amount: u64) {
+    let lp_token = StableLPToken {
+        lp_token_id,
+        ..StableLPToken::new(user_id, token_id, Nat::from(amount), 0)
+    };
+    LP_TOKEN_MAP.with(|m| m.borrow_mut().insert(StableLPTokenId(lp_token_id), lp_token));
+}
@@ -0,0 +1,265 @@
+use candid::Nat;
+use num::integer::{gcd, Integer};
+use num::BigRational;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{CheckedSub, One, ToPrimitive, Zero};
+
+use crate::helpers::nat_helpers::nat_to_biguint;
+
+// weighted product (Balancer) invariant for 2 tokens
+// x^w_x * y^w_y = k
+// all amounts must be in the same decimal precision
+// weights are integers, so with w_x / w_y = a / b reduced by their gcd, powers can be calculated exactly as
+// y' = y * (x / x')^(a / b) <=> y'^b = y^b * x^a / x'^a
+// weights that do not reduce to small integers, such as liquidity bootstrapping weights interpolated in pips, would need
+// huge powers. these are approximated in binary fixed point with ln and exp series and rounded in favour of the pool
+
+// largest a + b calculated with exact powers. whole percent weights always reduce to a + b <= 100
+const MAX_EXACT_WEIGHT: u32 = 100;
+// fractional bits of the fixed point ln and exp
+const FIXED_BITS: u32 = 128;
+
+// weights reduced by their gcd so the powers are as small as possible
+fn reduce(weight_x: u32, weight_y: u32) -> Option<(u32, u32)> {
+    if weight_x == 0 || weight_y == 0 {
+        None?
+    }
+    let divisor = gcd(weight_x, weight_y);
+    Some((weight_x / divisor, weight_y / divisor))
+}
+
+fn div_ceil(numerator: &BigUint, denominator: &BigUint) -> BigUint {
+    (numerator + denominator - 1_u32) / denominator
+}
+
+// n-th root of value rounded up
+fn nth_root_ceil(value: &BigUint, n: u32) -> BigUint {
+    let root = value.nth_root(n);
+    if root.pow(n) < *value {
+        root + 1_u32
+    } else {
+        root
+    }
+}
+
+// atanh(z) of z in fixed point, z + z^3 / 3 + z^5 / 5 + ... for 0 <= z < 1
+fn atanh_fixed(z: &BigUint) -> BigUint {
+    let z2 = (z * z) >> FIXED_BITS;
+    let mut term = z.clone();
+    let mut sum = BigUint::zero();
+    let mut n = 1_u32;
+    while !term.is_zero() {
+        sum += &term / n;
+        term = (&term * &z2) >> FIXED_BITS;
+        n += 2;
+    }
+    sum
+}
+
+// ln(2) in fixed point. ln(2) = 2 * atanh(1 / 3)
+fn ln2_fixed() -> BigInt {
+    BigInt::from(atanh_fixed(&((BigUint::one() << FIXED_BITS) / 3_u32)) * 2_u32)
+}
+
+// ln(num / den) in fixed point. num and den must be positive
+fn ln_fixed(num: &BigUint, den: &BigUint) -> BigInt {
+    // num / den = m * 2^k with m in (0.5, 2)
+    let k = num.bits() as i64 - den.bits() as i64;
+    let (n, d) = if k >= 0 {
+        (num.clone(), den << k as u64)
+    } else {
+        (num << (-k) as u64, den.clone())
+    };
+    // ln(m) = 2 * atanh((m - 1) / (m + 1)) = 2 * atanh((n - d) / (n + d)) with |z| < 1 / 3
+    let ln_m = if n >= d {
+        BigInt::from(atanh_fixed(&(((&n - &d) << FIXED_BITS) / (&n + &d))) * 2_u32)
+    } else {
+        -BigInt::from(atanh_fixed(&(((&d - &n) << FIXED_BITS) / (&n + &d))) * 2_u32)
+    };
+    ln_m + ln2_fixed() * k
+}
+
+// exp(v) of v in fixed point
+fn exp_fixed(v: &BigInt) -> BigUint {
+    // v = k * ln(2) + t with 0 <= t < ln(2)
+    let ln2 = ln2_fixed();
+    let k = v.div_floor(&ln2);
+    let t = (v - &k * &ln2).to_biguint().unwrap_or_default();
+    // exp(t) = 1 + t + t^2 / 2! + ...
+    let mut term = BigUint::one() << FIXED_BITS;
+    let mut sum = BigUint::zero();
+    let mut n = 1_u32;
+    while !term.is_zero() {
+        sum += &term;
+        term = ((&term * &t) >> FIXED_BITS) / n;
+        n += 1;
+    }
+    // exp(v) = exp(t) * 2^k
+    match k.to_i64().unwrap_or_default() {
+        k if k >= 0 => sum << k as u64,
+        k => sum >> (-k) as u64,
+    }
+}
+
+// value * (num / den)^(a / b) approximated in fixed point. rounded up with a margin of 1 for the approximation error
+fn mul_pow_ceil_fixed(value: &BigUint, num: &BigUint, den: &BigUint, a: u32, b: u32) -> BigUint {
+    let exponent = ln_fixed(num, den) * a / b;
+    ((value * exp_fixed(&exponent)) >> FIXED_BITS) + 2_u32
+}
+
+// value * (num / den)^(a / b) rounded up. num and den must be positive
+fn mul_pow_ceil(value: &BigUint, num: &BigUint, den: &BigUint, a: u32, b: u32) -> BigUint {
+    if a + b > MAX_EXACT_WEIGHT {
+        return mul_pow_ceil_fixed(value, num, den, a, b);
+    }
+    // result^b = value^b * num^a / den^a
+    nth_root_ceil(&div_ceil(&(value.pow(b) * num.pow(a)), &den.pow(a)), b)
+}
+
+/// amount_out received for amount_in before fees
+/// weight_in and weight_out are the weights of the tokens paid and received
+/// reserve_in, reserve_out and amount_in must be in the same decimal precision
+pub fn weighted_amount_out(reserve_in: &Nat, reserve_out: &Nat, amount_in: &Nat, weight_in: u32, weight_out: u32) -> Option<Nat> {
+    let (a, b) = reduce(weight_in, weight_out)?;
+    let x = nat_to_biguint(reserve_in);
+    let y = nat_to_biguint(reserve_out);
+    if x.is_zero() || y.is_zero() {
+        None?
+    }
+    let x_new = &x + nat_to_biguint(amount_in);
+    // y_new = y * (x / x_new)^(a / b). round y_new up so the invariant never decreases
+    let y_new = mul_pow_ceil(&y, &x, &x_new, a, b);
+    Some(Nat::from(y.checked_sub(&y_new).unwrap_or_default()))
+}
+
+/// amount_in needed to receive amount_out before fees. inverse of weighted_amount_out()
+/// reserve_in, reserve_out and amount_out must be in the same decimal precision
+pub fn weighted_amount_in(reserve_in: &Nat, reserve_out: &Nat, amount_out: &Nat, weight_in: u32, weight_out: u32) -> Option<Nat> {
+    let (a, b) = reduce(weight_in, weight_out)?;
+    let x = nat_to_biguint(reserve_in);
+    let y = nat_to_biguint(reserve_out);
+    let y_new = y.checked_sub(&nat_to_biguint(amount_out))?;
+    if x.is_zero() || y_new.is_zero() {
+        None?
+    }
+    // x_new = x * (y / y_new)^(b / a). round x_new up so the invariant never decreases
+    let x_new = mul_pow_ceil(&x, &y, &y_new, b, a);
+    Some(Nat::from(x_new.checked_sub(&x)?))
+}
+
+/// marginal price of x in y (-dy/dx) at reserves x and y
+/// price = (y / weight_y) / (x / weight_x)
+pub fn weighted_price(x: &Nat, y: &Nat, weight_x: u32, weight_y: u32) -> Option<BigRational> {
+    let x = BigInt::from(nat_to_biguint(x));
+    if x.is_zero() || weight_y == 0 {
+        None?
+    }
+    let y = BigInt::from(nat_to_biguint(y));
+    Some(BigRational::new(y * weight_x, x * weight_y))
+}
+
+/// invariant of reserves x and y normalized to the sum of the weights, x^(w_x / (w_x + w_y)) * y^(w_y / (w_x + w_y))
+/// scales linearly with the reserves so it is used as the initial LP token amount
+pub fn weighted_invariant(x: &Nat, y: &Nat, weight_x: u32, weight_y: u32) -> Option<Nat> {
+    let (a, b) = reduce(weight_x, weight_y)?;
+    let (x, y) = (nat_to_biguint(x), nat_to_biguint(y));
+    if a + b > MAX_EXACT_WEIGHT {
+        if x.is_zero() || y.is_zero() {
+            return Some(Nat::from(0_u32));
+        }
+        // exp((a * ln(x) + b * ln(y)) / (a + b))
+        let one = BigUint::one();
+        let exponent = (ln_fixed(&x, &one) * a + ln_fixed(&y, &one) * b) / (a + b);
+        return Some(Nat::from(exp_fixed(&exponent) >> FIXED_BITS));
+    }
+    let product = x.pow(a) * y.pow(b);
+    Some(Nat::from(product.nth_root(a + b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_traits::ToPrimitive;
+
+    #[test]
+    fn test_weighted_amount_out() {
+        let reserve = Nat::from(1_000_000_000_u128);
+        let amount_in = Nat::from(10_000_000_u128);
+
+        // 50/50 is constant product
+        let amount_out = weighted_amount_out(&reserve, &reserve, &amount_in, 50, 50).unwrap();
+        let amount_out_constant_product = amount_in.clone() * reserve.clone() / (reserve.clone() + amount_in.clone());
+        assert_eq!(amount_out, amount_out_constant_product);
+
+        // 80/20, y' = y * (x / x')^4
+        let amount_out = weighted_amount_out(&reserve, &reserve, &amount_in, 80, 20).unwrap();
+        let expected = 1_000_000_000_f64 * (1.0 - (1.0 / 1.01_f64).powi(4));
+        assert!((amount_out.0.to_f64().unwrap() - expected).abs() <= 1.0);
+        assert!(amount_out.0.to_f64().unwrap() <= expected);
+    }
+
+    #[test]
+    fn test_weighted_amount_in() {
+        let reserve_in = Nat::from(4_000_000_000_u128);
+        let reserve_out = Nat::from(1_000_000_000_u128);
+        let amount_out = Nat::from(10_000_000_u128);
+
+        let amount_in = weighted_amount_in(&reserve_in, &reserve_out, &amount_out, 20, 80).unwrap();
+        // paying amount_in receives at least amount_out
+        let received = weighted_amount_out(&reserve_in, &reserve_out, &amount_in, 20, 80).unwrap();
+        assert!(received >= amount_out);
+        assert!(received < amount_out + Nat::from(10_u128));
+
+        assert!(weighted_amount_in(&reserve_in, &reserve_out, &reserve_out, 20, 80).is_none());
+    }
+
+    #[test]
+    fn test_mul_pow_ceil_fixed() {
+        let value = BigUint::from(1_000_000_000_000_000_000_u128);
+        let (num, den) = (BigUint::from(1_000_000_000_u128), BigUint::from(1_010_000_000_u128));
+        // fixed point approximation is at most 2 above the exact result
+        for (a, b) in [(4, 1), (1, 4), (1, 1), (49, 1), (73, 27)] {
+            let exact = nth_root_ceil(&div_ceil(&(value.pow(b) * num.pow(a)), &den.pow(a)), b);
+            let approx = mul_pow_ceil_fixed(&value, &num, &den, a, b);
+            assert!(approx >= exact && approx <= &exact + 2_u32, "{}/{}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_weighted_amount_out_fine_weights() {
+        let reserve = Nat::from(1_000_000_000_u128);
+        let amount_in = Nat::from(10_000_000_u128);
+
+        // weights in pips that do not reduce to small integers, y' = y * (x / x')^(733_333 / 266_667)
+        let amount_out = weighted_amount_out(&reserve, &reserve, &amount_in, 733_333, 266_667).unwrap();
+        let expected = 1_000_000_000_f64 * (1.0 - (1.0 / 1.01_f64).powf(733_333.0 / 266_667.0));
+        assert!((amount_out.0.to_f64().unwrap() - expected).abs() <= 3.0);
+        assert!(amount_out.0.to_f64().unwrap() <= expected);
+
+        // paying amount_in receives at least amount_out
+        let amount_in = weighted_amount_in(&reserve, &reserve, &amount_out, 733_333, 266_667).unwrap();
+        let received = weighted_amount_out(&reserve, &reserve, &amount_in, 733_333, 266_667).unwrap();
+        assert!(received >= amount_out);
+
+        // invariant is x^0.733333 * y^0.266667
+        let x = Nat::from(4_000_000_u128);
+        let y = Nat::from(1_000_000_u128);
+        let invariant = weighted_invariant(&x, &y, 733_333, 266_667).unwrap().0.to_f64().unwrap();
+        let expected = 4_000_000_f64.powf(0.733333) * 1_000_000_f64.powf(0.266667);
+        assert!((invariant - expected).abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_weighted_price_and_invariant() {
+        // 80/20 pool with 4x the value in x has a price of 1
+        let x = Nat::from(4_000_000_u128);
+        let y = Nat::from(1_000_000_u128);
+        assert_eq!(weighted_price(&x, &y, 80, 20).unwrap().to_f64().unwrap(), 1.0);
+
+        // invariant is x^0.8 * y^0.2
+        let invariant = weighted_invariant(&x, &y, 80, 20).unwrap().0.to_f64().unwrap();
+        let expected = 4_000_000_f64.powf(0.8) * 1_000_000_f64.powf(0.2);
+        assert!((invariant - expected).abs() <= 1.0);
+    }
+}
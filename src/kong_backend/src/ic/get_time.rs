@@ -6,3 +6,38 @@
 pub fn get_time() -> u64 {
     ic_cdk::api::time()
 }
+
+/// Checks if a request deadline has passed.
+///
+/// # Arguments
+///
+/// * `deadline_ns` - Optional deadline in nanoseconds since the Unix epoch.
+///
+/// # Returns
+///
+/// * `bool` - true if a deadline is specified and the current time is past it.
+pub fn is_expired(deadline_ns: Option<u64>) -> bool {
+    deadline_ns.is_some_and(|_| is_expired_at(deadline_ns, get_time()))
+}
+
+/// Checks if a request deadline has passed at ts. A request is still valid at its deadline.
+pub fn is_expired_at(deadline_ns: Option<u64>, ts: u64) -> bool {
+    deadline_ns.is_some_and(|deadline_ns| ts > deadline_ns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_expired_at() {
+        // no deadline never expires
+        assert!(!is_expired_at(None, u64::MAX));
+        // valid before and at the deadline
+        assert!(!is_expired_at(Some(1_000), 999));
+        assert!(!is_expired_at(Some(1_000), 1_000));
+        // rejected after the deadline
+        assert!(is_expired_at(Some(1_000), 1_001));
+        assert!(is_expired_at(Some(0), 1));
+    }
+}
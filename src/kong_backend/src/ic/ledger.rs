@@ -55,6 +55,14 @@ pub async fn get_fee(ledger: &Principal) -> Result<Nat, String> {
         .map_err(|e| e.1)
 }
 
+/// account tokens are burned by transferring to. None if the ledger has no minting account
+pub async fn get_minting_account(ledger: &Principal) -> Result<Option<Account>, String> {
+    ic_cdk::call::<(), (Option<Account>,)>(*ledger, "icrc1_minting_account", ())
+        .await
+        .map(|(minting_account,)| minting_account)
+        .map_err(|e| e.1)
+}
+
 /// try icrc10_supported_standards first, if it fails, try icrc1_supported_standards
 pub async fn get_supported_standards(ledger: &Principal) -> Result<Vec<StandardRecord>, String> {
     match ic_cdk::call::<(), (Vec<StandardRecord>,)>(*ledger, "icrc10_supported_standards", ())
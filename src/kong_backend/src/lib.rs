@@ -2,25 +2,45 @@ mod add_liquidity;
 mod add_liquidity_amounts;
 mod add_pool;
 mod add_token;
+mod batch;
 mod canister;
 mod chains;
 mod claims;
 mod controllers;
+mod dca;
+mod farms;
 mod helpers;
 mod ic;
+mod lp_ledger;
+mod lp_locks;
+mod lp_performance;
 mod messages;
+mod orders;
 mod pools;
+mod positions;
 mod remove_liquidity;
 mod remove_liquidity_amounts;
 mod requests;
 mod send;
 mod stable_claim;
+mod stable_dca;
+mod stable_farm;
+mod stable_farm_stake;
+mod stable_kong_fee;
 mod stable_kong_settings;
+mod stable_lp_allowance;
+mod stable_lp_block;
+mod stable_lp_history;
+mod stable_lp_lock;
+mod stable_lp_position;
 mod stable_lp_token;
 mod stable_memory;
 mod stable_message;
+mod stable_order;
 mod stable_pool;
+mod stable_pool_observation;
 mod stable_request;
+mod stable_tick;
 mod stable_token;
 mod stable_transfer;
 mod stable_tx;
@@ -29,9 +49,12 @@ mod swap;
 mod swap_amounts;
 mod tokens;
 mod transfers;
+mod treasury;
+mod twap;
 mod txs;
 mod user;
 mod user_balances;
+mod zap_add_liquidity;
 
 pub const APP_NAME: &str = "Kong Swap";
 pub const APP_VERSION: &str = "v0.0.13";
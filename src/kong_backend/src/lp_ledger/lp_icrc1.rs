@@ -0,0 +1,103 @@
+use candid::Nat;
+use ic_cdk::{query, update};
+use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{TransferArg, TransferError};
+
+use super::lp_ledger::{
+    balance_of, check_duplicate, check_fee_and_created_at_time, check_memo, generic_error, get_lp_token, get_lp_token_or_trap, transfer,
+};
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller;
+use crate::stable_lp_block::stable_lp_block::{LPBlockTxArgs, LPBlockType};
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_token::token::Token;
+use crate::stable_user::user_map;
+
+// The LP ledger endpoints have the standard ICRC-1 arguments and return types, with the LP token as the first argument.
+// lp_token: LP token symbol or address. ie. "ckBTC_ckUSDT" or "LP.ckBTC_ckUSDT". queries trap if the LP token is not found
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_name(lp_token: String) -> String {
+    get_lp_token_or_trap(&lp_token).name()
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_symbol(lp_token: String) -> String {
+    get_lp_token_or_trap(&lp_token).symbol()
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_decimals(lp_token: String) -> u8 {
+    get_lp_token_or_trap(&lp_token).decimals()
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_fee(lp_token: String) -> Nat {
+    get_lp_token_or_trap(&lp_token).fee()
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_metadata(lp_token: String) -> Vec<(String, MetadataValue)> {
+    let lp_token = get_lp_token_or_trap(&lp_token);
+    vec![
+        MetadataValue::entry("icrc1:name", lp_token.name()),
+        MetadataValue::entry("icrc1:symbol", lp_token.symbol()),
+        MetadataValue::entry("icrc1:decimals", Nat::from(lp_token.decimals())),
+        MetadataValue::entry("icrc1:fee", lp_token.fee()),
+    ]
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_total_supply(lp_token: String) -> Nat {
+    let lp_token = get_lp_token_or_trap(&lp_token);
+    lp_token_map::get_total_supply(lp_token.token_id())
+}
+
+/// LP tokens are only minted and burned by add_liquidity() and remove_liquidity(), so there is no minting account
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_minting_account(lp_token: String) -> Option<Account> {
+    get_lp_token_or_trap(&lp_token);
+    None
+}
+
+/// transferable LP token balance of the account. locked LP tokens are not included
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_balance_of(lp_token: String, account: Account) -> Nat {
+    let lp_token = get_lp_token_or_trap(&lp_token);
+    balance_of(lp_token.token_id(), &account, get_time())
+}
+
+/// ICRC-1 transfer of an LP token from the caller's account with from_subaccount
+///
+/// # Returns
+/// the block_id of the ICRC-3 transfer block
+#[update(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_transfer(lp_token: String, args: TransferArg) -> Result<Nat, TransferError> {
+    let lp_token = get_lp_token(&lp_token).map_err(generic_error)?;
+    let from = Account {
+        owner: caller(),
+        subaccount: args.from_subaccount,
+    };
+    let ts = get_time();
+
+    check_fee_and_created_at_time(&lp_token, args.fee.as_ref(), args.created_at_time, ts)?;
+    let tx_args = LPBlockTxArgs {
+        memo: check_memo(args.memo.as_ref())?,
+        created_at_time: args.created_at_time,
+        ..Default::default()
+    };
+    check_duplicate(&lp_token, LPBlockType::Transfer, &from, Some(&args.to), &args.amount, &tx_args, ts)?;
+
+    let balance = balance_of(lp_token.token_id(), &from, ts);
+    if balance < args.amount {
+        return Err(TransferError::InsufficientFunds { balance });
+    }
+
+    let from_user_id = user_map::insert_by_account(&from).map_err(generic_error)?;
+    transfer(&lp_token, from_user_id, &args.to, &args.amount, &tx_args, ts)
+        .map(Nat::from)
+        .map_err(generic_error)
+}
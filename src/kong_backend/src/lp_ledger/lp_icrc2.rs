@@ -0,0 +1,202 @@
+use candid::Nat;
+use ic_cdk::{query, update};
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::TransferError;
+use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
+use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+
+use super::lp_ledger::{
+    balance_of, check_duplicate, check_fee_and_created_at_time, check_memo, generic_error, get_lp_token, get_lp_token_or_trap, transfer,
+};
+
+use crate::helpers::nat_helpers::{nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller;
+use crate::stable_lp_allowance::lp_allowance_map;
+use crate::stable_lp_allowance::stable_lp_allowance::StableLPAllowance;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_block::stable_lp_block::{LPBlockTxArgs, LPBlockType};
+use crate::stable_token::token::Token;
+use crate::stable_user::user_map;
+
+/// ICRC-2 approve of an LP token for the caller's account with from_subaccount
+///
+/// # Returns
+/// the block_id of the ICRC-3 approve block
+#[update(guard = "not_in_maintenance_mode")]
+fn lp_icrc2_approve(lp_token: String, args: ApproveArgs) -> Result<Nat, ApproveError> {
+    let lp_token = get_lp_token(&lp_token).map_err(|e| to_approve_error(generic_error(e)))?;
+    let owner = Account {
+        owner: caller(),
+        subaccount: args.from_subaccount,
+    };
+    let spender = args.spender.to_string();
+    let ts = get_time();
+
+    if owner.to_string() == spender {
+        return Err(to_approve_error(generic_error("Self approval not allowed".to_string())));
+    }
+
+    check_fee_and_created_at_time(&lp_token, args.fee.as_ref(), args.created_at_time, ts).map_err(to_approve_error)?;
+    let tx_args = LPBlockTxArgs {
+        spender: Some(args.spender),
+        memo: check_memo(args.memo.as_ref()).map_err(to_approve_error)?,
+        created_at_time: args.created_at_time,
+        expected_allowance: args.expected_allowance.clone(),
+        expires_at: args.expires_at,
+    };
+    check_duplicate(&lp_token, LPBlockType::Approve, &owner, None, &args.amount, &tx_args, ts).map_err(to_approve_error)?;
+
+    if args.expires_at.is_some_and(|expires_at| expires_at <= ts) {
+        return Err(ApproveError::Expired { ledger_time: ts });
+    }
+
+    let user_id = user_map::insert_by_account(&owner).map_err(|e| to_approve_error(generic_error(e)))?;
+    let allowance = lp_allowance_map::get(lp_token.token_id(), user_id, &spender);
+    if let Some(expected_allowance) = args.expected_allowance {
+        let current_allowance = allowance.as_ref().map_or_else(nat_zero, |allowance| allowance.amount_at(ts));
+        if expected_allowance != current_allowance {
+            return Err(ApproveError::AllowanceChanged { current_allowance });
+        }
+    }
+
+    // record the approve first so the allowance does not change if its block can not be recorded
+    let block_id = lp_block_map::insert_approve(lp_token.token_id(), user_id, &args.amount, &tx_args, ts)
+        .map_err(|e| to_approve_error(generic_error(e)))?;
+
+    lp_allowance_map::upsert(&StableLPAllowance {
+        allowance_id: allowance.map_or(0, |allowance| allowance.allowance_id),
+        user_id,
+        token_id: lp_token.token_id(),
+        spender,
+        amount: args.amount.clone(),
+        expires_at: args.expires_at,
+        ts,
+    });
+
+    Ok(Nat::from(block_id))
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc2_allowance(lp_token: String, args: AllowanceArgs) -> Allowance {
+    let lp_token = get_lp_token_or_trap(&lp_token);
+    let ts = get_time();
+
+    let allowance = user_map::get_by_account(&args.account)
+        .ok()
+        .flatten()
+        .and_then(|user| lp_allowance_map::get(lp_token.token_id(), user.user_id, &args.spender.to_string()))
+        .filter(|allowance| allowance.amount_at(ts) > nat_zero());
+
+    match allowance {
+        Some(allowance) => Allowance {
+            allowance: allowance.amount_at(ts),
+            expires_at: allowance.expires_at,
+        },
+        None => Allowance {
+            allowance: nat_zero(),
+            expires_at: None,
+        },
+    }
+}
+
+/// ICRC-2 transfer_from of an LP token by the caller's account with spender_subaccount
+///
+/// # Returns
+/// the block_id of the ICRC-3 transfer block
+#[update(guard = "not_in_maintenance_mode")]
+fn lp_icrc2_transfer_from(lp_token: String, args: TransferFromArgs) -> Result<Nat, TransferFromError> {
+    let lp_token = get_lp_token(&lp_token).map_err(|e| to_transfer_from_error(generic_error(e)))?;
+    let spender_account = Account {
+        owner: caller(),
+        subaccount: args.spender_subaccount,
+    };
+    let spender = spender_account.to_string();
+    let ts = get_time();
+
+    check_fee_and_created_at_time(&lp_token, args.fee.as_ref(), args.created_at_time, ts).map_err(to_transfer_from_error)?;
+    let tx_args = LPBlockTxArgs {
+        spender: Some(spender_account),
+        memo: check_memo(args.memo.as_ref()).map_err(to_transfer_from_error)?,
+        created_at_time: args.created_at_time,
+        ..Default::default()
+    };
+    check_duplicate(
+        &lp_token,
+        LPBlockType::TransferFrom,
+        &args.from,
+        Some(&args.to),
+        &args.amount,
+        &tx_args,
+        ts,
+    )
+    .map_err(to_transfer_from_error)?;
+
+    // the owner of the account does not need an allowance
+    let allowance = if args.from.to_string() == spender {
+        None
+    } else {
+        let allowance = user_map::get_by_account(&args.from)
+            .map_err(|e| to_transfer_from_error(generic_error(e)))?
+            .and_then(|user| lp_allowance_map::get(lp_token.token_id(), user.user_id, &spender));
+        let current_allowance = allowance.as_ref().map_or_else(nat_zero, |allowance| allowance.amount_at(ts));
+        if current_allowance < args.amount {
+            return Err(TransferFromError::InsufficientAllowance {
+                allowance: current_allowance,
+            });
+        }
+        allowance
+    };
+
+    let balance = balance_of(lp_token.token_id(), &args.from, ts);
+    if balance < args.amount {
+        return Err(TransferFromError::InsufficientFunds { balance });
+    }
+
+    let from_user_id = user_map::insert_by_account(&args.from).map_err(|e| to_transfer_from_error(generic_error(e)))?;
+    let block_id =
+        transfer(&lp_token, from_user_id, &args.to, &args.amount, &tx_args, ts).map_err(|e| to_transfer_from_error(generic_error(e)))?;
+
+    // decrement the allowance, remove it once used up
+    if let Some(allowance) = allowance {
+        let amount = nat_subtract(&allowance.amount, &args.amount).unwrap_or(nat_zero());
+        if nat_is_zero(&amount) {
+            lp_allowance_map::remove(allowance.allowance_id);
+        } else {
+            lp_allowance_map::upsert(&StableLPAllowance { amount, ts, ..allowance });
+        }
+    }
+
+    Ok(Nat::from(block_id))
+}
+
+fn to_approve_error(e: TransferError) -> ApproveError {
+    match e {
+        TransferError::BadFee { expected_fee } => ApproveError::BadFee { expected_fee },
+        TransferError::InsufficientFunds { balance } => ApproveError::InsufficientFunds { balance },
+        TransferError::TooOld => ApproveError::TooOld,
+        TransferError::CreatedInFuture { ledger_time } => ApproveError::CreatedInFuture { ledger_time },
+        TransferError::Duplicate { duplicate_of } => ApproveError::Duplicate { duplicate_of },
+        TransferError::TemporarilyUnavailable => ApproveError::TemporarilyUnavailable,
+        TransferError::BadBurn { .. } => ApproveError::GenericError {
+            error_code: nat_zero(),
+            message: e.to_string(),
+        },
+        TransferError::GenericError { error_code, message } => ApproveError::GenericError { error_code, message },
+    }
+}
+
+fn to_transfer_from_error(e: TransferError) -> TransferFromError {
+    match e {
+        TransferError::BadFee { expected_fee } => TransferFromError::BadFee { expected_fee },
+        TransferError::BadBurn { min_burn_amount } => TransferFromError::BadBurn { min_burn_amount },
+        TransferError::InsufficientFunds { balance } => TransferFromError::InsufficientFunds { balance },
+        TransferError::TooOld => TransferFromError::TooOld,
+        TransferError::CreatedInFuture { ledger_time } => TransferFromError::CreatedInFuture { ledger_time },
+        TransferError::Duplicate { duplicate_of } => TransferFromError::Duplicate { duplicate_of },
+        TransferError::TemporarilyUnavailable => TransferFromError::TemporarilyUnavailable,
+        TransferError::GenericError { error_code, message } => TransferFromError::GenericError { error_code, message },
+    }
+}
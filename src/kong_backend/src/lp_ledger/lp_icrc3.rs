@@ -0,0 +1,150 @@
+use candid::Nat;
+use ic_cdk::query;
+use icrc_ledger_types::icrc3::archive::{GetArchivesArgs, GetArchivesResult, ICRC3ArchiveInfo, QueryArchiveFn};
+use icrc_ledger_types::icrc3::blocks::{
+    ArchivedBlocks, BlockWithId, GetBlocksRequest, GetBlocksResult, ICRC3DataCertificate, SupportedBlockType,
+};
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::kong_backend;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_block::stable_lp_block::StableLPBlockId;
+use crate::stable_memory::LP_BLOCK_ARCHIVE_MAP;
+
+const MAX_BLOCKS: u64 = 1_000;
+const ICRC3_GET_ARCHIVED_BLOCKS: &str = "icrc3_get_archived_blocks";
+
+/// ICRC-3 blocks of the LP token log. blocks no longer in LP_BLOCK_MAP are returned as archived_blocks
+/// with icrc3_get_archived_blocks() as the callback
+#[query(guard = "not_in_maintenance_mode")]
+fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    let log_length = lp_block_map::get_log_length();
+    let first_block_id = lp_block_map::get_first_block_id();
+
+    let mut blocks = Vec::new();
+    let mut archived_args = Vec::new();
+    for (start, end) in to_ranges(&args, log_length) {
+        // archived part of the range
+        if start < first_block_id {
+            let archived_end = end.min(first_block_id);
+            archived_args.push(GetBlocksRequest {
+                start: Nat::from(start),
+                length: Nat::from(archived_end - start),
+            });
+        }
+        // blocks in LP_BLOCK_MAP
+        for block_id in start.max(first_block_id)..end {
+            if blocks.len() as u64 >= MAX_BLOCKS {
+                break;
+            }
+            if let Some(block) = lp_block_map::get(block_id) {
+                blocks.push(BlockWithId {
+                    id: Nat::from(block_id),
+                    block: block.to_value(),
+                });
+            }
+        }
+    }
+
+    let archived_blocks = if archived_args.is_empty() {
+        Vec::new()
+    } else {
+        vec![ArchivedBlocks {
+            args: archived_args,
+            callback: QueryArchiveFn::new(kong_backend(), ICRC3_GET_ARCHIVED_BLOCKS),
+        }]
+    };
+
+    GetBlocksResult {
+        log_length: Nat::from(log_length),
+        blocks,
+        archived_blocks,
+    }
+}
+
+/// archived ICRC-3 blocks of the LP token log from LP_BLOCK_ARCHIVE_MAP
+#[query(guard = "not_in_maintenance_mode")]
+fn icrc3_get_archived_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    let log_length = lp_block_map::get_log_length();
+
+    let mut blocks = Vec::new();
+    LP_BLOCK_ARCHIVE_MAP.with(|m| {
+        let map = m.borrow();
+        for (start, end) in to_ranges(&args, log_length) {
+            for (block_id, block) in map.range(StableLPBlockId(start)..StableLPBlockId(end)) {
+                if blocks.len() as u64 >= MAX_BLOCKS {
+                    return;
+                }
+                blocks.push(BlockWithId {
+                    id: Nat::from(block_id.0),
+                    block: block.to_value(),
+                });
+            }
+        }
+    });
+
+    GetBlocksResult {
+        log_length: Nat::from(log_length),
+        blocks,
+        archived_blocks: Vec::new(),
+    }
+}
+
+/// archives of the LP token log. Kong keeps its archive in its own stable memory
+#[query(guard = "not_in_maintenance_mode")]
+fn icrc3_get_archives(args: GetArchivesArgs) -> GetArchivesResult {
+    // only one archive, none after it
+    if args.from.is_some_and(|from| from == kong_backend()) {
+        return Vec::new();
+    }
+
+    LP_BLOCK_ARCHIVE_MAP.with(|m| {
+        let map = m.borrow();
+        match (map.first_key_value(), map.last_key_value()) {
+            (Some((start, _)), Some((end, _))) => vec![ICRC3ArchiveInfo {
+                canister_id: kong_backend(),
+                start: Nat::from(start.0),
+                end: Nat::from(end.0),
+            }],
+            _ => Vec::new(),
+        }
+    })
+}
+
+/// certificate of the hash and index of the last block of the LP token log
+#[query(guard = "not_in_maintenance_mode")]
+fn icrc3_get_tip_certificate() -> Option<ICRC3DataCertificate> {
+    let certificate = ic_cdk::api::data_certificate()?;
+    let tip = lp_block_map::get_tip()?;
+
+    let mut hash_tree = serde_cbor::ser::Serializer::new(Vec::new());
+    hash_tree.self_describe().ok()?;
+    lp_block_map::tip_hash_tree(&tip).serialize(&mut hash_tree).ok()?;
+
+    Some(ICRC3DataCertificate {
+        certificate: ByteBuf::from(certificate),
+        hash_tree: ByteBuf::from(hash_tree.into_inner()),
+    })
+}
+
+#[query]
+fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    ["1mint", "1burn", "1xfer", "2xfer", "2approve"]
+        .into_iter()
+        .map(|block_type| SupportedBlockType {
+            block_type: block_type.to_string(),
+            url: "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-3/README.md".to_string(),
+        })
+        .collect()
+}
+
+/// requested ranges as [start, end) block_ids capped at log_length
+fn to_ranges(args: &[GetBlocksRequest], log_length: u64) -> Vec<(u64, u64)> {
+    args.iter()
+        .filter_map(|arg| arg.as_start_and_length().ok())
+        .map(|(start, length)| (start.min(log_length), start.saturating_add(length).min(log_length)))
+        .filter(|(start, end)| start < end)
+        .collect()
+}
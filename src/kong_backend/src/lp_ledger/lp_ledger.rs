@@ -0,0 +1,236 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{Memo, TransferError};
+use serde_bytes::ByteBuf;
+
+use crate::chains::chains::LP_CHAIN;
+use crate::helpers::nat_helpers::{nat_subtract, nat_zero};
+use crate::send::send::{archive_to_kong_data, process_send};
+use crate::send::send_args::SendArgs;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_block::stable_lp_block::{LPBlockTxArgs, LPBlockType, StableLPBlock};
+use crate::stable_lp_lock::lp_lock_map;
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_request::{request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_user::user_map;
+
+// how far created_at_time can be ahead of the canister's time
+const PERMITTED_DRIFT_NANOSECS: u64 = 60_000_000_000; // 1 minute
+const MAX_MEMO_LENGTH: usize = 32;
+
+/// get LP token by symbol or address. ie. "ckBTC_ckUSDT" or "LP.ckBTC_ckUSDT"
+pub fn get_lp_token(lp_token: &str) -> Result<StableToken, String> {
+    match token_map::get_by_token(lp_token)? {
+        lp_token @ StableToken::LP(_) => Ok(lp_token),
+        _ => Err("Token is not an LP token".to_string()),
+    }
+}
+
+/// get LP token for the queries with the standard ICRC-1/ICRC-2 return types, which have no error
+pub fn get_lp_token_or_trap(lp_token: &str) -> StableToken {
+    get_lp_token(lp_token).unwrap_or_else(|e| ic_cdk::trap(&e))
+}
+
+/// transferable LP token balance of an ICRC-1 account. locked LP tokens are not included
+pub fn balance_of(token_id: u32, account: &Account, ts: u64) -> Nat {
+    user_map::get_by_account(account)
+        .ok()
+        .flatten()
+        .and_then(|user| {
+            lp_token_map::get_by_token_id_by_user_id(token_id, user.user_id).map(|lp_token| {
+                let locked_amount = lp_lock_map::get_locked_amount(token_id, user.user_id, ts);
+                nat_subtract(&lp_token.amount, &locked_amount).unwrap_or(nat_zero())
+            })
+        })
+        .unwrap_or(nat_zero())
+}
+
+/// make sure the fee, if specified, is the LP token's fee and created_at_time, if specified, is within the transfer expiry
+pub fn check_fee_and_created_at_time(
+    lp_token: &StableToken,
+    fee: Option<&Nat>,
+    created_at_time: Option<u64>,
+    ts: u64,
+) -> Result<(), TransferError> {
+    if let Some(fee) = fee {
+        if *fee != lp_token.fee() {
+            return Err(TransferError::BadFee {
+                expected_fee: lp_token.fee(),
+            });
+        }
+    }
+    if let Some(created_at_time) = created_at_time {
+        if created_at_time.saturating_add(kong_settings_map::get().transfer_expiry_nanosecs) < ts {
+            return Err(TransferError::TooOld);
+        }
+        if created_at_time > ts.saturating_add(PERMITTED_DRIFT_NANOSECS) {
+            return Err(TransferError::CreatedInFuture { ledger_time: ts });
+        }
+    }
+    Ok(())
+}
+
+/// make sure the memo, if specified, is at most 32 bytes. returns the memo to record in the block
+pub fn check_memo(memo: Option<&Memo>) -> Result<Option<ByteBuf>, TransferError> {
+    match memo {
+        Some(memo) if memo.0.len() > MAX_MEMO_LENGTH => Err(generic_error(format!("Memo longer than {} bytes", MAX_MEMO_LENGTH))),
+        Some(memo) => Ok(Some(memo.0.clone())),
+        None => Ok(None),
+    }
+}
+
+/// calls with created_at_time are deduplicated. fails with the block_id of the same call if it was recorded within the transfer expiry
+pub fn check_duplicate(
+    lp_token: &StableToken,
+    block_type: LPBlockType,
+    from: &Account,
+    to: Option<&Account>,
+    amount: &Nat,
+    tx_args: &LPBlockTxArgs,
+    ts: u64,
+) -> Result<(), TransferError> {
+    if tx_args.created_at_time.is_none() {
+        return Ok(());
+    }
+
+    let block = StableLPBlock {
+        block_id: 0,
+        phash: None,
+        block_type,
+        lp_token: lp_token.address_with_chain(),
+        from: Some(*from),
+        to: to.copied(),
+        amount: amount.clone(),
+        tx_args: tx_args.clone(),
+        ts,
+    };
+    let since_ts = ts.saturating_sub(
+        kong_settings_map::get()
+            .transfer_expiry_nanosecs
+            .saturating_add(PERMITTED_DRIFT_NANOSECS),
+    );
+    match lp_block_map::get_duplicate(&block, since_ts) {
+        Some(block_id) => Err(TransferError::Duplicate {
+            duplicate_of: Nat::from(block_id),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// transfer LP token from from_user_id to an ICRC-1 account. recorded as a send request and a send tx
+///
+/// # Returns
+/// the block_id of the ICRC-3 transfer block
+pub fn transfer(
+    lp_token: &StableToken,
+    from_user_id: u32,
+    to: &Account,
+    amount: &Nat,
+    tx_args: &LPBlockTxArgs,
+    ts: u64,
+) -> Result<u64, String> {
+    let to_user_id = user_map::insert_by_account(to)?;
+    let to_address = to.to_string();
+    let args = SendArgs {
+        token: lp_token.address_with_chain(),
+        amount: amount.clone(),
+        to_address: to_address.clone(),
+    };
+    let request_id = request_map::insert(&StableRequest::new(from_user_id, &Request::Send(args), ts));
+
+    let result = process_send(
+        request_id,
+        from_user_id,
+        to_user_id,
+        &to_address,
+        lp_token.token_id(),
+        LP_CHAIN,
+        &lp_token.symbol(),
+        amount,
+        tx_args,
+        ts,
+    )
+    .map_or_else(
+        |e| {
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            Err(e)
+        },
+        |(_, block_id)| {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(block_id)
+        },
+    );
+
+    request_map::get_by_request_and_user_id(Some(request_id), Some(from_user_id), None)
+        .first()
+        .map(archive_to_kong_data);
+
+    result
+}
+
+pub fn generic_error(message: String) -> TransferError {
+    TransferError::GenericError {
+        error_code: nat_zero(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    use crate::helpers::test_fixtures::{insert_lp_balance, insert_user};
+    use crate::stable_lp_lock::stable_lp_lock::{StableLPLock, StableLPLockId};
+    use crate::stable_memory::LP_LOCK_MAP;
+
+    #[test]
+    fn test_balance_of_excludes_locked() {
+        let account = Account {
+            owner: Principal::from_slice(&[1]),
+            subaccount: None,
+        };
+        insert_user(1, &account);
+        insert_lp_balance(1, 1, 2, 1_000);
+        assert_eq!(balance_of(2, &account, 10), Nat::from(1_000_u32));
+
+        LP_LOCK_MAP.with(|m| {
+            m.borrow_mut().insert(
+                StableLPLockId {
+                    token_id: 2,
+                    user_id: 1,
+                    lock_id: 1,
+                },
+                StableLPLock {
+                    lock_id: 1,
+                    user_id: 1,
+                    token_id: 2,
+                    amount: Nat::from(400_u32),
+                    unlock_ts: 100,
+                    vesting: false,
+                    ts: 0,
+                },
+            )
+        });
+        assert_eq!(balance_of(2, &account, 10), Nat::from(600_u32));
+        // all transferable once unlocked
+        assert_eq!(balance_of(2, &account, 100), Nat::from(1_000_u32));
+        // no balance for other accounts
+        let other = Account {
+            owner: Principal::from_slice(&[1]),
+            subaccount: Some([1; 32]),
+        };
+        assert_eq!(balance_of(2, &other, 10), nat_zero());
+    }
+
+    #[test]
+    fn test_check_memo() {
+        assert_eq!(check_memo(None).unwrap(), None);
+        let memo = Memo(ByteBuf::from(vec![1; 32]));
+        assert_eq!(check_memo(Some(&memo)).unwrap(), Some(ByteBuf::from(vec![1; 32])));
+        let memo = Memo(ByteBuf::from(vec![1; 33]));
+        assert!(check_memo(Some(&memo)).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod lp_icrc1;
+pub mod lp_icrc2;
+pub mod lp_icrc3;
+#[allow(clippy::module_inception)]
+pub mod lp_ledger;
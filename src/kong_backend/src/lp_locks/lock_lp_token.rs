@@ -0,0 +1,52 @@
+use ic_cdk::update;
+
+use super::lock_lp_token_args::LockLPTokenArgs;
+use super::lp_lock_reply::LPLockReply;
+use super::lp_lock_reply_helpers::to_lp_lock_reply;
+
+use crate::helpers::nat_helpers::{nat_is_zero, nat_subtract};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::lp_ledger::lp_ledger::get_lp_token;
+use crate::stable_lp_lock::{lp_lock_map, stable_lp_lock::StableLPLock};
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_token::token::Token;
+use crate::stable_user::user_map;
+
+/// lock LP tokens of the caller until unlock_at, optionally vesting linearly until then
+/// - locked LP tokens can not be sent or removed from the pool. they can not be unlocked early
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn lock_lp_token(args: LockLPTokenArgs) -> Result<LPLockReply, String> {
+    let ts = get_time();
+    let lp_token_id = get_lp_token(&args.lp_token)?.token_id();
+    if nat_is_zero(&args.amount) {
+        return Err("Amount is zero".to_string());
+    }
+    if args.unlock_at <= ts {
+        return Err("Unlock time must be in the future".to_string());
+    }
+    let user_id = user_map::get_by_caller()?.ok_or("Not enough LP token")?.user_id;
+
+    // only LP tokens not already locked can be locked
+    let balance = lp_token_map::get_by_token_id_by_user_id(lp_token_id, user_id)
+        .ok_or("Not enough LP token")?
+        .amount;
+    lp_lock_map::remove_unlocked(lp_token_id, user_id, ts);
+    let locked_amount = lp_lock_map::get_locked_amount(lp_token_id, user_id, ts);
+    if nat_subtract(&balance, &locked_amount).is_none_or(|unlocked_amount| unlocked_amount < args.amount) {
+        return Err(format!("Not enough unlocked LP token. {} locked", locked_amount));
+    }
+
+    let mut lock = StableLPLock {
+        lock_id: 0, // will be set with insert into LP_LOCK_MAP
+        user_id,
+        token_id: lp_token_id,
+        amount: args.amount,
+        unlock_ts: args.unlock_at,
+        vesting: args.vesting.unwrap_or(false),
+        ts,
+    };
+    lock.lock_id = lp_lock_map::insert(&lock);
+
+    Ok(to_lp_lock_reply(&lock, ts))
+}
@@ -0,0 +1,11 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `lock_lp_token` function.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LockLPTokenArgs {
+    pub lp_token: String,      // LP token symbol or address. ie. "ckBTC_ckUSDT" or "LP.ckBTC_ckUSDT"
+    pub amount: Nat,           // LP tokens to lock
+    pub unlock_at: u64,        // nanoseconds since the Unix epoch the LP tokens are fully unlocked
+    pub vesting: Option<bool>, // if true, the LP tokens unlock linearly until unlock_at. defaults to false
+}
@@ -0,0 +1,22 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the reply of the `lock_lp_token` and `lp_locks` functions.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LPLockReply {
+    pub lock_id: u64,
+    pub lp_token_symbol: String,
+    pub amount: Nat,        // LP tokens locked at ts
+    pub locked_amount: Nat, // LP tokens still locked now
+    pub unlock_at: u64,
+    pub vesting: bool,
+    pub ts: u64,
+}
+
+/// LP tokens of a lock still locked, in the `pools` and `tokens` replies
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LPUnlockReply {
+    pub amount: Nat, // LP tokens still locked
+    pub unlock_at: u64,
+    pub vesting: bool,
+}
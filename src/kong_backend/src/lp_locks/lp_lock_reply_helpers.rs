@@ -0,0 +1,39 @@
+use candid::Nat;
+
+use super::lp_lock_reply::{LPLockReply, LPUnlockReply};
+
+use crate::helpers::nat_helpers::{nat_add, nat_divide_as_f64, nat_zero};
+use crate::stable_lp_lock::lp_lock_map;
+use crate::stable_lp_lock::stable_lp_lock::StableLPLock;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+
+pub fn to_lp_lock_reply(lock: &StableLPLock, ts: u64) -> LPLockReply {
+    let lp_token_symbol =
+        token_map::get_by_token_id(lock.token_id).map_or_else(|| "LP token not found".to_string(), |token| token.symbol());
+    LPLockReply {
+        lock_id: lock.lock_id,
+        lp_token_symbol,
+        amount: lock.amount.clone(),
+        locked_amount: lock.locked_amount_at(ts),
+        unlock_at: lock.unlock_ts,
+        vesting: lock.vesting,
+        ts: lock.ts,
+    }
+}
+
+/// LP tokens of token_id locked at ts, the percentage of total_supply locked and the locks by unlock date
+pub fn to_lp_locked_reply(token_id: u32, total_supply: &Nat, ts: u64) -> (Nat, f64, Vec<LPUnlockReply>) {
+    let mut lp_unlocks: Vec<LPUnlockReply> = lp_lock_map::get_locked_by_token_id(token_id, ts)
+        .iter()
+        .map(|lock| LPUnlockReply {
+            amount: lock.locked_amount_at(ts),
+            unlock_at: lock.unlock_ts,
+            vesting: lock.vesting,
+        })
+        .collect();
+    lp_unlocks.sort_by_key(|unlock| unlock.unlock_at);
+    let lp_locked = lp_unlocks.iter().fold(nat_zero(), |acc, unlock| nat_add(&acc, &unlock.amount));
+    let lp_locked_pct = nat_divide_as_f64(&lp_locked, total_supply).map_or(0_f64, |ratio| ratio * 100_f64);
+    (lp_locked, lp_locked_pct, lp_unlocks)
+}
@@ -0,0 +1,25 @@
+use ic_cdk::query;
+
+use super::lp_lock_reply::LPLockReply;
+use super::lp_lock_reply_helpers::to_lp_lock_reply;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::lp_ledger::lp_ledger::get_lp_token;
+use crate::stable_lp_lock::lp_lock_map;
+use crate::stable_token::token::Token;
+use crate::stable_user::user_map;
+
+/// returns the locks of an LP token still locked, or all locks of the caller if lp_token is not specified
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_locks(lp_token: Option<String>) -> Result<Vec<LPLockReply>, String> {
+    let ts = get_time();
+    let locks = match lp_token {
+        Some(lp_token) => lp_lock_map::get_locked_by_token_id(get_lp_token(&lp_token)?.token_id(), ts),
+        None => match user_map::get_by_caller()? {
+            Some(user) => lp_lock_map::get_by_user_id(user.user_id),
+            None => Vec::new(),
+        },
+    };
+    Ok(locks.iter().map(|lock| to_lp_lock_reply(lock, ts)).collect())
+}
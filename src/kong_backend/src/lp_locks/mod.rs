@@ -0,0 +1,6 @@
+pub mod lock_lp_token;
+pub mod lock_lp_token_args;
+pub mod lp_lock_reply;
+pub mod lp_lock_reply_helpers;
+#[allow(clippy::module_inception)]
+pub mod lp_locks;
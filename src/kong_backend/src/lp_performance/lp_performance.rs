@@ -0,0 +1,128 @@
+use candid::Nat;
+use ic_cdk::query;
+
+use super::lp_performance_reply::LPPerformanceReply;
+
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_multiply, nat_subtract, nat_to_decimals_f64, nat_zero};
+use crate::ic::ckusdt::{ckusdt_amount, to_ckusdt_decimals_f64};
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode_and_caller_is_not_anonymous};
+use crate::lp_ledger::lp_ledger::get_lp_token;
+use crate::stable_farm_stake::farm_stake_map;
+use crate::stable_lp_history::{lp_history_map, stable_lp_history::StableLPHistory};
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::{PoolType, StablePool};
+use crate::stable_token::stable_token::StableToken;
+use crate::stable_token::token::Token;
+use crate::stable_user::user_map;
+
+/// returns the cost basis, LP fees earned, impermanent loss and PnL of the user's liquidity in each pool
+/// from the user's LP histories. includes pools the user has fully removed from
+///
+/// # Arguments
+/// lp_token: Option<String> - LP token symbol or address. ie. "ckBTC_ckUSDT" or "LP.ckBTC_ckUSDT". all pools if not specified
+#[query(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn lp_performance(lp_token: Option<String>) -> Result<Vec<LPPerformanceReply>, String> {
+    let user_id = user_map::get_by_caller().ok().flatten().ok_or("User not found")?.user_id;
+    let pool_id = match lp_token {
+        Some(lp_token) => match get_lp_token(&lp_token)? {
+            StableToken::LP(lp_token) => Some(lp_token.pool_of().ok_or("Pool not found")?.pool_id),
+            _ => None,
+        },
+        None => None,
+    };
+    let ts = get_time();
+
+    Ok(lp_history_map::get_by_user_id(user_id)
+        .iter()
+        .filter(|history| pool_id.is_none_or(|pool_id| history.pool_id == pool_id))
+        .filter_map(|history| {
+            // concentrated liquidity pools have positions instead of LP tokens
+            let pool = pool_map::get_by_pool_id(history.pool_id).filter(|pool| pool.pool_type != PoolType::ConcentratedLiquidity)?;
+            to_lp_performance_reply(&pool, history, user_id, ts)
+        })
+        .collect())
+}
+
+fn to_lp_performance_reply(pool: &StablePool, history: &StableLPHistory, user_id: u32, ts: u64) -> Option<LPPerformanceReply> {
+    let lp_token = pool.lp_token();
+    let lp_token_id = lp_token.token_id();
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+
+    // LP tokens held, including LP tokens staked in farms
+    let user_lp_token_balance = nat_add(
+        &lp_token_map::get_by_token_id_by_user_id(lp_token_id, user_id).map_or_else(nat_zero, |lp_token| lp_token.amount),
+        &farm_stake_map::get_staked_amount(lp_token_id, user_id),
+    );
+    let lp_token_total_supply = lp_token_map::get_total_supply(lp_token_id);
+
+    // user's share of the reserves and of the LP fees of the pool. LP fees already in the pool when the LP tokens were minted are not earned
+    let share = |amount: &Nat| nat_divide(&nat_multiply(amount, &user_lp_token_balance), &lp_token_total_supply).unwrap_or(nat_zero());
+    let raw_amount_0 = share(&nat_add(&pool.balance_0, &pool.lp_fee_0));
+    let raw_amount_1 = share(&nat_add(&pool.balance_1, &pool.lp_fee_1));
+    let earned_fees = |lp_fee: &Nat, fee_debt: &Nat| nat_subtract(&share(lp_fee), fee_debt).unwrap_or(nat_zero());
+    let raw_fees_0 = nat_add(&history.lp_fee_0, &earned_fees(&pool.lp_fee_0, &history.fee_debt_0));
+    let raw_fees_1 = nat_add(&history.lp_fee_1, &earned_fees(&pool.lp_fee_1, &history.fee_debt_1));
+
+    // ckUSDT value at current prices
+    let usd_value = |raw_amount_0: &Nat, raw_amount_1: &Nat| {
+        let usd_amount_0 = ckusdt_amount(&token_0, raw_amount_0)
+            .ok()
+            .and_then(|amount| to_ckusdt_decimals_f64(&amount));
+        let usd_amount_1 = ckusdt_amount(&token_1, raw_amount_1)
+            .ok()
+            .and_then(|amount| to_ckusdt_decimals_f64(&amount));
+        usd_amount_0.unwrap_or(0_f64) + usd_amount_1.unwrap_or(0_f64)
+    };
+    // ckUSDT values recorded at the time of the txs
+    let usd_deposited = to_ckusdt_decimals_f64(&history.usd_deposited).unwrap_or(0_f64);
+    let usd_withdrawn = to_ckusdt_decimals_f64(&history.usd_withdrawn).unwrap_or(0_f64);
+    let usd_hodl_value = usd_value(&history.deposited_0, &history.deposited_1);
+    let usd_balance = usd_value(&raw_amount_0, &raw_amount_1);
+    let usd_fees = usd_value(&raw_fees_0, &raw_fees_1);
+    let usd_pnl = usd_balance + usd_withdrawn - usd_deposited;
+    // against holding the deposited tokens, all at current prices
+    let usd_impermanent_loss = usd_balance + usd_value(&history.withdrawn_0, &history.withdrawn_1) - usd_hodl_value - usd_fees;
+    let impermanent_loss_pct = if usd_hodl_value > 0_f64 {
+        usd_impermanent_loss / usd_hodl_value * 100_f64
+    } else {
+        0_f64
+    };
+
+    let deposited_0 = nat_to_decimals_f64(token_0.decimals(), &history.deposited_0)?;
+    let withdrawn_0 = nat_to_decimals_f64(token_0.decimals(), &history.withdrawn_0)?;
+    let amount_0 = nat_to_decimals_f64(token_0.decimals(), &raw_amount_0)?;
+    let deposited_1 = nat_to_decimals_f64(token_1.decimals(), &history.deposited_1)?;
+    let withdrawn_1 = nat_to_decimals_f64(token_1.decimals(), &history.withdrawn_1)?;
+    let amount_1 = nat_to_decimals_f64(token_1.decimals(), &raw_amount_1)?;
+
+    Some(LPPerformanceReply {
+        symbol: pool.symbol(),
+        lp_token_symbol: lp_token.symbol(),
+        balance: nat_to_decimals_f64(lp_token.decimals(), &user_lp_token_balance)?,
+        symbol_0: token_0.symbol(),
+        deposited_0,
+        withdrawn_0,
+        amount_0,
+        fees_0: nat_to_decimals_f64(token_0.decimals(), &raw_fees_0)?,
+        pnl_0: amount_0 + withdrawn_0 - deposited_0,
+        symbol_1: token_1.symbol(),
+        deposited_1,
+        withdrawn_1,
+        amount_1,
+        fees_1: nat_to_decimals_f64(token_1.decimals(), &raw_fees_1)?,
+        pnl_1: amount_1 + withdrawn_1 - deposited_1,
+        usd_deposited,
+        usd_hodl_value,
+        usd_withdrawn,
+        usd_balance,
+        usd_fees,
+        usd_impermanent_loss,
+        impermanent_loss_pct,
+        usd_pnl,
+        first_ts: history.first_ts,
+        last_ts: history.last_ts,
+        ts,
+    })
+}
@@ -0,0 +1,34 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+/// performance of a user's liquidity in a pool. amounts are in token units
+/// usd_deposited and usd_withdrawn are ckUSDT values at the time of the txs, other ckUSDT values are at current prices
+#[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
+pub struct LPPerformanceReply {
+    pub symbol: String,
+    pub lp_token_symbol: String,
+    pub balance: f64, // LP tokens held, including LP tokens staked in farms
+    pub symbol_0: String,
+    pub deposited_0: f64, // cost basis. token_0 added with add_pool and add_liquidity, plus LP tokens received less sent
+    pub withdrawn_0: f64, // token_0 received with remove_liquidity, including LP fees
+    pub amount_0: f64,    // token_0 of the LP tokens held, including LP fees
+    pub fees_0: f64,      // LP fees of token_0 earned since the LP tokens were minted, received or held
+    pub pnl_0: f64,       // amount_0 + withdrawn_0 - deposited_0
+    pub symbol_1: String,
+    pub deposited_1: f64,
+    pub withdrawn_1: f64,
+    pub amount_1: f64,
+    pub fees_1: f64,
+    pub pnl_1: f64,
+    pub usd_deposited: f64,        // ckUSDT value of deposited_0 and deposited_1 when deposited
+    pub usd_hodl_value: f64,       // ckUSDT value of deposited_0 and deposited_1 at current prices
+    pub usd_withdrawn: f64,        // ckUSDT value of withdrawn_0 and withdrawn_1 when removed
+    pub usd_balance: f64,          // ckUSDT value of amount_0 and amount_1
+    pub usd_fees: f64,             // ckUSDT value of fees_0 and fees_1
+    pub usd_impermanent_loss: f64, // balance and withdrawn tokens less usd_hodl_value and usd_fees. negative is a loss against holding
+    pub impermanent_loss_pct: f64, // usd_impermanent_loss in percent of usd_hodl_value
+    pub usd_pnl: f64,              // usd_balance + usd_withdrawn - usd_deposited
+    pub first_ts: u64,             // first add liquidity or LP tokens received
+    pub last_ts: u64,              // last add or remove liquidity or LP token send
+    pub ts: u64,
+}
@@ -0,0 +1,3 @@
+#[allow(clippy::module_inception)]
+pub mod lp_performance;
+pub mod lp_performance_reply;
@@ -0,0 +1,35 @@
+use ic_cdk::update;
+
+use super::close_order::close_order;
+use super::order_reply::OrderReply;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::stable_order::{order_map, stable_order::OrderStatus};
+use crate::stable_request::{request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_user::user_map;
+
+/// cancel an open limit order and return the escrowed pay token
+#[update(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+pub async fn cancel_order(order_id: u64) -> Result<OrderReply, String> {
+    let user_id = user_map::get_by_caller()?.ok_or("Order not found")?.user_id;
+    let order = order_map::get_by_order_id(order_id)
+        .filter(|order| order.user_id == user_id)
+        .ok_or("Order not found")?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::CancelOrder(order_id), ts));
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+    request_map::update_status(request_id, StatusCode::CancelOrder, None);
+
+    close_order(request_id, &order, OrderStatus::Cancelled, ts).await.map_or_else(
+        |e| {
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            Err(e)
+        },
+        |reply| {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        },
+    )
+}
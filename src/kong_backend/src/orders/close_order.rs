@@ -0,0 +1,70 @@
+use super::order_reply::OrderReply;
+use super::order_reply_helpers::to_order_reply;
+
+use crate::helpers::nat_helpers::{nat_subtract, nat_zero};
+use crate::ic::{address::Address, transfer::icrc1_transfer};
+use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_order::{
+    order_map,
+    stable_order::{OrderStatus, StableOrder},
+};
+use crate::stable_request::{reply::Reply, request_map, status::StatusCode};
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+
+/// close an open order as cancelled or expired and return the escrowed pay token to the user
+/// if the transfer fails, the pay token is saved as a claim
+pub async fn close_order(request_id: u64, order: &StableOrder, status: OrderStatus, ts: u64) -> Result<OrderReply, String> {
+    // set the order to executing so it can not be filled while the escrow is returned
+    let order = order_map::update_executing_status(order.order_id, request_id).ok_or_else(|| {
+        let e = format!("Order #{} is not open", order.order_id);
+        request_map::update_status(request_id, StatusCode::OrderNotOpen, Some(&e));
+        e
+    })?;
+    let pay_token = token_map::get_by_token_id(order.pay_token_id).ok_or("Pay token not found")?;
+
+    let mut transfer_ids = Vec::new();
+    let mut claim_ids = Vec::new();
+
+    request_map::update_status(request_id, StatusCode::ReturnPayToken, None);
+
+    let pay_amount_with_gas = nat_subtract(&order.pay_amount, &pay_token.fee()).unwrap_or(nat_zero());
+    match icrc1_transfer(&pay_amount_with_gas, &order.from_principal_id, &pay_token, None).await {
+        Ok(tx_id) => {
+            let transfer_id = transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: false,
+                amount: pay_amount_with_gas,
+                token_id: pay_token.token_id(),
+                tx_id: TxId::BlockIndex(tx_id),
+                ts,
+            });
+            transfer_ids.push(transfer_id);
+            request_map::update_status(request_id, StatusCode::ReturnPayTokenSuccess, None);
+        }
+        Err(e) => {
+            let message = match claim_map::insert(&StableClaim::new(
+                order.user_id,
+                pay_token.token_id(),
+                &order.pay_amount,
+                Some(request_id),
+                Some(Address::PrincipalId(order.from_principal_id)),
+                ts,
+            )) {
+                Ok(claim_id) => {
+                    claim_ids.push(claim_id);
+                    format!("Saved as claim #{}. {}", claim_id, e)
+                }
+                Err(e) => format!("Failed to save claim. {}", e),
+            };
+            request_map::update_status(request_id, StatusCode::ReturnPayTokenFailed, Some(&message));
+        }
+    };
+
+    let order = order_map::update_status(order.order_id, status, &transfer_ids, &claim_ids, None).ok_or("Order not found")?;
+    let reply = to_order_reply(&order);
+    request_map::update_reply(request_id, Reply::Order(reply.clone()));
+
+    Ok(reply)
+}
@@ -0,0 +1,175 @@
+use candid::Nat;
+use ic_cdk::update;
+
+use super::limit_order_args::LimitOrderArgs;
+use super::order_reply::OrderReply;
+use super::order_reply_helpers::to_order_reply;
+
+use crate::helpers::nat_helpers::{nat_is_zero, nat_multiply_f64, nat_to_decimal_precision};
+use crate::ic::address::Address;
+use crate::ic::address_helpers::get_address;
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller_id;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_order::{order_map, stable_order::StableOrder};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_user::user_map;
+use crate::swap::swap_amounts::swap_mid_price;
+use crate::swap::swap_transfer_from::transfer_from_token;
+
+/// place a limit order to swap pay_amount of pay_token for receive_token at limit_price or better
+/// - before calling limit_order(), the user must icrc2_approve the pay_amount+gas of pay_token
+/// - pay_amount of pay_token is held in escrow until the order is filled, cancelled or expires
+/// - the order is filled by the background orders timer once the swap price reaches limit_price
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn limit_order(args: LimitOrderArgs) -> Result<OrderReply, String> {
+    let (user_id, pay_token, receive_token, receive_amount, to_address) = check_arguments(&args).await?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::LimitOrder(args.clone()), ts));
+
+    process_limit_order(
+        request_id,
+        user_id,
+        &pay_token,
+        &receive_token,
+        &receive_amount,
+        &to_address,
+        &args,
+        ts,
+    )
+    .await
+    .map_or_else(
+        |e| {
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            Err(e)
+        },
+        |reply| {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        },
+    )
+}
+
+/// returns (user_id, pay_token, receive_token, receive_amount, to_address)
+async fn check_arguments(args: &LimitOrderArgs) -> Result<(u32, StableToken, StableToken, Nat, Address), String> {
+    let pay_token = token_map::get_by_token(&args.pay_token)?;
+    let receive_token = token_map::get_by_token(&args.receive_token)?;
+    if pay_token.token_id() == receive_token.token_id() {
+        return Err("Pay and receive tokens must be different".to_string());
+    }
+    if !pay_token.is_icrc2() {
+        return Err("Pay token must support ICRC2".to_string());
+    }
+    if nat_is_zero(&args.pay_amount) {
+        return Err("Pay amount is zero".to_string());
+    }
+    if !args.limit_price.is_finite() || args.limit_price <= 0.0 {
+        return Err("Invalid limit price".to_string());
+    }
+    if args.expires_at <= get_time() {
+        return Err("Order has already expired".to_string());
+    }
+    // use specified address or default to caller's principal id
+    let to_address = match args.receive_address {
+        Some(ref address) => get_address(address).ok_or("Invalid receive address")?,
+        None => Address::PrincipalId(caller_id()),
+    };
+
+    // make sure there is a route between the tokens
+    swap_mid_price(&pay_token, &receive_token)?;
+
+    let receive_amount = calculate_receive_amount(&args.pay_amount, args.limit_price, pay_token.decimals(), receive_token.decimals())?;
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    Ok((user_id, pay_token, receive_token, receive_amount, to_address))
+}
+
+/// minimum receive amount at limit_price in receive token decimals
+fn calculate_receive_amount(pay_amount: &Nat, limit_price: f64, pay_decimals: u8, receive_decimals: u8) -> Result<Nat, String> {
+    let receive_amount_pay_decimals = nat_multiply_f64(pay_amount, limit_price).ok_or("Failed to calculate receive amount")?;
+    let receive_amount = nat_to_decimal_precision(&receive_amount_pay_decimals, pay_decimals, receive_decimals);
+    if nat_is_zero(&receive_amount) {
+        return Err("Receive amount is zero".to_string());
+    }
+    Ok(receive_amount)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_limit_order(
+    request_id: u64,
+    user_id: u32,
+    pay_token: &StableToken,
+    receive_token: &StableToken,
+    receive_amount: &Nat,
+    to_address: &Address,
+    args: &LimitOrderArgs,
+    ts: u64,
+) -> Result<OrderReply, String> {
+    let caller_id = caller_id();
+    let kong_backend = kong_settings_map::get().kong_backend_account;
+    let mut transfer_ids = Vec::new();
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // escrow the pay token. if this fails, nothing to return so just return the error
+    transfer_from_token(
+        request_id,
+        &caller_id,
+        pay_token,
+        &args.pay_amount,
+        &kong_backend,
+        &mut transfer_ids,
+        ts,
+    )
+    .await
+    .map_err(|e| format!("Req #{} failed. Pay token transfer_from failed. {}", request_id, e))?;
+
+    let order = StableOrder {
+        transfer_ids,
+        ..StableOrder::new(
+            user_id,
+            pay_token.token_id(),
+            &args.pay_amount,
+            receive_token.token_id(),
+            receive_amount,
+            args.limit_price,
+            &caller_id,
+            to_address,
+            args.expires_at,
+            request_id,
+            ts,
+        )
+    };
+    let order_id = order_map::insert(&order);
+    request_map::update_status(request_id, StatusCode::OrderOpen, Some(&format!("Order #{}", order_id)));
+
+    let reply = to_order_reply(&StableOrder { order_id, ..order });
+    request_map::update_reply(request_id, Reply::Order(reply.clone()));
+
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_receive_amount() {
+        // 1 pay token (8 decimals) at 2.5 receive token (6 decimals) per pay token
+        assert_eq!(
+            calculate_receive_amount(&Nat::from(100_000_000_u32), 2.5, 8, 6),
+            Ok(Nat::from(2_500_000_u32))
+        );
+        // 1 pay token (6 decimals) at 0.5 receive token (8 decimals) per pay token
+        assert_eq!(
+            calculate_receive_amount(&Nat::from(1_000_000_u32), 0.5, 6, 8),
+            Ok(Nat::from(50_000_000_u32))
+        );
+        // dust rounds to zero in receive token decimals
+        assert!(calculate_receive_amount(&Nat::from(10_u32), 1.0, 8, 6).is_err());
+    }
+}
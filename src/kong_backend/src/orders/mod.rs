@@ -0,0 +1,9 @@
+pub mod cancel_order;
+pub mod close_order;
+pub mod limit_order;
+pub mod limit_order_args;
+pub mod order_reply;
+pub mod order_reply_helpers;
+#[allow(clippy::module_inception)]
+pub mod orders;
+pub mod process_orders;
@@ -0,0 +1,26 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use crate::transfers::transfer_reply::TransferIdReply;
+
+/// Data structure for the reply of the `limit_order`, `cancel_order` and `orders` functions.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct OrderReply {
+    pub order_id: u64,
+    pub request_ids: Vec<u64>,
+    pub status: String,
+    pub pay_chain: String,
+    pub pay_symbol: String,
+    pub pay_amount: Nat,
+    pub receive_chain: String,
+    pub receive_symbol: String,
+    pub receive_amount: Nat, // minimum receive amount at limit_price
+    pub limit_price: f64,
+    pub to_address: String,
+    pub expires_at: u64,
+    pub transfer_ids: Vec<TransferIdReply>,
+    pub claim_ids: Vec<u64>,
+    pub tx_id: Option<u64>, // swap tx when the order is filled
+    pub ts: u64,
+}
@@ -0,0 +1,35 @@
+use super::order_reply::OrderReply;
+
+use crate::stable_order::stable_order::StableOrder;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+use crate::transfers::transfer_reply_helpers::to_transfer_ids;
+
+pub fn to_order_reply(order: &StableOrder) -> OrderReply {
+    let (pay_chain, pay_symbol) = token_map::get_by_token_id(order.pay_token_id).map_or_else(
+        || ("Pay chain not found".to_string(), "Pay symbol not found".to_string()),
+        |token| (token.chain().to_string(), token.symbol().to_string()),
+    );
+    let (receive_chain, receive_symbol) = token_map::get_by_token_id(order.receive_token_id).map_or_else(
+        || ("Receive chain not found".to_string(), "Receive symbol not found".to_string()),
+        |token| (token.chain().to_string(), token.symbol().to_string()),
+    );
+    OrderReply {
+        order_id: order.order_id,
+        request_ids: order.request_ids.clone(),
+        status: order.status.to_string(),
+        pay_chain,
+        pay_symbol,
+        pay_amount: order.pay_amount.clone(),
+        receive_chain,
+        receive_symbol,
+        receive_amount: order.receive_amount.clone(),
+        limit_price: order.limit_price,
+        to_address: order.to_address.to_string(),
+        expires_at: order.expires_at,
+        transfer_ids: to_transfer_ids(&order.transfer_ids),
+        claim_ids: order.claim_ids.clone(),
+        tx_id: order.tx_id,
+        ts: order.ts,
+    }
+}
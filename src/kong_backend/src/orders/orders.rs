@@ -0,0 +1,22 @@
+use ic_cdk::query;
+
+use super::order_reply::OrderReply;
+use super::order_reply_helpers::to_order_reply;
+
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::stable_order::order_map;
+use crate::stable_user::user_map;
+
+/// returns a specific limit order or all limit orders of the user
+#[query(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn orders(order_id: Option<u64>) -> Result<Vec<OrderReply>, String> {
+    let user_id = match user_map::get_by_caller() {
+        Ok(Some(caller)) => caller.user_id,
+        Ok(None) | Err(_) => return Ok(Vec::new()),
+    };
+    let orders = order_map::get_by_order_and_user_id(order_id, user_id)
+        .iter()
+        .map(to_order_reply)
+        .collect();
+    Ok(orders)
+}
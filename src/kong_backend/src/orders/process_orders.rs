@@ -0,0 +1,138 @@
+use super::close_order::close_order;
+
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode, logging::error_log};
+use crate::stable_order::{
+    order_map,
+    stable_order::{OrderStatus, StableOrder},
+};
+use crate::stable_request::{request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token_map};
+use crate::swap::calculate_amounts::calculate_amounts;
+use crate::swap::send_receive_token::send_receive_token;
+use crate::swap::swap_amounts::swap_mid_price;
+use crate::swap::update_liquidity_pool::update_liquidity_pool;
+
+// limit price already bounds the receive amount so slippage is not checked
+const ORDER_MAX_SLIPPAGE: f64 = 100.0;
+
+/// fill open limit orders that have reached their limit price and return the escrow of expired orders
+pub async fn process_orders() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let orders = order_map::get_open();
+    if orders.is_empty() {
+        return;
+    }
+
+    let ts = get_time();
+
+    for order in &orders {
+        if order.is_expired(ts) {
+            let request_id = request_map::insert(&StableRequest::new(order.user_id, &Request::ExpireOrder(order.order_id), ts));
+            request_map::update_status(request_id, StatusCode::Start, None);
+            request_map::update_status(request_id, StatusCode::OrderExpired, None);
+            match close_order(request_id, order, OrderStatus::Expired, ts).await {
+                Ok(_) => request_map::update_status(request_id, StatusCode::Success, None),
+                Err(e) => {
+                    error_log(&format!("Error expiring order #{}: {}", order.order_id, e));
+                    request_map::update_status(request_id, StatusCode::Failed, Some(&e))
+                }
+            };
+            continue;
+        }
+
+        let (pay_token, receive_token) = match (
+            token_map::get_by_token_id(order.pay_token_id),
+            token_map::get_by_token_id(order.receive_token_id),
+        ) {
+            (Some(pay_token), Some(receive_token)) => (pay_token, receive_token),
+            _ => continue, // continue to next order if token not found
+        };
+
+        // mid price is the best price possible. if it is below the limit price, the order can not be filled yet
+        match swap_mid_price(&pay_token, &receive_token) {
+            Ok(mid_price) if mid_price >= order.limit_price => (),
+            _ => continue,
+        }
+
+        // achievable price with the order's pay amount must give at least the minimum receive amount
+        if calculate_amounts(
+            &pay_token,
+            &order.pay_amount,
+            &receive_token,
+            Some(&order.receive_amount),
+            ORDER_MAX_SLIPPAGE,
+        )
+        .is_err()
+        {
+            continue;
+        }
+
+        if let Err(e) = execute_order(order, &pay_token, &receive_token, ts).await {
+            error_log(&format!("Error executing order #{}: {}", order.order_id, e));
+        }
+    }
+}
+
+/// fill the order through the swap path with the escrowed pay token
+async fn execute_order(order: &StableOrder, pay_token: &StableToken, receive_token: &StableToken, ts: u64) -> Result<(), String> {
+    let request_id = request_map::insert(&StableRequest::new(order.user_id, &Request::ExecuteOrder(order.order_id), ts));
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // set the order to executing so it can not be filled or cancelled twice
+    if order_map::update_executing_status(order.order_id, request_id).is_none() {
+        let e = format!("Order #{} is not open", order.order_id);
+        request_map::update_status(request_id, StatusCode::OrderNotOpen, Some(&e));
+        request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+        return Err(e);
+    }
+
+    request_map::update_status(request_id, StatusCode::ExecuteOrder, None);
+
+    // re-calculate receive_amount and swaps with the latest pool state
+    let (receive_amount, mid_price, price, slippage, swaps) = match update_liquidity_pool(
+        request_id,
+        pay_token,
+        &order.pay_amount,
+        receive_token,
+        Some(&order.receive_amount),
+        ORDER_MAX_SLIPPAGE,
+        Some(order.expires_at),
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            // limit price no longer met. escrow stays with Kong and the order is re-opened
+            order_map::update_status(order.order_id, OrderStatus::Open, &[], &[], None);
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+
+    let mut transfer_ids = Vec::new();
+    let mut claim_ids = Vec::new();
+    let reply = send_receive_token(
+        request_id,
+        order.user_id,
+        pay_token,
+        &order.pay_amount,
+        receive_token,
+        &receive_amount,
+        &order.to_address,
+        &mut transfer_ids,
+        &mut claim_ids,
+        mid_price,
+        price,
+        slippage,
+        &swaps,
+        ts,
+    )
+    .await;
+
+    order_map::update_status(order.order_id, OrderStatus::Filled, &transfer_ids, &claim_ids, Some(reply.tx_id));
+    request_map::update_status(request_id, StatusCode::Success, None);
+
+    Ok(())
+}
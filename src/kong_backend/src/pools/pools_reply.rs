@@ -1,6 +1,8 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::lp_locks::lp_lock_reply::LPUnlockReply;
+
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct PoolsReply {
     pub pools: Vec<PoolReply>,
@@ -26,7 +28,14 @@ pub struct PoolReply {
     pub balance_1: Nat,
     pub lp_fee_1: Nat,
     pub price: f64,
-    pub lp_fee_bps: u8,
+    pub lp_fee_pips: u32,      // current LP fee in pips. changes with volatility for dynamic fee pools
+    pub lp_fee_bps: u8,        // deprecated. lp_fee_pips rounded down to basis points
+    pub fee_tier: Option<u32>, // LP fee in pips of pools added to a token pair that already has a pool
+    pub pool_type: String,
+    pub amp: Option<u64>,          // current StableSwap amplification coefficient A
+    pub tick_spacing: Option<u32>, // tick spacing of concentrated liquidity pools
+    pub weight_0: Option<u8>,      // current weight of token_0 in percent of weighted and liquidity bootstrapping pools
+    pub lbp_end_ts: Option<u64>,   // end of the sale of liquidity bootstrapping pools
     pub on_kong: bool,
     pub tvl: Nat,
     pub rolling_24h_volume: Nat,
@@ -34,4 +43,7 @@ pub struct PoolReply {
     pub rolling_24h_num_swaps: Nat,
     pub rolling_24h_apy: f64,
     pub lp_token_symbol: String,
+    pub lp_locked: Nat,                 // LP tokens locked with lock_lp_token
+    pub lp_locked_pct: f64,             // percentage of the LP token supply locked
+    pub lp_unlocks: Vec<LPUnlockReply>, // locked LP tokens by unlock date
 }
@@ -3,7 +3,10 @@ use candid::Nat;
 use super::pools_reply::{PoolReply, PoolsReply};
 
 use crate::helpers::nat_helpers::{nat_add, nat_zero};
-use crate::stable_pool::stable_pool::StablePool;
+use crate::ic::get_time::get_time;
+use crate::lp_locks::lp_lock_reply_helpers::to_lp_locked_reply;
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_pool::stable_pool::{pips_to_bps, PoolType, StablePool, WEIGHT_PIPS_PER_PERCENT};
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 
@@ -12,7 +15,10 @@ pub fn to_pool_reply(pool: &StablePool) -> PoolReply {
     let token_1 = token_map::get_by_token_id(pool.token_id_1);
     let lp_token = pool.lp_token();
     let lp_token_symbol = lp_token.symbol().to_string();
+    let lp_token_id = lp_token.token_id();
+    let (lp_locked, lp_locked_pct, lp_unlocks) = to_lp_locked_reply(lp_token_id, &lp_token_map::get_total_supply(lp_token_id), get_time());
 
+    let lp_fee_pips = pool.get_lp_fee_pips();
     PoolReply {
         pool_id: pool.pool_id,
         name: pool.name(),
@@ -46,13 +52,25 @@ pub fn to_pool_reply(pool: &StablePool) -> PoolReply {
         balance_1: pool.balance_1.clone(),
         lp_fee_1: pool.lp_fee_1.clone(),
         price: pool.get_price_as_f64().unwrap_or(0_f64),
-        lp_fee_bps: pool.lp_fee_bps,
+        lp_fee_pips,
+        lp_fee_bps: pips_to_bps(lp_fee_pips),
+        fee_tier: pool.fee_tier,
+        pool_type: pool.pool_type.to_string(),
+        amp: pool.get_amp(),
+        tick_spacing: (pool.pool_type == PoolType::ConcentratedLiquidity).then_some(pool.tick_spacing),
+        weight_0: pool
+            .get_weights()
+            .map(|(weight_0, _)| ((weight_0 + WEIGHT_PIPS_PER_PERCENT / 2) / WEIGHT_PIPS_PER_PERCENT) as u8),
+        lbp_end_ts: (pool.pool_type == PoolType::LiquidityBootstrapping).then_some(pool.weight_end_ts),
         tvl: pool.tvl.clone(),
         rolling_24h_volume: pool.rolling_24h_volume.clone(),
         rolling_24h_lp_fee: pool.rolling_24h_lp_fee.clone(),
         rolling_24h_num_swaps: pool.rolling_24h_num_swaps.clone(),
         rolling_24h_apy: pool.rolling_24h_apy,
         lp_token_symbol,
+        lp_locked,
+        lp_locked_pct,
+        lp_unlocks,
         on_kong: pool.on_kong,
     }
 }
@@ -0,0 +1,300 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::add_position_args::AddPositionArgs;
+use super::add_position_reply::AddPositionReply;
+use super::position_reply_helpers::{create_add_position_reply, create_add_position_reply_failed};
+
+use crate::add_liquidity::add_liquidity::TokenIndex;
+use crate::add_liquidity::add_liquidity_transfer_from::{return_token, transfer_from_token};
+use crate::helpers::concentrated_liquidity_helpers::{amounts_for_liquidity, liquidity_for_amounts, sqrt_price_at_tick};
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode, id::caller_id};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::concentrated_liquidity::{check_ticks, mint_position};
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::{PoolType, StablePool};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::token::Token;
+use crate::stable_user::user_map;
+
+/// add a concentrated liquidity position between tick_lower and tick_upper
+/// - before calling add_position(), the user must icrc2_approve amount_0+gas of token_0 and amount_1+gas of token_1
+/// - only the amounts needed for the position at the current price are transferred. if the price moves while
+///   the tokens are transferred, any amount not used by the position is returned to the user
+/// - ticks must be multiples of the tick spacing of the pool. price of a tick is 1.0001^tick in raw token units
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn add_position(args: AddPositionArgs) -> Result<AddPositionReply, String> {
+    let (user_id, pool, add_amount_0, add_amount_1) = check_arguments(&args)?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddPosition(args.clone()), ts));
+
+    process_add_position(request_id, user_id, &pool, &add_amount_0, &add_amount_1, &args, ts)
+        .await
+        .map_or_else(
+            |e| {
+                request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+                Err(e)
+            },
+            |reply| {
+                request_map::update_status(request_id, StatusCode::Success, None);
+                Ok(reply)
+            },
+        )
+}
+
+/// returns (user_id, pool, add_amount_0, add_amount_1) where the amounts are needed for the position at the current price
+fn check_arguments(args: &AddPositionArgs) -> Result<(u32, StablePool, Nat, Nat), String> {
+    let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1, args.fee_tier)?;
+    if pool.pool_type != PoolType::ConcentratedLiquidity {
+        return Err(format!("Pool {} is not a concentrated liquidity pool", pool.symbol()));
+    }
+    check_ticks(&pool, args.tick_lower, args.tick_upper)?;
+
+    let sqrt_price_lower = sqrt_price_at_tick(args.tick_lower);
+    let sqrt_price_upper = sqrt_price_at_tick(args.tick_upper);
+    let liquidity = liquidity_for_amounts(
+        &pool.sqrt_price,
+        &sqrt_price_lower,
+        &sqrt_price_upper,
+        &args.amount_0,
+        &args.amount_1,
+    );
+    if nat_is_zero(&liquidity) {
+        return Err("Insufficient amounts for position".to_string());
+    }
+    let (add_amount_0, add_amount_1) = amounts_for_liquidity(&pool.sqrt_price, &sqrt_price_lower, &sqrt_price_upper, &liquidity, true);
+
+    // make sure tokens support ICRC2
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    if !token_0.is_icrc2() || !token_1.is_icrc2() {
+        return Err("Tokens must support ICRC2".to_string());
+    }
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    Ok((user_id, pool, add_amount_0, add_amount_1))
+}
+
+async fn process_add_position(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    add_amount_0: &Nat,
+    add_amount_1: &Nat,
+    args: &AddPositionArgs,
+    ts: u64,
+) -> Result<AddPositionReply, String> {
+    let caller_id = caller_id();
+    let kong_backend = kong_settings_map::get().kong_backend_account;
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    let mut transfer_ids = Vec::new();
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // a position out of range only needs one of the tokens
+    let transfer_0 = if nat_is_zero(add_amount_0) {
+        Ok(())
+    } else {
+        transfer_from_token(
+            request_id,
+            &caller_id,
+            &TokenIndex::Token0,
+            &token_0,
+            add_amount_0,
+            &kong_backend,
+            &mut transfer_ids,
+            ts,
+        )
+        .await
+    };
+    let transfer_1 = if transfer_0.is_err() || nat_is_zero(add_amount_1) {
+        Ok(())
+    } else {
+        transfer_from_token(
+            request_id,
+            &caller_id,
+            &TokenIndex::Token1,
+            &token_1,
+            add_amount_1,
+            &kong_backend,
+            &mut transfer_ids,
+            ts,
+        )
+        .await
+    };
+
+    if let Err(e) = transfer_0 {
+        // nothing was transferred so nothing to return
+        return Err(format!("Req #{} failed. {}", request_id, e));
+    }
+    if let Err(e) = transfer_1 {
+        return_tokens(
+            request_id,
+            user_id,
+            &caller_id,
+            pool,
+            Some(add_amount_0),
+            None,
+            args,
+            &mut transfer_ids,
+            ts,
+        )
+        .await;
+        return Err(format!("Req #{} failed. {}", request_id, e));
+    }
+
+    // re-calculate the position with the latest state of the pool (after token_0 and token_1 transfers)
+    request_map::update_status(request_id, StatusCode::AddPosition, None);
+    let mut pool = match pool_map::get_by_pool_id(pool.pool_id) {
+        Some(pool) => pool,
+        None => {
+            let e = format!("Pool {} not found", pool.symbol());
+            request_map::update_status(request_id, StatusCode::AddPositionFailed, Some(&e));
+            return_tokens(
+                request_id,
+                user_id,
+                &caller_id,
+                pool,
+                Some(add_amount_0),
+                Some(add_amount_1),
+                args,
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+    let (position, amount_0, amount_1) =
+        match mint_position(&mut pool, user_id, args.tick_lower, args.tick_upper, add_amount_0, add_amount_1, ts) {
+            Ok(result) => result,
+            Err(e) => {
+                request_map::update_status(request_id, StatusCode::AddPositionFailed, Some(&e));
+                return_tokens(
+                    request_id,
+                    user_id,
+                    &caller_id,
+                    &pool,
+                    Some(add_amount_0),
+                    Some(add_amount_1),
+                    args,
+                    &mut transfer_ids,
+                    ts,
+                )
+                .await;
+                return Err(format!("Req #{} failed. {}", request_id, e));
+            }
+        };
+    request_map::update_status(
+        request_id,
+        StatusCode::AddPositionSuccess,
+        Some(&format!("Position #{}", position.position_id)),
+    );
+
+    // amounts not used by the position are returned. if the amount is not enough to pay for the gas, it stays in the pool
+    let unused_amount_0 = nat_subtract(add_amount_0, &amount_0).unwrap_or(nat_zero());
+    let unused_amount_1 = nat_subtract(add_amount_1, &amount_1).unwrap_or(nat_zero());
+    let return_amount_0 = (unused_amount_0 > token_0.fee()).then_some(unused_amount_0);
+    let return_amount_1 = (unused_amount_1 > token_1.fee()).then_some(unused_amount_1);
+
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+    let pool_amount_0 = return_amount_0.as_ref().map_or(add_amount_0.clone(), |return_amount_0| {
+        nat_subtract(add_amount_0, return_amount_0).unwrap_or(nat_zero())
+    });
+    let pool_amount_1 = return_amount_1.as_ref().map_or(add_amount_1.clone(), |return_amount_1| {
+        nat_subtract(add_amount_1, return_amount_1).unwrap_or(nat_zero())
+    });
+    pool.balance_0 = nat_add(&pool.balance_0, &pool_amount_0);
+    pool.balance_1 = nat_add(&pool.balance_1, &pool_amount_1);
+    pool.update_tvl();
+    pool_map::update(&pool);
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+
+    let mut claim_ids = Vec::new();
+    if let Some(return_amount_0) = return_amount_0 {
+        return_token(
+            request_id,
+            user_id,
+            &caller_id,
+            &TokenIndex::Token0,
+            &token_0,
+            &return_amount_0,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+    if let Some(return_amount_1) = return_amount_1 {
+        return_token(
+            request_id,
+            user_id,
+            &caller_id,
+            &TokenIndex::Token1,
+            &token_1,
+            &return_amount_1,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+
+    let reply = create_add_position_reply(&pool, &position, request_id, &amount_0, &amount_1, &transfer_ids, &claim_ids, ts);
+    request_map::update_reply(request_id, Reply::AddPosition(reply.clone()));
+
+    Ok(reply)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn return_tokens(
+    request_id: u64,
+    user_id: u32,
+    to_principal_id: &Account,
+    pool: &StablePool,
+    amount_0: Option<&Nat>,
+    amount_1: Option<&Nat>,
+    args: &AddPositionArgs,
+    transfer_ids: &mut Vec<u64>,
+    ts: u64,
+) {
+    let mut claim_ids = Vec::new();
+
+    if let Some(amount_0) = amount_0.filter(|amount_0| !nat_is_zero(amount_0)) {
+        return_token(
+            request_id,
+            user_id,
+            to_principal_id,
+            &TokenIndex::Token0,
+            &pool.token_0(),
+            amount_0,
+            transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+
+    if let Some(amount_1) = amount_1.filter(|amount_1| !nat_is_zero(amount_1)) {
+        return_token(
+            request_id,
+            user_id,
+            to_principal_id,
+            &TokenIndex::Token1,
+            &pool.token_1(),
+            amount_1,
+            transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+
+    let reply = create_add_position_reply_failed(pool, request_id, args.tick_lower, args.tick_upper, transfer_ids, &claim_ids, ts);
+    request_map::update_reply(request_id, Reply::AddPosition(reply));
+}
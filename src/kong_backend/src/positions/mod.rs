@@ -0,0 +1,10 @@
+pub mod add_position;
+pub mod add_position_args;
+pub mod add_position_reply;
+pub mod position_reply;
+pub mod position_reply_helpers;
+#[allow(clippy::module_inception)]
+pub mod positions;
+pub mod remove_position;
+pub mod remove_position_args;
+pub mod remove_position_reply;
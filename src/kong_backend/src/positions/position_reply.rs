@@ -0,0 +1,24 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the reply of the `positions` function.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct PositionReply {
+    pub position_id: u64,
+    pub symbol: String,
+    pub chain_0: String,
+    pub symbol_0: String,
+    pub chain_1: String,
+    pub symbol_1: String,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub price_lower: f64, // price of token_0 in token_1 at tick_lower
+    pub price_upper: f64, // price of token_0 in token_1 at tick_upper
+    pub liquidity: Nat,
+    pub amount_0: Nat, // amount of token_0 at the current price of the pool
+    pub amount_1: Nat, // amount of token_1 at the current price of the pool
+    pub fee_0: Nat,    // uncollected fees of token_0
+    pub fee_1: Nat,    // uncollected fees of token_1
+    pub in_range: bool,
+    pub ts: u64,
+}
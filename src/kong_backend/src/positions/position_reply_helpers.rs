@@ -0,0 +1,137 @@
+use candid::Nat;
+
+use super::add_position_reply::AddPositionReply;
+use super::position_reply::PositionReply;
+use super::remove_position_reply::RemovePositionReply;
+
+use crate::helpers::math_helpers::price_rounded;
+use crate::helpers::nat_helpers::nat_zero;
+use crate::stable_lp_position::stable_lp_position::StableLPPosition;
+use crate::stable_pool::concentrated_liquidity::{position_amounts, position_fees};
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_tx::status_tx::StatusTx;
+use crate::transfers::transfer_reply_helpers::to_transfer_ids;
+
+pub fn to_position_reply(pool: &StablePool, position: &StableLPPosition) -> PositionReply {
+    let (amount_0, amount_1) = position_amounts(pool, position);
+    let (fee_0, fee_1) = position_fees(pool, position);
+    PositionReply {
+        position_id: position.position_id,
+        symbol: pool.symbol(),
+        chain_0: pool.chain_0(),
+        symbol_0: pool.symbol_0(),
+        chain_1: pool.chain_1(),
+        symbol_1: pool.symbol_1(),
+        tick_lower: position.tick_lower,
+        tick_upper: position.tick_upper,
+        price_lower: pool
+            .get_price_at_tick(position.tick_lower)
+            .and_then(|price| price_rounded(&price))
+            .unwrap_or(0_f64),
+        price_upper: pool
+            .get_price_at_tick(position.tick_upper)
+            .and_then(|price| price_rounded(&price))
+            .unwrap_or(0_f64),
+        liquidity: position.liquidity.clone(),
+        amount_0,
+        amount_1,
+        fee_0,
+        fee_1,
+        in_range: position.is_in_range(pool.tick),
+        ts: position.ts,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_add_position_reply(
+    pool: &StablePool,
+    position: &StableLPPosition,
+    request_id: u64,
+    amount_0: &Nat,
+    amount_1: &Nat,
+    transfer_ids: &[u64],
+    claim_ids: &[u64],
+    ts: u64,
+) -> AddPositionReply {
+    AddPositionReply {
+        position_id: position.position_id,
+        symbol: pool.symbol(),
+        request_id,
+        status: StatusTx::Success.to_string(),
+        chain_0: pool.chain_0(),
+        symbol_0: pool.symbol_0(),
+        amount_0: amount_0.clone(),
+        chain_1: pool.chain_1(),
+        symbol_1: pool.symbol_1(),
+        amount_1: amount_1.clone(),
+        tick_lower: position.tick_lower,
+        tick_upper: position.tick_upper,
+        liquidity: position.liquidity.clone(),
+        transfer_ids: to_transfer_ids(transfer_ids),
+        claim_ids: claim_ids.to_vec(),
+        ts,
+    }
+}
+
+pub fn create_add_position_reply_failed(
+    pool: &StablePool,
+    request_id: u64,
+    tick_lower: i32,
+    tick_upper: i32,
+    transfer_ids: &[u64],
+    claim_ids: &[u64],
+    ts: u64,
+) -> AddPositionReply {
+    AddPositionReply {
+        position_id: 0,
+        symbol: pool.symbol(),
+        request_id,
+        status: StatusTx::Failed.to_string(),
+        chain_0: pool.chain_0(),
+        symbol_0: pool.symbol_0(),
+        amount_0: nat_zero(),
+        chain_1: pool.chain_1(),
+        symbol_1: pool.symbol_1(),
+        amount_1: nat_zero(),
+        tick_lower,
+        tick_upper,
+        liquidity: nat_zero(),
+        transfer_ids: to_transfer_ids(transfer_ids),
+        claim_ids: claim_ids.to_vec(),
+        ts,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_remove_position_reply(
+    pool: &StablePool,
+    position_id: u64,
+    request_id: u64,
+    amount_0: &Nat,
+    fee_0: &Nat,
+    amount_1: &Nat,
+    fee_1: &Nat,
+    remove_liquidity: &Nat,
+    transfer_ids: &[u64],
+    claim_ids: &[u64],
+    ts: u64,
+) -> RemovePositionReply {
+    RemovePositionReply {
+        position_id,
+        symbol: pool.symbol(),
+        request_id,
+        status: StatusTx::Success.to_string(),
+        chain_0: pool.chain_0(),
+        symbol_0: pool.symbol_0(),
+        amount_0: amount_0.clone(),
+        fee_0: fee_0.clone(),
+        chain_1: pool.chain_1(),
+        symbol_1: pool.symbol_1(),
+        amount_1: amount_1.clone(),
+        fee_1: fee_1.clone(),
+        remove_liquidity: remove_liquidity.clone(),
+        transfer_ids: to_transfer_ids(transfer_ids),
+        claim_ids: claim_ids.to_vec(),
+        ts,
+    }
+}
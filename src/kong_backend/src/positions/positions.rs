@@ -0,0 +1,28 @@
+use ic_cdk::query;
+
+use super::position_reply::PositionReply;
+use super::position_reply_helpers::to_position_reply;
+
+use crate::ic::guards::not_in_maintenance_mode_and_caller_is_not_anonymous;
+use crate::stable_lp_position::lp_position_map;
+use crate::stable_pool::pool_map;
+use crate::stable_user::user_map;
+
+/// returns the concentrated liquidity positions of the user. if pool is specified, only positions of that pool
+/// pool is the symbol or address of the pool, ie. "ckBTC_ckUSDT"
+#[query(guard = "not_in_maintenance_mode_and_caller_is_not_anonymous")]
+fn positions(pool: Option<String>) -> Result<Vec<PositionReply>, String> {
+    let user_id = match user_map::get_by_caller() {
+        Ok(Some(caller)) => caller.user_id,
+        Ok(None) | Err(_) => return Ok(Vec::new()),
+    };
+    let pool_id = match pool {
+        Some(pool) => Some(pool_map::get_by_token(&pool)?.pool_id),
+        None => None,
+    };
+    let positions = lp_position_map::get_by_user_id(user_id, pool_id)
+        .iter()
+        .filter_map(|position| pool_map::get_by_pool_id(position.pool_id).map(|pool| to_position_reply(&pool, position)))
+        .collect();
+    Ok(positions)
+}
@@ -0,0 +1,214 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::position_reply_helpers::create_remove_position_reply;
+use super::remove_position_args::RemovePositionArgs;
+use super::remove_position_reply::RemovePositionReply;
+
+use crate::add_liquidity::add_liquidity::TokenIndex;
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_to_bigint, nat_zero};
+use crate::ic::{address::Address, get_time::get_time, guards::not_in_maintenance_mode, id::caller_id, transfer::icrc1_transfer};
+use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_lp_position::{lp_position_map, stable_lp_position::StableLPPosition};
+use crate::stable_pool::concentrated_liquidity::{position_amounts, update_position};
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+use crate::stable_user::user_map;
+
+/// remove liquidity from a concentrated liquidity position and collect its fees
+/// - liquidity defaults to all the liquidity of the position. 0 only collects the fees
+/// - the position is deleted once all its liquidity is removed and its fees are collected
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn remove_position(args: RemovePositionArgs) -> Result<RemovePositionReply, String> {
+    let (user_id, pool, position, remove_liquidity) = check_arguments(&args)?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemovePosition(args.clone()), ts));
+
+    process_remove_position(request_id, user_id, pool, position, &remove_liquidity, ts)
+        .await
+        .map_or_else(
+            |e| {
+                request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+                Err(e)
+            },
+            |reply| {
+                request_map::update_status(request_id, StatusCode::Success, None);
+                Ok(reply)
+            },
+        )
+}
+
+/// returns (user_id, pool, position, remove_liquidity)
+fn check_arguments(args: &RemovePositionArgs) -> Result<(u32, StablePool, StableLPPosition, Nat), String> {
+    let user_id = user_map::get_by_caller().ok().flatten().ok_or("User not found")?.user_id;
+    let position = lp_position_map::get_by_position_id(args.position_id)
+        .filter(|position| position.user_id == user_id)
+        .ok_or(format!("Position #{} not found", args.position_id))?;
+    let pool = pool_map::get_by_pool_id(position.pool_id).ok_or("Pool not found")?;
+
+    let remove_liquidity = args.liquidity.clone().unwrap_or(position.liquidity.clone());
+    if remove_liquidity > position.liquidity {
+        return Err("Insufficient liquidity in position".to_string());
+    }
+
+    Ok((user_id, pool, position, remove_liquidity))
+}
+
+async fn process_remove_position(
+    request_id: u64,
+    user_id: u32,
+    mut pool: StablePool,
+    mut position: StableLPPosition,
+    remove_liquidity: &Nat,
+    ts: u64,
+) -> Result<RemovePositionReply, String> {
+    let caller_id = caller_id();
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // amounts of the removed liquidity are rounded down so the pool always has enough to cover the remaining positions
+    request_map::update_status(request_id, StatusCode::RemovePosition, None);
+    let (amount_0, amount_1) = position_amounts(
+        &pool,
+        &StableLPPosition {
+            liquidity: remove_liquidity.clone(),
+            ..position.clone()
+        },
+    );
+    if let Err(e) = update_position(&mut pool, &mut position, &-nat_to_bigint(remove_liquidity)) {
+        request_map::update_status(request_id, StatusCode::RemovePositionFailed, Some(&e));
+        return Err(format!("Req #{} failed. {}", request_id, e));
+    }
+    let fee_0 = std::mem::replace(&mut position.fees_owed_0, nat_zero());
+    let fee_1 = std::mem::replace(&mut position.fees_owed_1, nat_zero());
+    if nat_is_zero(&position.liquidity) {
+        lp_position_map::remove(position.position_id);
+    } else {
+        lp_position_map::update(&position);
+    }
+    request_map::update_status(request_id, StatusCode::RemovePositionSuccess, None);
+
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+    pool.balance_0 = nat_subtract(&pool.balance_0, &amount_0).unwrap_or(nat_zero());
+    pool.lp_fee_0 = nat_subtract(&pool.lp_fee_0, &fee_0).unwrap_or(nat_zero());
+    pool.balance_1 = nat_subtract(&pool.balance_1, &amount_1).unwrap_or(nat_zero());
+    pool.lp_fee_1 = nat_subtract(&pool.lp_fee_1, &fee_1).unwrap_or(nat_zero());
+    pool.update_tvl();
+    pool_map::update(&pool);
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+
+    let mut transfer_ids = Vec::new();
+    let mut claim_ids = Vec::new();
+
+    // send token_0 and token_1 to the user
+    transfer_token(
+        request_id,
+        user_id,
+        &caller_id,
+        TokenIndex::Token0,
+        &pool.token_0(),
+        &nat_add(&amount_0, &fee_0),
+        &mut transfer_ids,
+        &mut claim_ids,
+        ts,
+    )
+    .await;
+    transfer_token(
+        request_id,
+        user_id,
+        &caller_id,
+        TokenIndex::Token1,
+        &pool.token_1(),
+        &nat_add(&amount_1, &fee_1),
+        &mut transfer_ids,
+        &mut claim_ids,
+        ts,
+    )
+    .await;
+
+    let reply = create_remove_position_reply(
+        &pool,
+        position.position_id,
+        request_id,
+        &amount_0,
+        &fee_0,
+        &amount_1,
+        &fee_1,
+        remove_liquidity,
+        &transfer_ids,
+        &claim_ids,
+        ts,
+    );
+    request_map::update_reply(request_id, Reply::RemovePosition(reply.clone()));
+
+    Ok(reply)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn transfer_token(
+    request_id: u64,
+    user_id: u32,
+    to_principal_id: &Account,
+    token_index: TokenIndex,
+    token: &StableToken,
+    amount: &Nat,
+    transfer_ids: &mut Vec<u64>,
+    claim_ids: &mut Vec<u64>,
+    ts: u64,
+) {
+    // nothing to send if the position is out of range on this side and has no fees
+    if nat_is_zero(amount) {
+        return;
+    }
+
+    let token_id = token.token_id();
+    let amount_with_gas = nat_subtract(amount, &token.fee()).unwrap_or(nat_zero());
+
+    match token_index {
+        TokenIndex::Token0 => request_map::update_status(request_id, StatusCode::ReceiveToken0, None),
+        TokenIndex::Token1 => request_map::update_status(request_id, StatusCode::ReceiveToken1, None),
+    };
+
+    match icrc1_transfer(&amount_with_gas, to_principal_id, token, None).await {
+        Ok(block_id) => {
+            let transfer_id = transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: false,
+                amount: amount_with_gas,
+                token_id,
+                tx_id: TxId::BlockIndex(block_id),
+                ts,
+            });
+            transfer_ids.push(transfer_id);
+            match token_index {
+                TokenIndex::Token0 => request_map::update_status(request_id, StatusCode::ReceiveToken0Success, None),
+                TokenIndex::Token1 => request_map::update_status(request_id, StatusCode::ReceiveToken1Success, None),
+            };
+        }
+        Err(e) => {
+            let message = match claim_map::insert(&StableClaim::new(
+                user_id,
+                token_id,
+                amount,
+                Some(request_id),
+                Some(Address::PrincipalId(*to_principal_id)),
+                ts,
+            )) {
+                Ok(claim_id) => {
+                    claim_ids.push(claim_id);
+                    format!("Saved as claim #{}. {}", claim_id, e)
+                }
+                Err(e) => format!("Failed to save claim. {}", e),
+            };
+            match token_index {
+                TokenIndex::Token0 => request_map::update_status(request_id, StatusCode::ReceiveToken0Failed, Some(&message)),
+                TokenIndex::Token1 => request_map::update_status(request_id, StatusCode::ReceiveToken1Failed, Some(&message)),
+            };
+        }
+    }
+}
@@ -7,15 +7,32 @@ use super::remove_liquidity_reply::RemoveLiquidityReply;
 use super::remove_liquidity_reply_helpers::{create_remove_liquidity_reply_failed, create_remove_liquidity_reply_with_tx_id};
 
 use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
-use crate::ic::{address::Address, get_time::get_time, guards::not_in_maintenance_mode, id::caller_id, transfer::icrc1_transfer};
+use crate::ic::{
+    address::Address,
+    get_time::{get_time, is_expired},
+    guards::not_in_maintenance_mode,
+    id::caller_id,
+    logging::error_log,
+    transfer::icrc1_transfer,
+};
 use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_lock::lp_lock_map;
 use crate::stable_lp_token::{lp_token_map, stable_lp_token::StableLPToken};
-use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_pool::{
+    pool_map,
+    stable_pool::{PoolType, StablePool},
+};
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
-use crate::stable_token::{stable_token::StableToken, token::Token};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
 use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
-use crate::stable_tx::{remove_liquidity_tx::RemoveLiquidityTx, stable_tx::StableTx, tx_map};
+use crate::stable_tx::{remove_liquidity_tx::RemoveLiquidityTx, stable_tx::StableTx, swap_tx::SwapTx, tx_map};
 use crate::stable_user::user_map;
+use crate::swap::calculate_amounts::calculate_amounts_single_pool;
+use crate::swap::swap_reply::SwapReply;
+use crate::swap::swap_reply_helpers::create_swap_reply_with_tx_id;
+use crate::swap::update_liquidity_pool::update_liquidity_pool_single_pool;
 
 enum TokenIndex {
     Token0,
@@ -30,12 +47,18 @@ enum TokenIndex {
 ///
 /// Notes regarding gas:
 ///   - payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1 does not include gas fees
+///
+/// Zap out:
+///   - if receive_token is specified, amount + lp_fee of the other token is swapped for receive_token through the pools
+///     through the pool and everything is paid out in receive_token. min_amount_0 and min_amount_1 still apply to the removed amounts,
+///     max_slippage bounds the swap and min_receive_amount bounds the total paid out in receive_token before gas.
+///     the swap takes no gas fee, gas is only taken once when the payout is sent
 #[update(guard = "not_in_maintenance_mode")]
 pub async fn remove_liquidity(args: RemoveLiquidityArgs) -> Result<RemoveLiquidityReply, String> {
     let (user_id, pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
         check_arguments(&args).await?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args.clone()), ts));
     let caller_id = caller_id();
 
     let result = process_remove_liquidity(
@@ -48,6 +71,12 @@ pub async fn remove_liquidity(args: RemoveLiquidityArgs) -> Result<RemoveLiquidi
         &payout_lp_fee_0,
         &payout_amount_1,
         &payout_lp_fee_1,
+        args.min_amount_0.as_ref(),
+        args.min_amount_1.as_ref(),
+        args.receive_token.as_ref(),
+        args.max_slippage,
+        args.min_receive_amount.as_ref(),
+        args.deadline_ns,
         ts,
     )
     .await
@@ -76,9 +105,9 @@ pub async fn remove_liquidity_from_pool(
     to_principal_id: &Account,
 ) -> Result<RemoveLiquidityReply, String> {
     let (pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
-        check_arguments_with_user(&args, user_id).await?;
+        check_arguments_with_user(&args, user_id)?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args.clone()), ts));
     request_map::update_status(request_id, StatusCode::RemoveLiquidityFromPool, None);
 
     let result = process_remove_liquidity(
@@ -91,6 +120,12 @@ pub async fn remove_liquidity_from_pool(
         &payout_lp_fee_0,
         &payout_amount_1,
         &payout_lp_fee_1,
+        args.min_amount_0.as_ref(),
+        args.min_amount_1.as_ref(),
+        args.receive_token.as_ref(),
+        args.max_slippage,
+        args.min_receive_amount.as_ref(),
+        args.deadline_ns,
         ts,
     )
     .await
@@ -117,7 +152,7 @@ pub async fn remove_liquidity_async(args: RemoveLiquidityArgs) -> Result<u64, St
     let (user_id, pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
         check_arguments(&args).await?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args.clone()), ts));
     let caller_id = caller_id();
 
     ic_cdk::spawn(async move {
@@ -131,6 +166,12 @@ pub async fn remove_liquidity_async(args: RemoveLiquidityArgs) -> Result<u64, St
             &payout_lp_fee_0,
             &payout_amount_1,
             &payout_lp_fee_1,
+            args.min_amount_0.as_ref(),
+            args.min_amount_1.as_ref(),
+            args.receive_token.as_ref(),
+            args.max_slippage,
+            args.min_receive_amount.as_ref(),
+            args.deadline_ns,
             ts,
         )
         .await
@@ -153,7 +194,7 @@ async fn check_arguments(args: &RemoveLiquidityArgs) -> Result<(u32, StablePool,
     // make sure user is not anonymous and exists
     let user_id = user_map::get_by_caller()?.ok_or("Insufficient LP balance")?.user_id;
     let (pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
-        check_arguments_with_user(args, user_id).await?;
+        check_arguments_with_user(args, user_id)?;
 
     Ok((
         user_id,
@@ -167,9 +208,14 @@ async fn check_arguments(args: &RemoveLiquidityArgs) -> Result<(u32, StablePool,
 }
 
 #[allow(clippy::type_complexity)]
-async fn check_arguments_with_user(args: &RemoveLiquidityArgs, user_id: u32) -> Result<(StablePool, Nat, Nat, Nat, Nat, Nat), String> {
+fn check_arguments_with_user(args: &RemoveLiquidityArgs, user_id: u32) -> Result<(StablePool, Nat, Nat, Nat, Nat, Nat), String> {
+    // fail early if the deadline has already passed
+    if is_expired(args.deadline_ns) {
+        return Err("Deadline expired".to_string());
+    }
+
     // Pool
-    let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1)?;
+    let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1, args.fee_tier)?;
     // Token0
     let balance_0 = &pool.balance_0;
     // Token1
@@ -190,9 +236,35 @@ async fn check_arguments_with_user(args: &RemoveLiquidityArgs, user_id: u32) ->
     } else {
         args.remove_lp_token_amount.clone()
     };
+    // locked LP tokens can not be removed
+    let locked_lp_token_amount = lp_lock_map::get_locked_amount(lp_token_id, user_id, get_time());
+    if nat_subtract(&user_lp_token_amount, &locked_lp_token_amount).is_none_or(|unlocked| remove_lp_token_amount > unlocked) {
+        return Err(format!("Insufficient unlocked LP balance. {} locked", locked_lp_token_amount));
+    }
+
+    // make sure receive_token is one of the pool's tokens
+    get_receive_token_index(&pool, args.receive_token.as_ref())?;
 
     // calculate the payout amounts.
     let (payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) = calculate_amounts(&pool, &args.remove_lp_token_amount)?;
+    check_min_amounts(
+        &payout_amount_0,
+        &payout_lp_fee_0,
+        &payout_amount_1,
+        &payout_lp_fee_1,
+        args.min_amount_0.as_ref(),
+        args.min_amount_1.as_ref(),
+    )?;
+    check_zap_out(
+        &pool,
+        args.receive_token.as_ref(),
+        args.max_slippage,
+        args.min_receive_amount.as_ref(),
+        &payout_amount_0,
+        &payout_lp_fee_0,
+        &payout_amount_1,
+        &payout_lp_fee_1,
+    )?;
 
     Ok((
         pool,
@@ -205,6 +277,9 @@ async fn check_arguments_with_user(args: &RemoveLiquidityArgs, user_id: u32) ->
 }
 
 pub fn calculate_amounts(pool: &StablePool, remove_lp_token_amount: &Nat) -> Result<(Nat, Nat, Nat, Nat), String> {
+    if pool.pool_type == PoolType::ConcentratedLiquidity {
+        return Err(format!("Use remove_position for concentrated liquidity pool {}", pool.symbol()));
+    }
     // Token0
     let balance_0 = &pool.balance_0;
     let lp_fee_0 = &pool.lp_fee_0;
@@ -236,6 +311,96 @@ pub fn calculate_amounts(pool: &StablePool, remove_lp_token_amount: &Nat) -> Res
     Ok((payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1))
 }
 
+/// returns the index of receive_token in the pool or None if all tokens are paid out
+fn get_receive_token_index(pool: &StablePool, receive_token: Option<&String>) -> Result<Option<TokenIndex>, String> {
+    let Some(receive_token) = receive_token else {
+        return Ok(None);
+    };
+    let receive_token_id = token_map::get_by_token(receive_token)?.token_id();
+    if receive_token_id == pool.token_id_0 {
+        Ok(Some(TokenIndex::Token0))
+    } else if receive_token_id == pool.token_id_1 {
+        Ok(Some(TokenIndex::Token1))
+    } else {
+        Err(format!("Receive token must be {} or {}", pool.symbol_0(), pool.symbol_1()))
+    }
+}
+
+/// make sure the payouts of token_0 (amount_0 + lp_fee_0) and token_1 (amount_1 + lp_fee_1) are at least min_amount_0 and min_amount_1 if specified
+fn check_min_amounts(
+    payout_amount_0: &Nat,
+    payout_lp_fee_0: &Nat,
+    payout_amount_1: &Nat,
+    payout_lp_fee_1: &Nat,
+    min_amount_0: Option<&Nat>,
+    min_amount_1: Option<&Nat>,
+) -> Result<(), String> {
+    let payout_0 = nat_add(payout_amount_0, payout_lp_fee_0);
+    if let Some(min_amount_0) = min_amount_0 {
+        if payout_0 < *min_amount_0 {
+            return Err(format!("Payout amount_0 {} below minimum {}", payout_0, min_amount_0));
+        }
+    }
+    let payout_1 = nat_add(payout_amount_1, payout_lp_fee_1);
+    if let Some(min_amount_1) = min_amount_1 {
+        if payout_1 < *min_amount_1 {
+            return Err(format!("Payout amount_1 {} below minimum {}", payout_1, min_amount_1));
+        }
+    }
+    Ok(())
+}
+
+/// make sure the zap out swap, with the pool after the liquidity is removed, is within max_slippage and
+/// the total paid out in receive_token is at least min_receive_amount if specified. checked before the pool is updated
+#[allow(clippy::too_many_arguments)]
+fn check_zap_out(
+    pool: &StablePool,
+    receive_token: Option<&String>,
+    max_slippage: Option<f64>,
+    min_receive_amount: Option<&Nat>,
+    payout_amount_0: &Nat,
+    payout_lp_fee_0: &Nat,
+    payout_amount_1: &Nat,
+    payout_lp_fee_1: &Nat,
+) -> Result<(), String> {
+    let Some(receive_token_index) = get_receive_token_index(pool, receive_token)? else {
+        return Ok(());
+    };
+    let max_slippage = max_slippage.unwrap_or(kong_settings_map::get().default_max_slippage);
+    let pool_after = pool_after_removal(pool, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1);
+    let (pay_token, pay_amount, receive_token, payout) = match receive_token_index {
+        TokenIndex::Token0 => (
+            pool.token_1(),
+            nat_add(payout_amount_1, payout_lp_fee_1),
+            pool.token_0(),
+            nat_add(payout_amount_0, payout_lp_fee_0),
+        ),
+        TokenIndex::Token1 => (
+            pool.token_0(),
+            nat_add(payout_amount_0, payout_lp_fee_0),
+            pool.token_1(),
+            nat_add(payout_amount_1, payout_lp_fee_1),
+        ),
+    };
+    if nat_is_zero(&pay_amount) {
+        return Err(format!("Zero {} to swap for {}", pay_token.symbol(), receive_token.symbol()));
+    }
+    let (receive_amount, _, _, _, _) =
+        calculate_amounts_single_pool(&pool_after, &pay_token, &pay_amount, &receive_token, None, max_slippage)?;
+    check_min_receive_amount(&payout, &receive_amount, min_receive_amount)
+}
+
+/// make sure the payout of receive_token plus the receive amount of the swap is at least min_receive_amount if specified
+fn check_min_receive_amount(payout: &Nat, receive_amount: &Nat, min_receive_amount: Option<&Nat>) -> Result<(), String> {
+    if let Some(min_receive_amount) = min_receive_amount {
+        let total = nat_add(payout, receive_amount);
+        if total < *min_receive_amount {
+            return Err(format!("Receive amount {} below minimum {}", total, min_receive_amount));
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn process_remove_liquidity(
     request_id: u64,
@@ -247,6 +412,12 @@ async fn process_remove_liquidity(
     payout_lp_fee_0: &Nat,
     payout_amount_1: &Nat,
     payout_lp_fee_1: &Nat,
+    min_amount_0: Option<&Nat>,
+    min_amount_1: Option<&Nat>,
+    receive_token: Option<&String>,
+    max_slippage: Option<f64>,
+    min_receive_amount: Option<&Nat>,
+    deadline_ns: Option<u64>,
     ts: u64,
 ) -> Result<RemoveLiquidityReply, String> {
     // LP token
@@ -254,6 +425,44 @@ async fn process_remove_liquidity(
 
     request_map::update_status(request_id, StatusCode::Start, None);
 
+    // make sure the request has not passed its deadline before any LP tokens are removed
+    if is_expired(deadline_ns) {
+        request_map::update_status(request_id, StatusCode::DeadlineExpired, None);
+        let reply = create_remove_liquidity_reply_failed(pool.pool_id, request_id, ts);
+        request_map::update_reply(request_id, Reply::RemoveLiquidity(reply));
+        return Err(format!("Req #{} failed. Deadline expired", request_id));
+    }
+
+    // make sure the payout amounts meet the minimum amounts before any LP tokens are removed
+    if let Err(e) = check_min_amounts(
+        payout_amount_0,
+        payout_lp_fee_0,
+        payout_amount_1,
+        payout_lp_fee_1,
+        min_amount_0,
+        min_amount_1,
+    ) {
+        request_map::update_status(request_id, StatusCode::PayoutAmountBelowMinimum, Some(&e));
+        let reply = create_remove_liquidity_reply_failed(pool.pool_id, request_id, ts);
+        request_map::update_reply(request_id, Reply::RemoveLiquidity(reply));
+        return Err(format!("Req #{} failed. {}", request_id, e));
+    }
+    if let Err(e) = check_zap_out(
+        pool,
+        receive_token,
+        max_slippage,
+        min_receive_amount,
+        payout_amount_0,
+        payout_lp_fee_0,
+        payout_amount_1,
+        payout_lp_fee_1,
+    ) {
+        request_map::update_status(request_id, StatusCode::PayoutAmountBelowMinimum, Some(&e));
+        let reply = create_remove_liquidity_reply_failed(pool.pool_id, request_id, ts);
+        request_map::update_reply(request_id, Reply::RemoveLiquidity(reply));
+        return Err(format!("Req #{} failed. {}", request_id, e));
+    }
+
     // remove LP tokens from user's ledger
     let transfer_lp_token = remove_lp_token(request_id, user_id, &lp_token, remove_lp_token_amount, ts);
     if transfer_lp_token.is_err() {
@@ -264,6 +473,30 @@ async fn process_remove_liquidity(
     // update liquidity pool with new removed amounts
     update_liquidity_pool(request_id, pool, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1);
 
+    // zap out. swap the other token for receive_token with the pools after the liquidity is removed
+    let swap_result = zap_out(
+        request_id,
+        user_id,
+        pool,
+        receive_token,
+        max_slippage,
+        payout_amount_0,
+        payout_lp_fee_0,
+        payout_amount_1,
+        payout_lp_fee_1,
+        deadline_ns,
+        ts,
+    );
+    let swap = match swap_result {
+        Ok(swap) => swap,
+        Err(e) => {
+            // nothing was paid out yet. put the removed amounts back in the pool and return the LP tokens
+            return_liquidity_pool(pool.pool_id, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1);
+            return_tokens(request_id, user_id, pool, &transfer_lp_token, remove_lp_token_amount, ts);
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+
     // successful, add tx and update request with reply
     let reply = send_payout_tokens(
         request_id,
@@ -275,6 +508,7 @@ async fn process_remove_liquidity(
         payout_amount_1,
         payout_lp_fee_1,
         remove_lp_token_amount,
+        swap,
         ts,
     )
     .await;
@@ -302,6 +536,21 @@ fn remove_lp_token(request_id: u64, user_id: u32, lp_token: &StableToken, remove
                     Err(message)?
                 }
             };
+            lp_lock_map::remove_unlocked(lp_token_id, user_id, ts);
+            let locked_amount = lp_lock_map::get_locked_amount(lp_token_id, user_id, ts);
+            if amount < locked_amount {
+                let message = format!(
+                    "Insufficient unlocked LP tokens. {} locked, {} remaining after remove",
+                    locked_amount, amount
+                );
+                request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&message));
+                Err(message)?
+            }
+            // record the burn first so nothing is removed if its block can not be recorded
+            if let Err(e) = lp_block_map::insert_burn(lp_token_id, user_id, remove_lp_token_amount, ts) {
+                request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e));
+                Err(e)?
+            }
             let new_user_lp_token = StableLPToken {
                 amount,
                 ts,
@@ -331,6 +580,10 @@ fn return_lp_token(user_id: u32, lp_token: &StableToken, remove_lp_token_amount:
                 ..lp_token.clone()
             };
             lp_token_map::update(&new_user_lp_token);
+            // the LP tokens are returned even if their block can not be recorded
+            if let Err(e) = lp_block_map::insert_mint(lp_token_id, user_id, remove_lp_token_amount, ts) {
+                error_log(&format!("Failed to record LP mint block for user #{}. {}", user_id, e));
+            }
             Ok(())
         }
         None => Err("Unable to find LP tokens balance".to_string())?,
@@ -340,20 +593,217 @@ fn return_lp_token(user_id: u32, lp_token: &StableToken, remove_lp_token_amount:
 fn update_liquidity_pool(request_id: u64, pool: &StablePool, amount_0: &Nat, lp_fee_0: &Nat, amount_1: &Nat, lp_fee_1: &Nat) {
     request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
 
-    let mut update_pool = StablePool {
+    let mut update_pool = pool_after_removal(pool, amount_0, lp_fee_0, amount_1, lp_fee_1);
+    update_pool.update_tvl();
+    pool_map::update(&update_pool);
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+}
+
+/// pool with the removed amounts taken out. TVL is not updated
+fn pool_after_removal(pool: &StablePool, amount_0: &Nat, lp_fee_0: &Nat, amount_1: &Nat, lp_fee_1: &Nat) -> StablePool {
+    StablePool {
         balance_0: nat_subtract(&pool.balance_0, amount_0).unwrap_or(nat_zero()),
         lp_fee_0: nat_subtract(&pool.lp_fee_0, lp_fee_0).unwrap_or(nat_zero()),
         balance_1: nat_subtract(&pool.balance_1, amount_1).unwrap_or(nat_zero()),
         lp_fee_1: nat_subtract(&pool.lp_fee_1, lp_fee_1).unwrap_or(nat_zero()),
         ..pool.clone()
+    }
+}
+
+/// used by batch() to remove liquidity for user_id without sending the payout tokens
+///
+/// # Returns
+/// the reply and the payout amounts by token_id, including the swap's receive amount if zapped out
+pub fn remove_liquidity_without_payout(
+    request_id: u64,
+    user_id: u32,
+    args: &RemoveLiquidityArgs,
+    ts: u64,
+) -> Result<(RemoveLiquidityReply, Vec<(u32, Nat)>), String> {
+    let (pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
+        check_arguments_with_user(args, user_id)?;
+
+    remove_lp_token(request_id, user_id, &pool.lp_token(), &remove_lp_token_amount, ts)?;
+
+    update_liquidity_pool(
+        request_id,
+        &pool,
+        &payout_amount_0,
+        &payout_lp_fee_0,
+        &payout_amount_1,
+        &payout_lp_fee_1,
+    );
+
+    let swap = match zap_out(
+        request_id,
+        user_id,
+        &pool,
+        args.receive_token.as_ref(),
+        args.max_slippage,
+        &payout_amount_0,
+        &payout_lp_fee_0,
+        &payout_amount_1,
+        &payout_lp_fee_1,
+        args.deadline_ns,
+        ts,
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return_liquidity_pool(pool.pool_id, &payout_amount_0, &payout_lp_fee_0, &payout_amount_1, &payout_lp_fee_1);
+            if let Err(e) = return_lp_token(user_id, &pool.lp_token(), &remove_lp_token_amount, ts) {
+                request_map::update_status(request_id, StatusCode::ReturnUserLPTokenAmountFailed, Some(&e));
+            }
+            return Err(e);
+        }
     };
-    update_pool.update_tvl();
-    pool_map::update(&update_pool);
-    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+
+    let payout_0 = nat_add(&payout_amount_0, &payout_lp_fee_0);
+    let payout_1 = nat_add(&payout_amount_1, &payout_lp_fee_1);
+    let (payout_0, payout_1, swap_reply) = match swap {
+        Some((TokenIndex::Token0, receive_amount, swap_reply)) => (nat_add(&payout_0, &receive_amount), nat_zero(), Some(swap_reply)),
+        Some((TokenIndex::Token1, receive_amount, swap_reply)) => (nat_zero(), nat_add(&payout_1, &receive_amount), Some(swap_reply)),
+        None => (payout_0, payout_1, None),
+    };
+
+    let remove_liquidity_tx = RemoveLiquidityTx::new_success(
+        pool.pool_id,
+        user_id,
+        request_id,
+        &payout_amount_0,
+        &payout_lp_fee_0,
+        &payout_amount_1,
+        &payout_lp_fee_1,
+        &remove_lp_token_amount,
+        &[],
+        &[],
+        ts,
+    );
+    let tx_id = tx_map::insert(&StableTx::RemoveLiquidity(remove_liquidity_tx.clone()));
+    let reply = RemoveLiquidityReply {
+        swap: swap_reply,
+        ..create_remove_liquidity_reply_with_tx_id(tx_id, &remove_liquidity_tx)
+    };
+
+    Ok((reply, vec![(pool.token_id_0, payout_0), (pool.token_id_1, payout_1)]))
+}
+
+/// swap the other token for receive_token if specified
+///
+/// # Returns
+/// the index of receive_token, the receive amount and the reply of the swap or None if not zapping out
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn zap_out(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    receive_token: Option<&String>,
+    max_slippage: Option<f64>,
+    payout_amount_0: &Nat,
+    payout_lp_fee_0: &Nat,
+    payout_amount_1: &Nat,
+    payout_lp_fee_1: &Nat,
+    deadline_ns: Option<u64>,
+    ts: u64,
+) -> Result<Option<(TokenIndex, Nat, Box<SwapReply>)>, String> {
+    let Some(receive_token_index) = get_receive_token_index(pool, receive_token)? else {
+        return Ok(None);
+    };
+    let max_slippage = max_slippage.unwrap_or(kong_settings_map::get().default_max_slippage);
+    let (receive_amount, swap_reply) = swap_payout(
+        request_id,
+        user_id,
+        pool,
+        &receive_token_index,
+        payout_amount_0,
+        payout_lp_fee_0,
+        payout_amount_1,
+        payout_lp_fee_1,
+        max_slippage,
+        deadline_ns,
+        ts,
+    )?;
+
+    Ok(Some((receive_token_index, receive_amount, swap_reply)))
+}
+
+// put the removed amounts back in the pool with the latest state of the pool
+fn return_liquidity_pool(pool_id: u32, amount_0: &Nat, lp_fee_0: &Nat, amount_1: &Nat, lp_fee_1: &Nat) {
+    if let Some(pool) = pool_map::get_by_pool_id(pool_id) {
+        let mut update_pool = StablePool {
+            balance_0: nat_add(&pool.balance_0, amount_0),
+            lp_fee_0: nat_add(&pool.lp_fee_0, lp_fee_0),
+            balance_1: nat_add(&pool.balance_1, amount_1),
+            lp_fee_1: nat_add(&pool.lp_fee_1, lp_fee_1),
+            ..pool
+        };
+        update_pool.update_tvl();
+        pool_map::update(&update_pool);
+    }
+}
+
+/// swap amount + lp_fee of the token other than receive_token for receive_token through the pool
+///
+/// # Returns
+/// the receive amount, after fees, and the reply of the swap
+#[allow(clippy::too_many_arguments)]
+fn swap_payout(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    receive_token_index: &TokenIndex,
+    payout_amount_0: &Nat,
+    payout_lp_fee_0: &Nat,
+    payout_amount_1: &Nat,
+    payout_lp_fee_1: &Nat,
+    max_slippage: f64,
+    deadline_ns: Option<u64>,
+    ts: u64,
+) -> Result<(Nat, Box<SwapReply>), String> {
+    let (pay_token, pay_amount, receive_token) = match receive_token_index {
+        TokenIndex::Token0 => (pool.token_1(), nat_add(payout_amount_1, payout_lp_fee_1), pool.token_0()),
+        TokenIndex::Token1 => (pool.token_0(), nat_add(payout_amount_0, payout_lp_fee_0), pool.token_1()),
+    };
+    if nat_is_zero(&pay_amount) {
+        return Err(format!("Zero {} to swap for {}", pay_token.symbol(), receive_token.symbol()));
+    }
+
+    // swap through the pool with the latest state after the liquidity is removed. no gas fee is taken as it is only taken once when the payout is sent
+    let pool = pool_map::get_by_pool_id(pool.pool_id).ok_or("Pool not found")?;
+    let (receive_amount, mid_price, price, slippage, swaps) = update_liquidity_pool_single_pool(
+        request_id,
+        &pool,
+        &pay_token,
+        &pay_amount,
+        &receive_token,
+        None,
+        max_slippage,
+        deadline_ns,
+    )?;
+
+    // the receive amount is paid out with the removed amounts so the swap has no transfers of its own
+    let swap_tx = SwapTx::new_success(
+        user_id,
+        request_id,
+        pay_token.token_id(),
+        &pay_amount,
+        receive_token.token_id(),
+        &receive_amount,
+        mid_price,
+        price,
+        slippage,
+        &swaps,
+        &[],
+        &[],
+        ts,
+    );
+    let swap_tx_id = tx_map::insert(&StableTx::Swap(swap_tx.clone()));
+
+    Ok((receive_amount, Box::new(create_swap_reply_with_tx_id(swap_tx_id, &swap_tx))))
 }
 
 // send payout tokens to user and final balance integrity checks
-// - send payout token_0 and token_1 to user
+// - send payout token_0 and token_1 to user. if zapped out, only receive_token is sent with the swap's receive amount
 // - any failures to send tokens will be saved as claims
 // - check the actual balances of the canister vs. expected balances in stable memory
 // - update successsful request reply
@@ -368,6 +818,7 @@ async fn send_payout_tokens(
     payout_amount_1: &Nat,
     payout_lp_fee_1: &Nat,
     remove_lp_token_amount: &Nat,
+    swap: Option<(TokenIndex, Nat, Box<SwapReply>)>,
     ts: u64,
 ) -> RemoveLiquidityReply {
     // Token0
@@ -378,35 +829,48 @@ async fn send_payout_tokens(
     let mut transfer_ids = Vec::new();
     let mut claim_ids = Vec::new();
 
+    // receive amounts of the swap added to the payouts. None if the token was swapped and is not paid out
+    let (swap_amount_0, swap_amount_1, swap_reply) = match swap {
+        Some((TokenIndex::Token0, receive_amount, swap_reply)) => (Some(receive_amount), None, Some(swap_reply)),
+        Some((TokenIndex::Token1, receive_amount, swap_reply)) => (None, Some(receive_amount), Some(swap_reply)),
+        None => (Some(nat_zero()), Some(nat_zero()), None),
+    };
+
     // send payout token_0 to the user
-    transfer_token(
-        request_id,
-        user_id,
-        to_principal_id,
-        TokenIndex::Token0,
-        &token_0,
-        payout_amount_0,
-        payout_lp_fee_0,
-        &mut transfer_ids,
-        &mut claim_ids,
-        ts,
-    )
-    .await;
+    if let Some(swap_amount_0) = swap_amount_0 {
+        transfer_token(
+            request_id,
+            user_id,
+            to_principal_id,
+            TokenIndex::Token0,
+            &token_0,
+            payout_amount_0,
+            payout_lp_fee_0,
+            &swap_amount_0,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
 
     // send payout token_1 to the user
-    transfer_token(
-        request_id,
-        user_id,
-        to_principal_id,
-        TokenIndex::Token1,
-        &token_1,
-        payout_amount_1,
-        payout_lp_fee_1,
-        &mut transfer_ids,
-        &mut claim_ids,
-        ts,
-    )
-    .await;
+    if let Some(swap_amount_1) = swap_amount_1 {
+        transfer_token(
+            request_id,
+            user_id,
+            to_principal_id,
+            TokenIndex::Token1,
+            &token_1,
+            payout_amount_1,
+            payout_lp_fee_1,
+            &swap_amount_1,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
 
     let remove_liquidity_tx = RemoveLiquidityTx::new_success(
         pool.pool_id,
@@ -422,7 +886,10 @@ async fn send_payout_tokens(
         ts,
     );
     let tx_id = tx_map::insert(&StableTx::RemoveLiquidity(remove_liquidity_tx.clone()));
-    let reply = create_remove_liquidity_reply_with_tx_id(tx_id, &remove_liquidity_tx);
+    let reply = RemoveLiquidityReply {
+        swap: swap_reply,
+        ..create_remove_liquidity_reply_with_tx_id(tx_id, &remove_liquidity_tx)
+    };
     request_map::update_reply(request_id, Reply::RemoveLiquidity(reply.clone()));
 
     reply
@@ -437,14 +904,15 @@ async fn transfer_token(
     token: &StableToken,
     payout_amount: &Nat,
     payout_lp_fee: &Nat,
+    swap_amount: &Nat,
     transfer_ids: &mut Vec<u64>,
     claim_ids: &mut Vec<u64>,
     ts: u64,
 ) {
     let token_id = token.token_id();
 
-    // total payout = amount + lp_fee - gas fee
-    let amount = nat_add(payout_amount, payout_lp_fee);
+    // total payout = amount + lp_fee + swap receive amount - gas fee
+    let amount = nat_add(&nat_add(payout_amount, payout_lp_fee), swap_amount);
     let amount_with_gas = nat_subtract(&amount, &token.fee()).unwrap_or(nat_zero());
 
     match token_index {
@@ -521,16 +989,15 @@ fn return_tokens(
 }
 
 pub fn archive_to_kong_data(request: &StableRequest) {
-    request_map::archive_request_to_kong_data(request.request_id);
-    if let Reply::RemoveLiquidity(reply) = &request.reply {
-        for claim_id in reply.claim_ids.iter() {
-            claim_map::archive_claim_to_kong_data(*claim_id);
-        }
-        for transfer_id_reply in reply.transfer_ids.iter() {
-            transfer_map::archive_transfer_to_kong_data(transfer_id_reply.transfer_id);
+    match &request.reply {
+        Reply::RemoveLiquidity(reply) => {
+            let tx_ids: Vec<u64> = std::iter::once(reply.tx_id)
+                .chain(reply.swap.as_ref().map(|swap| swap.tx_id))
+                .collect();
+            request_map::archive_request_and_reply_to_kong_data(request.request_id, &reply.claim_ids, &reply.transfer_ids, &tx_ids);
         }
-        tx_map::archive_tx_to_kong_data(reply.tx_id);
-    };
+        _ => request_map::archive_request_to_kong_data(request.request_id),
+    }
 }
 
 /// api to validate remove_liquidity for SNS proposals
@@ -538,3 +1005,58 @@ pub fn archive_to_kong_data(request: &StableRequest) {
 fn validate_remove_liquidity() -> Result<String, String> {
     Ok("remove_liquidity is valid".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::new_pool;
+
+    #[test]
+    fn test_check_min_amounts() {
+        let (amount_0, lp_fee_0) = (Nat::from(900_u32), Nat::from(100_u32));
+        let (amount_1, lp_fee_1) = (Nat::from(450_u32), Nat::from(50_u32));
+        assert!(check_min_amounts(&amount_0, &lp_fee_0, &amount_1, &lp_fee_1, None, None).is_ok());
+        // minimums include the LP fees paid out
+        assert!(check_min_amounts(
+            &amount_0,
+            &lp_fee_0,
+            &amount_1,
+            &lp_fee_1,
+            Some(&Nat::from(1_000_u32)),
+            Some(&Nat::from(500_u32))
+        )
+        .is_ok());
+        assert!(check_min_amounts(&amount_0, &lp_fee_0, &amount_1, &lp_fee_1, Some(&Nat::from(1_001_u32)), None).is_err());
+        assert!(check_min_amounts(&amount_0, &lp_fee_0, &amount_1, &lp_fee_1, None, Some(&Nat::from(501_u32))).is_err());
+    }
+
+    #[test]
+    fn test_check_min_receive_amount() {
+        let (payout, receive_amount) = (Nat::from(1_000_u32), Nat::from(480_u32));
+        assert!(check_min_receive_amount(&payout, &receive_amount, None).is_ok());
+        // minimum is of the removed amount of receive_token plus the swap's receive amount
+        assert!(check_min_receive_amount(&payout, &receive_amount, Some(&Nat::from(1_480_u32))).is_ok());
+        assert!(check_min_receive_amount(&payout, &receive_amount, Some(&Nat::from(1_481_u32))).is_err());
+    }
+
+    #[test]
+    fn test_pool_after_removal() {
+        let mut pool = new_pool(1, 1, 2, 10_000, 20_000);
+        pool.lp_fee_0 = Nat::from(100_u32);
+        pool.lp_fee_1 = Nat::from(200_u32);
+        let pool_after = pool_after_removal(
+            &pool,
+            &Nat::from(1_000_u32),
+            &Nat::from(10_u32),
+            &Nat::from(2_000_u32),
+            &Nat::from(20_u32),
+        );
+        assert_eq!(pool_after.balance_0, Nat::from(9_000_u32));
+        assert_eq!(pool_after.lp_fee_0, Nat::from(90_u32));
+        assert_eq!(pool_after.balance_1, Nat::from(18_000_u32));
+        assert_eq!(pool_after.lp_fee_1, Nat::from(180_u32));
+        // the pool passed in is not changed
+        assert_eq!(pool.balance_0, Nat::from(10_000_u32));
+    }
+}
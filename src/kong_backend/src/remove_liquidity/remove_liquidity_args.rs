@@ -7,5 +7,12 @@ use serde::{Deserialize, Serialize};
 pub struct RemoveLiquidityArgs {
     pub token_0: String,
     pub token_1: String,
+    pub fee_tier: Option<u32>, // if specified, the pool of the token pair with this LP fee in pips. otherwise the first pool of the token pair
     pub remove_lp_token_amount: Nat,
+    pub deadline_ns: Option<u64>, // if specified, remove liquidity fails if the pool is not updated by this time
+    pub min_amount_0: Option<Nat>, // if specified, remove liquidity fails if amount_0 + lp_fee_0 paid out is less than this
+    pub min_amount_1: Option<Nat>, // if specified, remove liquidity fails if amount_1 + lp_fee_1 paid out is less than this
+    pub receive_token: Option<String>, // if specified, token_0 or token_1. the other token is swapped and everything is paid out in this token
+    pub max_slippage: Option<f64>, // max slippage of the swap if receive_token is specified. if None, default_max_slippage of the Kong settings
+    pub min_receive_amount: Option<Nat>, // if specified with receive_token, remove liquidity fails if less than this is paid out in receive_token
 }
@@ -1,6 +1,7 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::swap::swap_reply::SwapReply;
 use crate::transfers::transfer_reply::TransferIdReply;
 
 /// Data structure for the reply of the `remove_liquidity` function.
@@ -20,6 +21,7 @@ pub struct RemoveLiquidityReply {
     pub amount_1: Nat,
     pub lp_fee_1: Nat,
     pub remove_lp_token_amount: Nat,
+    pub swap: Option<Box<SwapReply>>, // if receive_token was specified, the swap of the other token for receive_token
     pub transfer_ids: Vec<TransferIdReply>,
     pub claim_ids: Vec<u64>,
     pub ts: u64,
@@ -34,6 +34,7 @@ pub fn create_remove_liquidity_reply_with_tx_id(tx_id: u64, remove_liquidity_tx:
         amount_1: remove_liquidity_tx.amount_1.clone(),
         lp_fee_1: remove_liquidity_tx.lp_fee_1.clone(),
         remove_lp_token_amount: remove_liquidity_tx.remove_lp_token_amount.clone(),
+        swap: None,
         transfer_ids: to_transfer_ids(&remove_liquidity_tx.transfer_ids),
         claim_ids: remove_liquidity_tx.claim_ids.clone(),
         ts: remove_liquidity_tx.ts,
@@ -64,6 +65,7 @@ pub fn create_remove_liquidity_reply_failed(pool_id: u32, request_id: u64, ts: u
         amount_1: nat_zero(),
         lp_fee_1: nat_zero(),
         remove_lp_token_amount: nat_zero(),
+        swap: None,
         transfer_ids: Vec::new(), // if failed, transfer_ids is empty as no tokens are returned
         claim_ids: Vec::new(),    // if failed, claims_ids is empty as no LP tokens are returned
         ts,
@@ -9,9 +9,14 @@ use crate::stable_pool::pool_map;
 use crate::stable_token::token::Token;
 
 #[query(guard = "not_in_maintenance_mode")]
-fn remove_liquidity_amounts(token_0: String, token_1: String, remove_lp_token_amount: Nat) -> Result<RemoveLiquidityAmountsReply, String> {
+fn remove_liquidity_amounts(
+    token_0: String,
+    token_1: String,
+    remove_lp_token_amount: Nat,
+    fee_tier: Option<u32>,
+) -> Result<RemoveLiquidityAmountsReply, String> {
     // Pool
-    let pool = pool_map::get_by_tokens(&token_0, &token_1)?;
+    let pool = pool_map::get_by_tokens(&token_0, &token_1, fee_tier)?;
     let symbol = pool.symbol();
     // Token0
     let token_0 = pool.token_0();
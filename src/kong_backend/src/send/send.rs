@@ -7,6 +7,7 @@ use super::send_reply_helpers::{create_send_reply_failed, create_send_reply_with
 
 use crate::chains::chains::LP_CHAIN;
 use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode};
+use crate::stable_lp_block::stable_lp_block::LPBlockTxArgs;
 use crate::stable_lp_token::transfer::transfer;
 use crate::stable_request::request_map;
 use crate::stable_request::{reply::Reply, request::Request, stable_request::StableRequest, status::StatusCode};
@@ -54,6 +55,7 @@ async fn send(args: SendArgs) -> Result<SendReply, String> {
         lp_token_chain,
         &lp_token_symbol,
         amount,
+        &LPBlockTxArgs::default(),
         ts,
     )
     .map_or_else(
@@ -61,7 +63,7 @@ async fn send(args: SendArgs) -> Result<SendReply, String> {
             request_map::update_status(request_id, StatusCode::Failed, Some(&e));
             Err(e)
         },
-        |reply| {
+        |(reply, _)| {
             request_map::update_status(request_id, StatusCode::Success, None);
             Ok(reply)
         },
@@ -74,8 +76,10 @@ async fn send(args: SendArgs) -> Result<SendReply, String> {
     result
 }
 
+/// # Returns
+/// the reply and the block_id of the ICRC-3 transfer block
 #[allow(clippy::too_many_arguments)]
-fn process_send(
+pub fn process_send(
     request_id: u64,
     from_user_id: u32,
     to_user_id: u32,
@@ -84,14 +88,16 @@ fn process_send(
     lp_token_chain: &str,
     lp_token_symbol: &str,
     amount: &Nat,
+    tx_args: &LPBlockTxArgs,
     ts: u64,
-) -> Result<SendReply, String> {
+) -> Result<(SendReply, u64), String> {
     request_map::update_status(request_id, StatusCode::Start, None);
 
     request_map::update_status(request_id, StatusCode::SendLPTokenToUser, None);
-    match transfer(lp_token_id, to_user_id, amount) {
-        Ok(_) => {
+    let block_id = match transfer(lp_token_id, from_user_id, to_user_id, amount, tx_args) {
+        Ok(block_id) => {
             request_map::update_status(request_id, StatusCode::SendLPTokenToUserSuccess, None);
+            block_id
         }
         Err(e) => {
             request_map::update_status(request_id, StatusCode::SendLPTokenToUserFailed, Some(&e));
@@ -100,7 +106,7 @@ fn process_send(
             request_map::update_reply(request_id, Reply::Send(reply.clone()));
             return Err(format!("Req #{} failed. {}", request_id, e));
         }
-    }
+    };
 
     // successful, add send_tx and update request with reply
     let send_tx = SendTx::new_success(from_user_id, request_id, to_user_id, lp_token_id, amount, ts);
@@ -109,10 +115,10 @@ fn process_send(
     let reply = create_send_reply_with_tx_id(tx_id, &send_tx);
     request_map::update_reply(request_id, Reply::Send(reply.clone()));
 
-    Ok(reply)
+    Ok((reply, block_id))
 }
 
-fn archive_to_kong_data(request: &StableRequest) {
+pub fn archive_to_kong_data(request: &StableRequest) {
     request_map::archive_request_to_kong_data(request.request_id);
     if let Reply::Send(reply) = &request.reply {
         tx_map::archive_tx_to_kong_data(reply.tx_id);
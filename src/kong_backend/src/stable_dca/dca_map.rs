@@ -0,0 +1,72 @@
+use super::stable_dca::{DcaStatus, StableDca, StableDcaId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::DCA_MAP;
+
+pub fn get_by_dca_id(dca_id: u64) -> Option<StableDca> {
+    DCA_MAP.with(|m| m.borrow().get(&StableDcaId(dca_id)))
+}
+
+/// get DCA schedules of user_id, newest first. if dca_id is specified, only that schedule is returned
+pub fn get_by_dca_and_user_id(dca_id: Option<u64>, user_id: u32) -> Vec<StableDca> {
+    DCA_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .rev()
+            .filter_map(|(k, v)| {
+                if dca_id.is_some_and(|dca_id| k.0 != dca_id) || v.user_id != user_id {
+                    return None;
+                }
+                Some(v)
+            })
+            .collect()
+    })
+}
+
+/// get active DCA schedules with a swap due at ts, oldest first
+pub fn get_due(ts: u64) -> Vec<StableDca> {
+    DCA_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter_map(|(_, v)| {
+                if v.status == DcaStatus::Active && v.next_swap_at <= ts {
+                    Some(v)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+pub fn insert(dca: &StableDca) -> u64 {
+    DCA_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let dca_id = kong_settings_map::inc_dca_map_idx();
+        let insert_dca = StableDca { dca_id, ..dca.clone() };
+        map.insert(StableDcaId(dca_id), insert_dca);
+        dca_id
+    })
+}
+
+pub fn update(dca: &StableDca) {
+    DCA_MAP.with(|m| {
+        m.borrow_mut().insert(StableDcaId(dca.dca_id), dca.clone());
+    });
+}
+
+/// change the status of a schedule if it is currently in one of from_status
+/// returns the updated schedule or None if the schedule is not in one of from_status
+pub fn update_status(dca_id: u64, from_status: &[DcaStatus], status: DcaStatus) -> Option<StableDca> {
+    DCA_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        match map.get(&StableDcaId(dca_id)) {
+            Some(mut v) if from_status.contains(&v.status) => {
+                v.status = status;
+                map.insert(StableDcaId(dca_id), v.clone());
+                Some(v)
+            }
+            _ => None,
+        }
+    })
+}
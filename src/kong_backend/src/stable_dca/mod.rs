@@ -0,0 +1,3 @@
+pub mod dca_map;
+#[allow(clippy::module_inception)]
+pub mod stable_dca;
@@ -0,0 +1,127 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+use crate::ic::address::Address;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableDcaId(pub u64);
+
+impl Storable for StableDcaId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DcaStatus {
+    Active,
+    Executing, // used as a guard to prevent a swap from being executed twice
+    Paused,
+    Completed,
+    Cancelled,
+}
+
+impl std::fmt::Display for DcaStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DcaStatus::Active => write!(f, "Active"),
+            DcaStatus::Executing => write!(f, "Executing"),
+            DcaStatus::Paused => write!(f, "Paused"),
+            DcaStatus::Completed => write!(f, "Completed"),
+            DcaStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// what to do when a swap of the schedule fails
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DcaFailurePolicy {
+    Skip,      // skip the swap and continue at the next interval
+    Retry(u8), // retry the swap on the next timer run up to the number of times, then skip
+    Pause,     // pause the schedule. the swap is retried when the schedule is resumed
+}
+
+/// recurring swap schedule. swaps pay_amount of pay_token for receive_token every interval_secs, num_swaps times
+/// pay token is transferred from the user's ICRC-2 approval at every swap
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableDca {
+    pub dca_id: u64,
+    pub user_id: u32,
+    pub status: DcaStatus,
+    pub pay_token_id: u32,
+    pub pay_amount: Nat, // pay amount of each swap
+    pub receive_token_id: u32,
+    pub max_slippage: f64, // max slippage of each swap
+    pub from_principal_id: Account,
+    pub to_address: Address,
+    pub interval_secs: u64,
+    pub num_swaps: u32,     // total number of swaps
+    pub swaps_done: u32,    // number of swaps executed or skipped
+    pub swaps_skipped: u32, // number of swaps skipped after failing
+    pub retries: u8,        // number of retries of the current swap
+    pub on_failure: DcaFailurePolicy,
+    pub next_swap_at: u64,     // time of the next swap
+    pub request_ids: Vec<u64>, // swap requests
+    pub tx_ids: Vec<u64>,      // swap txs
+    pub ts: u64,
+}
+
+impl StableDca {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_id: u32,
+        pay_token_id: u32,
+        pay_amount: &Nat,
+        receive_token_id: u32,
+        max_slippage: f64,
+        from_principal_id: &Account,
+        to_address: &Address,
+        interval_secs: u64,
+        num_swaps: u32,
+        on_failure: &DcaFailurePolicy,
+        next_swap_at: u64,
+        ts: u64,
+    ) -> Self {
+        Self {
+            dca_id: 0, // will be set with insert into DCA_MAP
+            user_id,
+            status: DcaStatus::Active,
+            pay_token_id,
+            pay_amount: pay_amount.clone(),
+            receive_token_id,
+            max_slippage,
+            from_principal_id: *from_principal_id,
+            to_address: to_address.clone(),
+            interval_secs,
+            num_swaps,
+            swaps_done: 0,
+            swaps_skipped: 0,
+            retries: 0,
+            on_failure: on_failure.clone(),
+            next_swap_at,
+            request_ids: Vec::new(),
+            tx_ids: Vec::new(),
+            ts,
+        }
+    }
+}
+
+impl Storable for StableDca {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,41 @@
+use super::stable_farm::{StableFarm, StableFarmId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::FARM_MAP;
+
+pub fn get_by_farm_id(farm_id: u64) -> Option<StableFarm> {
+    FARM_MAP.with(|m| m.borrow().get(&StableFarmId(farm_id)))
+}
+
+/// get farms, newest first. if farm_id is specified, only that farm is returned
+pub fn get(farm_id: Option<u64>) -> Vec<StableFarm> {
+    FARM_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .rev()
+            .filter_map(|(k, v)| {
+                if farm_id.is_some_and(|farm_id| k.0 != farm_id) {
+                    None
+                } else {
+                    Some(v)
+                }
+            })
+            .collect()
+    })
+}
+
+pub fn insert(farm: &StableFarm) -> u64 {
+    FARM_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let farm_id = kong_settings_map::inc_farm_map_idx();
+        let insert_farm = StableFarm { farm_id, ..farm.clone() };
+        map.insert(StableFarmId(farm_id), insert_farm);
+        farm_id
+    })
+}
+
+pub fn update(farm: &StableFarm) {
+    FARM_MAP.with(|m| {
+        m.borrow_mut().insert(StableFarmId(farm.farm_id), farm.clone());
+    });
+}
@@ -0,0 +1,3 @@
+pub mod farm_map;
+#[allow(clippy::module_inception)]
+pub mod stable_farm;
@@ -0,0 +1,201 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
+
+// reward_per_share is scaled by 10^18 to keep precision for small rewards over large stakes
+pub const REWARD_PER_SHARE_SCALE: u128 = 1_000_000_000_000_000_000;
+const NANOSECS_PER_SEC: u64 = 1_000_000_000;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableFarmId(pub u64);
+
+impl Storable for StableFarmId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// liquidity mining farm. stakers of lp_token earn reward_rate of reward_token per second between start_ts and end_ts,
+/// shared pro rata to their stake. emissions stop early once reward_budget has been distributed
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableFarm {
+    pub farm_id: u64,
+    pub lp_token_id: u32,
+    pub reward_token_id: u32,
+    pub reward_rate: Nat,      // reward token amount per second
+    pub reward_budget: Nat,    // reward tokens transferred to Kong for the farm at creation
+    pub remaining_budget: Nat, // reward tokens not distributed yet
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub total_staked: Nat,     // LP tokens staked in the farm
+    pub reward_per_share: Nat, // accumulated rewards per staked LP token, scaled by REWARD_PER_SHARE_SCALE
+    pub last_reward_ts: u64,   // time rewards were accumulated until
+    pub total_harvested: Nat,  // rewards harvested by stakers
+    pub ts: u64,
+}
+
+impl StableFarm {
+    pub fn new(
+        lp_token_id: u32,
+        reward_token_id: u32,
+        reward_rate: &Nat,
+        reward_budget: &Nat,
+        start_ts: u64,
+        end_ts: u64,
+        ts: u64,
+    ) -> Self {
+        Self {
+            farm_id: 0, // will be set with insert into FARM_MAP
+            lp_token_id,
+            reward_token_id,
+            reward_rate: reward_rate.clone(),
+            reward_budget: reward_budget.clone(),
+            remaining_budget: reward_budget.clone(),
+            start_ts,
+            end_ts,
+            total_staked: nat_zero(),
+            reward_per_share: nat_zero(),
+            last_reward_ts: start_ts,
+            total_harvested: nat_zero(),
+            ts,
+        }
+    }
+
+    /// accumulate the rewards from last_reward_ts to ts into reward_per_share. no rewards before start_ts or after end_ts
+    /// - rewards are capped by remaining_budget, so no more than reward_budget is ever distributed
+    pub fn accrue(&mut self, ts: u64) {
+        let to_ts = ts.min(self.end_ts);
+        if to_ts <= self.last_reward_ts {
+            return;
+        }
+        if !nat_is_zero(&self.total_staked) {
+            let elapsed = Nat::from(to_ts - self.last_reward_ts);
+            let rewards = nat_divide(&nat_multiply(&self.reward_rate, &elapsed), &Nat::from(NANOSECS_PER_SEC))
+                .unwrap_or(nat_zero())
+                .min(self.remaining_budget.clone());
+            let reward_per_share =
+                nat_divide(&nat_multiply(&rewards, &Nat::from(REWARD_PER_SHARE_SCALE)), &self.total_staked).unwrap_or(nat_zero());
+            self.reward_per_share = nat_add(&self.reward_per_share, &reward_per_share);
+            self.remaining_budget = nat_subtract(&self.remaining_budget, &rewards).unwrap_or(nat_zero());
+        }
+        self.last_reward_ts = to_ts;
+    }
+
+    /// close the farm after end_ts. accrues the last rewards and takes the budget never distributed, which includes
+    /// the rewards of periods nothing was staked
+    ///
+    /// # Returns
+    /// the remaining budget to refund. Err if the farm has not ended yet
+    pub fn close(&mut self, ts: u64) -> Result<Nat, String> {
+        if ts < self.end_ts {
+            return Err("Farm has not ended yet".to_string());
+        }
+        self.accrue(ts);
+        Ok(std::mem::replace(&mut self.remaining_budget, nat_zero()))
+    }
+
+    /// rewards of amount staked LP tokens accumulated since the farm started
+    pub fn rewards_of(&self, amount: &Nat) -> Nat {
+        nat_divide(&nat_multiply(amount, &self.reward_per_share), &Nat::from(REWARD_PER_SHARE_SCALE)).unwrap_or(nat_zero())
+    }
+}
+
+impl Storable for StableFarm {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = NANOSECS_PER_SEC;
+
+    #[test]
+    fn test_accrue() {
+        // 100 reward tokens per second from 10s to 20s
+        let mut farm = StableFarm::new(1, 2, &Nat::from(100_u32), &Nat::from(10_000_u32), 10 * SEC, 20 * SEC, 0);
+
+        // no rewards before start
+        farm.total_staked = Nat::from(50_u32);
+        farm.accrue(5 * SEC);
+        assert_eq!(farm.last_reward_ts, 10 * SEC);
+        assert_eq!(farm.rewards_of(&Nat::from(50_u32)), nat_zero());
+
+        // 2 seconds of rewards shared by 50 LP tokens
+        farm.accrue(12 * SEC);
+        assert_eq!(farm.rewards_of(&Nat::from(50_u32)), Nat::from(200_u32));
+        assert_eq!(farm.rewards_of(&Nat::from(25_u32)), Nat::from(100_u32));
+
+        // no rewards after end
+        farm.accrue(30 * SEC);
+        assert_eq!(farm.last_reward_ts, 20 * SEC);
+        assert_eq!(farm.rewards_of(&Nat::from(50_u32)), Nat::from(1_000_u32));
+        assert_eq!(farm.remaining_budget, Nat::from(9_000_u32));
+    }
+
+    #[test]
+    fn test_accrue_nothing_staked() {
+        let mut farm = StableFarm::new(1, 2, &Nat::from(100_u32), &Nat::from(10_000_u32), 0, 20 * SEC, 0);
+        // rewards while nothing is staked are not distributed
+        farm.accrue(5 * SEC);
+        assert_eq!(farm.reward_per_share, nat_zero());
+        assert_eq!(farm.last_reward_ts, 5 * SEC);
+        assert_eq!(farm.remaining_budget, Nat::from(10_000_u32));
+    }
+
+    #[test]
+    fn test_accrue_budget_exhausted() {
+        // budget of 250 runs out after 2.5 seconds of 100 reward tokens per second
+        let mut farm = StableFarm::new(1, 2, &Nat::from(100_u32), &Nat::from(250_u32), 0, 20 * SEC, 0);
+        farm.total_staked = Nat::from(50_u32);
+        farm.accrue(2 * SEC);
+        assert_eq!(farm.rewards_of(&Nat::from(50_u32)), Nat::from(200_u32));
+        assert_eq!(farm.remaining_budget, Nat::from(50_u32));
+
+        // only the remaining budget is distributed
+        farm.accrue(5 * SEC);
+        assert_eq!(farm.rewards_of(&Nat::from(50_u32)), Nat::from(250_u32));
+        assert_eq!(farm.remaining_budget, nat_zero());
+
+        // no more rewards once the budget is exhausted
+        farm.accrue(20 * SEC);
+        assert_eq!(farm.rewards_of(&Nat::from(50_u32)), Nat::from(250_u32));
+        assert_eq!(farm.last_reward_ts, 20 * SEC);
+    }
+
+    #[test]
+    fn test_close() {
+        // staked for 2 of the 10 seconds, so 8 seconds of rewards were never distributed
+        let mut farm = StableFarm::new(1, 2, &Nat::from(100_u32), &Nat::from(10_000_u32), 10 * SEC, 20 * SEC, 0);
+        farm.accrue(12 * SEC);
+        farm.total_staked = Nat::from(50_u32);
+        farm.accrue(14 * SEC);
+        farm.total_staked = nat_zero();
+
+        assert!(farm.close(19 * SEC).is_err());
+        assert_eq!(farm.remaining_budget, Nat::from(9_800_u32));
+
+        // stakers keep the 200 distributed
+        assert_eq!(farm.close(20 * SEC).unwrap(), Nat::from(9_800_u32));
+        assert_eq!(farm.remaining_budget, nat_zero());
+        assert_eq!(farm.reward_per_share, Nat::from(4_u32) * Nat::from(REWARD_PER_SHARE_SCALE));
+        // nothing left to refund once closed
+        assert_eq!(farm.close(30 * SEC).unwrap(), nat_zero());
+    }
+}
@@ -0,0 +1,42 @@
+use candid::Nat;
+
+use super::stable_farm_stake::{StableFarmStake, StableFarmStakeId};
+
+use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::stable_farm::farm_map;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::FARM_STAKE_MAP;
+
+/// get the stake of user_id in farm_id
+pub fn get_by_farm_and_user_id(farm_id: u64, user_id: u32) -> Option<StableFarmStake> {
+    FARM_STAKE_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .find_map(|(_, v)| (v.farm_id == farm_id && v.user_id == user_id).then_some(v))
+    })
+}
+
+/// insert the stake if new or update the existing one
+pub fn upsert(stake: &StableFarmStake) -> u64 {
+    FARM_STAKE_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let stake_id = if stake.stake_id == 0 {
+            kong_settings_map::inc_farm_stake_map_idx()
+        } else {
+            stake.stake_id
+        };
+        map.insert(StableFarmStakeId(stake_id), StableFarmStake { stake_id, ..stake.clone() });
+        stake_id
+    })
+}
+
+/// LP tokens of lp_token_id staked by user_id in all farms
+pub fn get_staked_amount(lp_token_id: u32, user_id: u32) -> Nat {
+    FARM_STAKE_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter(|(_, v)| v.user_id == user_id)
+            .filter(|(_, v)| farm_map::get_by_farm_id(v.farm_id).is_some_and(|farm| farm.lp_token_id == lp_token_id))
+            .fold(nat_zero(), |acc, (_, v)| nat_add(&acc, &v.amount))
+    })
+}
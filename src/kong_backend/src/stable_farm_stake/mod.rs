@@ -0,0 +1,3 @@
+pub mod farm_stake_map;
+#[allow(clippy::module_inception)]
+pub mod stable_farm_stake;
@@ -0,0 +1,115 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::{nat_add, nat_subtract, nat_zero};
+use crate::stable_farm::stable_farm::StableFarm;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableFarmStakeId(pub u64);
+
+impl Storable for StableFarmStakeId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// LP tokens staked by a user in a farm
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableFarmStake {
+    pub stake_id: u64,
+    pub farm_id: u64,
+    pub user_id: u32,
+    pub amount: Nat,             // LP tokens staked
+    pub reward_debt: Nat,        // farm rewards of amount at the last stake, unstake or harvest
+    pub unharvested_reward: Nat, // rewards earned until the last stake, unstake or harvest and not harvested yet
+    pub ts: u64,
+}
+
+impl StableFarmStake {
+    pub fn new(farm_id: u64, user_id: u32, ts: u64) -> Self {
+        Self {
+            stake_id: 0, // will be set with insert into FARM_STAKE_MAP
+            farm_id,
+            user_id,
+            amount: nat_zero(),
+            reward_debt: nat_zero(),
+            unharvested_reward: nat_zero(),
+            ts,
+        }
+    }
+
+    /// rewards earned and not harvested yet. farm must be accrued
+    pub fn pending_reward(&self, farm: &StableFarm) -> Nat {
+        let earned = nat_subtract(&farm.rewards_of(&self.amount), &self.reward_debt).unwrap_or(nat_zero());
+        nat_add(&self.unharvested_reward, &earned)
+    }
+
+    /// move the pending rewards to unharvested_reward and set the stake to amount. farm must be accrued
+    pub fn settle(&mut self, farm: &StableFarm, amount: &Nat, ts: u64) {
+        self.unharvested_reward = self.pending_reward(farm);
+        self.amount = amount.clone();
+        self.reward_debt = farm.rewards_of(amount);
+        self.ts = ts;
+    }
+}
+
+impl Storable for StableFarmStake {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_pro_rata_rewards() {
+        // 100 reward tokens per second
+        let mut farm = StableFarm::new(1, 2, &Nat::from(100_u32), &Nat::from(1_000_000_u32), 0, 100 * SEC, 0);
+
+        // user 1 stakes 20 at 0s
+        let mut stake_1 = StableFarmStake::new(1, 1, 0);
+        farm.accrue(0);
+        stake_1.settle(&farm, &Nat::from(20_u32), 0);
+        farm.total_staked = Nat::from(20_u32);
+
+        // user 2 stakes 5 at 10s. user 1 earned all 1,000 so far
+        farm.accrue(10 * SEC);
+        let mut stake_2 = StableFarmStake::new(1, 2, 10 * SEC);
+        stake_2.settle(&farm, &Nat::from(5_u32), 10 * SEC);
+        farm.total_staked = Nat::from(25_u32);
+        assert_eq!(stake_1.pending_reward(&farm), Nat::from(1_000_u32));
+        assert_eq!(stake_2.pending_reward(&farm), nat_zero());
+
+        // at 20s, the next 1,000 is shared 4:1
+        farm.accrue(20 * SEC);
+        assert_eq!(stake_1.pending_reward(&farm), Nat::from(1_800_u32));
+        assert_eq!(stake_2.pending_reward(&farm), Nat::from(200_u32));
+
+        // user 1 unstakes everything. rewards so far are kept as unharvested
+        stake_1.settle(&farm, &nat_zero(), 20 * SEC);
+        farm.total_staked = Nat::from(5_u32);
+        assert_eq!(stake_1.unharvested_reward, Nat::from(1_800_u32));
+
+        // at 30s, user 2 earned all of the next 1,000
+        farm.accrue(30 * SEC);
+        assert_eq!(stake_1.pending_reward(&farm), Nat::from(1_800_u32));
+        assert_eq!(stake_2.pending_reward(&farm), Nat::from(1_200_u32));
+    }
+}
@@ -0,0 +1,100 @@
+use candid::Nat;
+
+use super::stable_kong_fee::{StableKongFee, StableKongFeeId};
+
+use crate::helpers::nat_helpers::nat_is_zero;
+use crate::stable_memory::KONG_FEE_MAP;
+use crate::stable_tx::sweep_kong_fees_tx::SweepKongFeesTx;
+
+/// daily Kong fees of pool_id, oldest first
+pub fn get_by_pool_id(pool_id: u32) -> Vec<StableKongFee> {
+    KONG_FEE_MAP.with(|m| {
+        let start = StableKongFeeId { pool_id, ts: 0 };
+        let end = StableKongFeeId { pool_id, ts: u64::MAX };
+        m.borrow().range(start..=end).map(|(_, v)| v).collect()
+    })
+}
+
+/// adds the Kong fees accrued by a swap at ts to the day of ts
+pub fn add_kong_fee(pool_id: u32, kong_fee_0: &Nat, kong_fee_1: &Nat, ts: u64) {
+    if nat_is_zero(kong_fee_0) && nat_is_zero(kong_fee_1) {
+        return;
+    }
+    update_day(pool_id, ts, |kong_fee| kong_fee.add_kong_fee(kong_fee_0, kong_fee_1));
+}
+
+/// logs a sweep of the pool's Kong fees in the day of the sweep
+pub fn add_sweep(sweep_kong_fees_tx: &SweepKongFeesTx) {
+    update_day(sweep_kong_fees_tx.pool_id, sweep_kong_fees_tx.ts, |kong_fee| {
+        kong_fee.sweeps.push(sweep_kong_fees_tx.clone())
+    });
+}
+
+fn update_day(pool_id: u32, ts: u64, f: impl FnOnce(&mut StableKongFee)) {
+    KONG_FEE_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let key = StableKongFeeId {
+            pool_id,
+            ts: StableKongFee::day_ts(ts),
+        };
+        let mut kong_fee = map.get(&key).unwrap_or_else(|| StableKongFee::new(pool_id, ts));
+        f(&mut kong_fee);
+        map.insert(key, kong_fee);
+    });
+}
+
+pub fn remove_by_pool_id(pool_id: u32) {
+    KONG_FEE_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let start = StableKongFeeId { pool_id, ts: 0 };
+        let end = StableKongFeeId { pool_id, ts: u64::MAX };
+        let keys_to_remove: Vec<_> = map.range(start..=end).map(|(k, _)| k).collect();
+        for key in keys_to_remove {
+            map.remove(&key);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icrc_ledger_types::icrc1::account::Account;
+
+    use crate::helpers::nat_helpers::nat_zero;
+    use crate::stable_kong_fee::stable_kong_fee::NANOS_PER_DAY;
+
+    fn sweep(pool_id: u32, amount_0: u64, ts: u64) -> SweepKongFeesTx {
+        let treasury_account = Account {
+            owner: candid::Principal::anonymous(),
+            subaccount: None,
+        };
+        SweepKongFeesTx::new_success(0, pool_id, &Nat::from(amount_0), None, &nat_zero(), None, &treasury_account, ts)
+    }
+
+    #[test]
+    fn test_kong_fees_over_time() {
+        let pool_id = 9_001;
+        add_kong_fee(pool_id, &Nat::from(100_u64), &nat_zero(), 10);
+        add_kong_fee(pool_id, &Nat::from(50_u64), &Nat::from(7_u64), NANOS_PER_DAY - 1);
+        add_kong_fee(pool_id, &nat_zero(), &nat_zero(), NANOS_PER_DAY); // nothing accrued, no record
+        add_sweep(&sweep(pool_id, 150, 2 * NANOS_PER_DAY + 5));
+        add_kong_fee(pool_id, &Nat::from(20_u64), &nat_zero(), 2 * NANOS_PER_DAY + 9);
+        // other pools are not in the history
+        add_kong_fee(pool_id + 1, &Nat::from(1_u64), &nat_zero(), 10);
+
+        let history = get_by_pool_id(pool_id);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].ts, 0);
+        assert_eq!(history[0].kong_fee_0, Nat::from(150_u64));
+        assert_eq!(history[0].kong_fee_1, Nat::from(7_u64));
+        assert!(history[0].sweeps.is_empty());
+        assert_eq!(history[1].ts, 2 * NANOS_PER_DAY);
+        assert_eq!(history[1].kong_fee_0, Nat::from(20_u64));
+        assert_eq!(history[1].sweeps.len(), 1);
+        assert_eq!(history[1].sweeps[0].amount_0, Nat::from(150_u64));
+
+        remove_by_pool_id(pool_id);
+        assert!(get_by_pool_id(pool_id).is_empty());
+        assert_eq!(get_by_pool_id(pool_id + 1).len(), 1);
+    }
+}
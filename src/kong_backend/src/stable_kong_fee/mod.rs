@@ -0,0 +1,3 @@
+pub mod kong_fee_map;
+#[allow(clippy::module_inception)]
+pub mod stable_kong_fee;
@@ -0,0 +1,85 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::stable_tx::sweep_kong_fees_tx::SweepKongFeesTx;
+
+pub const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+
+/// Kong fees are ordered by pool then day so the history of a pool can be found with a range query
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableKongFeeId {
+    pub pool_id: u32,
+    pub ts: u64, // start of the day
+}
+
+impl Storable for StableKongFeeId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Kong fees accrued by a pool and swept to the treasury during a day
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableKongFee {
+    pub pool_id: u32,
+    pub ts: u64,                      // start of the day
+    pub kong_fee_0: Nat,              // Kong fees of token_0 accrued during the day
+    pub kong_fee_1: Nat,              // Kong fees of token_1 accrued during the day
+    pub sweeps: Vec<SweepKongFeesTx>, // sweeps of the pool's Kong fees during the day, oldest first
+}
+
+impl StableKongFee {
+    pub fn new(pool_id: u32, ts: u64) -> Self {
+        Self {
+            pool_id,
+            ts: Self::day_ts(ts),
+            kong_fee_0: nat_zero(),
+            kong_fee_1: nat_zero(),
+            sweeps: Vec::new(),
+        }
+    }
+
+    /// start of the day of ts
+    pub fn day_ts(ts: u64) -> u64 {
+        ts - ts % NANOS_PER_DAY
+    }
+
+    pub fn add_kong_fee(&mut self, kong_fee_0: &Nat, kong_fee_1: &Nat) {
+        self.kong_fee_0 = nat_add(&self.kong_fee_0, kong_fee_0);
+        self.kong_fee_1 = nat_add(&self.kong_fee_1, kong_fee_1);
+    }
+}
+
+impl Storable for StableKongFee {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_ts() {
+        assert_eq!(StableKongFee::day_ts(0), 0);
+        assert_eq!(StableKongFee::day_ts(NANOS_PER_DAY - 1), 0);
+        assert_eq!(StableKongFee::day_ts(NANOS_PER_DAY), NANOS_PER_DAY);
+        assert_eq!(StableKongFee::day_ts(3 * NANOS_PER_DAY + 12_345), 3 * NANOS_PER_DAY);
+        assert_eq!(StableKongFee::new(1, 2 * NANOS_PER_DAY + 1).ts, 2 * NANOS_PER_DAY);
+    }
+}
@@ -1,7 +1,9 @@
+use candid::Nat;
 use std::cmp;
 
 use super::stable_kong_settings::StableKongSettings;
 
+use crate::helpers::nat_helpers::nat_add;
 use crate::stable_memory::KONG_SETTINGS;
 
 const LAST_SYSTEM_USER_ID: u32 = 99;
@@ -135,3 +137,116 @@ pub fn inc_message_map_idx() -> u64 {
         message_map_idx
     })
 }
+
+pub fn inc_order_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let order_map_idx = kong_settings.order_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            order_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        order_map_idx
+    })
+}
+
+pub fn inc_dca_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let dca_map_idx = kong_settings.dca_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            dca_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        dca_map_idx
+    })
+}
+
+pub fn inc_lp_position_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let lp_position_map_idx = kong_settings.lp_position_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            lp_position_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        lp_position_map_idx
+    })
+}
+
+pub fn inc_lp_allowance_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let lp_allowance_map_idx = kong_settings.lp_allowance_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            lp_allowance_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        lp_allowance_map_idx
+    })
+}
+
+pub fn inc_farm_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let farm_map_idx = kong_settings.farm_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            farm_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        farm_map_idx
+    })
+}
+
+pub fn inc_farm_stake_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let farm_stake_map_idx = kong_settings.farm_stake_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            farm_stake_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        farm_stake_map_idx
+    })
+}
+
+pub fn inc_lp_lock_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let lp_lock_map_idx = kong_settings.lp_lock_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            lp_lock_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        lp_lock_map_idx
+    })
+}
+
+/// add a buyback to the buyback totals
+pub fn add_buyback_burned(amount: &Nat, ts: u64) {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let new_kong_settings = StableKongSettings {
+            buyback_burned: nat_add(&kong_settings.buyback_burned, amount),
+            buyback_count: kong_settings.buyback_count + 1,
+            last_buyback_ts: ts,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+    })
+}
@@ -1,9 +1,10 @@
-use candid::{CandidType, Principal};
+use candid::{CandidType, Nat, Principal};
 use ic_stable_structures::{storable::Bound, Storable};
 use icrc_ledger_types::icrc1::account::Account;
 use serde::{Deserialize, Serialize};
 use std::cmp;
 
+use crate::helpers::nat_helpers::nat_zero;
 use crate::ic::{
     canister_address::KONG_DATA,
     ckusdt::{CKUSDT_ADDRESS, CKUSDT_ADDRESS_WITH_CHAIN, CKUSDT_SYMBOL, CKUSDT_SYMBOL_WITH_CHAIN, CKUSDT_TOKEN_ID},
@@ -11,9 +12,12 @@ use crate::ic::{
     id::{kong_account, kong_backend_id},
 };
 use crate::stable_memory::{
-    CLAIM_MAP, LP_TOKEN_MAP, MESSAGE_MAP, POOL_MAP, REQUEST_ARCHIVE_MAP, REQUEST_MAP, TOKEN_MAP, TRANSFER_ARCHIVE_MAP, TRANSFER_MAP,
-    TX_ARCHIVE_MAP, TX_MAP, USER_MAP,
+    CLAIM_MAP, DCA_MAP, FARM_MAP, FARM_STAKE_MAP, LP_ALLOWANCE_MAP, LP_LOCK_MAP, LP_POSITION_MAP, LP_TOKEN_MAP, MESSAGE_MAP, ORDER_MAP,
+    POOL_MAP, REQUEST_ARCHIVE_MAP, REQUEST_MAP, TOKEN_MAP, TRANSFER_ARCHIVE_MAP, TRANSFER_MAP, TX_ARCHIVE_MAP, TX_MAP, USER_MAP,
 };
+use crate::stable_pool::stable_pool::PIPS_PER_BPS;
+
+pub const MAX_SWAP_HOPS: u8 = 3; // maximum of max_swap_hops
 
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct StableKongSettings {
@@ -33,8 +37,20 @@ pub struct StableKongSettings {
     pub icp_address: String,
     pub icp_address_with_chain: String,
     pub default_max_slippage: f64,
-    pub default_lp_fee_bps: u8,
-    pub default_kong_fee_bps: u8,
+    #[serde(default = "default_lp_fee_pips")]
+    pub default_lp_fee_pips: u32, // default LP fee of new pools in pips (1/100 of a basis point)
+    #[serde(default)]
+    pub default_kong_fee_pips: u32, // default Kong's fee of new pools in pips
+    #[serde(default, skip_serializing)]
+    pub default_lp_fee_bps: u8, // deprecated. migrated to default_lp_fee_pips
+    #[serde(default, skip_serializing)]
+    pub default_kong_fee_bps: u8, // deprecated. migrated to default_kong_fee_pips
+    #[serde(default = "default_max_swap_hops")]
+    pub max_swap_hops: u8, // maximum number of pools a swap can be routed through
+    #[serde(default = "default_max_swap_splits")]
+    pub max_swap_splits: u8, // maximum number of routes a swap can be split across
+    #[serde(default = "default_swap_split_steps")]
+    pub swap_split_steps: u8, // number of steps the pay amount is divided into when splitting a swap
     pub user_map_idx: u32,     // counter for USER_MAP
     pub token_map_idx: u32,    // counter for TOKEN_MAP
     pub pool_map_idx: u32,     // counter for POOL_MAP
@@ -44,6 +60,20 @@ pub struct StableKongSettings {
     pub claim_map_idx: u64,    // counter for CLAIM_MAP
     pub lp_token_map_idx: u64, // counter for LP_TOKEN_MAP
     pub message_map_idx: u64,  // counter for MESSAGE_MAP
+    #[serde(default)]
+    pub order_map_idx: u64, // counter for ORDER_MAP
+    #[serde(default)]
+    pub dca_map_idx: u64, // counter for DCA_MAP
+    #[serde(default)]
+    pub lp_position_map_idx: u64, // counter for LP_POSITION_MAP
+    #[serde(default)]
+    pub lp_allowance_map_idx: u64, // counter for LP_ALLOWANCE_MAP
+    #[serde(default)]
+    pub farm_map_idx: u64, // counter for FARM_MAP
+    #[serde(default)]
+    pub farm_stake_map_idx: u64, // counter for FARM_STAKE_MAP
+    #[serde(default)]
+    pub lp_lock_map_idx: u64, // counter for LP_LOCK_MAP
     pub claims_interval_secs: u64,
     pub transfer_expiry_nanosecs: u64,
     pub stats_interval_secs: u64,
@@ -51,6 +81,54 @@ pub struct StableKongSettings {
     pub txs_archive_interval_secs: u64,
     pub transfers_archive_interval_secs: u64,
     pub lp_tokenss_interval_secs: u64,
+    #[serde(default = "default_orders_interval_secs")]
+    pub orders_interval_secs: u64,
+    #[serde(default = "default_dca_interval_secs")]
+    pub dca_interval_secs: u64,
+    #[serde(default = "default_twap_observation_interval_secs")]
+    pub twap_observation_interval_secs: u64, // minimum time between TWAP observations of a pool
+    #[serde(default = "default_twap_max_observations")]
+    pub twap_max_observations: u16, // number of TWAP observations kept per pool
+    #[serde(default)]
+    pub treasury_account: Option<Account>, // account Kong fees are swept to. sweep_kong_fees() fails until set
+    #[serde(default)]
+    pub buyback_token: Option<String>, // token Kong fees are bought back into and burned. buybacks are disabled until set
+    #[serde(default = "default_buyback_threshold_ckusdt")]
+    pub buyback_threshold_ckusdt: f64, // minimum value in ckUSDT of the Kong fees of a token to buy back
+    #[serde(default = "default_buyback_max_slippage")]
+    pub buyback_max_slippage: f64,
+    #[serde(default = "default_buyback_interval_secs")]
+    pub buyback_interval_secs: u64,
+    #[serde(default = "nat_zero")]
+    pub buyback_burned: Nat, // total amount of buyback_token burned
+    #[serde(default)]
+    pub buyback_count: u64, // number of buybacks burned
+    #[serde(default)]
+    pub last_buyback_ts: u64,
+}
+
+impl StableKongSettings {
+    /// settings stored before fees were in pips have the default fees in default_lp_fee_bps and default_kong_fee_bps
+    pub fn migrate_fee_bps(self) -> Self {
+        if self.default_lp_fee_bps == 0 && self.default_kong_fee_bps == 0 {
+            return self;
+        }
+        Self {
+            default_lp_fee_pips: self.default_lp_fee_bps as u32 * PIPS_PER_BPS,
+            default_kong_fee_pips: self.default_kong_fee_bps as u32 * PIPS_PER_BPS,
+            default_lp_fee_bps: 0,
+            default_kong_fee_bps: 0,
+            ..self
+        }
+    }
+
+    /// the number of routes grows exponentially with the number of hops, so max_swap_hops is kept between 1 and MAX_SWAP_HOPS
+    pub fn clamp_max_swap_hops(self) -> Self {
+        Self {
+            max_swap_hops: self.max_swap_hops.clamp(1, MAX_SWAP_HOPS),
+            ..self
+        }
+    }
 }
 
 impl Default for StableKongSettings {
@@ -61,6 +139,13 @@ impl Default for StableKongSettings {
         let claim_map_idx = CLAIM_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
         let lp_token_map_idx = LP_TOKEN_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
         let message_map_idx = MESSAGE_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let order_map_idx = ORDER_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let dca_map_idx = DCA_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let lp_position_map_idx = LP_POSITION_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let lp_allowance_map_idx = LP_ALLOWANCE_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let farm_map_idx = FARM_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let farm_stake_map_idx = FARM_STAKE_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let lp_lock_map_idx = LP_LOCK_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.lock_id).max().unwrap_or(0));
         let request_map_idx = cmp::max(
             REQUEST_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0)),
             REQUEST_ARCHIVE_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0)),
@@ -90,8 +175,13 @@ impl Default for StableKongSettings {
             icp_address: ICP_ADDRESS.to_string(),
             icp_address_with_chain: ICP_ADDRESS_WITH_CHAIN.to_string(),
             default_max_slippage: 2.0_f64,
-            default_lp_fee_bps: 30,
+            default_lp_fee_pips: default_lp_fee_pips(),
+            default_kong_fee_pips: 0,
+            default_lp_fee_bps: 0,
             default_kong_fee_bps: 0,
+            max_swap_hops: default_max_swap_hops(),
+            max_swap_splits: default_max_swap_splits(),
+            swap_split_steps: default_swap_split_steps(),
             user_map_idx,
             token_map_idx,
             pool_map_idx,
@@ -101,6 +191,13 @@ impl Default for StableKongSettings {
             claim_map_idx,
             lp_token_map_idx,
             message_map_idx,
+            order_map_idx,
+            dca_map_idx,
+            lp_position_map_idx,
+            lp_allowance_map_idx,
+            farm_map_idx,
+            farm_stake_map_idx,
+            lp_lock_map_idx,
             claims_interval_secs: 300,                   // claims every 5 minutes
             transfer_expiry_nanosecs: 3_600_000_000_000, // 1 hour (nano seconds)
             stats_interval_secs: 3600,                   // stats every hour
@@ -108,6 +205,18 @@ impl Default for StableKongSettings {
             txs_archive_interval_secs: 3600,             // archive txs every hour
             transfers_archive_interval_secs: 3600,       // archive transfers every hour
             lp_tokenss_interval_secs: 3600,              // archive lp_positions every hour
+            orders_interval_secs: default_orders_interval_secs(),
+            dca_interval_secs: default_dca_interval_secs(),
+            twap_observation_interval_secs: default_twap_observation_interval_secs(),
+            twap_max_observations: default_twap_max_observations(),
+            treasury_account: None,
+            buyback_token: None,
+            buyback_threshold_ckusdt: default_buyback_threshold_ckusdt(),
+            buyback_max_slippage: default_buyback_max_slippage(),
+            buyback_interval_secs: default_buyback_interval_secs(),
+            buyback_burned: nat_zero(),
+            buyback_count: 0,
+            last_buyback_ts: 0,
         }
     }
 }
@@ -118,8 +227,54 @@ impl Storable for StableKongSettings {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap_or_default()
+        serde_cbor::from_slice::<StableKongSettings>(&bytes)
+            .unwrap_or_default()
+            .migrate_fee_bps()
     }
 
     const BOUND: Bound = Bound::Unbounded;
 }
+
+fn default_lp_fee_pips() -> u32 {
+    3_000 // 0.3%
+}
+
+fn default_max_swap_hops() -> u8 {
+    MAX_SWAP_HOPS
+}
+
+fn default_max_swap_splits() -> u8 {
+    3
+}
+
+fn default_swap_split_steps() -> u8 {
+    10
+}
+
+fn default_orders_interval_secs() -> u64 {
+    60 // check limit orders every minute
+}
+
+fn default_dca_interval_secs() -> u64 {
+    60 // check DCA schedules every minute
+}
+
+fn default_twap_observation_interval_secs() -> u64 {
+    300 // observe prices at most every 5 minutes
+}
+
+fn default_twap_max_observations() -> u16 {
+    288 // 24 hours of 5 minute observations
+}
+
+fn default_buyback_threshold_ckusdt() -> f64 {
+    100.0 // buy back Kong fees of a token once worth 100 ckUSDT
+}
+
+fn default_buyback_max_slippage() -> f64 {
+    2.0
+}
+
+fn default_buyback_interval_secs() -> u64 {
+    86_400 // buy back once a day
+}
@@ -0,0 +1,38 @@
+use super::stable_lp_allowance::{StableLPAllowance, StableLPAllowanceId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::LP_ALLOWANCE_MAP;
+
+/// get the allowance of spender over the LP token of user_id
+pub fn get(token_id: u32, user_id: u32, spender: &str) -> Option<StableLPAllowance> {
+    LP_ALLOWANCE_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .find_map(|(_, v)| (v.token_id == token_id && v.user_id == user_id && v.spender == spender).then_some(v))
+    })
+}
+
+/// insert the allowance if new or update the existing one
+pub fn upsert(allowance: &StableLPAllowance) -> u64 {
+    LP_ALLOWANCE_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let allowance_id = if allowance.allowance_id == 0 {
+            kong_settings_map::inc_lp_allowance_map_idx()
+        } else {
+            allowance.allowance_id
+        };
+        map.insert(
+            StableLPAllowanceId(allowance_id),
+            StableLPAllowance {
+                allowance_id,
+                ..allowance.clone()
+            },
+        );
+        allowance_id
+    })
+}
+
+/// remove the allowance
+pub fn remove(allowance_id: u64) -> Option<StableLPAllowance> {
+    LP_ALLOWANCE_MAP.with(|m| m.borrow_mut().remove(&StableLPAllowanceId(allowance_id)))
+}
@@ -0,0 +1,80 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::nat_zero;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLPAllowanceId(pub u64);
+
+impl Storable for StableLPAllowanceId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// ICRC-2 allowance of a spender over the LP token of a user
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableLPAllowance {
+    pub allowance_id: u64,       // unique id for LP_ALLOWANCE_MAP
+    pub user_id: u32,            // user id of the LP token holder
+    pub token_id: u32,           // token id of the LP token
+    pub spender: String,         // ICRC-1 textual encoding of the spender's account
+    pub amount: Nat,             // amount the spender can still transfer
+    pub expires_at: Option<u64>, // allowance is zero after this time
+    pub ts: u64,                 // timestamp of the last approve or transfer_from
+}
+
+impl StableLPAllowance {
+    /// allowance amount at ts. zero if expired
+    pub fn amount_at(&self, ts: u64) -> Nat {
+        match self.expires_at {
+            Some(expires_at) if expires_at <= ts => nat_zero(),
+            _ => self.amount.clone(),
+        }
+    }
+}
+
+impl Storable for StableLPAllowance {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowance(expires_at: Option<u64>) -> StableLPAllowance {
+        StableLPAllowance {
+            allowance_id: 1,
+            user_id: 1,
+            token_id: 1,
+            spender: "aaaaa-aa".to_string(),
+            amount: Nat::from(100_u32),
+            expires_at,
+            ts: 0,
+        }
+    }
+
+    #[test]
+    fn test_amount_at() {
+        assert_eq!(allowance(None).amount_at(u64::MAX), Nat::from(100_u32));
+        assert_eq!(allowance(Some(10)).amount_at(9), Nat::from(100_u32));
+        // allowance is zero from expires_at
+        assert_eq!(allowance(Some(10)).amount_at(10), nat_zero());
+        assert_eq!(allowance(Some(10)).amount_at(11), nat_zero());
+    }
+}
@@ -0,0 +1,44 @@
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_memory::{LP_BLOCK_ARCHIVE_MAP, LP_BLOCK_MAP};
+
+use super::stable_lp_block::StableLPBlockId;
+
+pub fn archive_lp_block_map() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    // archive blocks
+    LP_BLOCK_MAP.with(|lp_block_map| {
+        LP_BLOCK_ARCHIVE_MAP.with(|lp_block_archive_map| {
+            let lp_block = lp_block_map.borrow();
+            let mut lp_block_archive = lp_block_archive_map.borrow_mut();
+            let start_block_id = lp_block_archive.last_key_value().map_or(0_u64, |(k, _)| k.0);
+            let end_block_id = lp_block.last_key_value().map_or(0_u64, |(k, _)| k.0);
+            for block_id in start_block_id..=end_block_id {
+                if let Some(block) = lp_block.get(&StableLPBlockId(block_id)) {
+                    lp_block_archive.insert(StableLPBlockId(block_id), block);
+                }
+            }
+        });
+    });
+
+    // only keep blocks from the last hour and always the last block as the tip of the log
+    let one_hour_ago = get_time() - 3_600_000_000_000;
+    let mut remove_list = Vec::new();
+    LP_BLOCK_MAP.with(|lp_block_map| {
+        let lp_block = lp_block_map.borrow();
+        let tip_block_id = lp_block.last_key_value().map_or(0_u64, |(k, _)| k.0);
+        lp_block.iter().for_each(|(block_id, block)| {
+            if block.ts < one_hour_ago && block_id.0 < tip_block_id {
+                remove_list.push(block_id);
+            }
+        });
+    });
+    LP_BLOCK_MAP.with(|lp_block_map| {
+        remove_list.iter().for_each(|block_id| {
+            lp_block_map.borrow_mut().remove(block_id);
+        });
+    });
+}
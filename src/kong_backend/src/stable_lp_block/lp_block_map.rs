@@ -0,0 +1,374 @@
+use candid::Nat;
+use ic_certification::{fork, label, leaf, HashTree};
+use icrc_ledger_types::icrc1::account::Account;
+use serde_bytes::ByteBuf;
+use std::str::FromStr;
+
+use super::stable_lp_block::{LPBlockTxArgs, LPBlockType, StableLPBlock, StableLPBlockId};
+
+use crate::helpers::nat_helpers::nat_is_zero;
+use crate::ic::logging::error_log;
+use crate::stable_lp_token::stable_lp_token::StableLPToken;
+use crate::stable_memory::{LP_BLOCK_ARCHIVE_MAP, LP_BLOCK_MAP, LP_TOKEN_MAP};
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_user::user_map;
+
+/// get block by block_id from LP_BLOCK_MAP or LP_BLOCK_ARCHIVE_MAP if archived
+pub fn get(block_id: u64) -> Option<StableLPBlock> {
+    LP_BLOCK_MAP
+        .with(|m| m.borrow().get(&StableLPBlockId(block_id)))
+        .or_else(|| LP_BLOCK_ARCHIVE_MAP.with(|m| m.borrow().get(&StableLPBlockId(block_id))))
+}
+
+/// get the last block of the log. LP_BLOCK_MAP always keeps the last block
+pub fn get_tip() -> Option<StableLPBlock> {
+    LP_BLOCK_MAP.with(|m| m.borrow().last_key_value().map(|(_, v)| v))
+}
+
+/// number of blocks in the log
+pub fn get_log_length() -> u64 {
+    get_tip().map_or(0, |tip| tip.block_id + 1)
+}
+
+/// block_id of the first block still in LP_BLOCK_MAP. blocks before it are in LP_BLOCK_ARCHIVE_MAP only
+pub fn get_first_block_id() -> u64 {
+    LP_BLOCK_MAP.with(|m| m.borrow().first_key_value().map_or(0, |(k, _)| k.0))
+}
+
+/// record LP tokens minted to user_id by add_pool, add_liquidity or refunded by remove_liquidity
+pub fn insert_mint(lp_token_id: u32, to_user_id: u32, amount: &Nat, ts: u64) -> Result<u64, String> {
+    insert(
+        LPBlockType::Mint,
+        lp_token_id,
+        None,
+        Some(to_user_id),
+        amount,
+        &LPBlockTxArgs::default(),
+        ts,
+    )
+}
+
+/// record LP tokens burned from user_id by remove_liquidity or by removing the pool
+pub fn insert_burn(lp_token_id: u32, from_user_id: u32, amount: &Nat, ts: u64) -> Result<u64, String> {
+    insert(
+        LPBlockType::Burn,
+        lp_token_id,
+        Some(from_user_id),
+        None,
+        amount,
+        &LPBlockTxArgs::default(),
+        ts,
+    )
+}
+
+/// record LP tokens transferred from from_user_id to to_user_id. a transfer with a spender is an icrc2_transfer_from
+pub fn insert_transfer(
+    lp_token_id: u32,
+    from_user_id: u32,
+    to_user_id: u32,
+    amount: &Nat,
+    tx_args: &LPBlockTxArgs,
+    ts: u64,
+) -> Result<u64, String> {
+    let block_type = if tx_args.spender.is_some() {
+        LPBlockType::TransferFrom
+    } else {
+        LPBlockType::Transfer
+    };
+    insert(block_type, lp_token_id, Some(from_user_id), Some(to_user_id), amount, tx_args, ts)
+}
+
+/// record the allowance of from_user_id approved for tx_args.spender
+pub fn insert_approve(lp_token_id: u32, from_user_id: u32, amount: &Nat, tx_args: &LPBlockTxArgs, ts: u64) -> Result<u64, String> {
+    insert(LPBlockType::Approve, lp_token_id, Some(from_user_id), None, amount, tx_args, ts)
+}
+
+/// block_id of a block recorded since since_ts with the same call as block. used to deduplicate calls with created_at_time
+pub fn get_duplicate(block: &StableLPBlock, since_ts: u64) -> Option<u64> {
+    let tip = get_tip()?;
+    (0..=tip.block_id)
+        .rev()
+        .map_while(|block_id| get(block_id).filter(|b| b.ts >= since_ts))
+        .find(|b| b.is_same_tx(block))
+        .map(|b| b.block_id)
+}
+
+/// one-time backfill of the LP balances from before the log. if the log is empty, records a mint block for each LP balance
+/// balances that can not be recorded are logged and skipped so the upgrade does not fail
+///
+/// # Returns
+/// number of mint blocks recorded
+pub fn backfill_mints(ts: u64) -> u64 {
+    if get_log_length() > 0 || LP_BLOCK_ARCHIVE_MAP.with(|m| !m.borrow().is_empty()) {
+        return 0;
+    }
+
+    let lp_tokens: Vec<StableLPToken> =
+        LP_TOKEN_MAP.with(|m| m.borrow().iter().map(|(_, v)| v).filter(|v| !nat_is_zero(&v.amount)).collect());
+    let mut num_blocks = 0;
+    for lp_token in lp_tokens {
+        match to_block(
+            LPBlockType::Mint,
+            lp_token.token_id,
+            None,
+            Some(lp_token.user_id),
+            &lp_token.amount,
+            &LPBlockTxArgs::default(),
+            ts,
+        ) {
+            Ok(block) => {
+                append(&block);
+                num_blocks += 1;
+            }
+            Err(e) => error_log(&format!(
+                "Failed to backfill LP block for lp_token_id #{}. {}",
+                lp_token.lp_token_id, e
+            )),
+        }
+    }
+    num_blocks
+}
+
+/// append a block to the log and certify it as the new tip
+///
+/// # Returns
+/// block_id of the block
+/// Err - if the LP token or a user's account is not found. nothing is recorded
+fn insert(
+    block_type: LPBlockType,
+    lp_token_id: u32,
+    from_user_id: Option<u32>,
+    to_user_id: Option<u32>,
+    amount: &Nat,
+    tx_args: &LPBlockTxArgs,
+    ts: u64,
+) -> Result<u64, String> {
+    let block = to_block(block_type, lp_token_id, from_user_id, to_user_id, amount, tx_args, ts)?;
+
+    append(&block);
+    set_certified_data(&block);
+    Ok(block.block_id)
+}
+
+fn append(block: &StableLPBlock) {
+    LP_BLOCK_MAP.with(|m| m.borrow_mut().insert(StableLPBlockId(block.block_id), block.clone()));
+}
+
+fn to_block(
+    block_type: LPBlockType,
+    lp_token_id: u32,
+    from_user_id: Option<u32>,
+    to_user_id: Option<u32>,
+    amount: &Nat,
+    tx_args: &LPBlockTxArgs,
+    ts: u64,
+) -> Result<StableLPBlock, String> {
+    let lp_token = token_map::get_by_token_id(lp_token_id).ok_or("LP token not found")?;
+    let from = from_user_id.map(user_account).transpose()?;
+    let to = to_user_id.map(user_account).transpose()?;
+    let tip = get_tip();
+
+    Ok(StableLPBlock {
+        block_id: tip.as_ref().map_or(0, |tip| tip.block_id + 1),
+        phash: tip.map(|tip| ByteBuf::from(tip.hash().to_vec())),
+        block_type,
+        lp_token: lp_token.address_with_chain(),
+        from,
+        to,
+        amount: amount.clone(),
+        tx_args: tx_args.clone(),
+        ts,
+    })
+}
+
+/// ICRC-1 account of a user. principal_id is either the principal or the textual encoding of the account
+fn user_account(user_id: u32) -> Result<Account, String> {
+    let user = user_map::get_by_user_id(user_id).ok_or(format!("User #{} not found", user_id))?;
+    Account::from_str(&user.principal_id).map_err(|e| format!("Invalid account {}. {}", user.principal_id, e))
+}
+
+/// ICRC-3 hash tree of the tip of the log with the hash and the LEB128 encoded index of the last block
+pub fn tip_hash_tree(tip: &StableLPBlock) -> HashTree {
+    let mut last_block_index = Vec::new();
+    // writing to a Vec never fails
+    _ = leb128::write::unsigned(&mut last_block_index, tip.block_id);
+    fork(
+        label("last_block_hash", leaf(tip.hash().to_vec())),
+        label("last_block_index", leaf(last_block_index)),
+    )
+}
+
+/// certify the tip of the log
+pub fn set_certified_data(tip: &StableLPBlock) {
+    ic_cdk::api::set_certified_data(&tip_hash_tree(tip).digest());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+    use std::collections::BTreeMap;
+
+    use crate::helpers::nat_helpers::{nat_add, nat_subtract, nat_zero};
+    use crate::helpers::test_fixtures::{insert_lp_balance, insert_lp_token, insert_user};
+
+    fn insert_block(block_id: u64, memo: u8, ts: u64) -> StableLPBlock {
+        let block = StableLPBlock {
+            block_id,
+            phash: None,
+            block_type: LPBlockType::Transfer,
+            lp_token: "LP.ckBTC_ckUSDT".to_string(),
+            from: Some(Account {
+                owner: Principal::from_slice(&[1]),
+                subaccount: None,
+            }),
+            to: Some(Account {
+                owner: Principal::from_slice(&[2]),
+                subaccount: None,
+            }),
+            amount: Nat::from(100_u32),
+            tx_args: LPBlockTxArgs {
+                memo: Some(ByteBuf::from(vec![memo])),
+                created_at_time: Some(ts),
+                ..Default::default()
+            },
+            ts,
+        };
+        LP_BLOCK_MAP.with(|m| m.borrow_mut().insert(StableLPBlockId(block_id), block.clone()));
+        block
+    }
+
+    #[test]
+    fn test_get_duplicate() {
+        let block_0 = insert_block(0, 0, 10);
+        let block_1 = insert_block(1, 1, 20);
+        insert_block(2, 2, 30);
+
+        assert_eq!(get_duplicate(&block_1, 0), Some(1));
+        assert_eq!(get_duplicate(&block_0, 0), Some(0));
+        // blocks before since_ts are not searched
+        assert_eq!(get_duplicate(&block_0, 15), None);
+        // different memo is a different call
+        let block = StableLPBlock {
+            tx_args: LPBlockTxArgs {
+                memo: Some(ByteBuf::from(vec![3])),
+                created_at_time: Some(10),
+                ..Default::default()
+            },
+            ..block_0
+        };
+        assert_eq!(get_duplicate(&block, 0), None);
+    }
+
+    #[test]
+    fn test_insert_failed() {
+        let account = Account {
+            owner: Principal::from_slice(&[1]),
+            subaccount: None,
+        };
+        insert_user(10, &account);
+        insert_lp_token(5, "ckBTC_ckUSDT");
+        let amount = Nat::from(100_u32);
+
+        // LP token not found
+        assert!(insert_mint(6, 10, &amount, 1).is_err());
+        // user not found
+        assert!(insert_burn(5, 11, &amount, 1).is_err());
+        assert!(insert_transfer(5, 10, 11, &amount, &LPBlockTxArgs::default(), 1).is_err());
+        // nothing is recorded and the caller can handle the error
+        assert_eq!(get_log_length(), 0);
+    }
+
+    fn append_block(block_type: LPBlockType, lp_token_id: u32, from_user_id: Option<u32>, to_user_id: Option<u32>, amount: u64) {
+        let block = to_block(
+            block_type,
+            lp_token_id,
+            from_user_id,
+            to_user_id,
+            &Nat::from(amount),
+            &LPBlockTxArgs::default(),
+            2,
+        )
+        .unwrap();
+        append(&block);
+    }
+
+    // LP balances by LP token and account from replaying the log
+    fn replay() -> BTreeMap<(String, Account), Nat> {
+        let mut balances: BTreeMap<(String, Account), Nat> = BTreeMap::new();
+        let mut phash = None;
+        for block_id in 0..get_log_length() {
+            let block = get(block_id).unwrap();
+            assert_eq!(block.phash, phash);
+            phash = Some(ByteBuf::from(block.hash().to_vec()));
+            if let (Some(from), LPBlockType::Burn | LPBlockType::Transfer | LPBlockType::TransferFrom) = (block.from, &block.block_type) {
+                let balance = balances.entry((block.lp_token.clone(), from)).or_insert(nat_zero());
+                *balance = nat_subtract(balance, &block.amount).expect("balance below zero");
+            }
+            if let Some(to) = block.to {
+                let balance = balances.entry((block.lp_token.clone(), to)).or_insert(nat_zero());
+                *balance = nat_add(balance, &block.amount);
+            }
+        }
+        balances.retain(|_, balance| !nat_is_zero(balance));
+        balances
+    }
+
+    // LP balances by LP token and account in LP_TOKEN_MAP
+    fn lp_token_balances() -> BTreeMap<(String, Account), Nat> {
+        LP_TOKEN_MAP.with(|m| {
+            m.borrow()
+                .iter()
+                .filter(|(_, v)| !nat_is_zero(&v.amount))
+                .map(|(_, v)| {
+                    let lp_token = token_map::get_by_token_id(v.token_id).unwrap().address_with_chain();
+                    ((lp_token, user_account(v.user_id).unwrap()), v.amount)
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_replay_log() {
+        let account_0 = Account {
+            owner: Principal::from_slice(&[1]),
+            subaccount: None,
+        };
+        let account_1 = Account {
+            owner: Principal::from_slice(&[2]),
+            subaccount: None,
+        };
+        let account_2 = Account {
+            owner: Principal::from_slice(&[1]),
+            subaccount: Some([1; 32]),
+        };
+        insert_user(10, &account_0);
+        insert_user(11, &account_1);
+        insert_user(12, &account_2);
+        insert_lp_token(5, "ckBTC_ckUSDT");
+        insert_lp_token(6, "ICP_ckUSDT");
+
+        // balances from before the log
+        insert_lp_balance(1, 10, 5, 1_000);
+        insert_lp_balance(2, 11, 5, 500);
+        insert_lp_balance(3, 10, 6, 300);
+        insert_lp_balance(4, 12, 6, 0);
+        assert_eq!(backfill_mints(1), 3);
+        assert_eq!(replay(), lp_token_balances());
+        // only once
+        assert_eq!(backfill_mints(1), 0);
+        assert_eq!(get_log_length(), 3);
+
+        // transfer 200 from account_0 to account_2, burn account_1 and mint to account_2
+        insert_lp_balance(1, 10, 5, 800);
+        insert_lp_balance(5, 12, 5, 200);
+        append_block(LPBlockType::Transfer, 5, Some(10), Some(12), 200);
+        insert_lp_balance(2, 11, 5, 0);
+        append_block(LPBlockType::Burn, 5, Some(11), None, 500);
+        insert_lp_balance(4, 12, 6, 50);
+        append_block(LPBlockType::Mint, 6, None, Some(12), 50);
+        // approvals do not change balances
+        append_block(LPBlockType::Approve, 6, Some(12), None, 1_000);
+        assert_eq!(replay(), lp_token_balances());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod lp_block_archive;
+pub mod lp_block_map;
+#[allow(clippy::module_inception)]
+pub mod stable_lp_block;
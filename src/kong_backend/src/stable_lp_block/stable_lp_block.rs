@@ -0,0 +1,255 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use icrc_ledger_types::icrc::generic_value::{Hash, ICRC3Value};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use std::collections::BTreeMap;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLPBlockId(pub u64);
+
+impl Storable for StableLPBlockId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LPBlockType {
+    Mint,
+    Burn,
+    Transfer,
+    TransferFrom,
+    Approve,
+}
+
+impl LPBlockType {
+    /// ICRC-3 btype of the block
+    pub fn btype(&self) -> &'static str {
+        match self {
+            LPBlockType::Mint => "1mint",
+            LPBlockType::Burn => "1burn",
+            LPBlockType::Transfer => "1xfer",
+            LPBlockType::TransferFrom => "2xfer",
+            LPBlockType::Approve => "2approve",
+        }
+    }
+}
+
+/// ICRC-1/ICRC-2 arguments of the call recorded in the block. default for the balance changes of Kong's own endpoints
+#[derive(CandidType, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LPBlockTxArgs {
+    pub spender: Option<Account>,        // spender of icrc2_transfer_from and icrc2_approve
+    pub memo: Option<ByteBuf>,           // memo of the call
+    pub created_at_time: Option<u64>,    // created_at_time of the call
+    pub expected_allowance: Option<Nat>, // expected_allowance of icrc2_approve
+    pub expires_at: Option<u64>,         // expires_at of icrc2_approve
+}
+
+/// ICRC-3 block of an LP token balance change or approval
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableLPBlock {
+    pub block_id: u64,           // index of the block in the log, starting at 0
+    pub phash: Option<ByteBuf>,  // hash of the previous block. None for the first block
+    pub block_type: LPBlockType, // mint, burn, transfer or approve
+    pub lp_token: String,        // LP token address with chain. ie. "LP.ckBTC_ckUSDT"
+    pub from: Option<Account>,   // account the LP tokens are burned, transferred or approved from
+    pub to: Option<Account>,     // account the LP tokens are minted or transferred to
+    pub amount: Nat,             // amount of LP tokens, or the allowance of an approve
+    #[serde(default)]
+    pub tx_args: LPBlockTxArgs, // arguments of the ICRC-1/ICRC-2 call, if any
+    pub ts: u64,                 // timestamp of the block
+}
+
+impl StableLPBlock {
+    /// true if the block records the same call as block, ignoring the block's position in the log and timestamp
+    /// used to deduplicate ICRC-1/ICRC-2 calls with created_at_time
+    pub fn is_same_tx(&self, block: &StableLPBlock) -> bool {
+        self.block_type == block.block_type
+            && self.lp_token == block.lp_token
+            && self.from == block.from
+            && self.to == block.to
+            && self.amount == block.amount
+            && self.tx_args == block.tx_args
+    }
+
+    /// ICRC-3 generic value of the block
+    pub fn to_value(&self) -> ICRC3Value {
+        let mut tx = BTreeMap::new();
+        tx.insert("amt".to_string(), ICRC3Value::Nat(self.amount.clone()));
+        if let Some(from) = &self.from {
+            tx.insert("from".to_string(), account_to_value(from));
+        }
+        if let Some(to) = &self.to {
+            tx.insert("to".to_string(), account_to_value(to));
+        }
+        if let Some(spender) = &self.tx_args.spender {
+            tx.insert("spender".to_string(), account_to_value(spender));
+        }
+        if let Some(memo) = &self.tx_args.memo {
+            tx.insert("memo".to_string(), ICRC3Value::Blob(memo.clone()));
+        }
+        if let Some(created_at_time) = self.tx_args.created_at_time {
+            tx.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(created_at_time)));
+        }
+        if let Some(expected_allowance) = &self.tx_args.expected_allowance {
+            tx.insert("expected_allowance".to_string(), ICRC3Value::Nat(expected_allowance.clone()));
+        }
+        if let Some(expires_at) = self.tx_args.expires_at {
+            tx.insert("expires_at".to_string(), ICRC3Value::Nat(Nat::from(expires_at)));
+        }
+        tx.insert("lp_token".to_string(), ICRC3Value::Text(self.lp_token.clone()));
+
+        let mut block = BTreeMap::new();
+        if let Some(phash) = &self.phash {
+            block.insert("phash".to_string(), ICRC3Value::Blob(phash.clone()));
+        }
+        block.insert("btype".to_string(), ICRC3Value::Text(self.block_type.btype().to_string()));
+        block.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(self.ts)));
+        block.insert("tx".to_string(), ICRC3Value::Map(tx));
+        ICRC3Value::Map(block)
+    }
+
+    /// representation-independent hash of the block
+    pub fn hash(&self) -> Hash {
+        self.to_value().hash()
+    }
+}
+
+/// ICRC-3 account is an array of the owner and the subaccount, if any
+fn account_to_value(account: &Account) -> ICRC3Value {
+    let mut value = vec![ICRC3Value::Blob(ByteBuf::from(account.owner.as_slice().to_vec()))];
+    if let Some(subaccount) = account.subaccount {
+        value.push(ICRC3Value::Blob(ByteBuf::from(subaccount.to_vec())));
+    }
+    ICRC3Value::Array(value)
+}
+
+impl Storable for StableLPBlock {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    fn block(block_id: u64, phash: Option<Hash>) -> StableLPBlock {
+        StableLPBlock {
+            block_id,
+            phash: phash.map(|phash| ByteBuf::from(phash.to_vec())),
+            block_type: LPBlockType::Transfer,
+            lp_token: "LP.ckBTC_ckUSDT".to_string(),
+            from: Some(Account {
+                owner: Principal::anonymous(),
+                subaccount: None,
+            }),
+            to: Some(Account {
+                owner: Principal::anonymous(),
+                subaccount: Some([1; 32]),
+            }),
+            amount: Nat::from(100_u32),
+            tx_args: LPBlockTxArgs::default(),
+            ts: 1,
+        }
+    }
+
+    #[test]
+    fn test_to_value() {
+        let ICRC3Value::Map(value) = block(0, None).to_value() else {
+            panic!("block is not a map");
+        };
+        // first block has no phash
+        assert!(!value.contains_key("phash"));
+        assert_eq!(value["btype"], ICRC3Value::Text("1xfer".to_string()));
+        let ICRC3Value::Map(tx) = &value["tx"] else {
+            panic!("tx is not a map");
+        };
+        assert_eq!(tx["amt"], ICRC3Value::Nat(Nat::from(100_u32)));
+        // default subaccount is omitted
+        let ICRC3Value::Array(from) = &tx["from"] else {
+            panic!("from is not an array");
+        };
+        assert_eq!(from.len(), 1);
+        let ICRC3Value::Array(to) = &tx["to"] else {
+            panic!("to is not an array");
+        };
+        assert_eq!(to.len(), 2);
+    }
+
+    #[test]
+    fn test_to_value_tx_args() {
+        let block = StableLPBlock {
+            block_type: LPBlockType::TransferFrom,
+            tx_args: LPBlockTxArgs {
+                spender: Some(Account {
+                    owner: Principal::anonymous(),
+                    subaccount: Some([2; 32]),
+                }),
+                memo: Some(ByteBuf::from(vec![1, 2, 3])),
+                created_at_time: Some(5),
+                ..Default::default()
+            },
+            ..block(0, None)
+        };
+        let ICRC3Value::Map(value) = block.to_value() else {
+            panic!("block is not a map");
+        };
+        assert_eq!(value["btype"], ICRC3Value::Text("2xfer".to_string()));
+        let ICRC3Value::Map(tx) = &value["tx"] else {
+            panic!("tx is not a map");
+        };
+        assert!(tx.contains_key("spender"));
+        assert_eq!(tx["memo"], ICRC3Value::Blob(ByteBuf::from(vec![1, 2, 3])));
+        // created_at_time is the ts of the tx, the ts of the block is when it was recorded
+        assert_eq!(tx["ts"], ICRC3Value::Nat(Nat::from(5_u32)));
+        assert_eq!(value["ts"], ICRC3Value::Nat(Nat::from(1_u32)));
+        assert!(!tx.contains_key("expires_at"));
+    }
+
+    #[test]
+    fn test_is_same_tx() {
+        let block_0 = block(0, None);
+        // position in the log and timestamp are ignored
+        let block_1 = StableLPBlock {
+            ts: 2,
+            ..block(1, Some(block_0.hash()))
+        };
+        assert!(block_1.is_same_tx(&block_0));
+        let block_2 = StableLPBlock {
+            tx_args: LPBlockTxArgs {
+                memo: Some(ByteBuf::from(vec![1])),
+                ..Default::default()
+            },
+            ..block(2, None)
+        };
+        assert!(!block_2.is_same_tx(&block_0));
+    }
+
+    #[test]
+    fn test_hash_chain() {
+        let block_0 = block(0, None);
+        let block_1 = block(1, Some(block_0.hash()));
+        let ICRC3Value::Map(value) = block_1.to_value() else {
+            panic!("block is not a map");
+        };
+        assert_eq!(value["phash"], ICRC3Value::Blob(ByteBuf::from(block_0.hash().to_vec())));
+        // phash is part of the hash
+        assert_ne!(block_0.hash(), block_1.hash());
+    }
+}
@@ -0,0 +1,151 @@
+use candid::Nat;
+
+use super::stable_lp_history::{StableLPHistory, StableLPHistoryId};
+
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_multiply, nat_zero};
+use crate::ic::ckusdt::ckusdt_amount;
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_memory::LP_HISTORY_MAP;
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_tx::stable_tx::StableTx;
+use crate::stable_tx::status_tx::StatusTx;
+use crate::stable_tx::tx_map;
+
+/// get the histories of user_id in all pools
+pub fn get_by_user_id(user_id: u32) -> Vec<StableLPHistory> {
+    LP_HISTORY_MAP.with(|m| {
+        m.borrow()
+            .range(
+                StableLPHistoryId { user_id, pool_id: 0 }..=StableLPHistoryId {
+                    user_id,
+                    pool_id: u32::MAX,
+                },
+            )
+            .map(|(_, v)| v)
+            .collect()
+    })
+}
+
+pub fn get(user_id: u32, pool_id: u32) -> Option<StableLPHistory> {
+    LP_HISTORY_MAP.with(|m| m.borrow().get(&StableLPHistoryId { user_id, pool_id }))
+}
+
+fn upsert(history: &StableLPHistory) {
+    LP_HISTORY_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StableLPHistoryId {
+                user_id: history.user_id,
+                pool_id: history.pool_id,
+            },
+            history.clone(),
+        )
+    });
+}
+
+/// record a successful add pool, add liquidity, remove liquidity or LP token send tx in the histories of its users
+/// - called by tx_map::insert() after the pool is updated, so ckUSDT values are at the prices of the tx
+pub fn insert_tx(tx: &StableTx) {
+    update_by_tx(tx, true);
+}
+
+/// record the liquidity txs from before LP_HISTORY_MAP at current prices. only runs if LP_HISTORY_MAP is empty
+/// - the pool's LP fees at the time of the add liquidity txs are not known, so all LP fees paid out count as earned
+///
+/// # Returns
+/// number of histories recorded
+pub fn backfill() -> u64 {
+    if LP_HISTORY_MAP.with(|m| !m.borrow().is_empty()) {
+        return 0;
+    }
+    tx_map::for_each_liquidity_tx(|tx| update_by_tx(tx, false));
+    LP_HISTORY_MAP.with(|m| m.borrow().len())
+}
+
+fn update_by_tx(tx: &StableTx, with_fee_debt: bool) {
+    match tx {
+        StableTx::AddPool(tx) if tx.status == StatusTx::Success => deposit(
+            tx.user_id,
+            tx.pool_id,
+            &tx.amount_0,
+            &tx.amount_1,
+            &tx.add_lp_token_amount,
+            with_fee_debt,
+            tx.ts,
+        ),
+        StableTx::AddLiquidity(tx) if tx.status == StatusTx::Success => deposit(
+            tx.user_id,
+            tx.pool_id,
+            &tx.amount_0,
+            &tx.amount_1,
+            &tx.add_lp_token_amount,
+            with_fee_debt,
+            tx.ts,
+        ),
+        StableTx::RemoveLiquidity(tx) if tx.status == StatusTx::Success => {
+            let Some(pool) = pool_map::get_by_pool_id(tx.pool_id) else {
+                return;
+            };
+            let mut history = get(tx.user_id, tx.pool_id).unwrap_or_else(|| StableLPHistory::new(tx.user_id, tx.pool_id, tx.ts));
+            history.withdraw(
+                &tx.amount_0,
+                &tx.lp_fee_0,
+                &tx.amount_1,
+                &tx.lp_fee_1,
+                &usd_amount(&pool, &nat_add(&tx.amount_0, &tx.lp_fee_0), &nat_add(&tx.amount_1, &tx.lp_fee_1)),
+                &tx.remove_lp_token_amount,
+                tx.ts,
+            );
+            upsert(&history);
+        }
+        StableTx::Send(tx) if tx.status == StatusTx::Success => {
+            let Some(pool) = pool_map::get_by_lp_token_id(tx.token_id) else {
+                return;
+            };
+            // LP tokens without a cost basis have nothing to move
+            let Some(mut from) = get(tx.user_id, pool.pool_id) else {
+                return;
+            };
+            let mut to = get(tx.to_user_id, pool.pool_id).unwrap_or_else(|| StableLPHistory::new(tx.to_user_id, pool.pool_id, tx.ts));
+            from.send(&mut to, &tx.amount, tx.ts);
+            upsert(&from);
+            upsert(&to);
+        }
+        _ => (),
+    }
+}
+
+fn deposit(user_id: u32, pool_id: u32, amount_0: &Nat, amount_1: &Nat, lp_amount: &Nat, with_fee_debt: bool, ts: u64) {
+    let Some(pool) = pool_map::get_by_pool_id(pool_id) else {
+        return;
+    };
+    let (fee_debt_0, fee_debt_1) = fee_debt(&pool, lp_amount, with_fee_debt);
+    let mut history = get(user_id, pool_id).unwrap_or_else(|| StableLPHistory::new(user_id, pool_id, ts));
+    history.deposit(
+        amount_0,
+        amount_1,
+        &usd_amount(&pool, amount_0, amount_1),
+        lp_amount,
+        &fee_debt_0,
+        &fee_debt_1,
+        ts,
+    );
+    upsert(&history);
+}
+
+/// the pool's LP fees for lp_amount LP tokens just minted. add liquidity pays for them as part of the reserves
+fn fee_debt(pool: &StablePool, lp_amount: &Nat, with_fee_debt: bool) -> (Nat, Nat) {
+    if !with_fee_debt {
+        return (nat_zero(), nat_zero());
+    }
+    let lp_total_supply = lp_token_map::get_total_supply(pool.lp_token_id);
+    let share = |lp_fee: &Nat| nat_divide(&nat_multiply(lp_fee, lp_amount), &lp_total_supply).unwrap_or(nat_zero());
+    (share(&pool.lp_fee_0), share(&pool.lp_fee_1))
+}
+
+/// ckUSDT value of amount_0 and amount_1 at the pool's current prices. tokens without a ckUSDT price are not included
+fn usd_amount(pool: &StablePool, amount_0: &Nat, amount_1: &Nat) -> Nat {
+    let usd_amount_0 = ckusdt_amount(&pool.token_0(), amount_0).unwrap_or(nat_zero());
+    let usd_amount_1 = ckusdt_amount(&pool.token_1(), amount_1).unwrap_or(nat_zero());
+    nat_add(&usd_amount_0, &usd_amount_1)
+}
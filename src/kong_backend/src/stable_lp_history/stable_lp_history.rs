@@ -0,0 +1,257 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
+
+/// LP_HISTORY_MAP is ordered by user_id first, so the histories of a user are a range of the map
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLPHistoryId {
+    pub user_id: u32,
+    pub pool_id: u32,
+}
+
+impl Storable for StableLPHistoryId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// tokens a user added to and removed from a pool, with their ckUSDT values at the time
+/// - the cost basis of LP tokens sent to another user moves with them
+/// - fee_debt is the pool's LP fees already in the reserves when the LP tokens were minted. add liquidity pays for them,
+///   so they are not fees earned by the user
+#[derive(CandidType, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StableLPHistory {
+    pub user_id: u32,
+    pub pool_id: u32,
+    pub lp_amount: Nat,     // LP tokens of the user with a cost basis
+    pub cost_0: Nat,        // cost basis of lp_amount in token_0
+    pub cost_1: Nat,        // cost basis of lp_amount in token_1
+    pub usd_cost: Nat,      // ckUSDT value of cost_0 and cost_1 when deposited
+    pub fee_debt_0: Nat,    // LP fees of token_0 in the pool for lp_amount when minted
+    pub fee_debt_1: Nat,    // LP fees of token_1 in the pool for lp_amount when minted
+    pub deposited_0: Nat,   // token_0 added, plus the cost basis of LP tokens received less sent
+    pub deposited_1: Nat,   // token_1 added, plus the cost basis of LP tokens received less sent
+    pub usd_deposited: Nat, // ckUSDT value of deposited_0 and deposited_1 when deposited
+    pub withdrawn_0: Nat,   // token_0 removed, including LP fees
+    pub withdrawn_1: Nat,   // token_1 removed, including LP fees
+    pub usd_withdrawn: Nat, // ckUSDT value of withdrawn_0 and withdrawn_1 when removed
+    pub lp_fee_0: Nat,      // LP fees of token_0 earned and paid out by remove liquidity
+    pub lp_fee_1: Nat,      // LP fees of token_1 earned and paid out by remove liquidity
+    pub first_ts: u64,
+    pub last_ts: u64,
+}
+
+/// cost basis of part of the LP tokens of a history
+struct CostBasis {
+    lp_amount: Nat,
+    cost_0: Nat,
+    cost_1: Nat,
+    usd_cost: Nat,
+    fee_debt_0: Nat,
+    fee_debt_1: Nat,
+}
+
+impl StableLPHistory {
+    pub fn new(user_id: u32, pool_id: u32, ts: u64) -> Self {
+        Self {
+            user_id,
+            pool_id,
+            lp_amount: nat_zero(),
+            cost_0: nat_zero(),
+            cost_1: nat_zero(),
+            usd_cost: nat_zero(),
+            fee_debt_0: nat_zero(),
+            fee_debt_1: nat_zero(),
+            deposited_0: nat_zero(),
+            deposited_1: nat_zero(),
+            usd_deposited: nat_zero(),
+            withdrawn_0: nat_zero(),
+            withdrawn_1: nat_zero(),
+            usd_withdrawn: nat_zero(),
+            lp_fee_0: nat_zero(),
+            lp_fee_1: nat_zero(),
+            first_ts: ts,
+            last_ts: ts,
+        }
+    }
+
+    /// add liquidity of amount_0 and amount_1 worth usd_amount for lp_amount LP tokens
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit(
+        &mut self,
+        amount_0: &Nat,
+        amount_1: &Nat,
+        usd_amount: &Nat,
+        lp_amount: &Nat,
+        fee_debt_0: &Nat,
+        fee_debt_1: &Nat,
+        ts: u64,
+    ) {
+        self.add_cost_basis(&CostBasis {
+            lp_amount: lp_amount.clone(),
+            cost_0: amount_0.clone(),
+            cost_1: amount_1.clone(),
+            usd_cost: usd_amount.clone(),
+            fee_debt_0: fee_debt_0.clone(),
+            fee_debt_1: fee_debt_1.clone(),
+        });
+        self.last_ts = ts;
+    }
+
+    /// remove liquidity of lp_amount LP tokens for amount_0 + lp_fee_0 and amount_1 + lp_fee_1 worth usd_amount
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw(&mut self, amount_0: &Nat, lp_fee_0: &Nat, amount_1: &Nat, lp_fee_1: &Nat, usd_amount: &Nat, lp_amount: &Nat, ts: u64) {
+        let cost_basis = self.remove_cost_basis(lp_amount);
+        self.withdrawn_0 = nat_add(&self.withdrawn_0, &nat_add(amount_0, lp_fee_0));
+        self.withdrawn_1 = nat_add(&self.withdrawn_1, &nat_add(amount_1, lp_fee_1));
+        self.usd_withdrawn = nat_add(&self.usd_withdrawn, usd_amount);
+        // LP fees paid for when adding liquidity are not earned
+        let earned_fee_0 = nat_subtract(lp_fee_0, &cost_basis.fee_debt_0).unwrap_or(nat_zero());
+        let earned_fee_1 = nat_subtract(lp_fee_1, &cost_basis.fee_debt_1).unwrap_or(nat_zero());
+        self.lp_fee_0 = nat_add(&self.lp_fee_0, &earned_fee_0);
+        self.lp_fee_1 = nat_add(&self.lp_fee_1, &earned_fee_1);
+        self.last_ts = ts;
+    }
+
+    /// move the cost basis of lp_amount LP tokens sent to the history of the receiver
+    pub fn send(&mut self, to: &mut StableLPHistory, lp_amount: &Nat, ts: u64) {
+        let cost_basis = self.remove_cost_basis(lp_amount);
+        self.deposited_0 = nat_subtract(&self.deposited_0, &cost_basis.cost_0).unwrap_or(nat_zero());
+        self.deposited_1 = nat_subtract(&self.deposited_1, &cost_basis.cost_1).unwrap_or(nat_zero());
+        self.usd_deposited = nat_subtract(&self.usd_deposited, &cost_basis.usd_cost).unwrap_or(nat_zero());
+        self.last_ts = ts;
+        to.add_cost_basis(&cost_basis);
+        to.last_ts = ts;
+    }
+
+    fn add_cost_basis(&mut self, cost_basis: &CostBasis) {
+        self.lp_amount = nat_add(&self.lp_amount, &cost_basis.lp_amount);
+        self.cost_0 = nat_add(&self.cost_0, &cost_basis.cost_0);
+        self.cost_1 = nat_add(&self.cost_1, &cost_basis.cost_1);
+        self.usd_cost = nat_add(&self.usd_cost, &cost_basis.usd_cost);
+        self.fee_debt_0 = nat_add(&self.fee_debt_0, &cost_basis.fee_debt_0);
+        self.fee_debt_1 = nat_add(&self.fee_debt_1, &cost_basis.fee_debt_1);
+        self.deposited_0 = nat_add(&self.deposited_0, &cost_basis.cost_0);
+        self.deposited_1 = nat_add(&self.deposited_1, &cost_basis.cost_1);
+        self.usd_deposited = nat_add(&self.usd_deposited, &cost_basis.usd_cost);
+    }
+
+    /// remove the pro rata cost basis of lp_amount LP tokens. LP tokens without a cost basis have none
+    fn remove_cost_basis(&mut self, lp_amount: &Nat) -> CostBasis {
+        let lp_amount = lp_amount.clone().min(self.lp_amount.clone());
+        let share = |amount: &Nat| {
+            if nat_is_zero(&self.lp_amount) {
+                return nat_zero();
+            }
+            nat_divide(&nat_multiply(amount, &lp_amount), &self.lp_amount).unwrap_or(nat_zero())
+        };
+        let cost_basis = CostBasis {
+            cost_0: share(&self.cost_0),
+            cost_1: share(&self.cost_1),
+            usd_cost: share(&self.usd_cost),
+            fee_debt_0: share(&self.fee_debt_0),
+            fee_debt_1: share(&self.fee_debt_1),
+            lp_amount,
+        };
+        let subtract = |amount: &Nat, share: &Nat| nat_subtract(amount, share).unwrap_or(nat_zero());
+        self.lp_amount = subtract(&self.lp_amount, &cost_basis.lp_amount);
+        self.cost_0 = subtract(&self.cost_0, &cost_basis.cost_0);
+        self.cost_1 = subtract(&self.cost_1, &cost_basis.cost_1);
+        self.usd_cost = subtract(&self.usd_cost, &cost_basis.usd_cost);
+        self.fee_debt_0 = subtract(&self.fee_debt_0, &cost_basis.fee_debt_0);
+        self.fee_debt_1 = subtract(&self.fee_debt_1, &cost_basis.fee_debt_1);
+        cost_basis
+    }
+}
+
+impl Storable for StableLPHistory {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nat(n: u32) -> Nat {
+        Nat::from(n)
+    }
+
+    #[test]
+    fn test_deposit_and_withdraw() {
+        let mut history = StableLPHistory::new(1, 1, 1);
+        history.deposit(&nat(100), &nat(200), &nat(400), &nat(10), &nat(0), &nat(0), 1);
+        // second deposit when the pool already had LP fees of 5 and 10 for the 10 LP tokens minted
+        history.deposit(&nat(105), &nat(210), &nat(600), &nat(10), &nat(5), &nat(10), 2);
+        assert_eq!(history.deposited_0, nat(205));
+        assert_eq!(history.usd_deposited, nat(1_000));
+        assert_eq!(history.fee_debt_0, nat(5));
+
+        // remove half. LP fees paid out less the half of the fee debt are earned
+        history.withdraw(&nat(100), &nat(8), &nat(200), &nat(15), &nat(700), &nat(10), 3);
+        assert_eq!(history.lp_amount, nat(10));
+        assert_eq!(history.withdrawn_0, nat(108));
+        assert_eq!(history.withdrawn_1, nat(215));
+        assert_eq!(history.usd_withdrawn, nat(700));
+        assert_eq!(history.lp_fee_0, nat(6));
+        assert_eq!(history.lp_fee_1, nat(10));
+        assert_eq!((history.cost_0.clone(), history.usd_cost.clone()), (nat(103), nat(500)));
+        assert_eq!((history.fee_debt_0.clone(), history.fee_debt_1.clone()), (nat(3), nat(5)));
+        // deposits are not reduced by removing liquidity
+        assert_eq!(history.deposited_0, nat(205));
+        assert_eq!((history.first_ts, history.last_ts), (1, 3));
+    }
+
+    #[test]
+    fn test_withdraw_without_cost_basis() {
+        // LP tokens from before the history was recorded have no cost basis or fee debt
+        let mut history = StableLPHistory::new(1, 1, 1);
+        history.withdraw(&nat(100), &nat(8), &nat(200), &nat(15), &nat(700), &nat(10), 2);
+        assert_eq!(history.lp_amount, nat_zero());
+        assert_eq!(history.lp_fee_0, nat(8));
+        assert_eq!(history.withdrawn_0, nat(108));
+    }
+
+    #[test]
+    fn test_send() {
+        let mut from = StableLPHistory::new(1, 1, 1);
+        let mut to = StableLPHistory::new(2, 1, 2);
+        from.deposit(&nat(100), &nat(200), &nat(400), &nat(10), &nat(4), &nat(8), 1);
+
+        // a fifth of the LP tokens and their cost basis move to the receiver
+        from.send(&mut to, &nat(2), 2);
+        assert_eq!(from.lp_amount, nat(8));
+        assert_eq!(to.lp_amount, nat(2));
+        assert_eq!(
+            (to.cost_0.clone(), to.cost_1.clone(), to.usd_cost.clone()),
+            (nat(20), nat(40), nat(80))
+        );
+        assert_eq!((to.deposited_0.clone(), to.usd_deposited.clone()), (nat(20), nat(80)));
+        assert_eq!((to.fee_debt_0.clone(), to.fee_debt_1.clone()), (nat(0), nat(1)));
+        assert_eq!((from.deposited_0.clone(), from.usd_deposited.clone()), (nat(80), nat(320)));
+        assert_eq!(to.last_ts, 2);
+
+        // sending more than the LP tokens with a cost basis only moves the cost basis there is
+        from.send(&mut to, &nat(100), 3);
+        assert_eq!(from.lp_amount, nat_zero());
+        assert_eq!(from.deposited_0, nat_zero());
+        assert_eq!(to.lp_amount, nat(10));
+        assert_eq!(to.deposited_0, nat(100));
+        assert_eq!(to.usd_deposited, nat(400));
+    }
+}
@@ -0,0 +1,137 @@
+use candid::Nat;
+use std::ops::RangeInclusive;
+
+use super::stable_lp_lock::{StableLPLock, StableLPLockId};
+
+use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::LP_LOCK_MAP;
+
+fn range_by_token_id_by_user_id(token_id: u32, user_id: u32) -> RangeInclusive<StableLPLockId> {
+    StableLPLockId {
+        token_id,
+        user_id,
+        lock_id: 0,
+    }..=StableLPLockId {
+        token_id,
+        user_id,
+        lock_id: u64::MAX,
+    }
+}
+
+/// get locks of user_id, newest first
+pub fn get_by_user_id(user_id: u32) -> Vec<StableLPLock> {
+    let mut locks: Vec<StableLPLock> =
+        LP_LOCK_MAP.with(|m| m.borrow().iter().filter_map(|(k, v)| (k.user_id == user_id).then_some(v)).collect());
+    locks.sort_by_key(|lock| std::cmp::Reverse(lock.lock_id));
+    locks
+}
+
+/// get locks of the LP token token_id still locked at ts
+pub fn get_locked_by_token_id(token_id: u32, ts: u64) -> Vec<StableLPLock> {
+    LP_LOCK_MAP.with(|m| {
+        m.borrow()
+            .range(
+                StableLPLockId {
+                    token_id,
+                    user_id: 0,
+                    lock_id: 0,
+                }..=StableLPLockId {
+                    token_id,
+                    user_id: u32::MAX,
+                    lock_id: u64::MAX,
+                },
+            )
+            .filter_map(|(_, v)| (v.unlock_ts > ts).then_some(v))
+            .collect()
+    })
+}
+
+/// LP tokens of user_id locked at ts
+pub fn get_locked_amount(token_id: u32, user_id: u32, ts: u64) -> Nat {
+    LP_LOCK_MAP.with(|m| {
+        m.borrow()
+            .range(range_by_token_id_by_user_id(token_id, user_id))
+            .fold(nat_zero(), |acc, (_, v)| nat_add(&acc, &v.locked_amount_at(ts)))
+    })
+}
+
+pub fn insert(lock: &StableLPLock) -> u64 {
+    LP_LOCK_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let lock_id = kong_settings_map::inc_lp_lock_map_idx();
+        map.insert(
+            StableLPLockId {
+                token_id: lock.token_id,
+                user_id: lock.user_id,
+                lock_id,
+            },
+            StableLPLock { lock_id, ..lock.clone() },
+        );
+        lock_id
+    })
+}
+
+/// remove the locks of user_id on the LP token token_id fully unlocked at ts, so they are not scanned again
+pub fn remove_unlocked(token_id: u32, user_id: u32, ts: u64) {
+    LP_LOCK_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let unlocked: Vec<StableLPLockId> = map
+            .range(range_by_token_id_by_user_id(token_id, user_id))
+            .filter_map(|(k, v)| (v.unlock_ts <= ts).then_some(k))
+            .collect();
+        for lock_id in unlocked {
+            map.remove(&lock_id);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(lock_id: u64, token_id: u32, user_id: u32, amount: u32, unlock_ts: u64) -> StableLPLock {
+        StableLPLock {
+            lock_id,
+            user_id,
+            token_id,
+            amount: Nat::from(amount),
+            unlock_ts,
+            vesting: false,
+            ts: 0,
+        }
+    }
+
+    fn insert_lock(lock: StableLPLock) {
+        LP_LOCK_MAP.with(|m| {
+            m.borrow_mut().insert(
+                StableLPLockId {
+                    token_id: lock.token_id,
+                    user_id: lock.user_id,
+                    lock_id: lock.lock_id,
+                },
+                lock,
+            )
+        });
+    }
+
+    #[test]
+    fn test_locked_amount_and_remove_unlocked() {
+        insert_lock(lock(1, 2, 1, 100, 50));
+        insert_lock(lock(2, 2, 1, 200, 100));
+        insert_lock(lock(3, 2, 3, 400, 100)); // other user
+        insert_lock(lock(4, 5, 1, 800, 100)); // other LP token
+
+        assert_eq!(get_locked_amount(2, 1, 10), Nat::from(300_u32));
+        assert_eq!(get_locked_amount(2, 1, 50), Nat::from(200_u32));
+        assert_eq!(get_locked_by_token_id(2, 50).len(), 2);
+        assert_eq!(get_by_user_id(1).iter().map(|lock| lock.lock_id).collect::<Vec<_>>(), vec![4, 2, 1]);
+
+        // only the fully unlocked lock of user 1 on LP token 2 is removed
+        remove_unlocked(2, 1, 50);
+        assert_eq!(get_locked_amount(2, 1, 10), Nat::from(200_u32));
+        assert_eq!(get_by_user_id(1).iter().map(|lock| lock.lock_id).collect::<Vec<_>>(), vec![4, 2]);
+        assert_eq!(get_locked_amount(2, 3, 10), Nat::from(400_u32));
+        assert_eq!(get_locked_amount(5, 1, 10), Nat::from(800_u32));
+    }
+}
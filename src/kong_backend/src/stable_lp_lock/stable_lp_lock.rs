@@ -0,0 +1,105 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::{nat_divide, nat_multiply, nat_subtract, nat_zero};
+
+/// LP_LOCK_MAP is ordered by token_id and user_id first, so the locks of a user on an LP token are a range of the map
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLPLockId {
+    pub token_id: u32,
+    pub user_id: u32,
+    pub lock_id: u64,
+}
+
+impl Storable for StableLPLockId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// LP tokens of a user locked until unlock_ts
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableLPLock {
+    pub lock_id: u64,
+    pub user_id: u32,
+    pub token_id: u32, // token_id of the LP token
+    pub amount: Nat,   // LP tokens locked at ts
+    pub unlock_ts: u64,
+    pub vesting: bool, // if true, amount unlocks linearly from ts to unlock_ts. otherwise all at unlock_ts
+    pub ts: u64,       // timestamp of the lock and start of the vesting
+}
+
+impl StableLPLock {
+    /// LP tokens still locked at ts
+    pub fn locked_amount_at(&self, ts: u64) -> Nat {
+        if ts >= self.unlock_ts {
+            return nat_zero();
+        }
+        if !self.vesting || ts <= self.ts {
+            return self.amount.clone();
+        }
+        // unlocked amount is rounded down so the lock never releases early
+        let duration = Nat::from(self.unlock_ts - self.ts);
+        let elapsed = Nat::from(ts - self.ts);
+        let unlocked = nat_divide(&nat_multiply(&self.amount, &elapsed), &duration).unwrap_or(nat_zero());
+        nat_subtract(&self.amount, &unlocked).unwrap_or(nat_zero())
+    }
+}
+
+impl Storable for StableLPLock {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    fn lock(vesting: bool) -> StableLPLock {
+        StableLPLock {
+            lock_id: 1,
+            user_id: 1,
+            token_id: 2,
+            amount: Nat::from(1_000_u32),
+            unlock_ts: 110 * SEC,
+            vesting,
+            ts: 10 * SEC,
+        }
+    }
+
+    #[test]
+    fn test_cliff_lock() {
+        let lock = lock(false);
+        assert_eq!(lock.locked_amount_at(10 * SEC), Nat::from(1_000_u32));
+        assert_eq!(lock.locked_amount_at(110 * SEC - 1), Nat::from(1_000_u32));
+        assert_eq!(lock.locked_amount_at(110 * SEC), nat_zero());
+    }
+
+    #[test]
+    fn test_vesting_lock() {
+        let lock = lock(true);
+        assert_eq!(lock.locked_amount_at(0), Nat::from(1_000_u32));
+        assert_eq!(lock.locked_amount_at(10 * SEC), Nat::from(1_000_u32));
+        assert_eq!(lock.locked_amount_at(35 * SEC), Nat::from(750_u32));
+        assert_eq!(lock.locked_amount_at(60 * SEC), Nat::from(500_u32));
+        // rounds in favour of the lock
+        assert_eq!(lock.locked_amount_at(110 * SEC - 1), Nat::from(1_u32));
+        assert_eq!(lock.locked_amount_at(110 * SEC), nat_zero());
+    }
+}
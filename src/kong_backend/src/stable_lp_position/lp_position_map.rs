@@ -0,0 +1,57 @@
+use super::stable_lp_position::{StableLPPosition, StableLPPositionId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::LP_POSITION_MAP;
+
+pub fn get_by_position_id(position_id: u64) -> Option<StableLPPosition> {
+    LP_POSITION_MAP.with(|m| m.borrow().get(&StableLPPositionId(position_id)))
+}
+
+/// get positions of user_id, oldest first. if pool_id is specified, only positions of that pool are returned
+pub fn get_by_user_id(user_id: u32, pool_id: Option<u32>) -> Vec<StableLPPosition> {
+    LP_POSITION_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter_map(|(_, v)| {
+                if v.user_id != user_id || pool_id.is_some_and(|pool_id| v.pool_id != pool_id) {
+                    return None;
+                }
+                Some(v)
+            })
+            .collect()
+    })
+}
+
+pub fn insert(position: &StableLPPosition) -> u64 {
+    LP_POSITION_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let position_id = kong_settings_map::inc_lp_position_map_idx();
+        let insert_position = StableLPPosition {
+            position_id,
+            ..position.clone()
+        };
+        map.insert(StableLPPositionId(position_id), insert_position);
+        position_id
+    })
+}
+
+pub fn update(position: &StableLPPosition) {
+    LP_POSITION_MAP.with(|m| m.borrow_mut().insert(StableLPPositionId(position.position_id), position.clone()));
+}
+
+pub fn remove(position_id: u64) -> Option<StableLPPosition> {
+    LP_POSITION_MAP.with(|m| m.borrow_mut().remove(&StableLPPositionId(position_id)))
+}
+
+pub fn remove_by_pool_id(pool_id: u32) {
+    LP_POSITION_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let keys_to_remove: Vec<_> = map
+            .iter()
+            .filter_map(|(k, v)| if v.pool_id == pool_id { Some(k) } else { None })
+            .collect();
+        for key in keys_to_remove {
+            map.remove(&key);
+        }
+    });
+}
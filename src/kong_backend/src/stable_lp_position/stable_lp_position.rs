@@ -0,0 +1,71 @@
+use candid::{CandidType, Int, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::nat_zero;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLPPositionId(pub u64);
+
+impl Storable for StableLPPositionId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// liquidity of a user in a concentrated liquidity pool between tick_lower and tick_upper
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableLPPosition {
+    pub position_id: u64,              // unique id for LP_POSITION_MAP
+    pub user_id: u32,                  // user id of the position owner
+    pub pool_id: u32,                  // pool id of the concentrated liquidity pool
+    pub tick_lower: i32,               // lower tick of the price range
+    pub tick_upper: i32,               // upper tick of the price range
+    pub liquidity: Nat,                // liquidity L of the position
+    pub fee_growth_inside_0_last: Int, // fee growth of token_0 per unit of liquidity inside the range at the last update
+    pub fee_growth_inside_1_last: Int, // fee growth of token_1 per unit of liquidity inside the range at the last update
+    pub fees_owed_0: Nat,              // token_0 fees earned and not yet collected
+    pub fees_owed_1: Nat,              // token_1 fees earned and not yet collected
+    pub ts: u64,                       // timestamp of the last position update
+}
+
+impl StableLPPosition {
+    pub fn new(user_id: u32, pool_id: u32, tick_lower: i32, tick_upper: i32, ts: u64) -> Self {
+        Self {
+            position_id: 0,
+            user_id,
+            pool_id,
+            tick_lower,
+            tick_upper,
+            liquidity: nat_zero(),
+            fee_growth_inside_0_last: Int::from(0),
+            fee_growth_inside_1_last: Int::from(0),
+            fees_owed_0: nat_zero(),
+            fees_owed_1: nat_zero(),
+            ts,
+        }
+    }
+
+    /// whether the position earns fees at the pool's current tick
+    pub fn is_in_range(&self, tick: i32) -> bool {
+        self.tick_lower <= tick && tick < self.tick_upper
+    }
+}
+
+impl Storable for StableLPPosition {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
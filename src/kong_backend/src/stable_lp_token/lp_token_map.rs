@@ -2,8 +2,11 @@ use candid::Nat;
 
 use super::stable_lp_token::{StableLPToken, StableLPTokenId};
 
-use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_zero};
+use crate::ic::get_time::get_time;
+use crate::ic::logging::error_log;
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
 use crate::stable_memory::LP_TOKEN_MAP;
 use crate::stable_user::user_map;
 
@@ -73,15 +76,23 @@ pub fn archive_lp_token_to_kong_data(lp_token: StableLPToken) {
     });
 }
 
+/// remove all balances of the LP token. balances are recorded as burned in the LP block log
+/// so the LP token must still be in TOKEN_MAP
 pub fn remove(lp_token_id: u32) -> Result<(), String> {
+    let ts = get_time();
     LP_TOKEN_MAP.with(|m| {
         let mut lp_tokens = m.borrow_mut();
-        let keys_to_remove: Vec<_> = lp_tokens
-            .iter()
-            .filter_map(|(k, v)| if v.token_id == lp_token_id { Some(k) } else { None })
-            .collect();
-        for key in keys_to_remove {
+        let lp_tokens_to_remove: Vec<_> = lp_tokens.iter().filter(|(_, v)| v.token_id == lp_token_id).collect();
+        for (key, lp_token) in lp_tokens_to_remove {
             lp_tokens.remove(&key);
+            if !nat_is_zero(&lp_token.amount) {
+                if let Err(e) = lp_block_map::insert_burn(lp_token_id, lp_token.user_id, &lp_token.amount, ts) {
+                    error_log(&format!(
+                        "Failed to record LP burn block for lp_token_id #{}. {}",
+                        lp_token.lp_token_id, e
+                    ));
+                }
+            }
         }
     });
     Ok(())
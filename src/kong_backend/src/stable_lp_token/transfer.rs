@@ -1,29 +1,38 @@
 use candid::Nat;
 
-use super::lp_token_map::{get_by_token_id, get_by_token_id_by_user_id, insert, update};
+use super::lp_token_map::{get_by_token_id_by_user_id, insert, update};
 use super::stable_lp_token::StableLPToken;
 
 use crate::helpers::nat_helpers::{nat_add, nat_subtract};
 use crate::ic::get_time::get_time;
+use crate::stable_lp_block::{lp_block_map, stable_lp_block::LPBlockTxArgs};
+use crate::stable_lp_lock::lp_lock_map;
 
-/// transfer LP token from caller to another user
+/// transfer LP token from a user to another user
 ///
 /// # Arguments
 /// token_id - token_id of the LP token
+/// from_user_id - user_id of the user to transfer LP token from
 /// to_user_id - user_id of the user to transfer LP token to
 /// amount - amount of LP token to transfer
+/// tx_args - ICRC-1/ICRC-2 arguments of the call recorded in the transfer block
 ///
 /// # Returns
-/// StableLPToken - updated LP token of the caller
-/// Err - if LP token not found or not enough LP token
-pub fn transfer(token_id: u32, to_user_id: u32, amount: &Nat) -> Result<StableLPToken, String> {
+/// block_id of the ICRC-3 transfer block
+/// Err - if LP token not found or not enough unlocked LP token
+pub fn transfer(token_id: u32, from_user_id: u32, to_user_id: u32, amount: &Nat, tx_args: &LPBlockTxArgs) -> Result<u64, String> {
     let ts = get_time();
 
-    let from_user = match get_by_token_id(token_id) {
+    let from_user = match get_by_token_id_by_user_id(token_id, from_user_id) {
         Some(from_user_lp_token) => {
             if from_user_lp_token.amount < *amount {
                 return Err("Not enough LP token".to_string());
             }
+            lp_lock_map::remove_unlocked(token_id, from_user_id, ts);
+            let locked_amount = lp_lock_map::get_locked_amount(token_id, from_user_id, ts);
+            if nat_subtract(&from_user_lp_token.amount, &locked_amount).is_none_or(|unlocked_amount| unlocked_amount < *amount) {
+                return Err(format!("Not enough unlocked LP token. {} locked", locked_amount));
+            }
             let amount = nat_subtract(&from_user_lp_token.amount, amount).ok_or("Error calculating new user balance")?;
             StableLPToken {
                 amount,
@@ -33,6 +42,8 @@ pub fn transfer(token_id: u32, to_user_id: u32, amount: &Nat) -> Result<StableLP
         }
         None => return Err("Not enough LP token".to_string()),
     };
+    // record the transfer first so no balance changes if its block can not be recorded
+    let block_id = lp_block_map::insert_transfer(token_id, from_user_id, to_user_id, amount, tx_args, ts)?;
     update(&from_user);
 
     // get user's LP token balance if already exists or create new
@@ -45,6 +56,5 @@ pub fn transfer(token_id: u32, to_user_id: u32, amount: &Nat) -> Result<StableLP
     } else {
         insert(&StableLPToken::new(to_user_id, token_id, amount.clone(), ts))?;
     }
-
-    Ok(from_user)
+    Ok(block_id)
 }
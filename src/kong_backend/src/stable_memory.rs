@@ -4,11 +4,23 @@ use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell};
 use std::cell::{Cell, RefCell};
 
 use crate::stable_claim::stable_claim::{StableClaim, StableClaimId};
+use crate::stable_dca::stable_dca::{StableDca, StableDcaId};
+use crate::stable_farm::stable_farm::{StableFarm, StableFarmId};
+use crate::stable_farm_stake::stable_farm_stake::{StableFarmStake, StableFarmStakeId};
+use crate::stable_kong_fee::stable_kong_fee::{StableKongFee, StableKongFeeId};
 use crate::stable_kong_settings::stable_kong_settings::StableKongSettings;
+use crate::stable_lp_allowance::stable_lp_allowance::{StableLPAllowance, StableLPAllowanceId};
+use crate::stable_lp_block::stable_lp_block::{StableLPBlock, StableLPBlockId};
+use crate::stable_lp_history::stable_lp_history::{StableLPHistory, StableLPHistoryId};
+use crate::stable_lp_lock::stable_lp_lock::{StableLPLock, StableLPLockId};
+use crate::stable_lp_position::stable_lp_position::{StableLPPosition, StableLPPositionId};
 use crate::stable_lp_token::stable_lp_token::{StableLPToken, StableLPTokenId};
 use crate::stable_message::stable_message::{StableMessage, StableMessageId};
+use crate::stable_order::stable_order::{StableOrder, StableOrderId};
 use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
+use crate::stable_pool_observation::stable_pool_observation::StablePoolObservations;
 use crate::stable_request::stable_request::{StableRequest, StableRequestId};
+use crate::stable_tick::stable_tick::{StableTick, StableTickId};
 use crate::stable_token::stable_token::{StableToken, StableTokenId};
 use crate::stable_transfer::stable_transfer::{StableTransfer, StableTransferId};
 use crate::stable_tx::stable_tx::{StableTx, StableTxId};
@@ -28,10 +40,23 @@ pub const TRANSFER_MEMORY_ID: MemoryId = MemoryId::new(27);
 pub const CLAIM_MEMORY_ID: MemoryId = MemoryId::new(28);
 pub const LP_TOKEN_MEMORY_ID: MemoryId = MemoryId::new(29);
 pub const MESSAGE_MEMORY_ID: MemoryId = MemoryId::new(30);
+pub const ORDER_MEMORY_ID: MemoryId = MemoryId::new(31);
+pub const POOL_OBSERVATION_MEMORY_ID: MemoryId = MemoryId::new(32);
+pub const DCA_MEMORY_ID: MemoryId = MemoryId::new(33);
+pub const LP_POSITION_MEMORY_ID: MemoryId = MemoryId::new(34);
+pub const TICK_MEMORY_ID: MemoryId = MemoryId::new(35);
+pub const KONG_FEE_MEMORY_ID: MemoryId = MemoryId::new(36);
+pub const LP_ALLOWANCE_MEMORY_ID: MemoryId = MemoryId::new(37);
+pub const LP_BLOCK_MEMORY_ID: MemoryId = MemoryId::new(38);
+pub const FARM_MEMORY_ID: MemoryId = MemoryId::new(39);
+pub const FARM_STAKE_MEMORY_ID: MemoryId = MemoryId::new(40);
+pub const LP_LOCK_MEMORY_ID: MemoryId = MemoryId::new(41);
+pub const LP_HISTORY_MEMORY_ID: MemoryId = MemoryId::new(42);
 // archives
 pub const TX_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(204);
 pub const REQUEST_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(205);
 pub const TRANSFER_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(206);
+pub const LP_BLOCK_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(207);
 
 thread_local! {
     // static variable to store the timer id for the background claims timer
@@ -50,6 +75,15 @@ thread_local! {
     // static variable to store the timer id for the background transfer archive timer
     pub static TRANSFER_MAP_ARCHIVE_TIMER_ID: Cell<TimerId> = Cell::default();
 
+    // static variable to store the timer id for the background limit orders timer
+    pub static ORDERS_TIMER_ID: Cell<TimerId> = Cell::default();
+
+    // static variable to store the timer id for the background DCA timer
+    pub static DCA_TIMER_ID: Cell<TimerId> = Cell::default();
+
+    // static variable to store the timer id for the background buyback timer
+    pub static BUYBACK_TIMER_ID: Cell<TimerId> = Cell::default();
+
     // MEMORY_MANAGER is given management of the entire stable memory. Given a 'MemoryId', it can
     // return a memory that can be used by stable structures
     pub static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
@@ -110,6 +144,66 @@ thread_local! {
         RefCell::new(StableBTreeMap::init(memory_manager.get(MESSAGE_MEMORY_ID)))
     });
 
+    // stable memory for storing limit orders
+    pub static ORDER_MAP: RefCell<StableBTreeMap<StableOrderId, StableOrder, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(ORDER_MEMORY_ID)))
+    });
+
+    // stable memory for storing price observations of pools. used for TWAPs
+    pub static POOL_OBSERVATION_MAP: RefCell<StableBTreeMap<StablePoolId, StablePoolObservations, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(POOL_OBSERVATION_MEMORY_ID)))
+    });
+
+    // stable memory for storing DCA schedules
+    pub static DCA_MAP: RefCell<StableBTreeMap<StableDcaId, StableDca, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(DCA_MEMORY_ID)))
+    });
+
+    // stable memory for storing concentrated liquidity positions
+    pub static LP_POSITION_MAP: RefCell<StableBTreeMap<StableLPPositionId, StableLPPosition, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_POSITION_MEMORY_ID)))
+    });
+
+    // stable memory for storing initialized ticks of concentrated liquidity pools
+    pub static TICK_MAP: RefCell<StableBTreeMap<StableTickId, StableTick, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(TICK_MEMORY_ID)))
+    });
+
+    // stable memory for storing daily Kong fees and sweeps of pools
+    pub static KONG_FEE_MAP: RefCell<StableBTreeMap<StableKongFeeId, StableKongFee, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(KONG_FEE_MEMORY_ID)))
+    });
+
+    // stable memory for storing ICRC-2 allowances of LP tokens
+    pub static LP_ALLOWANCE_MAP: RefCell<StableBTreeMap<StableLPAllowanceId, StableLPAllowance, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_ALLOWANCE_MEMORY_ID)))
+    });
+
+    // stable memory for storing the ICRC-3 block log of LP tokens
+    pub static LP_BLOCK_MAP: RefCell<StableBTreeMap<StableLPBlockId, StableLPBlock, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_BLOCK_MEMORY_ID)))
+    });
+
+    // stable memory for storing liquidity mining farms
+    pub static FARM_MAP: RefCell<StableBTreeMap<StableFarmId, StableFarm, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(FARM_MEMORY_ID)))
+    });
+
+    // stable memory for storing LP tokens staked in farms
+    pub static FARM_STAKE_MAP: RefCell<StableBTreeMap<StableFarmStakeId, StableFarmStake, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(FARM_STAKE_MEMORY_ID)))
+    });
+
+    // stable memory for storing time-locked LP tokens
+    pub static LP_LOCK_MAP: RefCell<StableBTreeMap<StableLPLockId, StableLPLock, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_LOCK_MEMORY_ID)))
+    });
+
+    // stable memory for storing the cost basis and LP fees of users' liquidity in each pool
+    pub static LP_HISTORY_MAP: RefCell<StableBTreeMap<StableLPHistoryId, StableLPHistory, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_HISTORY_MEMORY_ID)))
+    });
+
     //
     // Archive Stable Memory
     //
@@ -128,6 +222,11 @@ thread_local! {
     pub static TRANSFER_ARCHIVE_MAP: RefCell<StableBTreeMap<StableTransferId, StableTransfer, Memory>> = with_memory_manager(|memory_manager| {
         RefCell::new(StableBTreeMap::init(memory_manager.get(TRANSFER_ARCHIVE_MEMORY_ID)))
     });
+
+    // stable memory for storing LP block archive
+    pub static LP_BLOCK_ARCHIVE_MAP: RefCell<StableBTreeMap<StableLPBlockId, StableLPBlock, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_BLOCK_ARCHIVE_MEMORY_ID)))
+    });
 }
 
 /// A helper function to access the memory manager.
@@ -0,0 +1,160 @@
+use super::stable_order::{OrderStatus, StableOrder, StableOrderId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::ORDER_MAP;
+
+pub fn get_by_order_id(order_id: u64) -> Option<StableOrder> {
+    ORDER_MAP.with(|m| m.borrow().get(&StableOrderId(order_id)))
+}
+
+/// get orders of user_id, newest first. if order_id is specified, only that order is returned
+pub fn get_by_order_and_user_id(order_id: Option<u64>, user_id: u32) -> Vec<StableOrder> {
+    ORDER_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .rev()
+            .filter_map(|(k, v)| {
+                if order_id.is_some_and(|order_id| k.0 != order_id) || v.user_id != user_id {
+                    return None;
+                }
+                Some(v)
+            })
+            .collect()
+    })
+}
+
+/// get all open orders, oldest first
+pub fn get_open() -> Vec<StableOrder> {
+    ORDER_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter_map(|(_, v)| if v.status == OrderStatus::Open { Some(v) } else { None })
+            .collect()
+    })
+}
+
+pub fn insert(order: &StableOrder) -> u64 {
+    ORDER_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let order_id = kong_settings_map::inc_order_map_idx();
+        let insert_order = StableOrder { order_id, ..order.clone() };
+        map.insert(StableOrderId(order_id), insert_order);
+        order_id
+    })
+}
+
+/// set an open order to executing so it can not be executed, cancelled or expired twice
+/// returns None if the order is not open
+pub fn update_executing_status(order_id: u64, request_id: u64) -> Option<StableOrder> {
+    ORDER_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        match map.get(&StableOrderId(order_id)) {
+            Some(mut v) if v.status == OrderStatus::Open => {
+                v.status = OrderStatus::Executing;
+                v.request_ids.push(request_id);
+                map.insert(StableOrderId(order_id), v.clone());
+                Some(v)
+            }
+            _ => None,
+        }
+    })
+}
+
+/// set the final status of an order and record the transfers, claims and swap tx
+pub fn update_status(
+    order_id: u64,
+    status: OrderStatus,
+    transfer_ids: &[u64],
+    claim_ids: &[u64],
+    tx_id: Option<u64>,
+) -> Option<StableOrder> {
+    ORDER_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        match map.get(&StableOrderId(order_id)) {
+            Some(mut v) => {
+                v.status = status;
+                v.transfer_ids.extend_from_slice(transfer_ids);
+                v.claim_ids.extend_from_slice(claim_ids);
+                if tx_id.is_some() {
+                    v.tx_id = tx_id;
+                }
+                map.insert(StableOrderId(order_id), v.clone());
+                Some(v)
+            }
+            None => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use candid::{Nat, Principal};
+    use icrc_ledger_types::icrc1::account::Account;
+
+    use crate::ic::address::Address;
+
+    fn insert_order(order_id: u64) {
+        let account = Account::from(Principal::anonymous());
+        let order = StableOrder::new(
+            1,
+            1,
+            &Nat::from(1_000_u32),
+            2,
+            &Nat::from(2_000_u32),
+            2.0,
+            &account,
+            &Address::PrincipalId(account),
+            u64::MAX,
+            1,
+            0,
+        );
+        ORDER_MAP.with(|m| m.borrow_mut().insert(StableOrderId(order_id), StableOrder { order_id, ..order }));
+    }
+
+    #[test]
+    fn test_fill_order() {
+        insert_order(1);
+        assert_eq!(get_open().len(), 1);
+
+        let order = update_executing_status(1, 2).unwrap();
+        assert_eq!(order.status, OrderStatus::Executing);
+        assert_eq!(order.request_ids, vec![1, 2]);
+        // executing orders can not be filled, cancelled or expired again
+        assert!(get_open().is_empty());
+        assert!(update_executing_status(1, 3).is_none());
+
+        let order = update_status(1, OrderStatus::Filled, &[5], &[], Some(7)).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.transfer_ids, vec![5]);
+        assert_eq!(order.tx_id, Some(7));
+        assert!(update_executing_status(1, 4).is_none());
+    }
+
+    #[test]
+    fn test_reopen_order() {
+        insert_order(1);
+        update_executing_status(1, 2).unwrap();
+        // limit price no longer met. order is re-opened with the escrow
+        let order = update_status(1, OrderStatus::Open, &[], &[], None).unwrap();
+        assert_eq!(order.status, OrderStatus::Open);
+        assert_eq!(order.tx_id, None);
+        assert_eq!(get_open().len(), 1);
+        assert!(update_executing_status(1, 3).is_some());
+    }
+
+    #[test]
+    fn test_expire_order_with_refund_claim() {
+        insert_order(1);
+        update_executing_status(1, 2).unwrap();
+        // escrow refund failed and was saved as a claim
+        let order = update_status(1, OrderStatus::Expired, &[], &[9], None).unwrap();
+        assert_eq!(order.status, OrderStatus::Expired);
+        assert_eq!(order.claim_ids, vec![9]);
+        assert!(order.transfer_ids.is_empty());
+        assert!(get_open().is_empty());
+        // an expired order can not be cancelled
+        assert!(update_executing_status(1, 3).is_none());
+    }
+}
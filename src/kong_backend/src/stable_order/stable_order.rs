@@ -0,0 +1,148 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+use crate::ic::address::Address;
+use crate::ic::get_time::is_expired_at;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableOrderId(pub u64);
+
+impl Storable for StableOrderId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    Open,
+    Executing, // used as a guard to prevent the order from being executed or cancelled twice
+    Filled,
+    Cancelled,
+    Expired,
+}
+
+impl std::fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderStatus::Open => write!(f, "Open"),
+            OrderStatus::Executing => write!(f, "Executing"),
+            OrderStatus::Filled => write!(f, "Filled"),
+            OrderStatus::Cancelled => write!(f, "Cancelled"),
+            OrderStatus::Expired => write!(f, "Expired"),
+        }
+    }
+}
+
+/// limit order. the pay token is held in escrow by Kong until the order is filled, cancelled or expires
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableOrder {
+    pub order_id: u64,
+    pub user_id: u32,
+    pub status: OrderStatus,
+    pub pay_token_id: u32,
+    pub pay_amount: Nat, // amount in escrow
+    pub receive_token_id: u32,
+    pub receive_amount: Nat,        // minimum receive amount at limit_price
+    pub limit_price: f64,           // minimum price (receive token per pay token) the order will be filled at
+    pub from_principal_id: Account, // escrow is returned here if the order is cancelled or expires
+    pub to_address: Address,
+    pub expires_at: u64,
+    pub request_ids: Vec<u64>, // requests to place, execute, cancel or expire the order
+    pub transfer_ids: Vec<u64>,
+    pub claim_ids: Vec<u64>,
+    pub tx_id: Option<u64>, // swap tx when the order is filled
+    pub ts: u64,
+}
+
+impl StableOrder {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_id: u32,
+        pay_token_id: u32,
+        pay_amount: &Nat,
+        receive_token_id: u32,
+        receive_amount: &Nat,
+        limit_price: f64,
+        from_principal_id: &Account,
+        to_address: &Address,
+        expires_at: u64,
+        request_id: u64,
+        ts: u64,
+    ) -> Self {
+        Self {
+            order_id: 0, // will be set with insert into ORDER_MAP
+            user_id,
+            status: OrderStatus::Open,
+            pay_token_id,
+            pay_amount: pay_amount.clone(),
+            receive_token_id,
+            receive_amount: receive_amount.clone(),
+            limit_price,
+            from_principal_id: *from_principal_id,
+            to_address: to_address.clone(),
+            expires_at,
+            request_ids: vec![request_id],
+            transfer_ids: Vec::new(),
+            claim_ids: Vec::new(),
+            tx_id: None,
+            ts,
+        }
+    }
+
+    /// an order can still be filled at expires_at
+    pub fn is_expired(&self, ts: u64) -> bool {
+        is_expired_at(Some(self.expires_at), ts)
+    }
+}
+
+impl Storable for StableOrder {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use candid::Principal;
+
+    fn order(expires_at: u64) -> StableOrder {
+        let account = Account::from(Principal::anonymous());
+        StableOrder::new(
+            1,
+            1,
+            &Nat::from(1_000_u32),
+            2,
+            &Nat::from(2_000_u32),
+            2.0,
+            &account,
+            &Address::PrincipalId(account),
+            expires_at,
+            1,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_is_expired() {
+        assert!(!order(1_000).is_expired(999));
+        assert!(!order(1_000).is_expired(1_000));
+        assert!(order(1_000).is_expired(1_001));
+    }
+}
@@ -0,0 +1,335 @@
+use candid::{Int, Nat};
+use num_bigint::BigInt;
+use num_traits::Zero;
+
+use super::stable_pool::{PoolType, StablePool};
+
+use crate::helpers::concentrated_liquidity_helpers::{
+    amounts_for_liquidity, liquidity_for_amounts, sqrt_price_at_tick, swap_step, tick_at_sqrt_price, MAX_TICK, MIN_TICK,
+};
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_to_bigint, nat_zero};
+use crate::stable_lp_position::lp_position_map;
+use crate::stable_lp_position::stable_lp_position::StableLPPosition;
+use crate::stable_tick::{stable_tick::StableTick, tick_map};
+
+// fee growth is LP fees per unit of liquidity * 2^128
+const FEE_GROWTH_BITS: u32 = 128;
+
+/// part of a swap within a single tick range
+struct ConcentratedSwapStep {
+    amount_out: Nat,
+    liquidity: Nat,          // liquidity in range during the step
+    cross_tick: Option<i32>, // initialized tick crossed at the end of the step
+}
+
+/// result of a swap along the liquidity of a concentrated liquidity pool
+pub struct ConcentratedSwap {
+    pub amount_in: Nat,
+    pub amount_out: Nat,
+    pub sqrt_price: Nat,
+    pub tick: i32,
+    pub liquidity: Nat,
+    steps: Vec<ConcentratedSwapStep>,
+}
+
+fn bigint_to_nat(n: &BigInt) -> Nat {
+    Nat::from(n.to_biguint().unwrap_or_default())
+}
+
+/// calculates a swap of a concentrated liquidity pool crossing ticks as the price moves. the pool is not updated
+/// zero_for_one is paying token_0 for token_1. amount is the amount in if exact_input, otherwise the amount out
+/// no fees are taken as Kong takes the LP fee from the amount out
+pub fn swap(pool: &StablePool, zero_for_one: bool, amount: &Nat, exact_input: bool) -> Result<ConcentratedSwap, String> {
+    if pool.pool_type != PoolType::ConcentratedLiquidity || nat_is_zero(&pool.sqrt_price) {
+        return Err(format!("Pool {} is not a concentrated liquidity pool", pool.symbol()));
+    }
+
+    let mut amount_remaining = amount.clone();
+    let mut sqrt_price = pool.sqrt_price.clone();
+    let mut tick = pool.tick;
+    let mut liquidity = pool.liquidity.clone();
+    let mut amount_in = nat_zero();
+    let mut amount_out = nat_zero();
+    let mut steps = Vec::new();
+
+    while !nat_is_zero(&amount_remaining) {
+        // next initialized tick in the direction of the swap or the price limit if there are no more ticks
+        let next_tick = tick_map::get_next_initialized(pool.pool_id, tick, zero_for_one);
+        let tick_next = match &next_tick {
+            Some(next_tick) => next_tick.tick,
+            None if zero_for_one => MIN_TICK,
+            None => MAX_TICK,
+        };
+        let sqrt_price_next = sqrt_price_at_tick(tick_next);
+        if next_tick.is_none() && sqrt_price == sqrt_price_next {
+            break; // at the price limit
+        }
+
+        let (step_sqrt_price, step_amount_in, step_amount_out) =
+            swap_step(&sqrt_price, &sqrt_price_next, &liquidity, &amount_remaining, exact_input)
+                .ok_or_else(|| format!("Insufficient liquidity in pool {}", pool.symbol()))?;
+        amount_remaining = if exact_input {
+            nat_subtract(&amount_remaining, &step_amount_in).unwrap_or(nat_zero())
+        } else {
+            nat_subtract(&amount_remaining, &step_amount_out).unwrap_or(nat_zero())
+        };
+        amount_in = nat_add(&amount_in, &step_amount_in);
+        amount_out = nat_add(&amount_out, &step_amount_out);
+
+        let step_liquidity = liquidity.clone();
+        let mut cross_tick = None;
+        if step_sqrt_price == sqrt_price_next {
+            // reached the next tick. cross it and update the liquidity in range
+            if let Some(next_tick) = &next_tick {
+                let liquidity_net = if zero_for_one {
+                    -next_tick.liquidity_net.0.clone()
+                } else {
+                    next_tick.liquidity_net.0.clone()
+                };
+                liquidity = bigint_to_nat(&(nat_to_bigint(&liquidity) + liquidity_net));
+                cross_tick = Some(tick_next);
+            }
+            tick = if zero_for_one { tick_next - 1 } else { tick_next };
+        } else if step_sqrt_price != sqrt_price {
+            tick = tick_at_sqrt_price(&step_sqrt_price);
+        }
+        sqrt_price = step_sqrt_price;
+        steps.push(ConcentratedSwapStep {
+            amount_out: step_amount_out,
+            liquidity: step_liquidity,
+            cross_tick,
+        });
+
+        if next_tick.is_none() && sqrt_price == sqrt_price_next {
+            break;
+        }
+    }
+
+    if !nat_is_zero(&amount_remaining) {
+        return Err(format!("Insufficient liquidity in pool {}", pool.symbol()));
+    }
+
+    Ok(ConcentratedSwap {
+        amount_in,
+        amount_out,
+        sqrt_price,
+        tick,
+        liquidity,
+        steps,
+    })
+}
+
+/// moves the price of the pool for a swap of amount_in and credits lp_fee to the liquidity in range of each step
+/// lp_fee is in the token the user receives and is split across the steps by their amount out
+/// must be called before the pool is saved
+pub fn update_swap(pool: &mut StablePool, zero_for_one: bool, amount_in: &Nat, lp_fee: &Nat) -> Result<(), String> {
+    let swap = swap(pool, zero_for_one, amount_in, true)?;
+
+    let mut fee_growth_global_0 = pool.fee_growth_global_0.clone();
+    let mut fee_growth_global_1 = pool.fee_growth_global_1.clone();
+    for step in swap.steps.iter() {
+        if !nat_is_zero(&step.liquidity) && !nat_is_zero(&swap.amount_out) {
+            // step_fee = lp_fee * step.amount_out / swap.amount_out
+            let step_fee = nat_divide(&nat_multiply(lp_fee, &step.amount_out), &swap.amount_out).unwrap_or(nat_zero());
+            let fee_growth = nat_divide(&Nat::from(step_fee.0 << FEE_GROWTH_BITS), &step.liquidity).unwrap_or(nat_zero());
+            if zero_for_one {
+                fee_growth_global_1 = nat_add(&fee_growth_global_1, &fee_growth);
+            } else {
+                fee_growth_global_0 = nat_add(&fee_growth_global_0, &fee_growth);
+            }
+        }
+        if let Some(cross_tick) = step.cross_tick {
+            // flip the fee growth outside to the other side of the tick
+            if let Some(mut tick) = tick_map::get(pool.pool_id, cross_tick) {
+                tick.fee_growth_outside_0 = nat_subtract(&fee_growth_global_0, &tick.fee_growth_outside_0).unwrap_or(nat_zero());
+                tick.fee_growth_outside_1 = nat_subtract(&fee_growth_global_1, &tick.fee_growth_outside_1).unwrap_or(nat_zero());
+                tick_map::update(&tick);
+            }
+        }
+    }
+
+    pool.fee_growth_global_0 = fee_growth_global_0;
+    pool.fee_growth_global_1 = fee_growth_global_1;
+    pool.sqrt_price = swap.sqrt_price;
+    pool.tick = swap.tick;
+    pool.liquidity = swap.liquidity;
+    Ok(())
+}
+
+/// fee growth per unit of liquidity of token_0 and token_1 between tick_lower and tick_upper
+/// can be negative as fee growth outside of a tick is relative to when the tick was initialized. only differences are meaningful
+fn fee_growth_inside(pool: &StablePool, tick_lower: i32, tick_upper: i32) -> (BigInt, BigInt) {
+    let lower = tick_map::get(pool.pool_id, tick_lower).unwrap_or_else(|| StableTick::new(pool.pool_id, tick_lower));
+    let upper = tick_map::get(pool.pool_id, tick_upper).unwrap_or_else(|| StableTick::new(pool.pool_id, tick_upper));
+
+    let inside = |global: &Nat, lower_outside: &Nat, upper_outside: &Nat| {
+        let global = nat_to_bigint(global);
+        let lower_outside = nat_to_bigint(lower_outside);
+        let upper_outside = nat_to_bigint(upper_outside);
+        let below = if pool.tick >= tick_lower {
+            lower_outside
+        } else {
+            &global - lower_outside
+        };
+        let above = if pool.tick < tick_upper {
+            upper_outside
+        } else {
+            &global - upper_outside
+        };
+        global - below - above
+    };
+
+    (
+        inside(&pool.fee_growth_global_0, &lower.fee_growth_outside_0, &upper.fee_growth_outside_0),
+        inside(&pool.fee_growth_global_1, &lower.fee_growth_outside_1, &upper.fee_growth_outside_1),
+    )
+}
+
+// fees earned by liquidity since fee_growth_inside_last
+fn fees_earned(liquidity: &Nat, fee_growth_inside: &BigInt, fee_growth_inside_last: &Int) -> Nat {
+    let fee_growth = fee_growth_inside - &fee_growth_inside_last.0;
+    bigint_to_nat(&((nat_to_bigint(liquidity) * fee_growth) >> FEE_GROWTH_BITS))
+}
+
+/// uncollected fees of token_0 and token_1 of a position
+pub fn position_fees(pool: &StablePool, position: &StableLPPosition) -> (Nat, Nat) {
+    let (fee_growth_inside_0, fee_growth_inside_1) = fee_growth_inside(pool, position.tick_lower, position.tick_upper);
+    (
+        nat_add(
+            &position.fees_owed_0,
+            &fees_earned(&position.liquidity, &fee_growth_inside_0, &position.fee_growth_inside_0_last),
+        ),
+        nat_add(
+            &position.fees_owed_1,
+            &fees_earned(&position.liquidity, &fee_growth_inside_1, &position.fee_growth_inside_1_last),
+        ),
+    )
+}
+
+/// amounts of token_0 and token_1 of a position at the pool's current price, rounded down
+pub fn position_amounts(pool: &StablePool, position: &StableLPPosition) -> (Nat, Nat) {
+    amounts_for_liquidity(
+        &pool.sqrt_price,
+        &sqrt_price_at_tick(position.tick_lower),
+        &sqrt_price_at_tick(position.tick_upper),
+        &position.liquidity,
+        false,
+    )
+}
+
+// adds liquidity_delta to the tick. initializes the tick if it is new and removes it if no liquidity is left
+fn update_tick(pool: &StablePool, tick: i32, liquidity_delta: &BigInt, is_upper: bool) -> Result<(), String> {
+    let mut stable_tick = tick_map::get(pool.pool_id, tick).unwrap_or_else(|| StableTick::new(pool.pool_id, tick));
+    if nat_is_zero(&stable_tick.liquidity_gross) && tick <= pool.tick {
+        // by convention, all fee growth before the tick is initialized happened below the tick
+        stable_tick.fee_growth_outside_0 = pool.fee_growth_global_0.clone();
+        stable_tick.fee_growth_outside_1 = pool.fee_growth_global_1.clone();
+    }
+    let liquidity_gross = nat_to_bigint(&stable_tick.liquidity_gross) + liquidity_delta;
+    if liquidity_gross < BigInt::zero() {
+        return Err(format!("Insufficient liquidity at tick {}", tick));
+    }
+    stable_tick.liquidity_gross = bigint_to_nat(&liquidity_gross);
+    // liquidity is added when crossing the lower tick going up and removed when crossing the upper tick
+    stable_tick.liquidity_net = if is_upper {
+        Int::from(&stable_tick.liquidity_net.0 - liquidity_delta)
+    } else {
+        Int::from(&stable_tick.liquidity_net.0 + liquidity_delta)
+    };
+
+    if nat_is_zero(&stable_tick.liquidity_gross) {
+        tick_map::remove(pool.pool_id, tick);
+    } else {
+        tick_map::update(&stable_tick);
+    }
+    Ok(())
+}
+
+/// adds liquidity_delta (removes if negative) to a position, updating the ticks and the liquidity in range of the pool
+/// fees earned by the position so far are moved to fees_owed. the ticks are saved, the pool and position must be saved by the caller
+pub fn update_position(pool: &mut StablePool, position: &mut StableLPPosition, liquidity_delta: &BigInt) -> Result<(), String> {
+    let liquidity = nat_to_bigint(&position.liquidity) + liquidity_delta;
+    if liquidity < BigInt::zero() {
+        return Err("Insufficient liquidity in position".to_string());
+    }
+
+    // new ticks are initialized before the fees are calculated and removed ticks after
+    if liquidity_delta > &BigInt::zero() {
+        update_tick(pool, position.tick_lower, liquidity_delta, false)?;
+        update_tick(pool, position.tick_upper, liquidity_delta, true)?;
+    }
+    let (fee_growth_inside_0, fee_growth_inside_1) = fee_growth_inside(pool, position.tick_lower, position.tick_upper);
+    if !nat_is_zero(&position.liquidity) {
+        position.fees_owed_0 = nat_add(
+            &position.fees_owed_0,
+            &fees_earned(&position.liquidity, &fee_growth_inside_0, &position.fee_growth_inside_0_last),
+        );
+        position.fees_owed_1 = nat_add(
+            &position.fees_owed_1,
+            &fees_earned(&position.liquidity, &fee_growth_inside_1, &position.fee_growth_inside_1_last),
+        );
+    }
+    position.fee_growth_inside_0_last = Int::from(fee_growth_inside_0);
+    position.fee_growth_inside_1_last = Int::from(fee_growth_inside_1);
+    if liquidity_delta < &BigInt::zero() {
+        update_tick(pool, position.tick_lower, liquidity_delta, false)?;
+        update_tick(pool, position.tick_upper, liquidity_delta, true)?;
+    }
+    position.liquidity = bigint_to_nat(&liquidity);
+
+    if position.is_in_range(pool.tick) {
+        pool.liquidity = bigint_to_nat(&(nat_to_bigint(&pool.liquidity) + liquidity_delta));
+    }
+    Ok(())
+}
+
+/// widest tick range aligned to tick_spacing
+pub fn full_range_ticks(tick_spacing: u32) -> (i32, i32) {
+    let tick_spacing = tick_spacing as i32;
+    ((MIN_TICK / tick_spacing) * tick_spacing, (MAX_TICK / tick_spacing) * tick_spacing)
+}
+
+/// checks the ticks of a new position are ordered, within bounds and aligned to the tick spacing of the pool
+pub fn check_ticks(pool: &StablePool, tick_lower: i32, tick_upper: i32) -> Result<(), String> {
+    if tick_lower >= tick_upper {
+        return Err("Lower tick must be less than upper tick".to_string());
+    }
+    if tick_lower < MIN_TICK || tick_upper > MAX_TICK {
+        return Err(format!("Ticks must be between {} and {}", MIN_TICK, MAX_TICK));
+    }
+    let tick_spacing = pool.tick_spacing as i32;
+    if tick_spacing == 0 || tick_lower % tick_spacing != 0 || tick_upper % tick_spacing != 0 {
+        return Err(format!("Ticks must be multiples of tick spacing {}", pool.tick_spacing));
+    }
+    Ok(())
+}
+
+/// creates a position between tick_lower and tick_upper with the most liquidity amount_0 and amount_1 can provide
+/// returns the position and the amounts used, rounded up. the position is saved, the pool must be saved by the caller
+pub fn mint_position(
+    pool: &mut StablePool,
+    user_id: u32,
+    tick_lower: i32,
+    tick_upper: i32,
+    amount_0: &Nat,
+    amount_1: &Nat,
+    ts: u64,
+) -> Result<(StableLPPosition, Nat, Nat), String> {
+    check_ticks(pool, tick_lower, tick_upper)?;
+    let sqrt_price_lower = sqrt_price_at_tick(tick_lower);
+    let sqrt_price_upper = sqrt_price_at_tick(tick_upper);
+    let liquidity = liquidity_for_amounts(&pool.sqrt_price, &sqrt_price_lower, &sqrt_price_upper, amount_0, amount_1);
+    if nat_is_zero(&liquidity) {
+        return Err("Insufficient amounts for position".to_string());
+    }
+    let (used_amount_0, used_amount_1) = amounts_for_liquidity(&pool.sqrt_price, &sqrt_price_lower, &sqrt_price_upper, &liquidity, true);
+    if used_amount_0 > *amount_0 || used_amount_1 > *amount_1 {
+        return Err("Insufficient amounts for position".to_string());
+    }
+
+    let mut position = StableLPPosition::new(user_id, pool.pool_id, tick_lower, tick_upper, ts);
+    update_position(pool, &mut position, &nat_to_bigint(&liquidity))?;
+    let position_id = lp_position_map::insert(&position);
+    position.position_id = position_id;
+    Ok((position, used_amount_0, used_amount_1))
+}
@@ -0,0 +1,31 @@
+use super::pool_map;
+use super::stable_pool::{PoolType, StablePool};
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+
+/// converts liquidity bootstrapping pools whose sale has ended to normal pools
+/// pools ending at 50/50 become constant product pools, others become weighted pools with the end weights
+pub fn end_liquidity_bootstrapping() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let ts = get_time();
+    for pool in pool_map::get() {
+        if pool.pool_type != PoolType::LiquidityBootstrapping || ts < pool.weight_end_ts {
+            continue;
+        }
+        let pool_type = if pool.weight_0_end == 50 {
+            PoolType::ConstantProduct
+        } else {
+            PoolType::Weighted
+        };
+        pool_map::update(&StablePool {
+            pool_type,
+            weight_0: pool.weight_0_end,
+            buy_only: false,
+            ..pool
+        });
+    }
+}
@@ -1,4 +1,6 @@
 pub mod check_token_balance;
+pub mod concentrated_liquidity;
+pub mod liquidity_bootstrapping;
 pub mod pool_map;
 pub mod pool_stats;
 #[allow(clippy::module_inception)]
@@ -1,46 +1,81 @@
 use wildmatch::WildMatch;
 
 use crate::ic::logging::error_log;
+use crate::stable_kong_fee::kong_fee_map;
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_position::lp_position_map;
 use crate::stable_lp_token::lp_token_map;
 use crate::stable_memory::POOL_MAP;
 use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
+use crate::stable_pool_observation::pool_observation_map;
+use crate::stable_tick::tick_map;
 use crate::stable_token::stable_token::StableToken;
+use crate::stable_token::token;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 
-fn symbol_with_chain(symbol: &str) -> Result<String, String> {
+// returns the symbol of the token pair with chain and the fee tier if the symbol has one
+fn symbol_with_chain(symbol: &str) -> Result<(String, Option<u32>), String> {
     let mut symbols = symbol.split('_');
     let symbol_0 = symbols.next().ok_or_else(|| format!("Invalid symbol {}", symbol))?;
     let symbol_1 = symbols.next().ok_or_else(|| format!("Invalid symbol {}", symbol))?;
+    let fee_tier = match symbols.next() {
+        Some(fee_tier) => Some(fee_tier.parse::<u32>().map_err(|_| format!("Invalid symbol {}", symbol))?),
+        None => None,
+    };
     if symbols.next().is_some() {
         return Err(format!("Invalid symbol {}", symbol));
     }
 
-    Ok(format!(
-        "{}_{}",
-        token_map::symbol_with_chain(symbol_0)?,
-        token_map::symbol_with_chain(symbol_1)?
+    Ok((
+        format!(
+            "{}_{}",
+            token_map::symbol_with_chain(symbol_0)?,
+            token_map::symbol_with_chain(symbol_1)?
+        ),
+        fee_tier,
     ))
 }
 
-fn address_with_chain(address: &str) -> Result<String, String> {
+// returns the address of the token pair with chain and the fee tier if the address has one
+fn address_with_chain(address: &str) -> Result<(String, Option<u32>), String> {
     let mut addresses = address.split('_');
     let address_0 = addresses.next().ok_or_else(|| format!("Invalid address {}", address))?;
     let address_1 = addresses.next().ok_or_else(|| format!("Invalid address {}", address))?;
+    let fee_tier = match addresses.next() {
+        Some(fee_tier) => Some(fee_tier.parse::<u32>().map_err(|_| format!("Invalid address {}", address))?),
+        None => None,
+    };
     if addresses.next().is_some() {
         return Err(format!("Invalid address {}", address));
     }
 
-    Ok(format!(
-        "{}_{}",
-        token_map::address_with_chain(address_0)?,
-        token_map::address_with_chain(address_1)?
+    Ok((
+        format!(
+            "{}_{}",
+            token_map::address_with_chain(address_0)?,
+            token_map::address_with_chain(address_1)?
+        ),
+        fee_tier,
     ))
 }
 
-pub fn symbol(token_0: &StableToken, token_1: &StableToken) -> String {
-    format!("{}_{}", token_0.symbol(), token_1.symbol())
+/// fee_tier is the LP fee in pips of pools that are not the first pool of their token pair
+pub fn symbol(token_0: &StableToken, token_1: &StableToken, fee_tier: Option<u32>) -> String {
+    token::symbol(token_0, token_1, fee_tier)
+}
+
+// without a fee tier, the first pool of the token pair. otherwise the pool of the token pair with lp_fee_pips of fee_tier
+/// fee tier of the pool. the LP fee it was added with, which stays the same if lp_fee_pips is updated later
+fn pool_fee_tier(pool: &StablePool) -> u32 {
+    pool.fee_tier.unwrap_or(pool.lp_fee_pips)
+}
+
+fn is_fee_tier(pool: &StablePool, fee_tier: Option<u32>) -> bool {
+    match fee_tier {
+        Some(fee_tier) => pool_fee_tier(pool) == fee_tier,
+        None => pool.fee_tier.is_none(),
+    }
 }
 
 pub fn get_by_pool_id(pool_id: u32) -> Option<StablePool> {
@@ -67,6 +102,7 @@ pub fn get_by_token_wildcard(token: &str) -> Vec<StablePool> {
 }
 
 // token can be in the format of Symbol_Symbol, Chain.Symbol_Chain.Symbol, Address_Address, or Chain.Address_Chain.Address
+// followed by an optional fee tier in pips, for example ckBTC_ckUSDT_500
 pub fn get_by_token(token: &str) -> Result<StablePool, String> {
     if let Ok(pool) = get_by_symbol(token) {
         return Ok(pool);
@@ -80,39 +116,43 @@ pub fn get_by_token(token: &str) -> Result<StablePool, String> {
 // symbol can be in the format of Symbol_Symbol or Chain.Symbol_Chain.Symbol
 // where the Chain prefix will be added if not present
 fn get_by_symbol(symbol: &str) -> Result<StablePool, String> {
-    let symbol_with_chain = symbol_with_chain(symbol)?;
+    let (symbol_with_chain, fee_tier) = symbol_with_chain(symbol)?;
     POOL_MAP
         .with(|m| {
             m.borrow().iter().find_map(|(_, v)| {
-                if v.symbol_with_chain() == symbol_with_chain {
+                if format!("{}_{}", v.token_0().symbol_with_chain(), v.token_1().symbol_with_chain()) == symbol_with_chain
+                    && is_fee_tier(&v, fee_tier)
+                {
                     return Some(v);
                 }
                 None
             })
         })
-        .ok_or_else(|| format!("Pool {} not found", symbol_with_chain))
+        .ok_or_else(|| format!("Pool {} not found", symbol))
 }
 
 // address can be in the format of Address_Address or Chain.Address_Chain.Address
 // where the Chain prefix will be added if not present
 fn get_by_address(address: &str) -> Result<StablePool, String> {
-    let address_with_chain = address_with_chain(address)?;
+    let (address_with_chain, fee_tier) = address_with_chain(address)?;
     POOL_MAP
         .with(|m| {
             m.borrow().iter().find_map(|(_, v)| {
-                if v.address_with_chain() == address_with_chain {
+                if format!("{}_{}", v.token_0().address_with_chain(), v.token_1().address_with_chain()) == address_with_chain
+                    && is_fee_tier(&v, fee_tier)
+                {
                     return Some(v);
                 }
                 None
             })
         })
-        .ok_or_else(|| format!("Pool {} not found", address_with_chain))
+        .ok_or_else(|| format!("Pool {} not found", address))
 }
 
-pub fn get_by_token_ids(token_id_0: u32, token_id_1: u32) -> Option<StablePool> {
+pub fn get_by_token_ids(token_id_0: u32, token_id_1: u32, fee_tier: Option<u32>) -> Option<StablePool> {
     POOL_MAP.with(|m| {
         m.borrow().iter().find_map(|(_, v)| {
-            if v.token_id_0 == token_id_0 && v.token_id_1 == token_id_1 {
+            if v.token_id_0 == token_id_0 && v.token_id_1 == token_id_1 && is_fee_tier(&v, fee_tier) {
                 return Some(v);
             }
             None
@@ -120,10 +160,11 @@ pub fn get_by_token_ids(token_id_0: u32, token_id_1: u32) -> Option<StablePool>
     })
 }
 
-pub fn get_by_tokens(token_0: &str, token_1: &str) -> Result<StablePool, String> {
+pub fn get_by_tokens(token_0: &str, token_1: &str, fee_tier: Option<u32>) -> Result<StablePool, String> {
     let token_0: StableToken = token_map::get_by_token(token_0)?;
     let token_1 = token_map::get_by_token(token_1)?;
-    get_by_token_ids(token_0.token_id(), token_1.token_id()).ok_or_else(|| format!("Pool {} not found", symbol(&token_0, &token_1)))
+    get_by_token_ids(token_0.token_id(), token_1.token_id(), fee_tier)
+        .ok_or_else(|| format!("Pool {} not found", symbol(&token_0, &token_1, fee_tier)))
 }
 
 /// Get pool by LP token's id.
@@ -150,7 +191,7 @@ pub fn get() -> Vec<StablePool> {
     POOL_MAP.with(|m| m.borrow().iter().map(|(_, v)| v).collect())
 }
 
-/// check if pool exists
+/// check if any pool exists for the token pair
 pub fn exists(token_0: &StableToken, token_1: &StableToken) -> bool {
     POOL_MAP.with(|m| {
         m.borrow().iter().any(|(_, v)| {
@@ -160,8 +201,19 @@ pub fn exists(token_0: &StableToken, token_1: &StableToken) -> bool {
     })
 }
 
+/// check if a pool exists for the token pair with the LP fee of lp_fee_pips
+pub fn exists_fee_tier(token_0: &StableToken, token_1: &StableToken, lp_fee_pips: u32) -> bool {
+    POOL_MAP.with(|m| {
+        m.borrow().iter().any(|(_, v)| {
+            (v.token_id_0 == token_0.token_id() && v.token_id_1 == token_1.token_id()
+                || v.token_id_0 == token_1.token_id() && v.token_id_1 == token_0.token_id())
+                && pool_fee_tier(&v) == lp_fee_pips
+        })
+    })
+}
+
 pub fn insert(pool: &StablePool) -> Result<u32, String> {
-    if exists(&pool.token_0(), &pool.token_1()) {
+    if exists_fee_tier(&pool.token_0(), &pool.token_1(), pool.lp_fee_pips) {
         Err(format!("Pool {} already exists", pool.symbol()))?
     }
 
@@ -186,11 +238,21 @@ pub fn remove(pool_id: u32) -> Result<(), String> {
         .with(|m| m.borrow_mut().remove(&StablePoolId(pool_id)))
         .ok_or("Unable to remove pool".to_string())?;
 
+    // remove LP token balances
+    lp_token_map::remove(pool.lp_token_id)?;
+
     // remove LP token
     token_map::remove(pool.lp_token_id)?;
 
-    // remove LP token
-    lp_token_map::remove(pool.lp_token_id)?;
+    // remove TWAP observations
+    pool_observation_map::remove(pool_id);
+
+    // remove Kong fee history
+    kong_fee_map::remove_by_pool_id(pool_id);
+
+    // remove concentrated liquidity positions and ticks
+    lp_position_map::remove_by_pool_id(pool_id);
+    tick_map::remove_by_pool_id(pool_id);
 
     Ok(())
 }
@@ -214,3 +276,27 @@ fn archive_pool_to_kong_data(pool: StablePool) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_fee_tier() {
+        // first pool of a token pair
+        let pool = StablePool::new(1, 2, 3_000, 0, 3, true);
+        assert!(is_fee_tier(&pool, None));
+        assert!(is_fee_tier(&pool, Some(3_000)));
+        assert!(!is_fee_tier(&pool, Some(500)));
+
+        // pool added as the 0.05% fee tier keeps its tier after lp_fee_pips is updated
+        let pool = StablePool {
+            fee_tier: Some(500),
+            lp_fee_pips: 1_000,
+            ..pool
+        };
+        assert!(!is_fee_tier(&pool, None));
+        assert!(is_fee_tier(&pool, Some(500)));
+        assert!(!is_fee_tier(&pool, Some(1_000)));
+    }
+}
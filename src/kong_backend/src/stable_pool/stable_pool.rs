@@ -1,17 +1,49 @@
 use candid::{CandidType, Nat};
 use ic_stable_structures::{storable::Bound, Storable};
-use num::BigRational;
+use num::{BigInt, BigRational, One, Zero};
 use serde::{Deserialize, Serialize};
 
 use super::pool_map;
 
+use crate::helpers::concentrated_liquidity_helpers::sqrt_price_at_tick;
 use crate::helpers::math_helpers::price_rounded;
-use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_to_bigint, nat_to_decimal_precision, nat_zero};
+use crate::helpers::nat_helpers::{nat_10pow, nat_add, nat_is_zero, nat_to_bigint, nat_to_decimal_precision, nat_zero};
+use crate::helpers::stableswap_helpers::stableswap_price;
+use crate::helpers::weighted_helpers::weighted_price;
 use crate::ic::ckusdt::ckusdt_amount;
+use crate::ic::get_time::get_time;
+use crate::stable_pool_observation::pool_observation_map;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 
+// decimals of the prices accumulated in price_0_cumulative and price_1_cumulative
+pub const PRICE_CUMULATIVE_DECIMALS: u8 = 18;
+
+// bounds of the StableSwap amplification coefficient A
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+// tick spacing of concentrated liquidity pools
+pub const DEFAULT_TICK_SPACING: u32 = 60;
+pub const MAX_TICK_SPACING: u32 = 16_384;
+
+// fees are in pips, 1/100 of a basis point
+pub const PIPS_PER_BPS: u32 = 100;
+pub const MAX_LP_FEE_PIPS: u32 = 100_000; // 10%
+
+/// fee in pips rounded down to basis points for the deprecated lp_fee_bps of replies
+pub fn pips_to_bps(pips: u32) -> u8 {
+    (pips / PIPS_PER_BPS).min(u8::MAX as u32) as u8
+}
+
+// bounds of the weight of token_0 of weighted pools in percent
+pub const MIN_WEIGHT: u8 = 2;
+pub const MAX_WEIGHT: u8 = 98;
+// weights are calculated in pips, 1/10_000 of a percent, so liquidity bootstrapping weights shift smoothly
+pub const WEIGHT_PIPS_PER_PERCENT: u32 = 10_000;
+pub const WEIGHT_PIPS: u32 = 100 * WEIGHT_PIPS_PER_PERCENT;
+
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StablePoolId(pub u32);
 
@@ -27,6 +59,29 @@ impl Storable for StablePoolId {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+/// invariant used to price swaps of a pool
+#[derive(CandidType, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolType {
+    #[default]
+    ConstantProduct, // x * y = k
+    StableSwap,             // Curve StableSwap invariant with amplification coefficient A. for pegged pairs
+    ConcentratedLiquidity,  // liquidity is provided by positions in price ranges between ticks
+    Weighted,               // x^w_0 * y^w_1 = k with token weights other than 50/50
+    LiquidityBootstrapping, // weighted pool with weights shifting linearly over a sale for token launches
+}
+
+impl std::fmt::Display for PoolType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolType::ConstantProduct => write!(f, "ConstantProduct"),
+            PoolType::StableSwap => write!(f, "StableSwap"),
+            PoolType::ConcentratedLiquidity => write!(f, "ConcentratedLiquidity"),
+            PoolType::Weighted => write!(f, "Weighted"),
+            PoolType::LiquidityBootstrapping => write!(f, "LiquidityBootstrapping"),
+        }
+    }
+}
+
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct StablePool {
     pub pool_id: u32,
@@ -37,20 +92,80 @@ pub struct StablePool {
     pub token_id_1: u32,
     pub balance_1: Nat,
     pub lp_fee_1: Nat,
-    pub kong_fee_1: Nat,  // Kong's share of the LP fee
-    pub lp_fee_bps: u8,   // LP's fee in basis points
-    pub kong_fee_bps: u8, // Kong's fee in basis points
+    pub kong_fee_1: Nat, // Kong's share of the LP fee
+    #[serde(default)]
+    pub lp_fee_pips: u32, // LP's fee in pips (1/100 of a basis point). 1_000_000 pips = 100%
+    #[serde(default)]
+    pub kong_fee_pips: u32, // Kong's fee in pips
+    #[serde(default, skip_serializing)]
+    pub lp_fee_bps: u8, // deprecated. LP's fee of pools stored before fees were in pips, migrated to lp_fee_pips
+    #[serde(default, skip_serializing)]
+    pub kong_fee_bps: u8, // deprecated. Kong's fee of pools stored before fees were in pips, migrated to kong_fee_pips
     pub lp_token_id: u32, // token id of the LP token
-    pub on_kong: bool,    // whether the pool is on Kong
+    pub on_kong: bool,   // whether the pool is on Kong
     pub tvl: Nat,
     pub rolling_24h_volume: Nat,
     pub rolling_24h_lp_fee: Nat,
     pub rolling_24h_num_swaps: Nat,
     pub rolling_24h_apy: f64,
+    #[serde(default = "nat_zero")]
+    pub price_0_cumulative: Nat, // sum of price of token_0 in token_1 * elapsed nanoseconds
+    #[serde(default = "nat_zero")]
+    pub price_1_cumulative: Nat, // sum of price of token_1 in token_0 * elapsed nanoseconds
+    #[serde(default)]
+    pub price_cumulative_ts: u64, // timestamp of the last cumulative price update
+    #[serde(default)]
+    pub pool_type: PoolType,
+    #[serde(default)]
+    pub amp_initial: u64, // StableSwap amplification coefficient A at amp_initial_ts
+    #[serde(default)]
+    pub amp_future: u64, // A is ramped linearly from amp_initial to amp_future
+    #[serde(default)]
+    pub amp_initial_ts: u64,
+    #[serde(default)]
+    pub amp_future_ts: u64,
+    #[serde(default)]
+    pub tick_spacing: u32, // concentrated liquidity positions must use ticks that are multiples of tick_spacing
+    #[serde(default = "nat_zero")]
+    pub sqrt_price: Nat, // concentrated liquidity sqrt(price of token_0 in token_1) * 2^96 in raw token units
+    #[serde(default)]
+    pub tick: i32, // concentrated liquidity tick of sqrt_price
+    #[serde(default = "nat_zero")]
+    pub liquidity: Nat, // concentrated liquidity of the positions in range of the current tick
+    #[serde(default = "nat_zero")]
+    pub fee_growth_global_0: Nat, // concentrated liquidity LP fees of token_0 per unit of liquidity * 2^128
+    #[serde(default = "nat_zero")]
+    pub fee_growth_global_1: Nat, // concentrated liquidity LP fees of token_1 per unit of liquidity * 2^128
+    #[serde(default)]
+    pub weight_0: u8, // weight of token_0 in percent of weighted pools. weight of token_1 is 100 - weight_0
+    #[serde(default)]
+    pub weight_0_end: u8, // liquidity bootstrapping weight of token_0 is shifted linearly from weight_0 to weight_0_end
+    #[serde(default)]
+    pub weight_start_ts: u64,
+    #[serde(default)]
+    pub weight_end_ts: u64, // end of the sale. the pool is converted to a normal pool after
+    #[serde(default)]
+    pub buy_only: bool, // liquidity bootstrapping swaps can only buy token_0 during the sale
+    #[serde(default)]
+    pub dynamic_fee: bool, // LP fee rises with the volatility of the pool's price instead of lp_fee_pips
+    #[serde(default)]
+    pub min_lp_fee_pips: u32, // dynamic LP fee = min_lp_fee_pips + volatility in pips * volatility_fee_pct / 100, up to max_lp_fee_pips
+    #[serde(default)]
+    pub max_lp_fee_pips: u32,
+    #[serde(default)]
+    pub volatility_fee_pct: u32,
+    #[serde(default)]
+    pub volatility_window_secs: u64, // window of price observations used to measure the volatility
+    #[serde(default)]
+    pub fee_tier: Option<u32>, // lp_fee_pips of pools added to a token pair that already has a pool. qualifies the symbol and LP token
+    #[serde(default = "nat_zero")]
+    pub kong_fee_swept_0: Nat, // total Kong fees of token_0 swept to the treasury
+    #[serde(default = "nat_zero")]
+    pub kong_fee_swept_1: Nat, // total Kong fees of token_1 swept to the treasury
 }
 
 impl StablePool {
-    pub fn new(token_id_0: u32, token_id_1: u32, lp_fee_bps: u8, kong_fee_bps: u8, lp_token_id: u32, on_kong: bool) -> Self {
+    pub fn new(token_id_0: u32, token_id_1: u32, lp_fee_pips: u32, kong_fee_pips: u32, lp_token_id: u32, on_kong: bool) -> Self {
         Self {
             pool_id: 0,
             token_id_0,
@@ -61,8 +176,10 @@ impl StablePool {
             balance_1: nat_zero(),
             lp_fee_1: nat_zero(),
             kong_fee_1: nat_zero(),
-            lp_fee_bps,
-            kong_fee_bps,
+            lp_fee_pips,
+            kong_fee_pips,
+            lp_fee_bps: 0,
+            kong_fee_bps: 0,
             lp_token_id,
             on_kong,
             tvl: nat_zero(),
@@ -70,27 +187,74 @@ impl StablePool {
             rolling_24h_lp_fee: nat_zero(),
             rolling_24h_num_swaps: nat_zero(),
             rolling_24h_apy: 0_f64,
+            price_0_cumulative: nat_zero(),
+            price_1_cumulative: nat_zero(),
+            price_cumulative_ts: 0,
+            pool_type: PoolType::ConstantProduct,
+            amp_initial: 0,
+            amp_future: 0,
+            amp_initial_ts: 0,
+            amp_future_ts: 0,
+            tick_spacing: 0,
+            sqrt_price: nat_zero(),
+            tick: 0,
+            liquidity: nat_zero(),
+            fee_growth_global_0: nat_zero(),
+            fee_growth_global_1: nat_zero(),
+            weight_0: 0,
+            weight_0_end: 0,
+            weight_start_ts: 0,
+            weight_end_ts: 0,
+            buy_only: false,
+            dynamic_fee: false,
+            min_lp_fee_pips: 0,
+            max_lp_fee_pips: 0,
+            volatility_fee_pct: 0,
+            volatility_window_secs: 0,
+            fee_tier: None,
+            kong_fee_swept_0: nat_zero(),
+            kong_fee_swept_1: nat_zero(),
         }
     }
 
     pub fn symbol(&self) -> String {
-        format!("{}_{}", self.symbol_0(), self.symbol_1())
+        format!("{}_{}{}", self.symbol_0(), self.symbol_1(), self.fee_tier_suffix())
     }
 
     pub fn symbol_with_chain(&self) -> String {
-        format!("{}_{}", self.token_0().symbol_with_chain(), self.token_1().symbol_with_chain())
+        format!(
+            "{}_{}{}",
+            self.token_0().symbol_with_chain(),
+            self.token_1().symbol_with_chain(),
+            self.fee_tier_suffix()
+        )
     }
 
     pub fn address(&self) -> String {
-        format!("{}_{}", self.token_0().address(), self.token_1().address())
+        format!(
+            "{}_{}{}",
+            self.token_0().address(),
+            self.token_1().address(),
+            self.fee_tier_suffix()
+        )
     }
 
     pub fn address_with_chain(&self) -> String {
-        format!("{}_{}", self.token_0().address_with_chain(), self.token_1().address_with_chain())
+        format!(
+            "{}_{}{}",
+            self.token_0().address_with_chain(),
+            self.token_1().address_with_chain(),
+            self.fee_tier_suffix()
+        )
     }
 
     pub fn name(&self) -> String {
-        format!("{}_{} Liquidity Pool", self.symbol_0(), self.symbol_1())
+        format!("{} Liquidity Pool", self.symbol())
+    }
+
+    // the first pool of a token pair has no fee tier in its symbol
+    fn fee_tier_suffix(&self) -> String {
+        self.fee_tier.map_or_else(String::new, |fee_tier| format!("_{}", fee_tier))
     }
 
     pub fn token_0(&self) -> StableToken {
@@ -121,7 +285,118 @@ impl StablePool {
         token_map::get_by_token_id(self.lp_token_id).unwrap()
     }
 
+    /// StableSwap amplification coefficient A at ts. None for constant product pools
+    pub fn get_amp_at(&self, ts: u64) -> Option<u64> {
+        if self.pool_type != PoolType::StableSwap {
+            None?
+        }
+        if ts >= self.amp_future_ts || self.amp_future_ts <= self.amp_initial_ts {
+            return Some(self.amp_future);
+        }
+        // ramp linearly from amp_initial to amp_future
+        let elapsed = (ts.saturating_sub(self.amp_initial_ts)) as u128;
+        let duration = (self.amp_future_ts - self.amp_initial_ts) as u128;
+        let (amp_initial, amp_future) = (self.amp_initial as u128, self.amp_future as u128);
+        let amp = if amp_future > amp_initial {
+            amp_initial + (amp_future - amp_initial) * elapsed / duration
+        } else {
+            amp_initial - (amp_initial - amp_future) * elapsed / duration
+        };
+        Some(amp as u64)
+    }
+
+    /// current StableSwap amplification coefficient A. None for constant product pools
+    pub fn get_amp(&self) -> Option<u64> {
+        if self.pool_type != PoolType::StableSwap {
+            None?
+        }
+        self.get_amp_at(get_time())
+    }
+
+    /// weights of token_0 and token_1 in pips at ts. None for pools other than weighted and liquidity bootstrapping pools
+    pub fn get_weights_at(&self, ts: u64) -> Option<(u32, u32)> {
+        let weight_0 = match self.pool_type {
+            PoolType::Weighted => self.weight_0 as u32 * WEIGHT_PIPS_PER_PERCENT,
+            PoolType::LiquidityBootstrapping => {
+                let (weight_start, weight_end) = (
+                    self.weight_0 as u32 * WEIGHT_PIPS_PER_PERCENT,
+                    self.weight_0_end as u32 * WEIGHT_PIPS_PER_PERCENT,
+                );
+                if ts <= self.weight_start_ts {
+                    weight_start
+                } else if ts >= self.weight_end_ts || self.weight_end_ts <= self.weight_start_ts {
+                    weight_end
+                } else {
+                    // shift linearly from weight_0 to weight_0_end in steps of 1 pip
+                    let elapsed = (ts - self.weight_start_ts) as u128;
+                    let duration = (self.weight_end_ts - self.weight_start_ts) as u128;
+                    let (weight_start, weight_end) = (weight_start as u128, weight_end as u128);
+                    let weight_0 = if weight_end > weight_start {
+                        weight_start + (weight_end - weight_start) * elapsed / duration
+                    } else {
+                        weight_start - (weight_start - weight_end) * elapsed / duration
+                    };
+                    weight_0 as u32
+                }
+            }
+            _ => None?,
+        };
+        Some((weight_0, WEIGHT_PIPS - weight_0))
+    }
+
+    /// current weights of token_0 and token_1 in pips. None for pools other than weighted and liquidity bootstrapping pools
+    pub fn get_weights(&self) -> Option<(u32, u32)> {
+        match self.pool_type {
+            PoolType::Weighted | PoolType::LiquidityBootstrapping => self.get_weights_at(get_time()),
+            _ => None,
+        }
+    }
+
+    /// pools stored before fees were in pips have their fees in lp_fee_bps and kong_fee_bps
+    pub fn migrate_fee_bps(self) -> Self {
+        if self.lp_fee_bps == 0 && self.kong_fee_bps == 0 {
+            return self;
+        }
+        Self {
+            lp_fee_pips: self.lp_fee_bps as u32 * PIPS_PER_BPS,
+            kong_fee_pips: self.kong_fee_bps as u32 * PIPS_PER_BPS,
+            lp_fee_bps: 0,
+            kong_fee_bps: 0,
+            ..self
+        }
+    }
+
+    /// current LP fee in pips. for dynamic fee pools, this rises with the volatility of the pool's price
+    pub fn get_lp_fee_pips(&self) -> u32 {
+        if !self.dynamic_fee {
+            return self.lp_fee_pips;
+        }
+        let start_ts = get_time().saturating_sub(self.volatility_window_secs.saturating_mul(1_000_000_000));
+        let volatility_bps = pool_observation_map::get_by_pool_id(self.pool_id)
+            .and_then(|observations| observations.volatility_bps(start_ts))
+            .unwrap_or(0_f64);
+        self.get_lp_fee_pips_at(volatility_bps)
+    }
+
+    /// dynamic LP fee in pips for volatility_bps, clamped between min_lp_fee_pips and max_lp_fee_pips
+    pub fn get_lp_fee_pips_at(&self, volatility_bps: f64) -> u32 {
+        let volatility_pips = volatility_bps * PIPS_PER_BPS as f64;
+        let lp_fee_pips = self.min_lp_fee_pips as f64 + volatility_pips * self.volatility_fee_pct as f64 / 100_f64;
+        lp_fee_pips.clamp(self.min_lp_fee_pips as f64, self.max_lp_fee_pips as f64).round() as u32
+    }
+
+    /// true if only swaps buying token_0 are allowed, during the sale of a liquidity bootstrapping pool
+    pub fn is_buy_only(&self) -> bool {
+        self.pool_type == PoolType::LiquidityBootstrapping && self.buy_only && get_time() < self.weight_end_ts
+    }
+
+    /// price of token_0 in token_1. for StableSwap and weighted pools, this is the marginal price of the invariant
+    /// for concentrated liquidity pools, this is the price at sqrt_price
     pub fn get_price(&self) -> Option<BigRational> {
+        if self.pool_type == PoolType::ConcentratedLiquidity {
+            return self.get_price_at_sqrt_price(&self.sqrt_price);
+        }
+
         let reserve_0 = nat_add(&self.balance_0, &self.lp_fee_0);
         let reserve_1 = nat_add(&self.balance_1, &self.lp_fee_1);
         if nat_is_zero(&reserve_0) {
@@ -131,16 +406,76 @@ impl StablePool {
         let token_0 = self.token_0();
         let token_1 = self.token_1();
         let max_decimals = std::cmp::max(token_0.decimals(), token_1.decimals());
-        let reserve_0 = nat_to_bigint(&nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals));
-        let reserve_1 = nat_to_bigint(&nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals));
+        let reserve_0 = nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals);
+        let reserve_1 = nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals);
 
-        Some(BigRational::new(reserve_1, reserve_0))
+        if let Some((weight_0, weight_1)) = self.get_weights() {
+            return weighted_price(&reserve_0, &reserve_1, weight_0, weight_1);
+        }
+        match self.get_amp() {
+            Some(amp) => stableswap_price(&reserve_0, &reserve_1, amp),
+            None => Some(BigRational::new(nat_to_bigint(&reserve_1), nat_to_bigint(&reserve_0))),
+        }
     }
 
     pub fn get_price_as_f64(&self) -> Option<f64> {
         price_rounded(&self.get_price()?)
     }
 
+    /// price of token_0 in token_1 at tick of a concentrated liquidity pool
+    pub fn get_price_at_tick(&self, tick: i32) -> Option<BigRational> {
+        self.get_price_at_sqrt_price(&sqrt_price_at_tick(tick))
+    }
+
+    // sqrt_price is in raw token units so adjust by the decimals of the tokens
+    // price = sqrt_price^2 / 2^192 * 10^(decimals_0 - decimals_1)
+    fn get_price_at_sqrt_price(&self, sqrt_price: &Nat) -> Option<BigRational> {
+        if nat_is_zero(sqrt_price) {
+            None?
+        }
+        let decimals_0 = self.token_0().decimals();
+        let decimals_1 = self.token_1().decimals();
+        let max_decimals = std::cmp::max(decimals_0, decimals_1);
+        let sqrt_price = nat_to_bigint(sqrt_price);
+        let numerator = &sqrt_price * &sqrt_price * nat_to_bigint(&nat_10pow(max_decimals - decimals_1));
+        let denominator = (BigInt::one() << 192) * nat_to_bigint(&nat_10pow(max_decimals - decimals_0));
+        Some(BigRational::new(numerator, denominator))
+    }
+
+    /// returns (price of token_0 in token_1, price of token_1 in token_0) with PRICE_CUMULATIVE_DECIMALS decimals
+    fn get_prices_scaled(&self) -> Option<(Nat, Nat)> {
+        let price = self.get_price()?;
+        if price.is_zero() {
+            None?
+        }
+        let scale = BigRational::from_integer(nat_to_bigint(&nat_10pow(PRICE_CUMULATIVE_DECIMALS)));
+        let price_0 = (&price * &scale).to_integer().to_biguint()?;
+        let price_1 = (scale / price).to_integer().to_biguint()?;
+
+        Some((Nat::from(price_0), Nat::from(price_1)))
+    }
+
+    /// cumulative prices extrapolated to ts with the current reserves
+    pub fn get_price_cumulative(&self, ts: u64) -> (Nat, Nat) {
+        let elapsed = ts.saturating_sub(self.price_cumulative_ts);
+        if self.price_cumulative_ts == 0 || elapsed == 0 {
+            return (self.price_0_cumulative.clone(), self.price_1_cumulative.clone());
+        }
+        match self.get_prices_scaled() {
+            Some((price_0, price_1)) => (
+                nat_add(&self.price_0_cumulative, &(price_0 * elapsed)),
+                nat_add(&self.price_1_cumulative, &(price_1 * elapsed)),
+            ),
+            None => (self.price_0_cumulative.clone(), self.price_1_cumulative.clone()),
+        }
+    }
+
+    /// accumulates the current prices up to ts. must be called before the reserves change
+    pub fn update_price_cumulative(&mut self, ts: u64) {
+        (self.price_0_cumulative, self.price_1_cumulative) = self.get_price_cumulative(ts);
+        self.price_cumulative_ts = ts;
+    }
+
     /// sets balance = balance_0 + balance_1 in ckUSDT
     pub fn update_tvl(&mut self) {
         let token_0 = self.token_0();
@@ -168,8 +503,72 @@ impl Storable for StablePool {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
+        serde_cbor::from_slice::<StablePool>(&bytes).unwrap().migrate_fee_bps()
     }
 
     const BOUND: Bound = Bound::Unbounded;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_weights_at() {
+        let pool = StablePool {
+            pool_type: PoolType::LiquidityBootstrapping,
+            weight_0: 96,
+            weight_0_end: 50,
+            weight_start_ts: 1_000,
+            weight_end_ts: 2_000,
+            ..StablePool::new(1, 2, 30, 5, 3, true)
+        };
+        assert_eq!(pool.get_weights_at(0), Some((960_000, 40_000)));
+        assert_eq!(pool.get_weights_at(1_500), Some((730_000, 270_000)));
+        // weights shift in pips, not whole percents
+        assert_eq!(pool.get_weights_at(1_001), Some((959_540, 40_460)));
+        assert_eq!(pool.get_weights_at(1_999), Some((500_460, 499_540)));
+        assert_eq!(pool.get_weights_at(3_000), Some((500_000, 500_000)));
+
+        let pool = StablePool {
+            pool_type: PoolType::ConstantProduct,
+            ..pool
+        };
+        assert_eq!(pool.get_weights_at(1_500), None);
+    }
+
+    #[test]
+    fn test_get_lp_fee_pips_at() {
+        let pool = StablePool {
+            dynamic_fee: true,
+            min_lp_fee_pips: 1_000,
+            max_lp_fee_pips: 10_000,
+            volatility_fee_pct: 50,
+            ..StablePool::new(1, 2, 3_000, 500, 3, true)
+        };
+        assert_eq!(pool.get_lp_fee_pips_at(0_f64), 1_000);
+        assert_eq!(pool.get_lp_fee_pips_at(41.5_f64), 3_075);
+        assert_eq!(pool.get_lp_fee_pips_at(1_000_f64), 10_000);
+    }
+
+    #[test]
+    fn test_migrate_fee_bps() {
+        // pool stored with fees in basis points
+        let pool = StablePool::new(1, 2, 0, 0, 3, true);
+        let mut bytes = serde_cbor::value::to_value(&pool).unwrap();
+        if let serde_cbor::Value::Map(map) = &mut bytes {
+            map.insert(serde_cbor::Value::Text("lp_fee_bps".to_string()), serde_cbor::Value::Integer(30));
+            map.insert(serde_cbor::Value::Text("kong_fee_bps".to_string()), serde_cbor::Value::Integer(5));
+            map.remove(&serde_cbor::Value::Text("lp_fee_pips".to_string()));
+            map.remove(&serde_cbor::Value::Text("kong_fee_pips".to_string()));
+        }
+        let bytes = serde_cbor::to_vec(&bytes).unwrap();
+        let pool = StablePool::from_bytes(std::borrow::Cow::Owned(bytes));
+        assert_eq!((pool.lp_fee_pips, pool.kong_fee_pips), (3_000, 500));
+
+        // migrated pool is stored without the fees in basis points
+        let pool = StablePool::from_bytes(pool.to_bytes());
+        assert_eq!((pool.lp_fee_pips, pool.kong_fee_pips), (3_000, 500));
+        assert_eq!((pool.lp_fee_bps, pool.kong_fee_bps), (0, 0));
+    }
+}
@@ -0,0 +1,39 @@
+use super::stable_pool_observation::{PoolObservation, StablePoolObservations};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::POOL_OBSERVATION_MAP;
+use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
+
+pub fn get_by_pool_id(pool_id: u32) -> Option<StablePoolObservations> {
+    POOL_OBSERVATION_MAP.with(|m| m.borrow().get(&StablePoolId(pool_id)))
+}
+
+/// records the pool's cumulative prices at ts if the last observation is older than twap_observation_interval_secs
+/// pool's cumulative prices must already be updated to ts
+pub fn record(pool: &StablePool, ts: u64) {
+    let kong_settings = kong_settings_map::get();
+    let interval_ns = kong_settings.twap_observation_interval_secs * 1_000_000_000;
+    let max_observations = kong_settings.twap_max_observations as usize;
+
+    POOL_OBSERVATION_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let pool_id = StablePoolId(pool.pool_id);
+        let mut observations = map.get(&pool_id).unwrap_or_default();
+        if observations.last().is_some_and(|last| ts < last.ts + interval_ns) {
+            return;
+        }
+        observations.push(
+            PoolObservation {
+                ts,
+                price_0_cumulative: pool.price_0_cumulative.clone(),
+                price_1_cumulative: pool.price_1_cumulative.clone(),
+            },
+            max_observations,
+        );
+        map.insert(pool_id, observations);
+    });
+}
+
+pub fn remove(pool_id: u32) -> Option<StablePoolObservations> {
+    POOL_OBSERVATION_MAP.with(|m| m.borrow_mut().remove(&StablePoolId(pool_id)))
+}
@@ -0,0 +1,180 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::nat_subtract;
+
+/// snapshot of a pool's cumulative prices at ts
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolObservation {
+    pub ts: u64,
+    pub price_0_cumulative: Nat,
+    pub price_1_cumulative: Nat,
+}
+
+/// ring buffer of observations for a pool. used to calculate TWAPs
+#[derive(CandidType, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StablePoolObservations {
+    pub observations: Vec<PoolObservation>,
+    pub next_index: usize, // index the next observation will be written to once the buffer is full
+}
+
+impl StablePoolObservations {
+    /// the latest observation
+    pub fn last(&self) -> Option<&PoolObservation> {
+        if self.observations.is_empty() {
+            return None;
+        }
+        let index = (self.next_index + self.observations.len() - 1) % self.observations.len();
+        self.observations.get(index)
+    }
+
+    /// adds an observation, overwriting the oldest one when the buffer holds max_observations
+    pub fn push(&mut self, observation: PoolObservation, max_observations: usize) {
+        if max_observations == 0 {
+            return;
+        }
+        if self.observations.len() == max_observations {
+            // buffer is full. overwrite the oldest observation
+            self.observations[self.next_index] = observation;
+            self.next_index = (self.next_index + 1) % max_observations;
+            return;
+        }
+        if self.next_index != 0 || self.observations.len() > max_observations {
+            // buffer was resized. put observations in order, keeping only the latest ones that fit
+            let ordered = self.ordered();
+            let skip = (ordered.len() + 1).saturating_sub(max_observations);
+            self.observations = ordered.into_iter().skip(skip).cloned().collect();
+            self.next_index = 0;
+        }
+        self.observations.push(observation);
+    }
+
+    /// observations ordered oldest first
+    pub fn ordered(&self) -> Vec<&PoolObservation> {
+        let (newest, oldest) = self.observations.split_at(self.next_index);
+        oldest.iter().chain(newest.iter()).collect()
+    }
+
+    /// the latest observation taken at or before ts
+    pub fn get_at_or_before(&self, ts: u64) -> Option<&PoolObservation> {
+        self.ordered().into_iter().rev().find(|observation| observation.ts <= ts)
+    }
+
+    /// realized volatility in basis points of the observations taken at or after start_ts
+    /// the root mean square of the log changes between the average prices of consecutive observation intervals
+    /// None if there are less than 3 observations in the window
+    pub fn volatility_bps(&self, start_ts: u64) -> Option<f64> {
+        let observations: Vec<_> = self
+            .ordered()
+            .into_iter()
+            .filter(|observation| observation.ts >= start_ts)
+            .collect();
+        // average price of token_0 of each interval. PRICE_CUMULATIVE_DECIMALS scaling cancels out in the log changes
+        let prices: Vec<f64> = observations
+            .windows(2)
+            .filter_map(|pair| {
+                let elapsed = pair[1].ts.checked_sub(pair[0].ts).filter(|elapsed| *elapsed > 0)?;
+                let price_cumulative = nat_subtract(&pair[1].price_0_cumulative, &pair[0].price_0_cumulative)?;
+                let price = price_cumulative.0.to_f64()? / elapsed as f64;
+                (price > 0_f64).then_some(price)
+            })
+            .collect();
+        if prices.len() < 2 {
+            return None;
+        }
+        let sum_squares: f64 = prices.windows(2).map(|pair| (pair[1] / pair[0]).ln().powi(2)).sum();
+        Some((sum_squares / (prices.len() - 1) as f64).sqrt() * 10_000_f64)
+    }
+}
+
+impl Storable for StablePoolObservations {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(ts: u64) -> PoolObservation {
+        PoolObservation {
+            ts,
+            price_0_cumulative: Nat::from(ts),
+            price_1_cumulative: Nat::from(ts),
+        }
+    }
+
+    fn ts_list(observations: &StablePoolObservations) -> Vec<u64> {
+        observations.ordered().iter().map(|o| o.ts).collect()
+    }
+
+    #[test]
+    fn test_push() {
+        let mut observations = StablePoolObservations::default();
+        assert!(observations.last().is_none());
+
+        for ts in 1..=3 {
+            observations.push(observation(ts), 3);
+        }
+        assert_eq!(ts_list(&observations), vec![1, 2, 3]);
+        assert_eq!(observations.last().unwrap().ts, 3);
+
+        // oldest observations are overwritten
+        observations.push(observation(4), 3);
+        observations.push(observation(5), 3);
+        assert_eq!(ts_list(&observations), vec![3, 4, 5]);
+        assert_eq!(observations.last().unwrap().ts, 5);
+
+        // buffer shrunk
+        observations.push(observation(6), 2);
+        assert_eq!(ts_list(&observations), vec![5, 6]);
+
+        // buffer grown
+        observations.push(observation(7), 4);
+        assert_eq!(ts_list(&observations), vec![5, 6, 7]);
+        assert_eq!(observations.last().unwrap().ts, 7);
+    }
+
+    #[test]
+    fn test_get_at_or_before() {
+        let mut observations = StablePoolObservations::default();
+        for ts in [10, 20, 30, 40] {
+            observations.push(observation(ts), 3);
+        }
+        assert!(observations.get_at_or_before(15).is_none());
+        assert_eq!(observations.get_at_or_before(20).unwrap().ts, 20);
+        assert_eq!(observations.get_at_or_before(35).unwrap().ts, 30);
+        assert_eq!(observations.get_at_or_before(100).unwrap().ts, 40);
+    }
+
+    #[test]
+    fn test_volatility_bps() {
+        // price of 1.0 then 1.01 then 1.0 for 10 ns each
+        let mut observations = StablePoolObservations::default();
+        let mut price_0_cumulative = 0_u64;
+        for (ts, price) in [(0, 100), (10, 100), (20, 101), (30, 100)] {
+            price_0_cumulative += price * 10;
+            observations.push(
+                PoolObservation {
+                    ts,
+                    price_0_cumulative: Nat::from(price_0_cumulative),
+                    price_1_cumulative: Nat::from(0_u64),
+                },
+                10,
+            );
+        }
+        let expected = (1.01_f64).ln() * 10_000_f64;
+        assert!((observations.volatility_bps(0).unwrap() - expected).abs() < 1e-6);
+        // not enough observations in window
+        assert!(observations.volatility_bps(15).is_none());
+    }
+}
@@ -3,9 +3,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::add_liquidity::add_liquidity_args::AddLiquidityArgs;
 use crate::add_pool::add_pool_args::AddPoolArgs;
+use crate::batch::batch_args::BatchArgs;
+use crate::farms::create_farm_args::CreateFarmArgs;
+use crate::orders::limit_order_args::LimitOrderArgs;
+use crate::positions::add_position_args::AddPositionArgs;
+use crate::positions::remove_position_args::RemovePositionArgs;
 use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
 use crate::send::send_args::SendArgs;
 use crate::swap::swap_args::SwapArgs;
+use crate::zap_add_liquidity::zap_add_liquidity_args::ZapAddLiquidityArgs;
 
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub enum Request {
@@ -15,4 +21,14 @@ pub enum Request {
     Swap(SwapArgs),
     Claim(u64),
     Send(SendArgs),
+    LimitOrder(LimitOrderArgs),
+    CancelOrder(u64),
+    ExecuteOrder(u64),
+    ExpireOrder(u64),
+    AddPosition(AddPositionArgs),
+    RemovePosition(RemovePositionArgs),
+    ZapAddLiquidity(ZapAddLiquidityArgs),
+    Batch(BatchArgs),
+    CreateFarm(CreateFarmArgs),
+    CloseFarm(u64),
 }
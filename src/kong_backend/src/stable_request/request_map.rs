@@ -6,8 +6,12 @@ use super::stable_request::{StableRequest, StableRequestId};
 use super::status::{Status, StatusCode};
 
 use crate::ic::logging::error_log;
+use crate::stable_claim::claim_map;
 use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_memory::REQUEST_MAP;
+use crate::stable_transfer::transfer_map;
+use crate::stable_tx::tx_map;
+use crate::transfers::transfer_reply::TransferIdReply;
 
 const MAX_REQUESTS: usize = 20;
 
@@ -79,6 +83,20 @@ pub fn update_reply(key: u64, reply: Reply) -> Option<StableRequest> {
     })
 }
 
+/// archive the request and the claims, transfers and txs of its reply to kong_data
+pub fn archive_request_and_reply_to_kong_data(request_id: u64, claim_ids: &[u64], transfer_ids: &[TransferIdReply], tx_ids: &[u64]) {
+    archive_request_to_kong_data(request_id);
+    for claim_id in claim_ids.iter() {
+        claim_map::archive_claim_to_kong_data(*claim_id);
+    }
+    for transfer_id_reply in transfer_ids.iter() {
+        transfer_map::archive_transfer_to_kong_data(transfer_id_reply.transfer_id);
+    }
+    for tx_id in tx_ids.iter() {
+        tx_map::archive_tx_to_kong_data(*tx_id);
+    }
+}
+
 pub fn archive_request_to_kong_data(request_id: u64) {
     ic_cdk::spawn(async move {
         let request = match get_by_request_and_user_id(Some(request_id), None, Some(1)).pop() {
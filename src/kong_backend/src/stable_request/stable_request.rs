@@ -42,6 +42,17 @@ impl StableRequest {
             ts,
         }
     }
+
+    /// add pool replies stored before fees were in pips only have lp_fee_bps
+    pub fn migrate_fee_bps(self) -> Self {
+        match self.reply {
+            Reply::AddPool(reply) => Self {
+                reply: Reply::AddPool(reply.migrate_fee_bps()),
+                ..self
+            },
+            _ => self,
+        }
+    }
 }
 
 impl Storable for StableRequest {
@@ -50,8 +61,38 @@ impl Storable for StableRequest {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
+        serde_cbor::from_slice::<StableRequest>(&bytes).unwrap().migrate_fee_bps()
     }
 
     const BOUND: Bound = Bound::Unbounded;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::add_pool::add_pool_reply_helpers::create_add_pool_reply_failed;
+    use crate::stable_request::request::Request;
+
+    #[test]
+    fn test_migrate_add_pool_reply_fee_bps() {
+        // add pool reply stored before fees were in pips
+        let reply = create_add_pool_reply_failed("IC", "A", "IC", "B", 1, &[], &[], false, 0);
+        let mut value = serde_cbor::value::to_value(&reply).unwrap();
+        if let serde_cbor::Value::Map(map) = &mut value {
+            map.insert(serde_cbor::Value::Text("lp_fee_bps".to_string()), serde_cbor::Value::Integer(30));
+            map.remove(&serde_cbor::Value::Text("lp_fee_pips".to_string()));
+        }
+        let reply = serde_cbor::value::from_value(value).unwrap();
+        let request = StableRequest {
+            reply: Reply::AddPool(reply),
+            ..StableRequest::new(1, &Request::Claim(1), 0)
+        };
+
+        let request = StableRequest::from_bytes(request.to_bytes());
+        match request.reply {
+            Reply::AddPool(reply) => assert_eq!((reply.lp_fee_pips, reply.lp_fee_bps), (3_000, 30)),
+            _ => panic!("expected add pool reply"),
+        }
+    }
+}
@@ -87,6 +87,9 @@ pub enum StatusCode {
     ReturnPayToken,
     ReturnPayTokenSuccess,
     ReturnPayTokenFailed,
+    ReturnUnusedPayToken,
+    ReturnUnusedPayTokenSuccess,
+    ReturnUnusedPayTokenFailed,
     // claim
     ClaimToken,
     ClaimTokenSuccess,
@@ -106,6 +109,31 @@ pub enum StatusCode {
     SendLPTokenToUser,
     SendLPTokenToUserSuccess,
     SendLPTokenToUserFailed,
+    // deadline
+    DeadlineExpired,
+    // minimum amounts
+    LPTokenAmountBelowMinimum,
+    PayoutAmountBelowMinimum,
+    // limit order
+    OrderOpen,
+    OrderNotOpen,
+    ExecuteOrder,
+    CancelOrder,
+    OrderExpired,
+    // concentrated liquidity position
+    AddPosition,
+    AddPositionSuccess,
+    AddPositionFailed,
+    RemovePosition,
+    RemovePositionSuccess,
+    RemovePositionFailed,
+    // farm
+    ReceiveRewardBudget,
+    ReceiveRewardBudgetSuccess,
+    ReceiveRewardBudgetFailed,
+    ReturnRewardBudget,
+    ReturnRewardBudgetSuccess,
+    ReturnRewardBudgetFailed,
     // general
     Success,
     Failed,
@@ -179,6 +207,9 @@ impl std::fmt::Display for StatusCode {
             StatusCode::ReturnPayToken => write!(f, "Returning pay token"),
             StatusCode::ReturnPayTokenSuccess => write!(f, "Pay token returned"),
             StatusCode::ReturnPayTokenFailed => write!(f, "Failing returning pay token"),
+            StatusCode::ReturnUnusedPayToken => write!(f, "Returning unused pay token"),
+            StatusCode::ReturnUnusedPayTokenSuccess => write!(f, "Unused pay token returned"),
+            StatusCode::ReturnUnusedPayTokenFailed => write!(f, "Failed returning unused pay token"),
             StatusCode::ClaimToken => write!(f, "Claiming token"),
             StatusCode::ClaimTokenSuccess => write!(f, "Token claimed"),
             StatusCode::ClaimTokenFailed => write!(f, "Failed claiming token"),
@@ -194,6 +225,26 @@ impl std::fmt::Display for StatusCode {
             StatusCode::SendLPTokenToUser => write!(f, "Sending LP token to user"),
             StatusCode::SendLPTokenToUserSuccess => write!(f, "LP token sent to user"),
             StatusCode::SendLPTokenToUserFailed => write!(f, "Failed sending LP token to user"),
+            StatusCode::DeadlineExpired => write!(f, "Deadline expired"),
+            StatusCode::LPTokenAmountBelowMinimum => write!(f, "LP token amount below minimum"),
+            StatusCode::PayoutAmountBelowMinimum => write!(f, "Payout amount below minimum"),
+            StatusCode::OrderOpen => write!(f, "Order open"),
+            StatusCode::OrderNotOpen => write!(f, "Order not open"),
+            StatusCode::ExecuteOrder => write!(f, "Executing order"),
+            StatusCode::CancelOrder => write!(f, "Cancelling order"),
+            StatusCode::OrderExpired => write!(f, "Order expired"),
+            StatusCode::AddPosition => write!(f, "Adding position"),
+            StatusCode::AddPositionSuccess => write!(f, "Position added"),
+            StatusCode::AddPositionFailed => write!(f, "Failed adding position"),
+            StatusCode::RemovePosition => write!(f, "Removing position"),
+            StatusCode::RemovePositionSuccess => write!(f, "Position removed"),
+            StatusCode::RemovePositionFailed => write!(f, "Failed removing position"),
+            StatusCode::ReceiveRewardBudget => write!(f, "Receiving reward budget"),
+            StatusCode::ReceiveRewardBudgetSuccess => write!(f, "Reward budget received"),
+            StatusCode::ReceiveRewardBudgetFailed => write!(f, "Failed receiving reward budget"),
+            StatusCode::ReturnRewardBudget => write!(f, "Returning remaining reward budget"),
+            StatusCode::ReturnRewardBudgetSuccess => write!(f, "Remaining reward budget returned"),
+            StatusCode::ReturnRewardBudgetFailed => write!(f, "Failed returning remaining reward budget"),
             StatusCode::Success => write!(f, "Success"),
             StatusCode::Failed => write!(f, "Failed"),
         }
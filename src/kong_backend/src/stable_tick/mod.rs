@@ -0,0 +1,3 @@
+#[allow(clippy::module_inception)]
+pub mod stable_tick;
+pub mod tick_map;
@@ -0,0 +1,60 @@
+use candid::{CandidType, Int, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::nat_zero;
+
+/// ticks are ordered by pool then tick so the next initialized tick of a pool can be found with a range query
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableTickId {
+    pub pool_id: u32,
+    pub tick: i32,
+}
+
+impl Storable for StableTickId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// initialized tick of a concentrated liquidity pool. a tick is initialized while any position uses it as a bound
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableTick {
+    pub pool_id: u32,
+    pub tick: i32,
+    pub liquidity_gross: Nat,      // total liquidity of the positions using the tick as a bound
+    pub liquidity_net: Int,        // liquidity added to the pool when the price crosses the tick going up
+    pub fee_growth_outside_0: Nat, // fee growth of token_0 per unit of liquidity on the other side of the tick from the current price
+    pub fee_growth_outside_1: Nat, // fee growth of token_1 per unit of liquidity on the other side of the tick from the current price
+}
+
+impl StableTick {
+    pub fn new(pool_id: u32, tick: i32) -> Self {
+        Self {
+            pool_id,
+            tick,
+            liquidity_gross: nat_zero(),
+            liquidity_net: Int::from(0),
+            fee_growth_outside_0: nat_zero(),
+            fee_growth_outside_1: nat_zero(),
+        }
+    }
+}
+
+impl Storable for StableTick {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
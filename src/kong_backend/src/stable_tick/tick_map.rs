@@ -0,0 +1,56 @@
+use super::stable_tick::{StableTick, StableTickId};
+
+use crate::helpers::concentrated_liquidity_helpers::{MAX_TICK, MIN_TICK};
+use crate::stable_memory::TICK_MAP;
+
+pub fn get(pool_id: u32, tick: i32) -> Option<StableTick> {
+    TICK_MAP.with(|m| m.borrow().get(&StableTickId { pool_id, tick }))
+}
+
+/// next initialized tick of pool_id from tick
+/// if lte, the largest initialized tick <= tick (price going down), otherwise the smallest initialized tick > tick (price going up)
+pub fn get_next_initialized(pool_id: u32, tick: i32, lte: bool) -> Option<StableTick> {
+    TICK_MAP.with(|m| {
+        let map = m.borrow();
+        if lte {
+            let start = StableTickId { pool_id, tick: MIN_TICK };
+            let end = StableTickId { pool_id, tick };
+            map.range(start..=end).next_back().map(|(_, v)| v)
+        } else {
+            if tick >= MAX_TICK {
+                None?
+            }
+            let start = StableTickId { pool_id, tick: tick + 1 };
+            let end = StableTickId { pool_id, tick: MAX_TICK };
+            map.range(start..=end).next().map(|(_, v)| v)
+        }
+    })
+}
+
+pub fn update(tick: &StableTick) {
+    TICK_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StableTickId {
+                pool_id: tick.pool_id,
+                tick: tick.tick,
+            },
+            tick.clone(),
+        )
+    });
+}
+
+pub fn remove(pool_id: u32, tick: i32) -> Option<StableTick> {
+    TICK_MAP.with(|m| m.borrow_mut().remove(&StableTickId { pool_id, tick }))
+}
+
+pub fn remove_by_pool_id(pool_id: u32) {
+    TICK_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let start = StableTickId { pool_id, tick: MIN_TICK };
+        let end = StableTickId { pool_id, tick: MAX_TICK };
+        let keys_to_remove: Vec<_> = map.range(start..=end).map(|(k, _)| k).collect();
+        for key in keys_to_remove {
+            map.remove(&key);
+        }
+    });
+}
@@ -20,11 +20,11 @@ pub struct LPToken {
 }
 
 impl LPToken {
-    pub fn new(token_0: &StableToken, token_1: &StableToken, on_kong: bool) -> Self {
-        let symbol = token::symbol(token_0, token_1);
-        // LP token's address is the combination of token_0's token_id and token_1's token_id
+    pub fn new(token_0: &StableToken, token_1: &StableToken, fee_tier: Option<u32>, on_kong: bool) -> Self {
+        let symbol = token::symbol(token_0, token_1, fee_tier);
+        // LP token's address is the combination of token_0's token_id, token_1's token_id and the fee tier
         // which is unique making it a unique identifier for the LP token
-        let address = token::address(token_0, token_1);
+        let address = token::address(token_0, token_1, fee_tier);
         Self {
             token_id: 0,
             symbol,
@@ -129,10 +129,17 @@ impl Token for StableToken {
     }
 }
 
-pub fn symbol(token_0: &StableToken, token_1: &StableToken) -> String {
-    format!("{}_{}", token_0.symbol(), token_1.symbol())
+/// fee_tier is the LP fee in pips of pools that are not the first pool of their token pair
+pub fn symbol(token_0: &StableToken, token_1: &StableToken, fee_tier: Option<u32>) -> String {
+    match fee_tier {
+        Some(fee_tier) => format!("{}_{}_{}", token_0.symbol(), token_1.symbol(), fee_tier),
+        None => format!("{}_{}", token_0.symbol(), token_1.symbol()),
+    }
 }
 
-pub fn address(token_0: &StableToken, token_1: &StableToken) -> String {
-    format!("{}_{}", token_0.token_id(), token_1.token_id())
+pub fn address(token_0: &StableToken, token_1: &StableToken, fee_tier: Option<u32>) -> String {
+    match fee_tier {
+        Some(fee_tier) => format!("{}_{}_{}", token_0.token_id(), token_1.token_id(), fee_tier),
+        None => format!("{}_{}", token_0.token_id(), token_1.token_id()),
+    }
 }
@@ -6,6 +6,7 @@ pub mod send_tx;
 pub mod stable_tx;
 pub mod status_tx;
 pub mod swap_tx;
+pub mod sweep_kong_fees_tx;
 pub mod tx;
 pub mod tx_archive;
 pub mod tx_map;
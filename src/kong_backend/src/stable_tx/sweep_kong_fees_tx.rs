@@ -0,0 +1,48 @@
+use candid::{CandidType, Nat};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+use super::status_tx::StatusTx;
+
+/// Kong fees of a pool swept to the treasury account
+/// amount_0 and amount_1 are the Kong fees taken from the pool. the treasury receives them less the gas fees
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct SweepKongFeesTx {
+    pub tx_id: u64,
+    pub user_id: u32,
+    pub status: StatusTx,
+    pub pool_id: u32,
+    pub amount_0: Nat,
+    pub tx_id_0: Option<Nat>, // block index of the transfer of token_0 to the treasury
+    pub amount_1: Nat,
+    pub tx_id_1: Option<Nat>, // block index of the transfer of token_1 to the treasury
+    pub treasury_account: Account,
+    pub ts: u64,
+}
+
+impl SweepKongFeesTx {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_success(
+        user_id: u32,
+        pool_id: u32,
+        amount_0: &Nat,
+        tx_id_0: Option<&Nat>,
+        amount_1: &Nat,
+        tx_id_1: Option<&Nat>,
+        treasury_account: &Account,
+        ts: u64,
+    ) -> Self {
+        Self {
+            tx_id: 0,
+            user_id,
+            status: StatusTx::Success,
+            pool_id,
+            amount_0: amount_0.clone(),
+            tx_id_0: tx_id_0.cloned(),
+            amount_1: amount_1.clone(),
+            tx_id_1: tx_id_1.cloned(),
+            treasury_account: *treasury_account,
+            ts,
+        }
+    }
+}
@@ -14,6 +14,7 @@ impl Tx for StableTx {
             StableTx::RemoveLiquidity(tx) => tx.tx_id,
             StableTx::Swap(tx) => tx.tx_id,
             StableTx::Send(tx) => tx.tx_id,
+            StableTx::SweepKongFees(tx) => tx.tx_id,
         }
     }
 
@@ -24,6 +25,7 @@ impl Tx for StableTx {
             StableTx::RemoveLiquidity(tx) => tx.user_id,
             StableTx::Swap(tx) => tx.user_id,
             StableTx::Send(tx) => tx.user_id,
+            StableTx::SweepKongFees(tx) => tx.user_id,
         }
     }
 
@@ -34,6 +36,7 @@ impl Tx for StableTx {
             StableTx::RemoveLiquidity(tx) => tx.ts,
             StableTx::Swap(tx) => tx.ts,
             StableTx::Send(tx) => tx.ts,
+            StableTx::SweepKongFees(tx) => tx.ts,
         }
     }
 }
@@ -5,14 +5,16 @@ use super::add_liquidity_tx::AddLiquidityTx;
 use super::add_pool_tx::AddPoolTx;
 use super::remove_liquidity_tx::RemoveLiquidityTx;
 use super::send_tx::SendTx;
-use super::stable_tx::StableTx::{AddLiquidity, AddPool, RemoveLiquidity, Send, Swap};
+use super::stable_tx::StableTx::{AddLiquidity, AddPool, RemoveLiquidity, Send, Swap, SweepKongFees};
 use super::stable_tx::{StableTx, StableTxId};
 use super::swap_tx::SwapTx;
+use super::sweep_kong_fees_tx::SweepKongFeesTx;
 use super::tx::Tx;
 
 use crate::ic::logging::error_log;
 use crate::stable_kong_settings::kong_settings_map;
-use crate::stable_memory::TX_MAP;
+use crate::stable_lp_history::lp_history_map;
+use crate::stable_memory::{TX_ARCHIVE_MAP, TX_MAP};
 use crate::stable_pool::pool_map;
 
 const MAX_TXS: usize = 20;
@@ -78,6 +80,14 @@ pub fn get_by_user_and_token_id(
                                 return Some(v.clone());
                             }
                         }
+                        StableTx::SweepKongFees(ref sweep_kong_fees_tx) => {
+                            let pool_id = sweep_kong_fees_tx.pool_id;
+                            let token_0 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_0)?;
+                            let token_1 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_1)?;
+                            if token_0 == token_id || token_1 == token_id {
+                                return Some(v.clone());
+                            }
+                        }
                     }
                     return None;
                 }
@@ -88,7 +98,24 @@ pub fn get_by_user_and_token_id(
     })
 }
 
+/// call f with the add pool, add liquidity, remove liquidity and send txs, oldest first
+/// includes txs already moved to TX_ARCHIVE_MAP
+pub fn for_each_liquidity_tx(mut f: impl FnMut(&StableTx)) {
+    let is_liquidity_tx = |tx: &StableTx| matches!(tx, AddPool(_) | AddLiquidity(_) | RemoveLiquidity(_) | Send(_));
+    TX_ARCHIVE_MAP.with(|m| m.borrow().iter().filter(|(_, v)| is_liquidity_tx(v)).for_each(|(_, v)| f(&v)));
+    // txs in TX_MAP not archived yet
+    let start_tx_id = TX_ARCHIVE_MAP.with(|m| m.borrow().last_key_value().map_or(0, |(k, _)| k.0 + 1));
+    TX_MAP.with(|m| {
+        m.borrow()
+            .range(StableTxId(start_tx_id)..)
+            .filter(|(_, v)| is_liquidity_tx(v))
+            .for_each(|(_, v)| f(&v))
+    });
+}
+
 pub fn insert(tx: &StableTx) -> u64 {
+    // the LP histories of lp_performance() are updated with every add pool, add liquidity, remove liquidity and send tx
+    lp_history_map::insert_tx(tx);
     TX_MAP.with(|m| {
         let mut map = m.borrow_mut();
         let tx_id = kong_settings_map::inc_tx_map_idx();
@@ -98,6 +125,7 @@ pub fn insert(tx: &StableTx) -> u64 {
             RemoveLiquidity(tx) => RemoveLiquidity(RemoveLiquidityTx { tx_id, ..tx.clone() }),
             Swap(tx) => Swap(SwapTx { tx_id, ..tx.clone() }),
             Send(tx) => Send(SendTx { tx_id, ..tx.clone() }),
+            SweepKongFees(tx) => SweepKongFees(SweepKongFeesTx { tx_id, ..tx.clone() }),
         };
         map.insert(StableTxId(tx_id), insert_tx);
         tx_id
@@ -13,7 +13,6 @@ use serde::{Deserialize, Serialize};
 pub const ANONYMOUS_USER_ID: u32 = 0;
 #[allow(dead_code)]
 pub const ALL_USERS_USER_ID: u32 = 1;
-#[allow(dead_code)]
 pub const SYSTEM_USER_ID: u32 = 2;
 #[allow(dead_code)]
 pub const CLAIMS_TIMER_USER_ID: u32 = 3;
@@ -1,3 +1,5 @@
+use icrc_ledger_types::icrc1::account::Account;
+
 use super::referral_code::{generate_referral_code, REFERRAL_INTERVAL};
 use super::stable_user::{StableUser, StableUserId};
 
@@ -52,6 +54,19 @@ pub fn get_by_caller() -> Result<Option<StableUser>, String> {
     get_by_principal_id(&caller_principal_id())
 }
 
+/// return StableUser of an ICRC-1 account
+/// the default subaccount is the user of the owner's principal_id. other subaccounts are users with
+/// the ICRC-1 textual encoding of the account as principal_id
+///
+/// # Returns
+///
+/// * `Ok(Some(StableUser))` if account is a known user or None if account is not registered
+/// * `Err(String)` if the owner is anonymous
+pub fn get_by_account(account: &Account) -> Result<Option<StableUser>, String> {
+    principal_id_is_not_anonymous(&account.owner.to_text())?;
+    get_by_principal_id(&account.to_string())
+}
+
 /// return StableUser by referral code
 ///
 /// # Arguments
@@ -126,6 +141,29 @@ pub fn insert(referred_by: Option<&str>) -> Result<u32, String> {
     })
 }
 
+/// get the user_id of an ICRC-1 account, if not registered create a new user for the account
+pub fn insert_by_account(account: &Account) -> Result<u32, String> {
+    if let Some(user) = get_by_account(account)? {
+        return Ok(user.user_id);
+    }
+
+    let mut rng = get_pseudo_seed()?;
+    let user = StableUser {
+        user_id: kong_settings_map::inc_user_map_idx(),
+        principal_id: account.to_string(),
+        user_name: generate_user_name(&mut rng),
+        my_referral_code: generate_referral_code(&mut rng),
+        ..Default::default()
+    };
+    archive_user_to_kong_data(user.clone());
+
+    USER_MAP.with(|m| {
+        let user_id = user.user_id;
+        m.borrow_mut().insert(StableUserId(user_id), user);
+        Ok(user_id)
+    })
+}
+
 fn archive_user_to_kong_data(user: StableUser) {
     ic_cdk::spawn(async move {
         match serde_json::to_string(&user) {
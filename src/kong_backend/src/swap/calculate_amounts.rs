@@ -1,9 +1,10 @@
 use candid::Nat;
 
-use super::swap_amounts::swap_amounts;
+use super::swap_amounts::{swap_amounts, swap_amounts_exact_output, swap_amounts_single_pool};
 use super::swap_calc::SwapCalc;
 
 use crate::helpers::nat_helpers::nat_to_decimals_f64;
+use crate::stable_pool::stable_pool::StablePool;
 use crate::stable_token::{stable_token::StableToken, token::Token};
 
 pub fn calculate_amounts(
@@ -43,3 +44,80 @@ pub fn calculate_amounts(
 
     Ok((receive_amount, mid_price, price, slippage, txs))
 }
+
+/// exact-output version of calculate_amounts(). returns the pay amount needed to receive exactly receive_amount
+pub fn calculate_amounts_exact_output(
+    pay_token: &StableToken,
+    receive_token: &StableToken,
+    receive_amount: &Nat,
+    max_pay_amount: &Nat,
+    user_max_slippage: f64,
+) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    let (pay_amount, price, mid_price, slippage, txs) = swap_amounts_exact_output(pay_token, receive_token, receive_amount)?;
+
+    // check if pay_amount is within user's specified
+    if pay_amount > *max_pay_amount {
+        let decimals = pay_token.decimals();
+        let pay_amount_f64 = nat_to_decimals_f64(decimals, &pay_amount).unwrap_or(0_f64);
+        return Err(format!(
+            "Insufficient max pay amount. Requires {} {} with {}% slippage",
+            pay_amount_f64,
+            pay_token.symbol(),
+            slippage
+        ));
+    }
+
+    // check if slippage is within user's specified
+    if slippage > user_max_slippage {
+        let decimals = pay_token.decimals();
+        let pay_amount_f64 = nat_to_decimals_f64(decimals, &pay_amount).unwrap_or(0_f64);
+        return Err(format!(
+            "Slippage exceeded. Requires {} {} with {}% slippage",
+            pay_amount_f64,
+            pay_token.symbol(),
+            slippage
+        ));
+    }
+
+    Ok((pay_amount, mid_price, price, slippage, txs))
+}
+
+/// single pool version of calculate_amounts(). swaps through the pool only and without a gas fee
+pub fn calculate_amounts_single_pool(
+    pool: &StablePool,
+    pay_token: &StableToken,
+    pay_amount: &Nat,
+    receive_token: &StableToken,
+    user_receive_amount: Option<&Nat>,
+    user_max_slippage: f64,
+) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    let (receive_amount, price, mid_price, slippage, txs) = swap_amounts_single_pool(pool, pay_token.token_id(), pay_amount)?;
+
+    // check if receive_amount is within user's specified
+    if let Some(user_receive_amount) = user_receive_amount {
+        if receive_amount < *user_receive_amount {
+            let decimals = receive_token.decimals();
+            let receive_amount_with_fees_and_gas_f64 = nat_to_decimals_f64(decimals, &receive_amount).unwrap_or(0_f64);
+            return Err(format!(
+                "Insufficient receive amount. Can only receive {} {} with {}% slippage",
+                receive_amount_with_fees_and_gas_f64,
+                receive_token.symbol(),
+                slippage
+            ));
+        }
+    }
+
+    // check if slippage is within user's specified
+    if slippage > user_max_slippage {
+        let decimals = receive_token.decimals();
+        let receive_amount_with_fees_and_gas_f64 = nat_to_decimals_f64(decimals, &receive_amount).unwrap_or(0_f64);
+        return Err(format!(
+            "Slippage exceeded. Can only receive {} {} with {}% slippage",
+            receive_amount_with_fees_and_gas_f64,
+            receive_token.symbol(),
+            slippage
+        ));
+    }
+
+    Ok((receive_amount, mid_price, price, slippage, txs))
+}
@@ -1,3 +1,4 @@
+pub mod archive_to_kong_data;
 pub mod calculate_amounts;
 pub mod return_pay_token;
 pub mod send_receive_token;
@@ -9,7 +10,7 @@ pub mod swap_calc;
 pub mod swap_calc_impl;
 pub mod swap_reply;
 pub mod swap_reply_helpers;
+pub mod swap_router;
 pub mod swap_transfer;
 pub mod swap_transfer_from;
 pub mod update_liquidity_pool;
-pub mod archive_to_kong_data;
@@ -3,7 +3,7 @@ use icrc_ledger_types::icrc1::account::Account;
 
 use super::swap_reply_helpers::create_swap_reply_failed;
 
-use crate::helpers::nat_helpers::{nat_subtract, nat_zero};
+use crate::helpers::nat_helpers::{nat_is_zero, nat_subtract, nat_zero};
 use crate::ic::{address::Address, transfer::icrc1_transfer};
 use crate::stable_claim::{claim_map, stable_claim::StableClaim};
 use crate::stable_request::reply::Reply;
@@ -64,3 +64,111 @@ pub async fn return_pay_token(
     let reply = create_swap_reply_failed(request_id, pay_token, pay_amount, receive_token, transfer_ids, &claim_ids, ts);
     request_map::update_reply(request_id, Reply::Swap(reply));
 }
+
+/// return the part of the pay token that was not used by an exact-output swap
+/// unlike return_pay_token(), the swap has succeeded so no failed reply is created
+#[allow(clippy::too_many_arguments)]
+pub async fn return_unused_pay_token(
+    request_id: u64,
+    user_id: u32,
+    to_principal_id: &Account,
+    pay_token: &StableToken,
+    unused_pay_amount: &Nat,
+    transfer_ids: &mut Vec<u64>,
+    claim_ids: &mut Vec<u64>,
+    ts: u64,
+) {
+    // nothing to return if the unused amount does not cover the gas fee
+    if nat_is_zero(unused_pay_amount) || record_dust(request_id, StatusCode::ReturnUnusedPayTokenFailed, pay_token, unused_pay_amount) {
+        return;
+    }
+    let unused_pay_amount_with_gas = nat_subtract(unused_pay_amount, &pay_token.fee()).unwrap_or(nat_zero());
+
+    request_map::update_status(request_id, StatusCode::ReturnUnusedPayToken, None);
+
+    match icrc1_transfer(&unused_pay_amount_with_gas, to_principal_id, pay_token, None).await {
+        Ok(tx_id) => {
+            let transfer_id = transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: false,
+                amount: unused_pay_amount_with_gas,
+                token_id: pay_token.token_id(),
+                tx_id: TxId::BlockIndex(tx_id),
+                ts,
+            });
+            transfer_ids.push(transfer_id);
+            request_map::update_status(request_id, StatusCode::ReturnUnusedPayTokenSuccess, None);
+        }
+        Err(e) => {
+            let message = match claim_map::insert(&StableClaim::new(
+                user_id,
+                pay_token.token_id(),
+                unused_pay_amount,
+                Some(request_id),
+                Some(Address::PrincipalId(*to_principal_id)),
+                ts,
+            )) {
+                Ok(claim_id) => {
+                    claim_ids.push(claim_id);
+                    format!("Saved as claim #{}. {}", claim_id, e)
+                }
+                Err(e) => format!("Failed to save claim. {}", e),
+            };
+            request_map::update_status(request_id, StatusCode::ReturnUnusedPayTokenFailed, Some(&message));
+        }
+    };
+}
+
+/// record an amount not more than the gas fee on the request. it can not be returned so it is kept by Kong
+///
+/// # Returns
+/// true if amount was recorded as dust
+pub fn record_dust(request_id: u64, status_code: StatusCode, token: &StableToken, amount: &Nat) -> bool {
+    if *amount > token.fee() {
+        return false;
+    }
+    let message = format!(
+        "{} {} not enough to pay the gas fee {}. Kept by Kong",
+        amount,
+        token.symbol(),
+        token.fee()
+    );
+    request_map::update_status(request_id, status_code, Some(&message));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::ic_token;
+    use crate::stable_memory::REQUEST_MAP;
+    use crate::stable_request::request::Request;
+    use crate::stable_request::stable_request::{StableRequest, StableRequestId};
+
+    #[test]
+    fn test_record_dust() {
+        REQUEST_MAP.with(|m| {
+            m.borrow_mut().insert(
+                StableRequestId(1),
+                StableRequest {
+                    request_id: 1,
+                    ..StableRequest::new(1, &Request::Claim(1), 0)
+                },
+            )
+        });
+        // gas fee of 10
+        let token = StableToken::IC(ic_token(1, "ckBTC"));
+        assert!(!record_dust(1, StatusCode::ReturnUnusedPayTokenFailed, &token, &Nat::from(11_u32)));
+        assert!(record_dust(1, StatusCode::ReturnUnusedPayTokenFailed, &token, &Nat::from(10_u32)));
+
+        let statuses = request_map::get_by_request_and_user_id(Some(1), None, None).pop().unwrap().statuses;
+        assert_eq!(statuses.len(), 1);
+        assert!(matches!(statuses[0].status_code, StatusCode::ReturnUnusedPayTokenFailed));
+        assert_eq!(
+            statuses[0].message.as_deref(),
+            Some("10 ckBTC not enough to pay the gas fee 10. Kept by Kong")
+        );
+    }
+}
@@ -24,14 +24,13 @@ pub async fn send_receive_token(
     receive_amount: &Nat,
     to_address: &Address,
     transfer_ids: &mut Vec<u64>,
+    claim_ids: &mut Vec<u64>,
     mid_price: f64,
     price: f64,
     slippage: f64,
     txs: &[SwapCalc],
     ts: u64,
 ) -> SwapReply {
-    let mut claim_ids = Vec::new();
-
     request_map::update_status(request_id, StatusCode::SendReceiveToken, None);
 
     // send ICP using icp_transfer or ICRC1 using icrc1_transfer
@@ -84,7 +83,7 @@ pub async fn send_receive_token(
         slippage,
         txs,
         transfer_ids,
-        &claim_ids,
+        claim_ids,
         ts,
     );
     let tx_id = tx_map::insert(&StableTx::Swap(swap_tx.clone()));
@@ -4,18 +4,25 @@ use num::{FromPrimitive, One, Zero};
 use num_traits::ToPrimitive;
 
 use super::swap_calc::SwapCalc;
+use super::swap_router::{RoutingPolicy, SwapHop, TokenGraph, MAX_SPLIT_CANDIDATES};
 
 use crate::helpers::math_helpers::price_rounded;
 use crate::helpers::math_helpers::round_f64;
 use crate::helpers::nat_helpers::nat_zero;
-use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_to_decimal_precision};
-use crate::stable_pool::pool_map;
-use crate::stable_pool::stable_pool::StablePool;
+use crate::helpers::nat_helpers::{
+    nat_add, nat_divide, nat_divide_ceil, nat_is_zero, nat_multiply, nat_subtract, nat_to_bigint, nat_to_decimal_precision,
+    nat_to_decimal_precision_ceil,
+};
+use crate::helpers::stableswap_helpers::{stableswap_amount_in, stableswap_amount_out};
+use crate::helpers::weighted_helpers::{weighted_amount_in, weighted_amount_out};
+use crate::stable_pool::concentrated_liquidity;
+use crate::stable_pool::stable_pool::{PoolType, StablePool};
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
-use crate::stable_token::token_map;
 use crate::stable_user::user_map;
 
+const MAX_PAY_AMOUNT_ITERATIONS: usize = 8;
+
 pub fn swap_mid_price(pay_token: &StableToken, receive_token: &StableToken) -> Result<f64, String> {
     let (_, mid_price, _, _, _) = swap_amounts(pay_token, &nat_zero(), receive_token)?;
     Ok(mid_price)
@@ -31,11 +38,9 @@ pub fn swap_amounts(
     // Receive token
     let receive_token_id = receive_token.token_id();
 
-    let mut txs = Vec::new();
-
     // if tokens are the same return the same amount
     if pay_token_id == receive_token_id {
-        return Ok((pay_amount.clone(), 1.0, 1.0, 0.0, txs));
+        return Ok((pay_amount.clone(), 1.0, 1.0, 0.0, Vec::new()));
     }
 
     // if called from swap_mid_price, no need to check user's fee level
@@ -45,328 +50,391 @@ pub fn swap_amounts(
         user_map::get_by_caller().ok().flatten().unwrap_or_default().fee_level
     };
 
-    // check if direct pool exists
-    if let Some(pool) = pool_map::get_by_token_ids(pay_token_id, receive_token_id) {
-        let swap = swap_amount_0(&pool, pay_amount, Some(user_fee_level), None, None)?;
-        let receive_amount = swap.receive_amount_with_fees_and_gas();
-        let price = swap.get_price().ok_or("Invalid price")?;
-        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-        let mid_price = swap.get_mid_price().ok_or("Invalid mid price")?;
-        let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-        let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
-        txs.push(swap);
-        return Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs));
+    let policy = RoutingPolicy::from_kong_settings();
+    let graph = TokenGraph::on_kong();
+    let routes = graph.get_routes(pay_token_id, receive_token_id, policy.max_hops);
+    if routes.is_empty() {
+        return Err("Pool not found".to_string());
+    }
+
+    // evaluate every route and keep the one with the most receive amount
+    let ranked_routes = rank_routes(&graph, &policy, routes, pay_amount, user_fee_level)?;
+    let (mut receive_amount, _, mut txs) = ranked_routes[0].clone();
+
+    // see if splitting the pay amount across several routes gives a better receive amount
+    if !nat_is_zero(pay_amount) {
+        let candidate_routes = split_candidates(ranked_routes);
+        if let Some((split_receive_amount, split_txs)) = swap_amounts_split(&graph, &policy, &candidate_routes, pay_amount, user_fee_level)
+        {
+            if split_receive_amount > receive_amount {
+                receive_amount = split_receive_amount;
+                txs = split_txs;
+            }
+        }
+    }
+
+    let (price, mid_price) = swap_prices(&txs)?;
+    let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
+    let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
+    let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
+
+    Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs))
+}
+
+/// swap pay_amount through a single pool without a gas fee. used when the receive amount stays in Kong, ie. zap_add_liquidity()
+pub fn swap_amounts_single_pool(
+    pool: &StablePool,
+    pay_token_id: u32,
+    pay_amount: &Nat,
+) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    let user_fee_level = if nat_is_zero(pay_amount) {
+        0
+    } else {
+        user_map::get_by_caller().ok().flatten().unwrap_or_default().fee_level
     };
 
-    if let Some(pool) = pool_map::get_by_token_ids(receive_token_id, pay_token_id) {
-        let swap = swap_amount_1(&pool, pay_amount, Some(user_fee_level), None, None)?;
-        let receive_amount = swap.receive_amount_with_fees_and_gas();
-        let price = swap.get_price().ok_or("Invalid price")?;
-        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-        let mid_price = swap.get_mid_price().ok_or("Invalid mid price")?;
-        let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-        let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
-        txs.push(swap);
-        return Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs));
+    let swap = swap_amount_single_pool(pool, pay_token_id, pay_amount, user_fee_level)?;
+    let receive_amount = swap.receive_amount_with_fees_and_gas();
+    let txs = vec![swap];
+
+    let (price, mid_price) = swap_prices(&txs)?;
+    let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
+    let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
+    let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
+
+    Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs))
+}
+
+/// swap of pay_amount through the pool only and without a gas fee
+pub fn swap_amount_single_pool(pool: &StablePool, pay_token_id: u32, pay_amount: &Nat, user_fee_level: u8) -> Result<SwapCalc, String> {
+    if pay_token_id == pool.token_id_0 {
+        swap_amount_0(pool, pay_amount, Some(user_fee_level), None, Some(&nat_zero()))
+    } else if pay_token_id == pool.token_id_1 {
+        swap_amount_1(pool, pay_amount, Some(user_fee_level), None, Some(&nat_zero()))
+    } else {
+        Err(format!("Token not in pool {}", pool.symbol()))
+    }
+}
+
+/// exact-output swap. calculates the pay amount needed to receive exactly receive_amount (after fees and gas)
+/// returns the pay amount, price, mid price, slippage and the swaps. the route that needs the least pay amount is used
+pub fn swap_amounts_exact_output(
+    pay_token: &StableToken,
+    receive_token: &StableToken,
+    receive_amount: &Nat,
+) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    // Pay token
+    let pay_token_id = pay_token.token_id();
+    // Receive token
+    let receive_token_id = receive_token.token_id();
+
+    if pay_token_id == receive_token_id {
+        return Ok((receive_amount.clone(), 1.0, 1.0, 0.0, Vec::new()));
+    }
+
+    if nat_is_zero(receive_amount) {
+        return Err("Receive amount is zero".to_string());
+    }
+
+    let user_fee_level = user_map::get_by_caller().ok().flatten().unwrap_or_default().fee_level;
+
+    let policy = RoutingPolicy::from_kong_settings();
+    let graph = TokenGraph::on_kong();
+    let routes = graph.get_routes(pay_token_id, receive_token_id, policy.max_hops);
+    if routes.is_empty() {
+        return Err("Pool not found".to_string());
+    }
+
+    // evaluate every route and keep the one with the least pay amount
+    let mut best_route: Option<(Nat, Vec<SwapCalc>)> = None;
+    let mut route_error = None;
+    for route in routes.iter() {
+        match swap_amounts_route_exact_output(&graph, &policy, route, receive_amount, user_fee_level) {
+            Ok(txs) => {
+                let pay_amount = txs.first().map(|tx| tx.pay_amount.clone()).unwrap_or(nat_zero());
+                if best_route.as_ref().is_none_or(|(best_amount, _)| pay_amount < *best_amount) {
+                    best_route = Some((pay_amount, txs));
+                }
+            }
+            Err(e) => {
+                if route_error.is_none() {
+                    route_error = Some(e);
+                }
+            }
+        }
+    }
+    let (pay_amount, txs) = match best_route {
+        Some(best_route) => best_route,
+        None => return Err(route_error.unwrap_or("Pool not found".to_string())),
     };
 
-    // test for 2-step swap via ckUSDT or ICP
-    let ckusdt_token_id = token_map::get_ckusdt()?.token_id();
-    let icp_token_id = token_map::get_icp()?.token_id();
-    let pool1_ckusdt = pool_map::get_by_token_ids(pay_token_id, ckusdt_token_id);
-    let pool2_ckusdt = pool_map::get_by_token_ids(receive_token_id, ckusdt_token_id);
-    let pool1_icp = pool_map::get_by_token_ids(pay_token_id, icp_token_id);
-    let pool2_icp = pool_map::get_by_token_ids(receive_token_id, icp_token_id);
-    if pool1_ckusdt.is_some() && pool2_ckusdt.is_some() || pool1_icp.is_some() && pool2_icp.is_some() {
-        let swaps_ckusdt = if pool1_ckusdt.is_some() && pool2_ckusdt.is_some() {
-            // 2-step swap
-            // split the LP fee between the two swaps. the "+ 1) / 2" will round up the integer
-            // 1st swap no gas fees as this is intermediate swap
-            // 2nd swap use standard gas fees
-            // both pools: token0/ckUSDT and token1/ckUSDT
-            let pool1 = match pool1_ckusdt {
-                Some(ref pool) => pool,
-                None => return Err("Pool not found".to_string()),
-            };
-            let pool2 = match pool2_ckusdt {
-                Some(ref pool) => pool,
-                None => return Err("Pool not found".to_string()),
-            };
-            let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-            // swap token0 to ckUSDT
-            let swap1 = swap_amount_0(pool1, pay_amount, Some(user_fee_level), Some(swap1_lp_fee), Some(&nat_zero()))?;
-            let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-            // swap ckUSDT to token1 (reverse order of pool)
-            let swap2 = swap_amount_1(
-                pool2,
-                &swap1.receive_amount_with_fees_and_gas(),
-                Some(user_fee_level),
-                Some(swap2_lp_fee),
-                None,
-            )?;
-            Some((swap1, swap2))
-        } else {
-            None
-        };
+    let (price, mid_price) = swap_prices(&txs)?;
+    let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
+    let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
+    let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
 
-        let swaps_icp = if pool1_icp.is_some() && pool2_icp.is_some() {
-            // both pools: token0/ICP and token1/ICP
-            let pool1 = match pool1_icp {
-                Some(ref pool) => pool,
-                None => return Err("Pool not found".to_string()),
-            };
-            let pool2 = match pool2_icp {
-                Some(ref pool) => pool,
-                None => return Err("Pool not found".to_string()),
-            };
-            let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-            // swap token0 to ICP
-            let swap1 = swap_amount_0(pool1, pay_amount, Some(user_fee_level), Some(swap1_lp_fee), Some(&nat_zero()))?;
-            let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-            // swap ICP to token1 (reverse order of pool)
-            let swap2 = swap_amount_1(
-                pool2,
-                &swap1.receive_amount_with_fees_and_gas(),
-                Some(user_fee_level),
-                Some(swap2_lp_fee),
-                None,
-            )?;
-            Some((swap1, swap2))
-        } else {
-            None
-        };
+    Ok((pay_amount, price_f64, mid_price_f64, slippage_f64, txs))
+}
 
-        // if both ckUSDT and ICP routes are possible, choose the one with the most receive amount
-        let (swap1, swap2) = if swaps_ckusdt.is_some() && swaps_icp.is_some() {
-            let swaps_ckusdt = swaps_ckusdt.unwrap();
-            let swaps_icp = swaps_icp.unwrap();
-            if swaps_ckusdt.1.receive_amount >= swaps_icp.1.receive_amount {
-                (swaps_ckusdt.0, swaps_ckusdt.1)
-            } else {
-                (swaps_icp.0, swaps_icp.1)
+/// calculate the swaps of each route for the whole pay amount, most receive amount first
+/// routes are ordered by number of hops so on a tie (ie. mid price with zero pay_amount) the shortest route comes first
+///
+/// # Returns
+/// the receive amount, route and swaps of each route that could be calculated
+/// Err - the error of the first route if no route could be calculated
+#[allow(clippy::type_complexity)]
+fn rank_routes(
+    graph: &TokenGraph,
+    policy: &RoutingPolicy,
+    routes: Vec<Vec<SwapHop>>,
+    pay_amount: &Nat,
+    user_fee_level: u8,
+) -> Result<Vec<(Nat, Vec<SwapHop>, Vec<SwapCalc>)>, String> {
+    let mut ranked_routes = Vec::with_capacity(routes.len());
+    let mut route_error = None;
+    for route in routes {
+        match swap_amounts_route(graph, policy, &route, pay_amount, user_fee_level, 0, true) {
+            Ok(txs) => ranked_routes.push((route_receive_amount(&txs), route, txs)),
+            Err(e) => {
+                if route_error.is_none() {
+                    route_error = Some(e);
+                }
             }
-        } else if swaps_ckusdt.is_some() {
-            let swaps_ckusdt = swaps_ckusdt.unwrap();
-            (swaps_ckusdt.0, swaps_ckusdt.1)
-        } else if swaps_icp.is_some() {
-            let swaps_icp = swaps_icp.unwrap();
-            (swaps_icp.0, swaps_icp.1)
+        }
+    }
+    if ranked_routes.is_empty() {
+        return Err(route_error.unwrap_or("Pool not found".to_string()));
+    }
+    // stable sort keeps the shortest route first on a tie
+    ranked_routes.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(ranked_routes)
+}
+
+/// the best ranked routes to split across. every step of a split prices every candidate, so only MAX_SPLIT_CANDIDATES are kept
+#[allow(clippy::type_complexity)]
+fn split_candidates(ranked_routes: Vec<(Nat, Vec<SwapHop>, Vec<SwapCalc>)>) -> Vec<Vec<SwapHop>> {
+    ranked_routes
+        .into_iter()
+        .take(MAX_SPLIT_CANDIDATES)
+        .map(|(_, route, _)| route)
+        .collect()
+}
+
+/// calculate the swaps for each hop of a route. the receive amount of a hop is the pay amount of the next hop
+/// route_idx is recorded in each leg. if charge_gas is false, the last hop does not take gas fees (used when splitting)
+fn swap_amounts_route(
+    graph: &TokenGraph,
+    policy: &RoutingPolicy,
+    route: &[SwapHop],
+    pay_amount: &Nat,
+    user_fee_level: u8,
+    route_idx: u8,
+    charge_gas: bool,
+) -> Result<Vec<SwapCalc>, String> {
+    let num_hops = route.len();
+    let mut txs: Vec<SwapCalc> = Vec::with_capacity(num_hops);
+    let mut amount = pay_amount.clone();
+    for (hop, swap_hop) in route.iter().enumerate() {
+        let pool = graph.get_pool(swap_hop.pool_id).ok_or("Pool not found")?;
+        let use_lp_fee = policy.lp_fee_pips(pool, num_hops);
+        let use_gas_fee = if charge_gas {
+            policy.gas_fee(hop, num_hops)
         } else {
-            // should never happen
-            return Err("Pool not found".to_string());
+            Some(nat_zero())
         };
-        let receive_amount = swap2.receive_amount_with_fees_and_gas();
-        let swap1_price = swap1.get_price().ok_or("Invalid swap1 price")?;
-        let swap2_price = swap2.get_price().ok_or("Invalid swap2 price")?;
-        let price = swap1_price * swap2_price;
-        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-        let swap1_mid_price = swap1.get_mid_price().ok_or("Invalid swap1 mid price")?;
-        let swap2_mid_price = swap2.get_mid_price().ok_or("Invalid swap2 mid price")?;
-        let mid_price = swap1_mid_price * swap2_mid_price;
-        let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-        let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
-        txs.push(swap1);
-        txs.push(swap2);
-        return Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs));
-    }
-
-    // special case where pay token is ckUSDT and token0/ckUSDT pool does not exist so need to use token0/ICP pool
-    let pool1_icp_ckusdt = pool_map::get_by_token_ids(icp_token_id, ckusdt_token_id);
-    if pay_token_id == ckusdt_token_id && pool1_icp_ckusdt.is_some() && pool2_icp.is_some() {
-        let pool1 = match pool1_icp_ckusdt {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
-        };
-        let pool2 = match pool2_icp {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
+        let mut swap = if swap_hop.is_token_0(pool) {
+            swap_amount_0(pool, &amount, Some(user_fee_level), use_lp_fee, use_gas_fee.as_ref())?
+        } else {
+            // reverse order of pool
+            swap_amount_1(pool, &amount, Some(user_fee_level), use_lp_fee, use_gas_fee.as_ref())?
         };
-        let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-        // swap ckUSDT to ICP (reverse order of pool)
-        let swap1 = swap_amount_1(pool1, pay_amount, Some(user_fee_level), Some(swap1_lp_fee), Some(&nat_zero()))?;
-        let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-        // swap ICP to token1 (reverse order of pool)
-        let swap2 = swap_amount_1(
-            pool2,
-            &swap1.receive_amount_with_fees_and_gas(),
-            Some(user_fee_level),
-            Some(swap2_lp_fee),
-            None,
-        )?;
-        let receive_amount = swap2.receive_amount_with_fees_and_gas();
-        let swap1_price = swap1.get_price().ok_or("Invalid swap1 price")?;
-        let swap2_price = swap2.get_price().ok_or("Invalid swap2 price")?;
-        let price = swap1_price * swap2_price;
-        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-        let swap1_mid_price = swap1.get_mid_price().ok_or("Invalid swap1 mid price")?;
-        let swap2_mid_price = swap2.get_mid_price().ok_or("Invalid swap2 mid price")?;
-        let mid_price = swap1_mid_price * swap2_mid_price;
-        let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-        let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
-        txs.push(swap1);
-        txs.push(swap2);
-        return Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs));
-    };
+        swap.route = route_idx;
+        amount = swap.receive_amount_with_fees_and_gas();
+        txs.push(swap);
+    }
+    Ok(txs)
+}
 
-    // special case where receieve token is ckUSDT and token0/ckUSDT pool does not exist so need to use token0/ICP pool
-    let pool2_icp_ckusdt = pool_map::get_by_token_ids(icp_token_id, ckusdt_token_id);
-    if receive_token_id == ckusdt_token_id && pool1_icp.is_some() && pool2_icp_ckusdt.is_some() {
-        let pool1 = match pool1_icp {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
-        };
-        let pool2 = match pool2_icp_ckusdt {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
-        };
-        let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-        // swap token0 to ICP
-        let swap1 = swap_amount_0(pool1, pay_amount, Some(user_fee_level), Some(swap1_lp_fee), Some(&nat_zero()))?;
-        let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-        // swap ICP to ckUSDT
-        let swap2 = swap_amount_0(
-            pool2,
-            &swap1.receive_amount_with_fees_and_gas(),
-            Some(user_fee_level),
-            Some(swap2_lp_fee),
-            None,
-        )?;
-        let receive_amount = swap2.receive_amount_with_fees_and_gas();
-        let swap1_price = swap1.get_price().ok_or("Invalid swap1 price")?;
-        let swap2_price = swap2.get_price().ok_or("Invalid swap2 price")?;
-        let price = swap1_price * swap2_price;
-        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-        let swap1_mid_price = swap1.get_mid_price().ok_or("Invalid swap1 mid price")?;
-        let swap2_mid_price = swap2.get_mid_price().ok_or("Invalid swap2 mid price")?;
-        let mid_price = swap1_mid_price * swap2_mid_price;
-        let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-        let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
-        txs.push(swap1);
-        txs.push(swap2);
-        return Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs));
-    };
+/// split the pay amount across the best routes that do not share any pools
+/// the pay amount is divided into policy.split_steps parts and each part is given to the route that adds the most receive amount.
+/// returns None if the swap cannot be split across at least 2 routes
+fn swap_amounts_split(
+    graph: &TokenGraph,
+    policy: &RoutingPolicy,
+    routes: &[Vec<SwapHop>],
+    pay_amount: &Nat,
+    user_fee_level: u8,
+) -> Option<(Nat, Vec<SwapCalc>)> {
+    if policy.max_splits < 2 || policy.split_steps < 2 {
+        return None;
+    }
 
-    // test for 3-step swap via token0/ckUSDT, ICP/ckUSDT and token1/ICP
-    let pool3_icp = pool2_icp;
-    if pool1_ckusdt.is_some() && pool2_icp_ckusdt.is_some() && pool3_icp.is_some() {
-        let pool1 = match pool1_ckusdt {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
-        };
-        let pool2 = match pool2_icp_ckusdt {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
-        };
-        let pool3 = match pool3_icp {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
-        };
-        let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 3;
-        // swap token0 to ckUSDT
-        let swap1 = swap_amount_0(
-            pool1,
-            pay_amount,
-            Some(user_fee_level),
-            Some(swap1_lp_fee),
-            Some(&nat_zero()), // swap1 do not take gas fees
-        )?;
-        let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 3;
-        // swap ckUSDT to ICP (reverse order of pool)
-        let swap2 = swap_amount_1(
-            pool2,
-            &swap1.receive_amount_with_fees_and_gas(),
-            Some(user_fee_level),
-            Some(swap2_lp_fee),
-            Some(&nat_zero()), // swap2 do not take gas fees
-        )?;
-        let swap3_lp_fee = (pool3.lp_fee_bps + 1) / 3;
-        // swap ICP to token1 (reverse order of pool)
-        let swap3 = swap_amount_1(
-            pool3,
-            &swap2.receive_amount_with_fees_and_gas(),
-            Some(user_fee_level),
-            Some(swap3_lp_fee),
-            None,
-        )?;
-        let receive_amount = swap3.receive_amount_with_fees_and_gas();
-        let swap1_price = swap1.get_price().ok_or("Invalid swap1 price")?;
-        let swap2_price = swap2.get_price().ok_or("Invalid swap2 price")?;
-        let swap3_price = swap3.get_price().ok_or("Invalid swap3 price")?;
-        let price = swap1_price * swap2_price * swap3_price;
-        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-        let swap1_mid_price = swap1.get_mid_price().ok_or("Invalid swap1 mid price")?;
-        let swap2_mid_price = swap2.get_mid_price().ok_or("Invalid swap2 mid price")?;
-        let swap3_mid_price = swap3.get_mid_price().ok_or("Invalid swap3 mid price")?;
-        let mid_price = swap1_mid_price * swap2_mid_price * swap3_mid_price;
-        let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-        let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
-        txs.push(swap1);
-        txs.push(swap2);
-        txs.push(swap3);
-        return Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs));
-    };
+    // rank the routes by receive amount for the whole pay amount
+    let mut ranked_routes: Vec<(Nat, &Vec<SwapHop>)> = routes
+        .iter()
+        .filter_map(|route| {
+            swap_amounts_route(graph, policy, route, pay_amount, user_fee_level, 0, false)
+                .ok()
+                .map(|txs| (route_receive_amount(&txs), route))
+        })
+        .collect();
+    ranked_routes.sort_by(|a, b| b.0.cmp(&a.0));
+
+    // pick the best routes. routes must not share pools as each route is calculated against the current pool balances
+    let mut split_routes: Vec<&Vec<SwapHop>> = Vec::new();
+    let mut split_pool_ids: Vec<u32> = Vec::new();
+    for (_, route) in ranked_routes {
+        if route.iter().any(|hop| split_pool_ids.contains(&hop.pool_id)) {
+            continue;
+        }
+        split_pool_ids.extend(route.iter().map(|hop| hop.pool_id));
+        split_routes.push(route);
+        if split_routes.len() == policy.max_splits {
+            break;
+        }
+    }
+    if split_routes.len() < 2 {
+        return None;
+    }
 
-    // test for 3-step swap via token0/ICP, ICP/ckUSDT and token1/ckUSDT
-    let pool3_ckusdt = pool2_ckusdt;
-    if pool1_icp.is_some() && pool2_icp_ckusdt.is_some() && pool3_ckusdt.is_some() {
-        let pool1 = match pool1_icp {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
-        };
-        let pool2 = match pool2_icp_ckusdt {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
+    // give each step of the pay amount to the route with the best marginal receive amount
+    let steps = Nat::from(policy.split_steps);
+    let step_amount = nat_divide(pay_amount, &steps)?;
+    if nat_is_zero(&step_amount) {
+        return None;
+    }
+    let last_step_amount = nat_subtract(pay_amount, &nat_multiply(&step_amount, &nat_subtract(&steps, &Nat::from(1_u8))?))?;
+    let mut pay_amounts = vec![nat_zero(); split_routes.len()];
+    let mut receive_amounts = vec![nat_zero(); split_routes.len()];
+    for step in 0..policy.split_steps {
+        let amount = if step + 1 == policy.split_steps {
+            &last_step_amount
+        } else {
+            &step_amount
         };
-        let pool3 = match pool3_ckusdt {
-            Some(ref pool) => pool,
-            None => return Err("Pool not found".to_string()),
+        let mut best_step: Option<(usize, Nat, Nat)> = None; // (route, marginal receive amount, receive amount)
+        for (i, route) in split_routes.iter().enumerate() {
+            let route_pay_amount = nat_add(&pay_amounts[i], amount);
+            let Ok(txs) = swap_amounts_route(graph, policy, route, &route_pay_amount, user_fee_level, 0, false) else {
+                continue;
+            };
+            let route_receive_amount = route_receive_amount(&txs);
+            let marginal_amount = nat_subtract(&route_receive_amount, &receive_amounts[i]).unwrap_or(nat_zero());
+            if best_step.as_ref().is_none_or(|(_, best_amount, _)| marginal_amount > *best_amount) {
+                best_step = Some((i, marginal_amount, route_receive_amount));
+            }
+        }
+        let (i, _, route_receive_amount) = best_step?;
+        pay_amounts[i] = nat_add(&pay_amounts[i], amount);
+        receive_amounts[i] = route_receive_amount;
+    }
+
+    // only keep the routes that were used. the route with the largest pay amount takes the gas fee
+    let mut used_routes: Vec<(&Vec<SwapHop>, Nat)> = split_routes
+        .into_iter()
+        .zip(pay_amounts)
+        .filter(|(_, route_pay_amount)| !nat_is_zero(route_pay_amount))
+        .collect();
+    if used_routes.len() < 2 {
+        return None;
+    }
+    used_routes.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let mut receive_amount = nat_zero();
+    let mut txs = Vec::new();
+    for (route_idx, (route, route_pay_amount)) in used_routes.iter().enumerate() {
+        let route_txs = swap_amounts_route(
+            graph,
+            policy,
+            route,
+            route_pay_amount,
+            user_fee_level,
+            route_idx as u8,
+            route_idx == 0,
+        )
+        .ok()?;
+        receive_amount = nat_add(&receive_amount, &route_receive_amount(&route_txs));
+        txs.extend(route_txs);
+    }
+    Some((receive_amount, txs))
+}
+
+/// calculate the swaps of a route so the last hop receives exactly receive_amount (after fees and gas)
+/// works backwards from the last hop to find the pay amount of each hop, then calculates the swaps forward from the first hop.
+/// any rounding excess of the last hop is added to its LP fee so the receive amount is exact
+fn swap_amounts_route_exact_output(
+    graph: &TokenGraph,
+    policy: &RoutingPolicy,
+    route: &[SwapHop],
+    receive_amount: &Nat,
+    user_fee_level: u8,
+) -> Result<Vec<SwapCalc>, String> {
+    let num_hops = route.len();
+    let mut amount = receive_amount.clone();
+    for (hop, swap_hop) in route.iter().enumerate().rev() {
+        let pool = graph.get_pool(swap_hop.pool_id).ok_or("Pool not found")?;
+        let use_lp_fee = policy.lp_fee_pips(pool, num_hops);
+        let use_gas_fee = policy.gas_fee(hop, num_hops);
+        amount = if swap_hop.is_token_0(pool) {
+            swap_pay_amount_0(pool, &amount, Some(user_fee_level), use_lp_fee, use_gas_fee.as_ref())?
+        } else {
+            // reverse order of pool
+            swap_pay_amount_1(pool, &amount, Some(user_fee_level), use_lp_fee, use_gas_fee.as_ref())?
         };
-        let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 3;
-        // swap token0 to ICP
-        let swap1 = swap_amount_0(
-            pool1,
-            pay_amount,
-            Some(user_fee_level),
-            Some(swap1_lp_fee),
-            Some(&nat_zero()), // swap1 do not take gas fees
-        )?;
-        let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 3;
-        // swap ICP to ckUSDT
-        let swap2 = swap_amount_0(
-            pool2,
-            &swap1.receive_amount_with_fees_and_gas(),
-            Some(user_fee_level),
-            Some(swap2_lp_fee),
-            Some(&nat_zero()), // swap2 do not take gas fees
-        )?;
-        let swap3_lp_fee = (pool3.lp_fee_bps + 1) / 3;
-        // swap ckUSDT to token1 (reverse order of pool)
-        let swap3 = swap_amount_1(
-            pool3,
-            &swap2.receive_amount_with_fees_and_gas(),
-            Some(user_fee_level),
-            Some(swap3_lp_fee),
-            None,
-        )?;
-        let receive_amount = swap3.receive_amount_with_fees_and_gas();
-        let swap1_price = swap1.get_price().ok_or("Invalid swap1 price")?;
-        let swap2_price = swap2.get_price().ok_or("Invalid swap2 price")?;
-        let swap3_price = swap3.get_price().ok_or("Invalid swap3 price")?;
-        let price = swap1_price * swap2_price * swap3_price;
-        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-        let swap1_mid_price = swap1.get_mid_price().ok_or("Invalid swap1 mid price")?;
-        let swap2_mid_price = swap2.get_mid_price().ok_or("Invalid swap2 mid price")?;
-        let swap3_mid_price = swap3.get_mid_price().ok_or("Invalid swap3 mid price")?;
-        let mid_price = swap1_mid_price * swap2_mid_price * swap3_mid_price;
-        let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-        let slippage_f64 = get_slippage(&price, &mid_price).ok_or("Invalid slippage")?;
-        txs.push(swap1);
-        txs.push(swap2);
-        txs.push(swap3);
-        return Ok((receive_amount, price_f64, mid_price_f64, slippage_f64, txs));
-    };
+    }
+
+    let mut txs = swap_amounts_route(graph, policy, route, &amount, user_fee_level, 0, true)?;
+    let last_tx = txs.last_mut().ok_or("Invalid route")?;
+    let excess_amount = nat_subtract(&last_tx.receive_amount_with_fees_and_gas(), receive_amount).ok_or("Invalid receive amount")?;
+    last_tx.lp_fee = nat_add(&last_tx.lp_fee, &excess_amount);
+    Ok(txs)
+}
+
+/// receive amount of a route is the receive amount of the last hop
+fn route_receive_amount(txs: &[SwapCalc]) -> Nat {
+    txs.last().map(|tx| tx.receive_amount_with_fees_and_gas()).unwrap_or(nat_zero())
+}
+
+/// price and mid price of the swap
+/// price and mid price of a route is the product of the price of each hop.
+/// if the swap is split across routes, the prices are the average of the routes weighted by their pay amounts
+fn swap_prices(txs: &[SwapCalc]) -> Result<(BigRational, BigRational), String> {
+    let mut route_prices: Vec<(BigRational, BigRational, BigRational)> = Vec::new(); // (pay_amount, price, mid_price)
+    let mut route = None;
+    for (hop, tx) in txs.iter().enumerate() {
+        let price = tx.get_price().ok_or(format!("Invalid swap{} price", hop + 1))?;
+        let mid_price = tx.get_mid_price().ok_or(format!("Invalid swap{} mid price", hop + 1))?;
+        if route == Some(tx.route) {
+            let (_, route_price, route_mid_price) = route_prices.last_mut().ok_or("Invalid route")?;
+            *route_price *= price;
+            *route_mid_price *= mid_price;
+        } else {
+            route = Some(tx.route);
+            let pay_amount = BigRational::from_integer(nat_to_bigint(&tx.pay_amount));
+            route_prices.push((pay_amount, price, mid_price));
+        }
+    }
+
+    if route_prices.len() == 1 {
+        let (_, price, mid_price) = route_prices.remove(0);
+        return Ok((price, mid_price));
+    }
 
-    Err("Pool not found".to_string())
+    let total_pay_amount = route_prices
+        .iter()
+        .fold(BigRational::zero(), |acc, (pay_amount, _, _)| acc + pay_amount);
+    if total_pay_amount.is_zero() {
+        return Err("Invalid price".to_string());
+    }
+    let price = route_prices
+        .iter()
+        .fold(BigRational::zero(), |acc, (pay_amount, price, _)| acc + pay_amount * price)
+        / &total_pay_amount;
+    let mid_price = route_prices
+        .iter()
+        .fold(BigRational::zero(), |acc, (pay_amount, _, mid_price)| acc + pay_amount * mid_price)
+        / &total_pay_amount;
+    Ok((price, mid_price))
 }
 
 /// Swap amount 0 of a given pool
@@ -375,7 +443,7 @@ fn swap_amount_0(
     pool: &StablePool,
     amount_0: &Nat,
     user_fee_level: Option<u8>, // user specific fee level, 0 = 100% fee (no discount), 100 = 0% fee (max discount)
-    use_lp_fee: Option<u8>,     // overwrite for LP fee in case of 2-legged synthetic swaps
+    use_lp_fee: Option<u32>,    // overwrite for LP fee in case of 2-legged synthetic swaps
     use_gas_fee: Option<&Nat>,  // overwrite for gas fee in case of synethetic swaps
 ) -> Result<SwapCalc, String> {
     // Token 0
@@ -392,6 +460,9 @@ fn swap_amount_0(
         return Err("Zero balance in pool".to_string());
     }
 
+    // effective LP fee of the leg before the user's fee level discount
+    let lp_fee_pips = use_lp_fee.unwrap_or(pool.get_lp_fee_pips());
+
     if nat_is_zero(amount_0) {
         // return "mid" swap price if amount_1 is zero
         return Ok(SwapCalc {
@@ -402,33 +473,48 @@ fn swap_amount_0(
             receive_amount: nat_zero(),
             lp_fee: nat_zero(),
             gas_fee: nat_zero(),
+            lp_fee_pips,
+            route: 0,
         });
     }
 
+    if pool.is_buy_only() {
+        return Err(format!(
+            "Pool {} only allows buying {} during the sale",
+            pool.symbol(),
+            pool.symbol_0()
+        ));
+    }
+
+    if pool.pool_type == PoolType::ConcentratedLiquidity {
+        return concentrated_swap_amount(pool, true, amount_0, user_fee_level, lp_fee_pips, use_gas_fee);
+    }
+
     // convert amount_0 and pool balances to the max_decimals precision
     let max_decimals = std::cmp::max(token_0.decimals(), token_1.decimals());
     let reserve_0_in_max_decimals = nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals);
     let reserve_1_in_max_decimals = nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals);
     let amount_0_in_max_decimals = nat_to_decimal_precision(amount_0, token_0.decimals(), max_decimals);
 
-    // amount_1 = (amount_0 * reserve_1) / (reserve_0 + amount_0)
-    let numerator_in_max_decimals = nat_multiply(&amount_0_in_max_decimals, &reserve_1_in_max_decimals);
-    let denominator_in_max_decimals = nat_add(&reserve_0_in_max_decimals, &amount_0_in_max_decimals);
-    let amount_1_in_max_decimals = nat_divide(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid amount_1")?;
+    let amount_1_in_max_decimals = get_amount_out(
+        &reserve_0_in_max_decimals,
+        &reserve_1_in_max_decimals,
+        &amount_0_in_max_decimals,
+        pool.get_amp(),
+        pool.get_weights(),
+    )
+    .ok_or("Invalid amount_1")?;
 
     // calculate the LP fees
     // any user fee discount. user.fee_level is 0 = 100% fee (no discount), 100 = 0% fee (max discount)
     // user_lp_fee_pct = 100 - user.fee_level
     let user_lp_fee_pct = nat_subtract(&Nat::from(100_u8), &Nat::from(user_fee_level.unwrap_or(0_u8))).unwrap_or(Nat::from(100_u8));
-    // user_lp_fee_bps = (user_lp_fee * user_lp_fee_pct) / 100 - user's fee level in bps with discount
-    let user_lp_fee_bps = nat_divide(
-        &nat_multiply(&user_lp_fee_pct, &Nat::from(use_lp_fee.unwrap_or(pool.lp_fee_bps))),
-        &Nat::from(100_u8),
-    )
-    .ok_or("Invalid LP fee")?;
-    // lp_fee_1 = (amount_1 * user_lp_fee_bps) / 10_000
-    let numerator_in_max_decimals = nat_multiply(&amount_1_in_max_decimals, &user_lp_fee_bps);
-    let lp_fee_1_in_max_decimals = nat_divide(&numerator_in_max_decimals, &Nat::from(10_000_u128)).ok_or("Invalid LP fee")?;
+    // user_lp_fee_pips = (user_lp_fee * user_lp_fee_pct) / 100 - user's fee level in pips with discount
+    let user_lp_fee_pips =
+        nat_divide(&nat_multiply(&user_lp_fee_pct, &Nat::from(lp_fee_pips)), &Nat::from(100_u8)).ok_or("Invalid LP fee")?;
+    // lp_fee_1 = (amount_1 * user_lp_fee_pips) / 1_000_000
+    let numerator_in_max_decimals = nat_multiply(&amount_1_in_max_decimals, &user_lp_fee_pips);
+    let lp_fee_1_in_max_decimals = nat_divide(&numerator_in_max_decimals, &Nat::from(1_000_000_u128)).ok_or("Invalid LP fee")?;
 
     // convert amount_1 and lp_fee_1 from max_decimals to token_1 precision
     let amount_1 = nat_to_decimal_precision(&amount_1_in_max_decimals, max_decimals, token_1.decimals());
@@ -447,6 +533,8 @@ fn swap_amount_0(
         receive_amount: amount_1,
         lp_fee,
         gas_fee,
+        lp_fee_pips,
+        route: 0,
     })
 }
 
@@ -456,7 +544,7 @@ fn swap_amount_1(
     pool: &StablePool,
     amount_1: &Nat,
     user_fee_level: Option<u8>,
-    use_lp_fee: Option<u8>,
+    use_lp_fee: Option<u32>,
     use_gas_fee: Option<&Nat>,
 ) -> Result<SwapCalc, String> {
     // Token 0
@@ -473,6 +561,9 @@ fn swap_amount_1(
         return Err(format!("Pool {} insufficent balances", pool.symbol()));
     }
 
+    // effective LP fee of the leg before the user's fee level discount
+    let lp_fee_pips = use_lp_fee.unwrap_or(pool.get_lp_fee_pips());
+
     if nat_is_zero(amount_1) {
         // return "mid" swap price if amount_1 is zero
         return Ok(SwapCalc {
@@ -483,30 +574,37 @@ fn swap_amount_1(
             receive_amount: nat_zero(),
             lp_fee: nat_zero(),
             gas_fee: nat_zero(),
+            lp_fee_pips,
+            route: 0,
         });
     }
 
+    if pool.pool_type == PoolType::ConcentratedLiquidity {
+        return concentrated_swap_amount(pool, false, amount_1, user_fee_level, lp_fee_pips, use_gas_fee);
+    }
+
     // convert amount_1 and pool balances to the max_decimals precision
     let max_decimals = std::cmp::max(token_0.decimals(), token_1.decimals());
     let reserve_0_in_max_decimals = nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals);
     let reserve_1_in_max_decimals = nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals);
     let amount_1_in_max_decimals = nat_to_decimal_precision(amount_1, token_1.decimals(), max_decimals);
 
-    // amount_0 = (amount_1 * reserve_0) / (reserve_1 + amount_1)
-    let numerator_in_max_decimals = nat_multiply(&amount_1_in_max_decimals, &reserve_0_in_max_decimals);
-    let denominator_in_max_decimals = nat_add(&reserve_1_in_max_decimals, &amount_1_in_max_decimals);
-    let amount_0_in_max_decimals = nat_divide(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid amount_0")?;
+    let amount_0_in_max_decimals = get_amount_out(
+        &reserve_1_in_max_decimals,
+        &reserve_0_in_max_decimals,
+        &amount_1_in_max_decimals,
+        pool.get_amp(),
+        pool.get_weights().map(|(weight_0, weight_1)| (weight_1, weight_0)),
+    )
+    .ok_or("Invalid amount_0")?;
 
     // calculate the LP fees
     // user_lp_fee_pct = 100 - user.fee_level
     let user_lp_fee_pct = nat_subtract(&Nat::from(100_u8), &Nat::from(user_fee_level.unwrap_or(0_u8))).unwrap_or(Nat::from(100_u8));
-    let user_lp_fee_bps = nat_divide(
-        &nat_multiply(&user_lp_fee_pct, &Nat::from(use_lp_fee.unwrap_or(pool.lp_fee_bps))),
-        &Nat::from(100_u8),
-    )
-    .ok_or("Invalid LP fee")?;
-    let numerator_in_max_decimals = nat_multiply(&amount_0_in_max_decimals, &user_lp_fee_bps);
-    let lp_fee_0_in_max_decimals = nat_divide(&numerator_in_max_decimals, &Nat::from(10_000_u128)).ok_or("Invalid LP fee")?;
+    let user_lp_fee_pips =
+        nat_divide(&nat_multiply(&user_lp_fee_pct, &Nat::from(lp_fee_pips)), &Nat::from(100_u8)).ok_or("Invalid LP fee")?;
+    let numerator_in_max_decimals = nat_multiply(&amount_0_in_max_decimals, &user_lp_fee_pips);
+    let lp_fee_0_in_max_decimals = nat_divide(&numerator_in_max_decimals, &Nat::from(1_000_000_u128)).ok_or("Invalid LP fee")?;
 
     // convert amount_0 and lp_fee_0 to token_0 precision
     let amount_0 = nat_to_decimal_precision(&amount_0_in_max_decimals, max_decimals, token_0.decimals());
@@ -525,9 +623,241 @@ fn swap_amount_1(
         receive_amount: amount_0,
         lp_fee,
         gas_fee,
+        lp_fee_pips,
+        route: 0,
+    })
+}
+
+/// Pay amount 0 needed to receive receive_amount_1 (after fees and gas) of a given pool. inverse of swap_amount_0()
+fn swap_pay_amount_0(
+    pool: &StablePool,
+    receive_amount_1: &Nat,
+    user_fee_level: Option<u8>,
+    use_lp_fee: Option<u32>,
+    use_gas_fee: Option<&Nat>,
+) -> Result<Nat, String> {
+    if pool.is_buy_only() {
+        return Err(format!(
+            "Pool {} only allows buying {} during the sale",
+            pool.symbol(),
+            pool.symbol_0()
+        ));
+    }
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    let reserve_0 = nat_add(&pool.balance_0, &pool.lp_fee_0);
+    let reserve_1 = nat_add(&pool.balance_1, &pool.lp_fee_1);
+    let lp_fee_pips = user_lp_fee_pips(use_lp_fee.unwrap_or(pool.get_lp_fee_pips()), user_fee_level)?;
+    let gas_fee = use_gas_fee.map_or_else(|| token_1.fee(), |fee| fee.clone());
+    if pool.pool_type == PoolType::ConcentratedLiquidity {
+        return concentrated_pay_amount(pool, true, receive_amount_1, &lp_fee_pips, &gas_fee);
+    }
+    get_pay_amount(
+        &token_0,
+        &reserve_0,
+        &token_1,
+        &reserve_1,
+        receive_amount_1,
+        &lp_fee_pips,
+        &gas_fee,
+        pool.get_amp(),
+        pool.get_weights(),
+    )
+}
+
+/// Pay amount 1 needed to receive receive_amount_0 (after fees and gas) of a given pool. inverse of swap_amount_1()
+fn swap_pay_amount_1(
+    pool: &StablePool,
+    receive_amount_0: &Nat,
+    user_fee_level: Option<u8>,
+    use_lp_fee: Option<u32>,
+    use_gas_fee: Option<&Nat>,
+) -> Result<Nat, String> {
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    let reserve_0 = nat_add(&pool.balance_0, &pool.lp_fee_0);
+    let reserve_1 = nat_add(&pool.balance_1, &pool.lp_fee_1);
+    let lp_fee_pips = user_lp_fee_pips(use_lp_fee.unwrap_or(pool.get_lp_fee_pips()), user_fee_level)?;
+    let gas_fee = use_gas_fee.map_or_else(|| token_0.fee(), |fee| fee.clone());
+    if pool.pool_type == PoolType::ConcentratedLiquidity {
+        return concentrated_pay_amount(pool, false, receive_amount_0, &lp_fee_pips, &gas_fee);
+    }
+    get_pay_amount(
+        &token_1,
+        &reserve_1,
+        &token_0,
+        &reserve_0,
+        receive_amount_0,
+        &lp_fee_pips,
+        &gas_fee,
+        pool.get_amp(),
+        pool.get_weights().map(|(weight_0, weight_1)| (weight_1, weight_0)),
+    )
+}
+
+/// swap of a concentrated liquidity pool. zero_for_one is paying token_0 for token_1
+/// the price moves along the liquidity in range of the pool so no decimal conversion is needed
+fn concentrated_swap_amount(
+    pool: &StablePool,
+    zero_for_one: bool,
+    pay_amount: &Nat,
+    user_fee_level: Option<u8>,
+    lp_fee_pips: u32,
+    use_gas_fee: Option<&Nat>,
+) -> Result<SwapCalc, String> {
+    let (pay_token, receive_token, receive_balance) = if zero_for_one {
+        (pool.token_0(), pool.token_1(), &pool.balance_1)
+    } else {
+        (pool.token_1(), pool.token_0(), &pool.balance_0)
+    };
+
+    let swap = concentrated_liquidity::swap(pool, zero_for_one, pay_amount, true)?;
+    // lp_fee = (amount_out * user_lp_fee_pips) / 1_000_000
+    let user_lp_fee_pips = user_lp_fee_pips(lp_fee_pips, user_fee_level)?;
+    let lp_fee = nat_divide(&nat_multiply(&swap.amount_out, &user_lp_fee_pips), &Nat::from(1_000_000_u128)).ok_or("Invalid LP fee")?;
+    let gas_fee = use_gas_fee.map_or_else(|| receive_token.fee(), |fee| fee.clone());
+
+    if swap.amount_out > *receive_balance {
+        return Err(format!("Insufficient {} in pool", receive_token.symbol()));
+    }
+
+    Ok(SwapCalc {
+        pool_id: pool.pool_id,
+        pay_token_id: pay_token.token_id(),
+        pay_amount: pay_amount.clone(),
+        receive_token_id: receive_token.token_id(),
+        receive_amount: swap.amount_out,
+        lp_fee,
+        gas_fee,
+        lp_fee_pips,
+        route: 0,
     })
 }
 
+/// pay amount needed to receive receive_amount after fees and gas of a concentrated liquidity pool. inverse of concentrated_swap_amount()
+fn concentrated_pay_amount(
+    pool: &StablePool,
+    zero_for_one: bool,
+    receive_amount: &Nat,
+    user_lp_fee_pips: &Nat,
+    gas_fee: &Nat,
+) -> Result<Nat, String> {
+    let (receive_token, receive_balance) = if zero_for_one {
+        (pool.token_1(), &pool.balance_1)
+    } else {
+        (pool.token_0(), &pool.balance_0)
+    };
+
+    // amount before LP fee. amount_out = receive_amount * 1_000_000 / (1_000_000 - user_lp_fee_pips)
+    let fee_denominator = nat_subtract(&Nat::from(1_000_000_u128), user_lp_fee_pips).ok_or("Invalid LP fee")?;
+    let amount_out = nat_divide_ceil(
+        &nat_multiply(&nat_add(receive_amount, gas_fee), &Nat::from(1_000_000_u128)),
+        &fee_denominator,
+    )
+    .ok_or("Invalid LP fee")?;
+    if amount_out >= *receive_balance {
+        return Err(format!("Insufficient {} in pool", receive_token.symbol()));
+    }
+
+    let mut amount_in = concentrated_liquidity::swap(pool, zero_for_one, &amount_out, false)?.amount_in;
+    // sqrt prices are rounded so make sure amount_in is enough to receive amount_out
+    for _ in 0..MAX_PAY_AMOUNT_ITERATIONS {
+        if concentrated_liquidity::swap(pool, zero_for_one, &amount_in, true)?.amount_out >= amount_out {
+            return Ok(amount_in);
+        }
+        amount_in = nat_add(&amount_in, &Nat::from(1_u8));
+    }
+    Err("Invalid amount".to_string())
+}
+
+// user_lp_fee_pips = (lp_fee_pips * (100 - user.fee_level)) / 100 - user's fee level in pips with discount
+fn user_lp_fee_pips(lp_fee_pips: u32, user_fee_level: Option<u8>) -> Result<Nat, String> {
+    let user_lp_fee_pct = nat_subtract(&Nat::from(100_u8), &Nat::from(user_fee_level.unwrap_or(0_u8))).unwrap_or(Nat::from(100_u8));
+    nat_divide(&nat_multiply(&user_lp_fee_pct, &Nat::from(lp_fee_pips)), &Nat::from(100_u8)).ok_or("Invalid LP fee".to_string())
+}
+
+/// amount out of a pool before fees for amount_in. amounts must be in the same decimal precision
+/// amp is the StableSwap amplification coefficient or None for constant product pools
+/// weights are (weight_in, weight_out) of weighted pools or None for other pools
+fn get_amount_out(reserve_in: &Nat, reserve_out: &Nat, amount_in: &Nat, amp: Option<u64>, weights: Option<(u32, u32)>) -> Option<Nat> {
+    match (amp, weights) {
+        (Some(amp), _) => stableswap_amount_out(reserve_in, reserve_out, amount_in, amp),
+        (_, Some((weight_in, weight_out))) => weighted_amount_out(reserve_in, reserve_out, amount_in, weight_in, weight_out),
+        (None, None) => {
+            // amount_out = (amount_in * reserve_out) / (reserve_in + amount_in)
+            let numerator = nat_multiply(amount_in, reserve_out);
+            let denominator = nat_add(reserve_in, amount_in);
+            nat_divide(&numerator, &denominator)
+        }
+    }
+}
+
+/// pay amount needed to receive receive_amount after fees and gas. all divisions round up so the pay amount is never short
+#[allow(clippy::too_many_arguments)]
+fn get_pay_amount(
+    pay_token: &StableToken,
+    pay_reserve: &Nat,
+    receive_token: &StableToken,
+    receive_reserve: &Nat,
+    receive_amount: &Nat,
+    user_lp_fee_pips: &Nat,
+    gas_fee: &Nat,
+    amp: Option<u64>,
+    weights: Option<(u32, u32)>,
+) -> Result<Nat, String> {
+    if nat_is_zero(pay_reserve) || nat_is_zero(receive_reserve) {
+        return Err("Zero balance in pool".to_string());
+    }
+
+    let max_decimals = std::cmp::max(pay_token.decimals(), receive_token.decimals());
+    let pay_reserve_in_max_decimals = nat_to_decimal_precision(pay_reserve, pay_token.decimals(), max_decimals);
+    let receive_reserve_in_max_decimals = nat_to_decimal_precision(receive_reserve, receive_token.decimals(), max_decimals);
+    let receive_amount_with_gas = nat_add(receive_amount, gas_fee);
+    let receive_amount_in_max_decimals = nat_to_decimal_precision(&receive_amount_with_gas, receive_token.decimals(), max_decimals);
+
+    // amount before LP fee. amount_out = receive_amount * 1_000_000 / (1_000_000 - user_lp_fee_pips)
+    let fee_denominator = nat_subtract(&Nat::from(1_000_000_u128), user_lp_fee_pips).ok_or("Invalid LP fee")?;
+    let amount_out_in_max_decimals = nat_divide_ceil(
+        &nat_multiply(&receive_amount_in_max_decimals, &Nat::from(1_000_000_u128)),
+        &fee_denominator,
+    )
+    .ok_or("Invalid LP fee")?;
+    if amount_out_in_max_decimals >= receive_reserve_in_max_decimals {
+        return Err(format!("Insufficient {} in pool", receive_token.symbol()));
+    }
+
+    let amount_in_in_max_decimals = match (amp, weights) {
+        (Some(amp), _) => stableswap_amount_in(
+            &pay_reserve_in_max_decimals,
+            &receive_reserve_in_max_decimals,
+            &amount_out_in_max_decimals,
+            amp,
+        )
+        .ok_or("Invalid amount")?,
+        (_, Some((weight_in, weight_out))) => weighted_amount_in(
+            &pay_reserve_in_max_decimals,
+            &receive_reserve_in_max_decimals,
+            &amount_out_in_max_decimals,
+            weight_in,
+            weight_out,
+        )
+        .ok_or("Invalid amount")?,
+        (None, None) => {
+            // amount_in = (amount_out * reserve_in) / (reserve_out - amount_out)
+            let numerator_in_max_decimals = nat_multiply(&amount_out_in_max_decimals, &pay_reserve_in_max_decimals);
+            let denominator_in_max_decimals =
+                nat_subtract(&receive_reserve_in_max_decimals, &amount_out_in_max_decimals).ok_or("Invalid amount")?;
+            nat_divide_ceil(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid amount")?
+        }
+    };
+
+    Ok(nat_to_decimal_precision_ceil(
+        &amount_in_in_max_decimals,
+        max_decimals,
+        pay_token.decimals(),
+    ))
+}
+
 fn get_slippage(price_achieved: &BigRational, price_expected: &BigRational) -> Option<f64> {
     if price_achieved > price_expected {
         return Some(0.0); // if price is greater than expected, slippage is 0
@@ -542,3 +872,125 @@ fn get_slippage(price_achieved: &BigRational, price_expected: &BigRational) -> O
         .abs();
     Some(round_f64(raw_slippage, 2)) // 2 decimals
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::{insert_token, new_pool};
+
+    fn pool(pool_id: u32, balance_0: u64, balance_1: u64) -> StablePool {
+        new_pool(pool_id, 1, 2, balance_0, balance_1)
+    }
+
+    fn policy(max_splits: usize) -> RoutingPolicy {
+        RoutingPolicy {
+            max_hops: 1,
+            max_splits,
+            split_steps: 10,
+        }
+    }
+
+    fn route_pay_amounts(txs: &[SwapCalc]) -> Vec<(u32, Nat)> {
+        txs.iter().map(|tx| (tx.pool_id, tx.pay_amount.clone())).collect()
+    }
+
+    #[test]
+    fn test_split_equal_pools() {
+        insert_token(1, "TOKEN_0");
+        insert_token(2, "TOKEN_1");
+        // two fee tiers of the same pair with the same reserves
+        let graph = TokenGraph::from_pools(vec![pool(1, 1_000_000_000, 1_000_000_000), pool(2, 1_000_000_000, 1_000_000_000)]);
+        let routes = graph.get_routes(1, 2, 1);
+        let pay_amount = Nat::from(100_000_000_u64);
+
+        let single = swap_amounts_route(&graph, &policy(2), &routes[0], &pay_amount, 0, 0, true).unwrap();
+        let (receive_amount, txs) = swap_amounts_split(&graph, &policy(2), &routes, &pay_amount, 0).unwrap();
+
+        // pay amount is shared equally and all of it is used
+        let pay_amounts = route_pay_amounts(&txs);
+        assert_eq!(pay_amounts.len(), 2);
+        assert_eq!(pay_amounts[0].1, Nat::from(50_000_000_u64));
+        assert_eq!(pay_amounts[1].1, Nat::from(50_000_000_u64));
+        // each leg is recorded with its route and only the first route takes the gas fee
+        assert_eq!((txs[0].route, txs[1].route), (0, 1));
+        assert_eq!(txs[0].gas_fee, Nat::from(10_u32));
+        assert_eq!(txs[1].gas_fee, nat_zero());
+        // less slippage than a single route
+        assert!(receive_amount > route_receive_amount(&single));
+    }
+
+    #[test]
+    fn test_split_unequal_pools() {
+        insert_token(1, "TOKEN_0");
+        insert_token(2, "TOKEN_1");
+        // pool 1 is 4 times deeper than pool 2
+        let graph = TokenGraph::from_pools(vec![pool(1, 4_000_000_000, 4_000_000_000), pool(2, 1_000_000_000, 1_000_000_000)]);
+        let routes = graph.get_routes(1, 2, 1);
+        let pay_amount = Nat::from(500_000_000_u64);
+
+        let (_, txs) = swap_amounts_split(&graph, &policy(2), &routes, &pay_amount, 0).unwrap();
+
+        // the deeper pool gets 4/5 of the pay amount and is the first route
+        let pay_amounts = route_pay_amounts(&txs);
+        assert_eq!(pay_amounts[0], (1, Nat::from(400_000_000_u64)));
+        assert_eq!(pay_amounts[1], (2, Nat::from(100_000_000_u64)));
+    }
+
+    #[test]
+    fn test_no_split() {
+        insert_token(1, "TOKEN_0");
+        insert_token(2, "TOKEN_1");
+        let graph = TokenGraph::from_pools(vec![pool(1, 1_000_000_000, 1_000_000_000), pool(2, 1_000_000_000, 1_000_000_000)]);
+        let routes = graph.get_routes(1, 2, 1);
+        let pay_amount = Nat::from(100_000_000_u64);
+
+        // splitting disabled
+        assert!(swap_amounts_split(&graph, &policy(1), &routes, &pay_amount, 0).is_none());
+        // only one route
+        assert!(swap_amounts_split(&graph, &policy(2), &routes[..1], &pay_amount, 0).is_none());
+    }
+
+    #[test]
+    fn test_rank_routes_dense_graph() {
+        // a pool for every pair of 10 tokens. the direct pool of token 1 and 2 is the deepest
+        let mut pools = Vec::new();
+        for token_id_0 in 1..=10 {
+            insert_token(token_id_0, &format!("TOKEN_{}", token_id_0));
+            for token_id_1 in token_id_0 + 1..=10 {
+                let balance = if (token_id_0, token_id_1) == (1, 2) {
+                    10_000_000_000
+                } else {
+                    1_000_000_000
+                };
+                pools.push(new_pool(pools.len() as u32 + 1, token_id_0, token_id_1, balance, balance));
+            }
+        }
+        let graph = TokenGraph::from_pools(pools);
+        // 1 direct route, 8 routes through 1 other token and 8 * 7 routes through 2 other tokens
+        let routes = graph.get_routes(1, 2, 3);
+        assert_eq!(routes.len(), 65);
+
+        let ranked_routes = rank_routes(&graph, &policy(MAX_SPLIT_CANDIDATES), routes, &Nat::from(100_000_000_u64), 0).unwrap();
+        assert_eq!(ranked_routes.len(), 65);
+        assert_eq!(ranked_routes[0].1.len(), 1);
+        assert!(ranked_routes.windows(2).all(|w| w[0].0 >= w[1].0));
+        // the 2 hop routes are better than the 3 hop routes
+        assert!(ranked_routes[1..9].iter().all(|(_, route, _)| route.len() == 2));
+
+        // the split is chosen from the best routes only
+        let candidate_routes = split_candidates(ranked_routes);
+        assert_eq!(candidate_routes.len(), MAX_SPLIT_CANDIDATES);
+        let (_, txs) = swap_amounts_split(
+            &graph,
+            &policy(MAX_SPLIT_CANDIDATES),
+            &candidate_routes,
+            &Nat::from(5_000_000_000_u64),
+            0,
+        )
+        .unwrap();
+        // the direct pool gets the largest part of the pay amount and the rest goes through other tokens
+        assert_eq!(txs[0].pool_id, 1);
+        assert!(txs.iter().any(|tx| tx.route > 0));
+    }
+}
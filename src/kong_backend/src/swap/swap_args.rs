@@ -11,8 +11,10 @@ pub struct SwapArgs {
     pub pay_amount: Nat,
     pub pay_tx_id: Option<TxId>,
     pub receive_token: String,
-    pub receive_amount: Option<Nat>,
+    pub receive_amount: Option<Nat>, // minimum receive amount. for exact-output swaps, the exact receive amount
     pub receive_address: Option<String>,
     pub max_slippage: Option<f64>,
     pub referred_by: Option<String>,
+    pub max_pay_amount: Option<Nat>, // if specified, swap is exact-output. pay amount is calculated and capped by max_pay_amount
+    pub deadline_ns: Option<u64>,    // if specified, swap fails if the pool is not updated by this time
 }
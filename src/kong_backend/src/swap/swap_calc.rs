@@ -11,4 +11,8 @@ pub struct SwapCalc {
     pub receive_amount: Nat, // does not include any fees. used to keep a constant K with pay amount
     pub lp_fee: Nat,         // will be in receive_token
     pub gas_fee: Nat,        // will be in receive_token
+    #[serde(default)]
+    pub lp_fee_pips: u32, // effective LP fee of the leg in pips before the user's fee level discount
+    #[serde(default)]
+    pub route: u8, // index of the route the leg belongs to when the swap is split across routes
 }
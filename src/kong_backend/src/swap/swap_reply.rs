@@ -17,9 +17,21 @@ pub struct SwapTxReply {
     pub price: f64,
     pub lp_fee: Nat,  // will be in receive_symbol
     pub gas_fee: Nat, // will be in receive_symbol
+    #[serde(default)]
+    pub lp_fee_pips: u32, // effective LP fee in pips before the user's fee level discount
+    #[serde(default)]
+    pub route: u8, // index of the route when the swap is split across routes
     pub ts: u64,
 }
 
+/// Pay and receive amounts of each route when the swap is split across routes
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct SwapSplitReply {
+    pub route: u8,
+    pub pay_amount: Nat,
+    pub receive_amount: Nat, // after fees and gas
+}
+
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct SwapReply {
     pub tx_id: u64,
@@ -35,7 +47,92 @@ pub struct SwapReply {
     pub price: f64,
     pub slippage: f64,
     pub txs: Vec<SwapTxReply>,
+    #[serde(default)]
+    pub splits: Vec<SwapSplitReply>,
     pub transfer_ids: Vec<TransferIdReply>,
     pub claim_ids: Vec<u64>,
     pub ts: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct SwapTxReplyV1 {
+        pool_symbol: String,
+        pay_chain: String,
+        pay_symbol: String,
+        pay_amount: Nat,
+        receive_chain: String,
+        receive_symbol: String,
+        receive_amount: Nat,
+        price: f64,
+        lp_fee: Nat,
+        gas_fee: Nat,
+        ts: u64,
+    }
+
+    #[derive(Serialize)]
+    struct SwapReplyV1 {
+        tx_id: u64,
+        request_id: u64,
+        status: String,
+        pay_chain: String,
+        pay_symbol: String,
+        pay_amount: Nat,
+        receive_chain: String,
+        receive_symbol: String,
+        receive_amount: Nat,
+        mid_price: f64,
+        price: f64,
+        slippage: f64,
+        txs: Vec<SwapTxReplyV1>,
+        transfer_ids: Vec<TransferIdReply>,
+        claim_ids: Vec<u64>,
+        ts: u64,
+    }
+
+    #[test]
+    fn test_decode_swap_reply_without_routes() {
+        // SwapReply as stored in StableRequest before swaps could be split across routes
+        let reply_v1 = SwapReplyV1 {
+            tx_id: 1,
+            request_id: 2,
+            status: "Success".to_string(),
+            pay_chain: "IC".to_string(),
+            pay_symbol: "ICP".to_string(),
+            pay_amount: Nat::from(100_u32),
+            receive_chain: "IC".to_string(),
+            receive_symbol: "ckUSDT".to_string(),
+            receive_amount: Nat::from(99_u32),
+            mid_price: 1.0,
+            price: 0.99,
+            slippage: 1.0,
+            txs: vec![SwapTxReplyV1 {
+                pool_symbol: "ICP_ckUSDT".to_string(),
+                pay_chain: "IC".to_string(),
+                pay_symbol: "ICP".to_string(),
+                pay_amount: Nat::from(100_u32),
+                receive_chain: "IC".to_string(),
+                receive_symbol: "ckUSDT".to_string(),
+                receive_amount: Nat::from(99_u32),
+                price: 0.99,
+                lp_fee: Nat::from(1_u32),
+                gas_fee: Nat::from(0_u32),
+                ts: 3,
+            }],
+            transfer_ids: Vec::new(),
+            claim_ids: Vec::new(),
+            ts: 3,
+        };
+        let bytes = serde_cbor::to_vec(&reply_v1).unwrap();
+
+        let reply: SwapReply = serde_cbor::from_slice(&bytes).unwrap();
+        assert!(reply.splits.is_empty());
+        assert_eq!(reply.txs.len(), 1);
+        assert_eq!(reply.txs[0].route, 0);
+        assert_eq!(reply.txs[0].lp_fee_pips, 0);
+        assert_eq!(reply.receive_amount, Nat::from(99_u32));
+    }
+}
@@ -13,7 +13,7 @@ use crate::stable_tx::status_tx::StatusTx;
 use crate::stable_tx::swap_tx::SwapTx;
 use crate::transfers::transfer_reply_helpers::to_transfer_ids;
 
-use super::swap_reply::{SwapReply, SwapTxReply};
+use super::swap_reply::{SwapReply, SwapSplitReply, SwapTxReply};
 
 pub fn to_txs(txs: &[SwapCalc], ts: u64) -> Vec<SwapTxReply> {
     txs.iter().filter_map(|tx| to_swap_tx_reply(tx, ts)).collect()
@@ -40,10 +40,28 @@ fn to_swap_tx_reply(swap: &SwapCalc, ts: u64) -> Option<SwapTxReply> {
         price: price_f64,
         lp_fee: swap.lp_fee.clone(),
         gas_fee: swap.gas_fee.clone(),
+        lp_fee_pips: swap.lp_fee_pips,
+        route: swap.route,
         ts,
     })
 }
 
+/// group the legs of the swap by route. first leg of a route has the pay amount and the last leg has the receive amount
+pub fn to_splits(txs: &[SwapCalc]) -> Vec<SwapSplitReply> {
+    let mut splits: Vec<SwapSplitReply> = Vec::new();
+    for tx in txs.iter() {
+        match splits.last_mut() {
+            Some(split) if split.route == tx.route => split.receive_amount = tx.receive_amount_with_fees_and_gas(),
+            _ => splits.push(SwapSplitReply {
+                route: tx.route,
+                pay_amount: tx.pay_amount.clone(),
+                receive_amount: tx.receive_amount_with_fees_and_gas(),
+            }),
+        }
+    }
+    splits
+}
+
 pub fn create_swap_reply(swap_tx: &SwapTx) -> SwapReply {
     create_swap_reply_with_tx_id(swap_tx.tx_id, swap_tx)
 }
@@ -73,6 +91,7 @@ pub fn create_swap_reply_with_tx_id(tx_id: u64, swap_tx: &SwapTx) -> SwapReply {
         price: swap_tx.price,
         slippage: swap_tx.slippage,
         txs: to_txs(&swap_tx.txs, swap_tx.ts),
+        splits: to_splits(&swap_tx.txs),
         transfer_ids: to_transfer_ids(&swap_tx.transfer_ids),
         claim_ids: swap_tx.claim_ids.clone(),
         ts: swap_tx.ts,
@@ -108,6 +127,7 @@ pub fn create_swap_reply_failed(
         price: 0_f64,
         slippage: 0_f64,
         txs: Vec::new(),
+        splits: Vec::new(),
         transfer_ids: to_transfer_ids(transfer_ids),
         claim_ids: claim_ids.to_vec(),
         ts,
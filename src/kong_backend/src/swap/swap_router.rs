@@ -0,0 +1,240 @@
+use candid::Nat;
+use std::collections::HashMap;
+
+use crate::helpers::nat_helpers::nat_zero;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_kong_settings::stable_kong_settings::MAX_SWAP_HOPS;
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::StablePool;
+
+/// One leg of a swap route
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapHop {
+    pub pool_id: u32,
+    pub pay_token_id: u32,
+    pub receive_token_id: u32,
+}
+
+impl SwapHop {
+    /// true if the hop pays token_0 of the pool and receives token_1
+    pub fn is_token_0(&self, pool: &StablePool) -> bool {
+        self.pay_token_id == pool.token_id_0
+    }
+}
+
+/// number of routes with the best receive amounts a split is chosen from
+pub const MAX_SPLIT_CANDIDATES: usize = 8;
+
+/// Routing policy applied to every route the router evaluates
+#[derive(Debug, Clone)]
+pub struct RoutingPolicy {
+    pub max_hops: usize,    // maximum number of pools a route can go through
+    pub max_splits: usize,  // maximum number of routes a swap can be split across
+    pub split_steps: usize, // number of steps the pay amount is divided into when splitting
+}
+
+impl RoutingPolicy {
+    pub fn from_kong_settings() -> Self {
+        let kong_settings = kong_settings_map::get();
+        Self {
+            max_hops: kong_settings.max_swap_hops.min(MAX_SWAP_HOPS) as usize,
+            max_splits: kong_settings.max_swap_splits as usize,
+            split_steps: kong_settings.swap_split_steps as usize,
+        }
+    }
+
+    /// LP fee to use for a hop of a route with num_hops legs
+    /// single hop uses the pool's LP fee. multi-hop routes split the LP fee between the legs. the "+ 1" rounds up the integer
+    pub fn lp_fee_pips(&self, pool: &StablePool, num_hops: usize) -> Option<u32> {
+        if num_hops <= 1 {
+            return None;
+        }
+        Some((pool.get_lp_fee_pips() + 1) / num_hops as u32)
+    }
+
+    /// gas fee to use for hop index hop of a route with num_hops legs
+    /// intermediate hops do not take gas fees, last hop uses the receive token's fee
+    pub fn gas_fee(&self, hop: usize, num_hops: usize) -> Option<Nat> {
+        if hop + 1 < num_hops {
+            Some(nat_zero())
+        } else {
+            None
+        }
+    }
+}
+
+/// token graph built from the pools on Kong. each token maps to the pools it is in and the token on the other side
+pub struct TokenGraph {
+    pools: HashMap<u32, StablePool>,
+    edges: HashMap<u32, Vec<(u32, u32)>>, // token_id -> [(pool_id, other token_id)]
+}
+
+impl TokenGraph {
+    /// graph of all the pools on Kong
+    pub fn on_kong() -> Self {
+        Self::from_pools(pool_map::get_on_kong())
+    }
+
+    pub fn from_pools(pools: Vec<StablePool>) -> Self {
+        let mut edges: HashMap<u32, Vec<(u32, u32)>> = HashMap::new();
+        for pool in pools.iter() {
+            edges.entry(pool.token_id_0).or_default().push((pool.pool_id, pool.token_id_1));
+            edges.entry(pool.token_id_1).or_default().push((pool.pool_id, pool.token_id_0));
+        }
+        // sort so routes are found in a deterministic order
+        for token_edges in edges.values_mut() {
+            token_edges.sort();
+        }
+        let pools = pools.into_iter().map(|pool| (pool.pool_id, pool)).collect();
+        Self { pools, edges }
+    }
+
+    pub fn get_pool(&self, pool_id: u32) -> Option<&StablePool> {
+        self.pools.get(&pool_id)
+    }
+
+    /// all routes from pay_token_id to receive_token_id with up to max_hops legs. a route never visits the same token twice
+    /// routes are returned with the fewest hops first
+    pub fn get_routes(&self, pay_token_id: u32, receive_token_id: u32, max_hops: usize) -> Vec<Vec<SwapHop>> {
+        let mut routes = Vec::new();
+        if pay_token_id == receive_token_id {
+            return routes;
+        }
+        for num_hops in 1..=max_hops {
+            let mut route = Vec::with_capacity(num_hops);
+            let mut visited = vec![pay_token_id];
+            self.search_routes(pay_token_id, receive_token_id, num_hops, &mut route, &mut visited, &mut routes);
+        }
+        routes
+    }
+
+    // depth-first search for routes of exactly num_hops legs
+    fn search_routes(
+        &self,
+        token_id: u32,
+        receive_token_id: u32,
+        num_hops: usize,
+        route: &mut Vec<SwapHop>,
+        visited: &mut Vec<u32>,
+        routes: &mut Vec<Vec<SwapHop>>,
+    ) {
+        let Some(token_edges) = self.edges.get(&token_id) else {
+            return;
+        };
+        let last_hop = route.len() + 1 == num_hops;
+        for (pool_id, other_token_id) in token_edges {
+            if last_hop {
+                // last leg must land on the receive token
+                if *other_token_id != receive_token_id {
+                    continue;
+                }
+            } else if *other_token_id == receive_token_id || visited.contains(other_token_id) {
+                continue;
+            }
+            route.push(SwapHop {
+                pool_id: *pool_id,
+                pay_token_id: token_id,
+                receive_token_id: *other_token_id,
+            });
+            if last_hop {
+                routes.push(route.clone());
+            } else {
+                visited.push(*other_token_id);
+                self.search_routes(*other_token_id, receive_token_id, num_hops, route, visited, routes);
+                visited.pop();
+            }
+            route.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::new_pool;
+
+    fn pool(pool_id: u32, token_id_0: u32, token_id_1: u32) -> StablePool {
+        new_pool(pool_id, token_id_0, token_id_1, 0, 0)
+    }
+
+    fn route_pools(route: &[SwapHop]) -> Vec<u32> {
+        route.iter().map(|hop| hop.pool_id).collect()
+    }
+
+    #[test]
+    fn test_get_routes() {
+        // tokens: 1 = ckUSDT, 2 = ICP, 3 = ckBTC, 4 = ckETH, 5 = ckUSDC
+        let graph = TokenGraph::from_pools(vec![
+            pool(1, 2, 1), // ICP/ckUSDT
+            pool(2, 3, 1), // ckBTC/ckUSDT
+            pool(3, 4, 2), // ckETH/ICP
+            pool(4, 3, 5), // ckBTC/ckUSDC
+            pool(5, 4, 5), // ckETH/ckUSDC
+        ]);
+
+        let routes = graph.get_routes(3, 4, 1);
+        assert!(routes.is_empty());
+
+        let routes = graph.get_routes(3, 4, 2);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(route_pools(&routes[0]), vec![4, 5]);
+
+        let routes = graph.get_routes(3, 4, 3);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(route_pools(&routes[0]), vec![4, 5]);
+        assert_eq!(route_pools(&routes[1]), vec![2, 1, 3]);
+        assert_eq!(
+            routes[1][1],
+            SwapHop {
+                pool_id: 1,
+                pay_token_id: 1,
+                receive_token_id: 2
+            }
+        );
+
+        let routes = graph.get_routes(3, 3, 3);
+        assert!(routes.is_empty());
+
+        let routes = graph.get_routes(3, 6, 3);
+        assert!(routes.is_empty());
+    }
+
+    #[test]
+    fn test_get_routes_fee_tiers() {
+        // tokens: 1 = ckUSDT, 2 = ICP, 3 = ckBTC. ckBTC/ckUSDT has 2 fee tiers
+        let graph = TokenGraph::from_pools(vec![
+            pool(1, 2, 1), // ICP/ckUSDT
+            pool(2, 3, 1), // ckBTC/ckUSDT
+            pool(3, 3, 2), // ckBTC/ICP
+            pool(4, 3, 1), // ckBTC/ckUSDT_500
+        ]);
+
+        let routes = graph.get_routes(3, 1, 1);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(route_pools(&routes[0]), vec![2]);
+        assert_eq!(route_pools(&routes[1]), vec![4]);
+
+        let routes = graph.get_routes(2, 3, 2);
+        assert_eq!(routes.len(), 3);
+        assert_eq!(route_pools(&routes[0]), vec![3]);
+        assert_eq!(route_pools(&routes[1]), vec![1, 2]);
+        assert_eq!(route_pools(&routes[2]), vec![1, 4]);
+    }
+
+    #[test]
+    fn test_routing_policy() {
+        let policy = RoutingPolicy {
+            max_hops: 3,
+            max_splits: 3,
+            split_steps: 10,
+        };
+        let pool = pool(1, 2, 1);
+        assert_eq!(policy.lp_fee_pips(&pool, 1), None);
+        assert_eq!(policy.lp_fee_pips(&pool, 2), Some(1_500));
+        assert_eq!(policy.lp_fee_pips(&pool, 3), Some(1_000));
+        assert_eq!(policy.gas_fee(0, 1), None);
+        assert_eq!(policy.gas_fee(0, 2), Some(nat_zero()));
+        assert_eq!(policy.gas_fee(1, 2), None);
+    }
+}
@@ -1,13 +1,13 @@
 use candid::Nat;
 
 use super::archive_to_kong_data::archive_to_kong_data;
-use super::return_pay_token::return_pay_token;
+use super::return_pay_token::{return_pay_token, return_unused_pay_token};
 use super::send_receive_token::send_receive_token;
 use super::swap_args::SwapArgs;
 use super::swap_reply::SwapReply;
-use super::update_liquidity_pool::update_liquidity_pool;
+use super::update_liquidity_pool::{update_liquidity_pool, update_liquidity_pool_exact_output};
 
-use crate::helpers::nat_helpers::nat_is_zero;
+use crate::helpers::nat_helpers::{nat_is_zero, nat_subtract};
 use crate::ic::address::Address;
 use crate::ic::address_helpers::get_address;
 use crate::ic::get_time::get_time;
@@ -169,34 +169,79 @@ async fn process_swap(
         None => Address::PrincipalId(caller_id),
     };
 
-    let (receive_amount, mid_price, price, slippage, swaps) =
-        match update_liquidity_pool(request_id, pay_token, pay_amount, &receive_token, receive_amount, max_slippage) {
-            Ok((receive_amount, mid_price, price, slippage, swaps)) => (receive_amount, mid_price, price, slippage, swaps),
-            Err(e) => {
-                return_pay_token(
-                    request_id,
-                    user_id,
-                    &caller_id,
-                    pay_token,
-                    pay_amount,
-                    Some(&receive_token),
-                    &mut transfer_ids,
-                    ts,
-                )
-                .await;
-                return Err(format!("Req #{} failed. {}", request_id, e));
-            }
-        };
+    // for exact-output swaps, the pay amount transferred is the most that can be used, capped by max_pay_amount
+    let update_result = match (receive_amount, &args.max_pay_amount) {
+        (Some(receive_amount), Some(max_pay_amount)) => {
+            let max_pay_amount = std::cmp::min(pay_amount, max_pay_amount);
+            update_liquidity_pool_exact_output(
+                request_id,
+                pay_token,
+                &receive_token,
+                receive_amount,
+                max_pay_amount,
+                max_slippage,
+                args.deadline_ns,
+            )
+            .map(|(used_pay_amount, mid_price, price, slippage, swaps)| {
+                (used_pay_amount, receive_amount.clone(), mid_price, price, slippage, swaps)
+            })
+        }
+        (None, Some(_)) => Err("Receive amount is required for exact-output swaps".to_string()),
+        _ => update_liquidity_pool(
+            request_id,
+            pay_token,
+            pay_amount,
+            &receive_token,
+            receive_amount,
+            max_slippage,
+            args.deadline_ns,
+        )
+        .map(|(receive_amount, mid_price, price, slippage, swaps)| (pay_amount.clone(), receive_amount, mid_price, price, slippage, swaps)),
+    };
+    let (used_pay_amount, receive_amount, mid_price, price, slippage, swaps) = match update_result {
+        Ok(result) => result,
+        Err(e) => {
+            return_pay_token(
+                request_id,
+                user_id,
+                &caller_id,
+                pay_token,
+                pay_amount,
+                Some(&receive_token),
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+
+    // return any pay token not used by an exact-output swap
+    let mut claim_ids = Vec::new();
+    if let Some(unused_pay_amount) = nat_subtract(pay_amount, &used_pay_amount).filter(|amount| !nat_is_zero(amount)) {
+        return_unused_pay_token(
+            request_id,
+            user_id,
+            &caller_id,
+            pay_token,
+            &unused_pay_amount,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
 
     let reply = send_receive_token(
         request_id,
         user_id,
         pay_token,
-        pay_amount,
+        &used_pay_amount,
         &receive_token,
         &receive_amount,
         &to_address,
         &mut transfer_ids,
+        &mut claim_ids,
         mid_price,
         price,
         slippage,
@@ -2,17 +2,17 @@ use candid::Nat;
 use icrc_ledger_types::icrc1::account::Account;
 
 use super::archive_to_kong_data::archive_to_kong_data;
-use super::calculate_amounts::calculate_amounts;
-use super::return_pay_token::return_pay_token;
+use super::calculate_amounts::{calculate_amounts, calculate_amounts_exact_output};
+use super::return_pay_token::{return_pay_token, return_unused_pay_token};
 use super::send_receive_token::send_receive_token;
 use super::swap_args::SwapArgs;
 use super::swap_reply::SwapReply;
-use super::update_liquidity_pool::update_liquidity_pool;
+use super::update_liquidity_pool::{update_liquidity_pool, update_liquidity_pool_exact_output};
 
-use crate::helpers::nat_helpers::nat_is_zero;
+use crate::helpers::nat_helpers::{nat_is_zero, nat_subtract};
 use crate::ic::address::Address;
 use crate::ic::address_helpers::get_address;
-use crate::ic::get_time::get_time;
+use crate::ic::get_time::{get_time, is_expired};
 use crate::ic::id::caller_id;
 use crate::ic::transfer::icrc2_transfer_from;
 use crate::stable_kong_settings::kong_settings_map;
@@ -25,16 +25,21 @@ pub async fn swap_transfer_from(args: SwapArgs) -> Result<SwapReply, String> {
     let (user_id, pay_token, pay_amount, receive_token, max_slippage, to_address) = check_arguments(&args).await?;
     let ts = get_time();
     let receive_amount = args.receive_amount.clone();
+    let exact_output = args.max_pay_amount.is_some();
+    let deadline_ns = args.deadline_ns;
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::Swap(args), ts));
 
     let result = process_swap(
         request_id,
         user_id,
+        &caller_id(),
         &pay_token,
         &pay_amount,
         &receive_token,
         receive_amount.as_ref(),
+        exact_output,
         max_slippage,
+        deadline_ns,
         &to_address,
         ts,
     )
@@ -61,17 +66,23 @@ pub async fn swap_transfer_from_async(args: SwapArgs) -> Result<u64, String> {
     let (user_id, pay_token, pay_amount, receive_token, max_slippage, to_address) = check_arguments(&args).await?;
     let ts = get_time();
     let receive_amount = args.receive_amount.clone();
+    let exact_output = args.max_pay_amount.is_some();
+    let deadline_ns = args.deadline_ns;
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::Swap(args), ts));
+    let caller_id = caller_id();
 
     ic_cdk::spawn(async move {
         match process_swap(
             request_id,
             user_id,
+            &caller_id,
             &pay_token,
             &pay_amount,
             &receive_token,
             receive_amount.as_ref(),
+            exact_output,
             max_slippage,
+            deadline_ns,
             &to_address,
             ts,
         )
@@ -91,7 +102,6 @@ pub async fn swap_transfer_from_async(args: SwapArgs) -> Result<u64, String> {
 
 async fn check_arguments(args: &SwapArgs) -> Result<(u32, StableToken, Nat, StableToken, f64, Address), String> {
     let pay_token = token_map::get_by_token(&args.pay_token)?;
-    let pay_amount = args.pay_amount.clone();
     let receive_token = token_map::get_by_token(&args.receive_token)?;
     // use specified max slippage or use default
     let max_slippage = args.max_slippage.unwrap_or(kong_settings_map::get().default_max_slippage);
@@ -100,8 +110,10 @@ async fn check_arguments(args: &SwapArgs) -> Result<(u32, StableToken, Nat, Stab
         Some(ref address) => get_address(address).ok_or("Invalid receive address")?,
         None => Address::PrincipalId(caller_id()),
     };
-    if nat_is_zero(&pay_amount) {
-        return Err("Pay amount is zero".to_string());
+
+    // fail early if the deadline has already passed
+    if is_expired(args.deadline_ns) {
+        return Err("Deadline expired".to_string());
     }
 
     // check to make sure pay_tx_id is not specified
@@ -116,67 +128,142 @@ async fn check_arguments(args: &SwapArgs) -> Result<(u32, StableToken, Nat, Stab
     // make sure user is registered, if not create a new user with referred_by if specified
     let user_id = user_map::insert(args.referred_by.as_deref())?;
 
-    // calculate receive_amount and swaps. do after user_id is created as it will be needed to calculate the receive_amount (user fee level)
-    // no needs to store the return values as it'll be called again in process_swap
-    calculate_amounts(&pay_token, &pay_amount, &receive_token, args.receive_amount.as_ref(), max_slippage)?;
+    let pay_amount = match args.max_pay_amount {
+        // exact-output swap. check receive_amount can be received for at most max_pay_amount at the current pool state
+        // max_pay_amount is transferred so the swap still goes through if the price moves before the pool is updated
+        // the pay amount not used is returned to the user
+        Some(ref max_pay_amount) => {
+            let receive_amount = args
+                .receive_amount
+                .as_ref()
+                .ok_or("Receive amount is required for exact-output swaps")?;
+            if nat_is_zero(receive_amount) {
+                return Err("Receive amount is zero".to_string());
+            }
+            calculate_amounts_exact_output(&pay_token, &receive_token, receive_amount, max_pay_amount, max_slippage)?;
+            max_pay_amount.clone()
+        }
+        None => {
+            let pay_amount = args.pay_amount.clone();
+            if nat_is_zero(&pay_amount) {
+                return Err("Pay amount is zero".to_string());
+            }
+            // calculate receive_amount and swaps. do after user_id is created as it will be needed to calculate the receive_amount (user fee level)
+            // no needs to store the return values as it'll be called again in process_swap
+            calculate_amounts(&pay_token, &pay_amount, &receive_token, args.receive_amount.as_ref(), max_slippage)?;
+            pay_amount
+        }
+    };
 
     Ok((user_id, pay_token, pay_amount, receive_token, max_slippage, to_address))
 }
 
 // swaps needs to be passed in to get the pool of the pay token which is needed to determine if the
 // pay_tx_id is a double spend
+// from_principal_id is the account the pay token is transferred from and returned to on failure
 #[allow(clippy::too_many_arguments)]
-async fn process_swap(
+pub async fn process_swap(
     request_id: u64,
     user_id: u32,
+    from_principal_id: &Account,
     pay_token: &StableToken,
     pay_amount: &Nat,
     receive_token: &StableToken,
     receive_amount: Option<&Nat>,
+    exact_output: bool,
     max_slippage: f64,
+    deadline_ns: Option<u64>,
     to_address: &Address,
     ts: u64,
 ) -> Result<SwapReply, String> {
-    let caller_id = caller_id();
     let kong_backend = kong_settings_map::get().kong_backend_account;
     let mut transfer_ids = Vec::new();
 
     request_map::update_status(request_id, StatusCode::Start, None);
 
-    transfer_from_token(request_id, &caller_id, pay_token, pay_amount, &kong_backend, &mut transfer_ids, ts)
-        .await
-        .map_err(|e| format!("Pay token transfer_from failed. {}", e))?;
+    transfer_from_token(
+        request_id,
+        from_principal_id,
+        pay_token,
+        pay_amount,
+        &kong_backend,
+        &mut transfer_ids,
+        ts,
+    )
+    .await
+    .map_err(|e| format!("Pay token transfer_from failed. {}", e))?;
 
     // re-calculate receive_amount and swaps with the latest pool state
-    let (receive_amount, mid_price, price, slippage, swaps) =
-        match update_liquidity_pool(request_id, pay_token, pay_amount, receive_token, receive_amount, max_slippage) {
-            Ok((receive_amount, mid_price, price, slippage, swaps)) => (receive_amount, mid_price, price, slippage, swaps),
-            Err(e) => {
-                // return pay token back to user
-                return_pay_token(
-                    request_id,
-                    user_id,
-                    &caller_id,
-                    pay_token,
-                    pay_amount,
-                    Some(receive_token),
-                    &mut transfer_ids,
-                    ts,
-                )
-                .await;
-                return Err(format!("Req #{} failed. {}", request_id, e));
-            }
-        };
+    // for exact-output swaps, re-calculate the pay amount needed. the pay amount transferred is the most that can be used
+    let update_result = match receive_amount {
+        Some(receive_amount) if exact_output => update_liquidity_pool_exact_output(
+            request_id,
+            pay_token,
+            receive_token,
+            receive_amount,
+            pay_amount,
+            max_slippage,
+            deadline_ns,
+        )
+        .map(|(used_pay_amount, mid_price, price, slippage, swaps)| {
+            (used_pay_amount, receive_amount.clone(), mid_price, price, slippage, swaps)
+        }),
+        _ => update_liquidity_pool(
+            request_id,
+            pay_token,
+            pay_amount,
+            receive_token,
+            receive_amount,
+            max_slippage,
+            deadline_ns,
+        )
+        .map(|(receive_amount, mid_price, price, slippage, swaps)| (pay_amount.clone(), receive_amount, mid_price, price, slippage, swaps)),
+    };
+    let (used_pay_amount, receive_amount, mid_price, price, slippage, swaps) = match update_result {
+        Ok(result) => result,
+        Err(e) => {
+            // return pay token back to user
+            return_pay_token(
+                request_id,
+                user_id,
+                from_principal_id,
+                pay_token,
+                pay_amount,
+                Some(receive_token),
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+
+    // return any pay token not used by an exact-output swap
+    let mut claim_ids = Vec::new();
+    if let Some(unused_pay_amount) = nat_subtract(pay_amount, &used_pay_amount).filter(|amount| !nat_is_zero(amount)) {
+        return_unused_pay_token(
+            request_id,
+            user_id,
+            from_principal_id,
+            pay_token,
+            &unused_pay_amount,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
 
     let reply = send_receive_token(
         request_id,
         user_id,
         pay_token,
-        pay_amount,
+        &used_pay_amount,
         receive_token,
         &receive_amount,
         to_address,
         &mut transfer_ids,
+        &mut claim_ids,
         mid_price,
         price,
         slippage,
@@ -188,7 +275,7 @@ async fn process_swap(
     Ok(reply)
 }
 
-async fn transfer_from_token(
+pub async fn transfer_from_token(
     request_id: u64,
     from_principal_id: &Account,
     token: &StableToken,
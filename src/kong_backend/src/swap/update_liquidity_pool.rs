@@ -1,6 +1,6 @@
 use candid::Nat;
 
-use super::calculate_amounts::calculate_amounts;
+use super::calculate_amounts::{calculate_amounts, calculate_amounts_exact_output, calculate_amounts_single_pool};
 use super::swap_calc::SwapCalc;
 
 use crate::helpers::nat_helpers::nat_divide_as_f64;
@@ -9,7 +9,12 @@ use crate::helpers::{
     nat_helpers::{nat_add, nat_divide, nat_multiply, nat_subtract, nat_zero},
 };
 use crate::ic::ckusdt::ckusdt_amount;
+use crate::ic::get_time::{get_time, is_expired};
+use crate::stable_kong_fee::kong_fee_map;
+use crate::stable_pool::concentrated_liquidity;
 use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::{PoolType, StablePool};
+use crate::stable_pool_observation::pool_observation_map;
 use crate::stable_request::request_map;
 use crate::stable_request::status::StatusCode;
 use crate::stable_token::stable_token::StableToken;
@@ -21,7 +26,10 @@ pub fn update_liquidity_pool(
     receive_token: &StableToken,
     receive_amount: Option<&Nat>,
     max_slippage: f64,
+    deadline_ns: Option<u64>,
 ) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    check_deadline(request_id, deadline_ns)?;
+
     request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
 
     match calculate_amounts(pay_token, pay_amount, receive_token, receive_amount, max_slippage) {
@@ -30,61 +38,80 @@ pub fn update_liquidity_pool(
 
             // update the pool, in some cases there could be multiple pools
             request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
-            for swap in &swaps {
-                // refresh pool with the latest state
-                let mut pool = match pool_map::get_by_pool_id(swap.pool_id) {
-                    Some(pool) => pool,
-                    None => continue, // should not get here
-                };
-
-                if swap.receive_token_id == pool.token_id_1 {
-                    // user pays token_0 and receives token_1
-                    pool.balance_0 = nat_add(&pool.balance_0, &swap.pay_amount); // pay_amount is in token_0
-                    pool.balance_1 = nat_subtract(&pool.balance_1, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_1
-                    if let Ok(ckusdt_volume) = ckusdt_amount(&pool.token_1(), &swap.receive_amount) {
-                        // update 24h stats
-                        pool.rolling_24h_volume = nat_add(&pool.rolling_24h_volume, &ckusdt_volume);
-                    }
-                    // fees are in token_1. take out Kong's fee
-                    // kong_fee_1 = lp_fee * kong_fee_bps / lp_fee_bps
-                    // lp_fee_1 = lp_fee - kong_fee_1
-                    let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_bps)); //swap.lp_fee is in token_1
-                    let kong_fee_1 = nat_divide(&numerator, &Nat::from(pool.lp_fee_bps)).unwrap_or(nat_zero());
-                    let lp_fee_1 = nat_subtract(&swap.lp_fee, &kong_fee_1).unwrap_or(nat_zero());
-                    pool.lp_fee_1 = nat_add(&pool.lp_fee_1, &lp_fee_1);
-                    pool.kong_fee_1 = nat_add(&pool.kong_fee_1, &kong_fee_1);
-                    if let Ok(ckusdt_lp_fee) = ckusdt_amount(&pool.token_1(), &lp_fee_1) {
-                        pool.rolling_24h_lp_fee = nat_add(&pool.rolling_24h_lp_fee, &ckusdt_lp_fee);
-                    }
-                } else {
-                    // user pays token_1 and receives token_0
-                    pool.balance_1 = nat_add(&pool.balance_1, &swap.pay_amount); // pay_amount is in token_1
-                    pool.balance_0 = nat_subtract(&pool.balance_0, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_0
-                    if let Ok(ckusdt_volume) = ckusdt_amount(&pool.token_0(), &swap.receive_amount) {
-                        pool.rolling_24h_volume = nat_add(&pool.rolling_24h_volume, &ckusdt_volume);
-                    }
-                    // fees are in token_0. take out Kong's fee
-                    // kong_fee_0 = lp_fee * kong_fee_bps / lp_fee_bps
-                    // lp_fee_0 = lp_fee - kong_fee_0
-                    let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_bps)); //swap.lp_fee is in token_0
-                    let kong_fee_0 = nat_divide(&numerator, &Nat::from(pool.lp_fee_bps)).unwrap_or(nat_zero());
-                    let lp_fee_0 = nat_subtract(&swap.lp_fee, &kong_fee_0).unwrap_or(nat_zero());
-                    pool.lp_fee_0 = nat_add(&pool.lp_fee_0, &lp_fee_0);
-                    pool.kong_fee_0 = nat_add(&pool.kong_fee_0, &kong_fee_0);
-                    if let Ok(ckusdt_lp_fee) = ckusdt_amount(&pool.token_0(), &lp_fee_0) {
-                        pool.rolling_24h_lp_fee = nat_add(&pool.rolling_24h_lp_fee, &ckusdt_lp_fee);
-                    }
-                }
-                pool.update_tvl();
-                pool.rolling_24h_num_swaps = nat_add(&pool.rolling_24h_num_swaps, &Nat::from(1_u128));
-                // APY = (total_fees / total_liquidity) * 365 * 100
-                pool.rolling_24h_apy = round_f64(
-                    nat_divide_as_f64(&pool.rolling_24h_lp_fee, &pool.tvl).unwrap_or(0_f64) * 365_f64 * 100_f64,
-                    2,
-                );
-                pool_map::update(&pool);
+            if let Err(e) = update_pools(&swaps) {
+                request_map::update_status(request_id, StatusCode::UpdatePoolAmountsFailed, Some(&e));
+                return Err(e);
+            }
+            request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+
+            Ok((receive_amount, mid_price, price, slippage, swaps))
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsFailed, Some(&e));
+            Err(e)
+        }
+    }
+}
+
+/// exact-output version of update_liquidity_pool(). returns the pay amount used
+pub fn update_liquidity_pool_exact_output(
+    request_id: u64,
+    pay_token: &StableToken,
+    receive_token: &StableToken,
+    receive_amount: &Nat,
+    max_pay_amount: &Nat,
+    max_slippage: f64,
+    deadline_ns: Option<u64>,
+) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    check_deadline(request_id, deadline_ns)?;
+
+    request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
+
+    match calculate_amounts_exact_output(pay_token, receive_token, receive_amount, max_pay_amount, max_slippage) {
+        Ok((pay_amount, mid_price, price, slippage, swaps)) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
+
+            request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+            if let Err(e) = update_pools(&swaps) {
+                request_map::update_status(request_id, StatusCode::UpdatePoolAmountsFailed, Some(&e));
+                return Err(e);
             }
+            request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+
+            Ok((pay_amount, mid_price, price, slippage, swaps))
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsFailed, Some(&e));
+            Err(e)
+        }
+    }
+}
+
+/// single pool version of update_liquidity_pool(). the swap goes through pool only and has no gas fee as the receive amount stays in Kong
+#[allow(clippy::too_many_arguments)]
+pub fn update_liquidity_pool_single_pool(
+    request_id: u64,
+    pool: &StablePool,
+    pay_token: &StableToken,
+    pay_amount: &Nat,
+    receive_token: &StableToken,
+    receive_amount: Option<&Nat>,
+    max_slippage: f64,
+    deadline_ns: Option<u64>,
+) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    check_deadline(request_id, deadline_ns)?;
+
+    request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
+
+    match calculate_amounts_single_pool(pool, pay_token, pay_amount, receive_token, receive_amount, max_slippage) {
+        Ok((receive_amount, mid_price, price, slippage, swaps)) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
 
+            request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+            if let Err(e) = update_pools(&swaps) {
+                request_map::update_status(request_id, StatusCode::UpdatePoolAmountsFailed, Some(&e));
+                return Err(e);
+            }
             request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
 
             Ok((receive_amount, mid_price, price, slippage, swaps))
@@ -95,3 +122,95 @@ pub fn update_liquidity_pool(
         }
     }
 }
+
+/// make sure the request has not passed its deadline before the pool is updated
+fn check_deadline(request_id: u64, deadline_ns: Option<u64>) -> Result<(), String> {
+    if is_expired(deadline_ns) {
+        let e = "Deadline expired".to_string();
+        request_map::update_status(request_id, StatusCode::DeadlineExpired, Some(&e));
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// update the balances, fees and stats of each pool in swaps
+/// concentrated liquidity swaps are validated first so a failing swap leaves all pools unchanged
+fn update_pools(swaps: &[SwapCalc]) -> Result<(), String> {
+    for swap in swaps {
+        if let Some(pool) = pool_map::get_by_pool_id(swap.pool_id) {
+            if pool.pool_type == PoolType::ConcentratedLiquidity {
+                let zero_for_one = swap.receive_token_id == pool.token_id_1;
+                concentrated_liquidity::swap(&pool, zero_for_one, &swap.pay_amount, true)?;
+            }
+        }
+    }
+
+    for swap in swaps {
+        // refresh pool with the latest state
+        let mut pool = match pool_map::get_by_pool_id(swap.pool_id) {
+            Some(pool) => pool,
+            None => continue, // should not get here
+        };
+        // accumulate prices before the swap changes the reserves
+        let ts = get_time();
+        pool.update_price_cumulative(ts);
+
+        let (zero_for_one, lp_fee) = if swap.receive_token_id == pool.token_id_1 {
+            // user pays token_0 and receives token_1
+            pool.balance_0 = nat_add(&pool.balance_0, &swap.pay_amount); // pay_amount is in token_0
+            pool.balance_1 = nat_subtract(&pool.balance_1, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_1
+            if let Ok(ckusdt_volume) = ckusdt_amount(&pool.token_1(), &swap.receive_amount) {
+                // update 24h stats
+                pool.rolling_24h_volume = nat_add(&pool.rolling_24h_volume, &ckusdt_volume);
+            }
+            // fees are in token_1. take out Kong's fee
+            // kong_fee_1 = lp_fee * kong_fee_pips / lp_fee_pips
+            // lp_fee_1 = lp_fee - kong_fee_1
+            let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_pips)); //swap.lp_fee is in token_1
+            let kong_fee_1 = nat_divide(&numerator, &Nat::from(pool.lp_fee_pips)).unwrap_or(nat_zero());
+            let lp_fee_1 = nat_subtract(&swap.lp_fee, &kong_fee_1).unwrap_or(nat_zero());
+            pool.lp_fee_1 = nat_add(&pool.lp_fee_1, &lp_fee_1);
+            pool.kong_fee_1 = nat_add(&pool.kong_fee_1, &kong_fee_1);
+            kong_fee_map::add_kong_fee(pool.pool_id, &nat_zero(), &kong_fee_1, ts);
+            if let Ok(ckusdt_lp_fee) = ckusdt_amount(&pool.token_1(), &lp_fee_1) {
+                pool.rolling_24h_lp_fee = nat_add(&pool.rolling_24h_lp_fee, &ckusdt_lp_fee);
+            }
+            (true, lp_fee_1)
+        } else {
+            // user pays token_1 and receives token_0
+            pool.balance_1 = nat_add(&pool.balance_1, &swap.pay_amount); // pay_amount is in token_1
+            pool.balance_0 = nat_subtract(&pool.balance_0, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_0
+            if let Ok(ckusdt_volume) = ckusdt_amount(&pool.token_0(), &swap.receive_amount) {
+                pool.rolling_24h_volume = nat_add(&pool.rolling_24h_volume, &ckusdt_volume);
+            }
+            // fees are in token_0. take out Kong's fee
+            // kong_fee_0 = lp_fee * kong_fee_pips / lp_fee_pips
+            // lp_fee_0 = lp_fee - kong_fee_0
+            let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_pips)); //swap.lp_fee is in token_0
+            let kong_fee_0 = nat_divide(&numerator, &Nat::from(pool.lp_fee_pips)).unwrap_or(nat_zero());
+            let lp_fee_0 = nat_subtract(&swap.lp_fee, &kong_fee_0).unwrap_or(nat_zero());
+            pool.lp_fee_0 = nat_add(&pool.lp_fee_0, &lp_fee_0);
+            pool.kong_fee_0 = nat_add(&pool.kong_fee_0, &kong_fee_0);
+            kong_fee_map::add_kong_fee(pool.pool_id, &kong_fee_0, &nat_zero(), ts);
+            if let Ok(ckusdt_lp_fee) = ckusdt_amount(&pool.token_0(), &lp_fee_0) {
+                pool.rolling_24h_lp_fee = nat_add(&pool.rolling_24h_lp_fee, &ckusdt_lp_fee);
+            }
+            (false, lp_fee_0)
+        };
+        if pool.pool_type == PoolType::ConcentratedLiquidity {
+            // move the price along the liquidity and credit the LP fee to the positions in range
+            concentrated_liquidity::update_swap(&mut pool, zero_for_one, &swap.pay_amount, &lp_fee)?;
+        }
+        pool.update_tvl();
+        pool.rolling_24h_num_swaps = nat_add(&pool.rolling_24h_num_swaps, &Nat::from(1_u128));
+        // APY = (total_fees / total_liquidity) * 365 * 100
+        pool.rolling_24h_apy = round_f64(
+            nat_divide_as_f64(&pool.rolling_24h_lp_fee, &pool.tvl).unwrap_or(0_f64) * 365_f64 * 100_f64,
+            2,
+        );
+        pool_map::update(&pool);
+        pool_observation_map::record(&pool, ts);
+    }
+
+    Ok(())
+}
@@ -8,6 +8,7 @@ use crate::ic::guards::not_in_maintenance_mode;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 use crate::swap;
+use crate::swap::swap_reply_helpers::to_splits;
 
 #[query(guard = "not_in_maintenance_mode")]
 pub fn swap_amounts(pay_token: String, pay_amount: Nat, receive_token: String) -> Result<SwapAmountsReply, String> {
@@ -43,5 +44,41 @@ pub fn swap_amounts(pay_token: String, pay_amount: Nat, receive_token: String) -
         mid_price,
         slippage,
         txs: swap_amounts_tx_reply,
+        splits: to_splits(&txs),
+    })
+}
+
+/// exact-output quote. returns the pay amount needed to receive exactly receive_amount
+#[query(guard = "not_in_maintenance_mode")]
+pub fn swap_amounts_exact_output(pay_token: String, receive_token: String, receive_amount: Nat) -> Result<SwapAmountsReply, String> {
+    // Pay token
+    let pay_token = token_map::get_by_token(&pay_token)?;
+    let pay_chain = pay_token.chain();
+    let pay_symbol = pay_token.symbol();
+    let pay_address = pay_token.address();
+    // Receive token
+    let receive_token = token_map::get_by_token(&receive_token)?;
+    let receive_chain = receive_token.chain();
+    let receive_symbol = receive_token.symbol();
+    let receive_address = receive_token.address();
+
+    let (pay_amount, price, mid_price, slippage, txs) =
+        swap::swap_amounts::swap_amounts_exact_output(&pay_token, &receive_token, &receive_amount)?;
+    let swap_amounts_tx_reply = txs.iter().filter_map(to_swap_amounts_tx_reply).collect();
+
+    Ok(SwapAmountsReply {
+        pay_chain,
+        pay_symbol,
+        pay_amount,
+        pay_address,
+        receive_chain,
+        receive_symbol,
+        receive_address,
+        receive_amount,
+        price,
+        mid_price,
+        slippage,
+        txs: swap_amounts_tx_reply,
+        splits: to_splits(&txs),
     })
 }
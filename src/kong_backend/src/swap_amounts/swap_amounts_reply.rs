@@ -1,6 +1,8 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::swap::swap_reply::SwapSplitReply;
+
 #[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
 pub struct SwapAmountsTxReply {
     pub pool_symbol: String,
@@ -15,6 +17,8 @@ pub struct SwapAmountsTxReply {
     pub price: f64,
     pub lp_fee: Nat,
     pub gas_fee: Nat,
+    pub lp_fee_pips: u32, // effective LP fee in pips before the user's fee level discount
+    pub route: u8,
 }
 
 #[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
@@ -31,4 +35,5 @@ pub struct SwapAmountsReply {
     pub mid_price: f64,
     pub slippage: f64,
     pub txs: Vec<SwapAmountsTxReply>,
+    pub splits: Vec<SwapSplitReply>,
 }
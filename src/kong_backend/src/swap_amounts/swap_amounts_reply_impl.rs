@@ -32,5 +32,7 @@ pub fn to_swap_amounts_tx_reply(swap: &SwapCalc) -> Option<SwapAmountsTxReply> {
         price: price_f64,
         lp_fee: swap.lp_fee.clone(),
         gas_fee: swap.gas_fee.clone(),
+        lp_fee_pips: swap.lp_fee_pips,
+        route: swap.route,
     })
 }
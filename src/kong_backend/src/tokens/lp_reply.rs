@@ -1,6 +1,8 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::lp_locks::lp_lock_reply::LPUnlockReply;
+
 #[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
 pub struct LPReply {
     pub token_id: u32,
@@ -14,4 +16,7 @@ pub struct LPReply {
     pub fee: Nat,
     pub total_supply: Nat,
     pub on_kong: bool,
+    pub lp_locked: Nat,                 // LP tokens locked with lock_lp_token
+    pub lp_locked_pct: f64,             // percentage of total_supply locked
+    pub lp_unlocks: Vec<LPUnlockReply>, // locked LP tokens by unlock date
 }
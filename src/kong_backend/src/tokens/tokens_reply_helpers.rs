@@ -3,6 +3,8 @@ use super::tokens_reply::TokensReply;
 use super::ic_reply::ICReply;
 use super::lp_reply::LPReply;
 
+use crate::ic::get_time::get_time;
+use crate::lp_locks::lp_lock_reply_helpers::to_lp_locked_reply;
 use crate::stable_lp_token::lp_token_map;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::stable_token::StableToken::{IC, LP};
@@ -11,22 +13,29 @@ use crate::stable_token::token::Token;
 pub fn to_token_reply(token: &StableToken) -> TokensReply {
     let token_id = token.token_id();
     match token {
-        LP(lp_token) => TokensReply::LP(LPReply {
-            token_id,
-            chain: token.chain(),
-            name: token.name(),
-            symbol: token.symbol(),
-            token: token.address_with_chain(),
-            address: token.address(),
-            pool_id_of: match lp_token.pool_of() {
-                Some(pool) => pool.pool_id,
-                None => 0,
-            },
-            decimals: token.decimals(),
-            fee: token.fee(),
-            total_supply: lp_token_map::get_total_supply(token_id),
-            on_kong: token.on_kong(),
-        }),
+        LP(lp_token) => {
+            let total_supply = lp_token_map::get_total_supply(token_id);
+            let (lp_locked, lp_locked_pct, lp_unlocks) = to_lp_locked_reply(token_id, &total_supply, get_time());
+            TokensReply::LP(LPReply {
+                token_id,
+                chain: token.chain(),
+                name: token.name(),
+                symbol: token.symbol(),
+                token: token.address_with_chain(),
+                address: token.address(),
+                pool_id_of: match lp_token.pool_of() {
+                    Some(pool) => pool.pool_id,
+                    None => 0,
+                },
+                decimals: token.decimals(),
+                fee: token.fee(),
+                total_supply,
+                on_kong: token.on_kong(),
+                lp_locked,
+                lp_locked_pct,
+                lp_unlocks,
+            })
+        }
         IC(ic_token) => TokensReply::IC(ICReply {
             token_id,
             chain: token.chain(),
@@ -0,0 +1,27 @@
+use ic_cdk::query;
+
+use super::buybacks_reply::BuybacksReply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_token::{token::Token, token_map};
+
+/// totals of the Kong fees bought back into the buyback token and burned. the buybacks are the swap txs of the system user
+#[query(guard = "not_in_maintenance_mode")]
+fn buybacks() -> Result<BuybacksReply, String> {
+    let kong_settings = kong_settings_map::get();
+    let buyback_token = kong_settings.buyback_token.ok_or("Buyback token not set")?;
+    let token = token_map::get_by_token(&buyback_token)?;
+
+    Ok(BuybacksReply {
+        chain: token.chain(),
+        symbol: token.symbol(),
+        address: token.address(),
+        burned: kong_settings.buyback_burned,
+        buybacks: kong_settings.buyback_count,
+        last_buyback_ts: kong_settings.last_buyback_ts,
+        threshold_ckusdt: kong_settings.buyback_threshold_ckusdt,
+        max_slippage: kong_settings.buyback_max_slippage,
+        interval_secs: kong_settings.buyback_interval_secs,
+    })
+}
@@ -0,0 +1,15 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct BuybacksReply {
+    pub chain: String,
+    pub symbol: String,
+    pub address: String,
+    pub burned: Nat,   // total amount of the buyback token bought back and burned
+    pub buybacks: u64, // number of buybacks burned
+    pub last_buyback_ts: u64,
+    pub threshold_ckusdt: f64,
+    pub max_slippage: f64,
+    pub interval_secs: u64,
+}
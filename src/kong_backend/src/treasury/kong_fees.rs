@@ -0,0 +1,19 @@
+use ic_cdk::query;
+
+use super::kong_fees_reply::KongFeesReply;
+use super::kong_fees_reply_helpers::create_kong_fees_reply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_pool::pool_map;
+
+/// Kong fees accrued by pools. the total accrued is the Kong fees not swept yet plus the Kong fees swept to the treasury
+///
+/// # Arguments
+/// pool: Option<String> - pool symbol or address. ie. "ckBTC_ckUSDT". if None, all pools
+#[query(guard = "not_in_maintenance_mode")]
+fn kong_fees(pool: Option<String>) -> Result<Vec<KongFeesReply>, String> {
+    match pool {
+        Some(pool) => Ok(vec![create_kong_fees_reply(&pool_map::get_by_token(&pool)?)]),
+        None => Ok(pool_map::get().iter().map(create_kong_fees_reply).collect()),
+    }
+}
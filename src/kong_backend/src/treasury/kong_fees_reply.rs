@@ -0,0 +1,29 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use super::sweep_kong_fees_reply::SweepKongFeesReply;
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct KongFeesReply {
+    pub pool_id: u32,
+    pub symbol: String,
+    pub chain_0: String,
+    pub symbol_0: String,
+    pub address_0: String,
+    pub kong_fee_0: Nat,       // Kong fees of token_0 in the pool not swept yet
+    pub kong_fee_swept_0: Nat, // total Kong fees of token_0 swept to the treasury
+    pub chain_1: String,
+    pub symbol_1: String,
+    pub address_1: String,
+    pub kong_fee_1: Nat,
+    pub kong_fee_swept_1: Nat,
+    pub history: Vec<KongFeeHistoryReply>, // Kong fees accrued and swept per day, oldest first
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct KongFeeHistoryReply {
+    pub ts: u64,         // start of the day
+    pub kong_fee_0: Nat, // Kong fees of token_0 accrued during the day
+    pub kong_fee_1: Nat, // Kong fees of token_1 accrued during the day
+    pub sweeps: Vec<SweepKongFeesReply>,
+}
@@ -0,0 +1,35 @@
+use crate::stable_kong_fee::kong_fee_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_token::token::Token;
+
+use super::kong_fees_reply::{KongFeeHistoryReply, KongFeesReply};
+use super::sweep_kong_fees_reply_helpers::create_sweep_kong_fees_reply;
+
+pub fn create_kong_fees_reply(pool: &StablePool) -> KongFeesReply {
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    let history = kong_fee_map::get_by_pool_id(pool.pool_id)
+        .iter()
+        .map(|kong_fee| KongFeeHistoryReply {
+            ts: kong_fee.ts,
+            kong_fee_0: kong_fee.kong_fee_0.clone(),
+            kong_fee_1: kong_fee.kong_fee_1.clone(),
+            sweeps: kong_fee.sweeps.iter().map(create_sweep_kong_fees_reply).collect(),
+        })
+        .collect();
+    KongFeesReply {
+        pool_id: pool.pool_id,
+        symbol: pool.symbol(),
+        chain_0: token_0.chain(),
+        symbol_0: token_0.symbol(),
+        address_0: token_0.address(),
+        kong_fee_0: pool.kong_fee_0.clone(),
+        kong_fee_swept_0: pool.kong_fee_swept_0.clone(),
+        chain_1: token_1.chain(),
+        symbol_1: token_1.symbol(),
+        address_1: token_1.address(),
+        kong_fee_1: pool.kong_fee_1.clone(),
+        kong_fee_swept_1: pool.kong_fee_swept_1.clone(),
+        history,
+    }
+}
@@ -0,0 +1,9 @@
+pub mod buybacks;
+pub mod buybacks_reply;
+pub mod kong_fees;
+pub mod kong_fees_reply;
+pub mod kong_fees_reply_helpers;
+pub mod process_buybacks;
+pub mod sweep_kong_fees;
+pub mod sweep_kong_fees_reply;
+pub mod sweep_kong_fees_reply_helpers;
@@ -0,0 +1,259 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::account::Account;
+use std::collections::BTreeSet;
+
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::address::Address;
+use crate::ic::ckusdt::{ckusdt_amount, to_ckusdt_decimals_f64};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::ledger::get_minting_account;
+use crate::ic::logging::error_log;
+use crate::stable_claim::stable_claim::StableClaim;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_request::{request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_user::stable_user::SYSTEM_USER_ID;
+use crate::swap::archive_to_kong_data::archive_to_kong_data;
+use crate::swap::send_receive_token::send_receive_token;
+use crate::swap::swap_args::SwapArgs;
+use crate::swap::swap_calc::SwapCalc;
+use crate::swap::update_liquidity_pool::update_liquidity_pool;
+
+/// buy back the buyback token with the Kong fees of the pools and burn it
+pub async fn process_buybacks() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let kong_settings = kong_settings_map::get();
+    let Some(buyback_token) = kong_settings.buyback_token else {
+        return;
+    };
+    let buyback_token = match token_map::get_by_token(&buyback_token) {
+        Ok(token) => token,
+        Err(e) => {
+            error_log(&format!("Buyback token not found. {}", e));
+            return;
+        }
+    };
+    let Some(ledger) = buyback_token.canister_id() else {
+        error_log(&format!("Buyback token {} is not an IC token", buyback_token.symbol()));
+        return;
+    };
+    let minting_account = match get_minting_account(ledger).await {
+        Ok(Some(minting_account)) => minting_account,
+        Ok(None) => {
+            error_log(&format!("{} has no minting account to burn buybacks", buyback_token.symbol()));
+            return;
+        }
+        Err(e) => {
+            error_log(&format!("Failed to get minting account of {}. {}", buyback_token.symbol(), e));
+            return;
+        }
+    };
+
+    // tokens, other than the buyback token, with Kong fees
+    let token_ids: BTreeSet<u32> = pool_map::get()
+        .iter()
+        .flat_map(|pool| {
+            [
+                (!nat_is_zero(&pool.kong_fee_0)).then_some(pool.token_id_0),
+                (!nat_is_zero(&pool.kong_fee_1)).then_some(pool.token_id_1),
+            ]
+        })
+        .flatten()
+        .filter(|token_id| *token_id != buyback_token.token_id())
+        .collect();
+
+    for token_id in token_ids {
+        let Some(pay_token) = token_map::get_by_token_id(token_id) else {
+            continue;
+        };
+        if let Err(e) = buyback(&pay_token, &buyback_token, &minting_account).await {
+            error_log(&format!(
+                "Failed to buy back {} with {}. {}",
+                buyback_token.symbol(),
+                pay_token.symbol(),
+                e
+            ));
+        }
+    }
+}
+
+/// swap the Kong fees of pay_token for the buyback token through the pools and send it to the minting account
+async fn buyback(pay_token: &StableToken, buyback_token: &StableToken, minting_account: &Account) -> Result<(), String> {
+    let kong_settings = kong_settings_map::get();
+    let ts = get_time();
+
+    // Kong fees of pay_token in each pool. read with the latest state of the pools as the previous buyback awaited
+    let pool_kong_fees: Vec<(u32, Nat)> = pool_map::get()
+        .iter()
+        .filter_map(|pool| {
+            if pool.token_id_0 == pay_token.token_id() {
+                Some((pool.pool_id, pool.kong_fee_0.clone()))
+            } else if pool.token_id_1 == pay_token.token_id() {
+                Some((pool.pool_id, pool.kong_fee_1.clone()))
+            } else {
+                None
+            }
+        })
+        .filter(|(_, kong_fee)| !nat_is_zero(kong_fee))
+        .collect();
+
+    let pay_amount = pool_kong_fees.iter().fold(nat_zero(), |acc, (_, kong_fee)| nat_add(&acc, kong_fee));
+    let pay_amount_ckusdt = ckusdt_amount(pay_token, &pay_amount)
+        .ok()
+        .and_then(|amount| to_ckusdt_decimals_f64(&amount));
+    if pay_amount_ckusdt.is_none_or(|amount| amount < kong_settings.buyback_threshold_ckusdt) {
+        return Ok(());
+    }
+
+    // take the Kong fees out of the pools. the pools are updated by the swap before any await so the Kong fees can not be used twice
+    update_kong_fees(pay_token, &pool_kong_fees, |kong_fee, amount| {
+        nat_subtract(kong_fee, amount).unwrap_or(nat_zero())
+    });
+
+    let to_address = Address::PrincipalId(*minting_account);
+    let args = SwapArgs {
+        pay_token: pay_token.address_with_chain(),
+        pay_amount: pay_amount.clone(),
+        pay_tx_id: None,
+        receive_token: buyback_token.address_with_chain(),
+        receive_amount: None,
+        receive_address: Some(minting_account.to_string()),
+        max_slippage: Some(kong_settings.buyback_max_slippage),
+        referred_by: None,
+        max_pay_amount: None,
+        deadline_ns: None,
+    };
+    let request_id = request_map::insert(&StableRequest::new(SYSTEM_USER_ID, &Request::Swap(args), ts));
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    let (receive_amount, mid_price, price, slippage, swaps) = match update_liquidity_pool(
+        request_id,
+        pay_token,
+        &pay_amount,
+        buyback_token,
+        None,
+        kong_settings.buyback_max_slippage,
+        None,
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            // return the Kong fees to the pools
+            update_kong_fees(pay_token, &pool_kong_fees, nat_add);
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            archive_request(request_id);
+            return Err(e);
+        }
+    };
+
+    // send to the minting account to burn. if the transfer fails, it is saved as a claim of the system user and added to the totals once claimed
+    let burn_amount = burn_amount(&receive_amount, &swaps);
+    let mut transfer_ids = Vec::new();
+    let mut claim_ids = Vec::new();
+    send_receive_token(
+        request_id,
+        SYSTEM_USER_ID,
+        pay_token,
+        &pay_amount,
+        buyback_token,
+        &burn_amount,
+        &to_address,
+        &mut transfer_ids,
+        &mut claim_ids,
+        mid_price,
+        price,
+        slippage,
+        &swaps,
+        ts,
+    )
+    .await;
+    if claim_ids.is_empty() {
+        kong_settings_map::add_buyback_burned(&burn_amount, ts);
+    }
+    request_map::update_status(request_id, StatusCode::Success, None);
+    archive_request(request_id);
+
+    Ok(())
+}
+
+/// amount of the buyback token to burn. burns pay no fee to the ledger, so the gas fee the swap charges for the receive token is burned too
+fn burn_amount(receive_amount: &Nat, swaps: &[SwapCalc]) -> Nat {
+    swaps.iter().fold(receive_amount.clone(), |acc, swap| nat_add(&acc, &swap.gas_fee))
+}
+
+/// claims of the system user are buybacks that failed to be sent to the minting account
+pub fn is_burn_claim(claim: &StableClaim) -> bool {
+    claim.user_id == SYSTEM_USER_ID
+}
+
+/// update the Kong fees of pay_token with the latest state of the pools
+fn update_kong_fees(pay_token: &StableToken, pool_kong_fees: &[(u32, Nat)], f: impl Fn(&Nat, &Nat) -> Nat) {
+    for (pool_id, amount) in pool_kong_fees {
+        let Some(pool) = pool_map::get_by_pool_id(*pool_id) else {
+            continue;
+        };
+        if pool.token_id_0 == pay_token.token_id() {
+            pool_map::update(&StablePool {
+                kong_fee_0: f(&pool.kong_fee_0, amount),
+                ..pool
+            });
+        } else {
+            pool_map::update(&StablePool {
+                kong_fee_1: f(&pool.kong_fee_1, amount),
+                ..pool
+            });
+        }
+    }
+}
+
+fn archive_request(request_id: u64) {
+    if let Some(request) = request_map::get_by_request_and_user_id(Some(request_id), Some(SYSTEM_USER_ID), None).first() {
+        archive_to_kong_data(request);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(pool_id: u32, gas_fee: u64) -> SwapCalc {
+        SwapCalc {
+            pool_id,
+            pay_token_id: 1,
+            pay_amount: Nat::from(1_000_u64),
+            receive_token_id: 2,
+            receive_amount: Nat::from(900_u64),
+            lp_fee: Nat::from(3_u64),
+            gas_fee: Nat::from(gas_fee),
+            lp_fee_pips: 3_000,
+            route: 0,
+        }
+    }
+
+    #[test]
+    fn test_burn_amount() {
+        // the receive token gas fee charged by the swap is burned with the receive amount
+        assert_eq!(burn_amount(&Nat::from(887_u64), &[swap(1, 10)]), Nat::from(897_u64));
+        // intermediate hops take no gas
+        assert_eq!(burn_amount(&Nat::from(887_u64), &[swap(1, 0), swap(2, 10)]), Nat::from(897_u64));
+        assert_eq!(burn_amount(&Nat::from(887_u64), &[]), Nat::from(887_u64));
+    }
+
+    #[test]
+    fn test_is_burn_claim() {
+        assert!(is_burn_claim(&StableClaim::new(
+            SYSTEM_USER_ID,
+            1,
+            &Nat::from(1_u64),
+            Some(1),
+            None,
+            0
+        )));
+        assert!(!is_burn_claim(&StableClaim::new(100, 1, &Nat::from(1_u64), Some(1), None, 0)));
+    }
+}
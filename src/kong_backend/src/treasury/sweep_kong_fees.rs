@@ -0,0 +1,190 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::sweep_kong_fees_reply::SweepKongFeesReply;
+use super::sweep_kong_fees_reply_helpers::create_sweep_kong_fees_reply;
+
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::caller_is_kingkong;
+use crate::ic::logging::error_log;
+use crate::ic::transfer::icrc1_transfer;
+use crate::stable_kong_fee::kong_fee_map;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_token::stable_token::StableToken;
+use crate::stable_token::token::Token;
+use crate::stable_tx::stable_tx::StableTx;
+use crate::stable_tx::sweep_kong_fees_tx::SweepKongFeesTx;
+use crate::stable_tx::tx_map;
+use crate::stable_user::user_map;
+
+enum TokenIndex {
+    Token0,
+    Token1,
+}
+
+/// sweep the Kong fees of pools to the treasury account of the Kong settings
+///
+/// # Arguments
+/// pool: Option<String> - pool symbol or address. ie. "ckBTC_ckUSDT". if None, all pools
+///
+/// # Returns
+/// a SweepKongFeesReply for each pool with Kong fees swept. pools with Kong fees less than the gas fee are skipped
+#[update(hidden = true, guard = "caller_is_kingkong")]
+async fn sweep_kong_fees(pool: Option<String>) -> Result<Vec<SweepKongFeesReply>, String> {
+    let treasury_account = kong_settings_map::get().treasury_account.ok_or("Treasury account not set")?;
+    let user_id = user_map::insert(None)?;
+
+    if let Some(pool) = pool {
+        let pool_id = pool_map::get_by_token(&pool)?.pool_id;
+        return Ok(sweep_pool_kong_fees(user_id, pool_id, &treasury_account)
+            .await?
+            .into_iter()
+            .collect());
+    }
+
+    let pool_ids: Vec<u32> = pool_map::get().iter().map(|pool| pool.pool_id).collect();
+    let mut replies = Vec::new();
+    for pool_id in pool_ids {
+        match sweep_pool_kong_fees(user_id, pool_id, &treasury_account).await {
+            Ok(Some(reply)) => replies.push(reply),
+            Ok(None) => (),
+            Err(e) => error_log(&format!("Failed to sweep Kong fees of pool_id #{}. {}", pool_id, e)),
+        }
+    }
+
+    Ok(replies)
+}
+
+async fn sweep_pool_kong_fees(user_id: u32, pool_id: u32, treasury_account: &Account) -> Result<Option<SweepKongFeesReply>, String> {
+    let ts = get_time();
+
+    // take the Kong fees out of the pool before the transfers so they cannot be swept twice while the transfers are in flight
+    let pool = pool_map::get_by_pool_id(pool_id).ok_or("Pool not found")?;
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    let amount_0 = sweep_amount(&pool.kong_fee_0, &token_0);
+    let amount_1 = sweep_amount(&pool.kong_fee_1, &token_1);
+    if nat_is_zero(&amount_0) && nat_is_zero(&amount_1) {
+        return Ok(None);
+    }
+    pool_map::update(&StablePool {
+        kong_fee_0: nat_subtract(&pool.kong_fee_0, &amount_0).unwrap_or(nat_zero()),
+        kong_fee_1: nat_subtract(&pool.kong_fee_1, &amount_1).unwrap_or(nat_zero()),
+        ..pool
+    });
+
+    let tx_id_0 = transfer_kong_fee(pool_id, TokenIndex::Token0, &token_0, &amount_0, treasury_account).await;
+    let tx_id_1 = transfer_kong_fee(pool_id, TokenIndex::Token1, &token_1, &amount_1, treasury_account).await;
+    if tx_id_0.is_none() && tx_id_1.is_none() {
+        return Err("Failed to transfer Kong fees to treasury".to_string());
+    }
+    let swept_amount_0 = if tx_id_0.is_some() { amount_0 } else { nat_zero() };
+    let swept_amount_1 = if tx_id_1.is_some() { amount_1 } else { nat_zero() };
+
+    let sweep_kong_fees_tx = SweepKongFeesTx::new_success(
+        user_id,
+        pool_id,
+        &swept_amount_0,
+        tx_id_0.as_ref(),
+        &swept_amount_1,
+        tx_id_1.as_ref(),
+        treasury_account,
+        ts,
+    );
+    let tx_id = tx_map::insert(&StableTx::SweepKongFees(sweep_kong_fees_tx.clone()));
+    let sweep_kong_fees_tx = SweepKongFeesTx {
+        tx_id,
+        ..sweep_kong_fees_tx
+    };
+    // TX_MAP is only kept for an hour, so the sweep is also logged with the pool's Kong fee history
+    kong_fee_map::add_sweep(&sweep_kong_fees_tx);
+
+    // add to the pool's totals with the latest state of the pool
+    if let Some(pool) = pool_map::get_by_pool_id(pool_id) {
+        pool_map::update(&StablePool {
+            kong_fee_swept_0: nat_add(&pool.kong_fee_swept_0, &swept_amount_0),
+            kong_fee_swept_1: nat_add(&pool.kong_fee_swept_1, &swept_amount_1),
+            ..pool
+        });
+    }
+
+    Ok(Some(create_sweep_kong_fees_reply(&sweep_kong_fees_tx)))
+}
+
+// Kong fee can only be swept if it is more than the gas fee
+fn sweep_amount(kong_fee: &Nat, token: &StableToken) -> Nat {
+    if *kong_fee > token.fee() {
+        kong_fee.clone()
+    } else {
+        nat_zero()
+    }
+}
+
+/// transfer the Kong fee less gas to the treasury. if the transfer fails, the Kong fee is returned to the pool
+///
+/// # Returns
+/// the block index of the transfer or None if there was nothing to transfer or the transfer failed
+async fn transfer_kong_fee(
+    pool_id: u32,
+    token_index: TokenIndex,
+    token: &StableToken,
+    amount: &Nat,
+    treasury_account: &Account,
+) -> Option<Nat> {
+    if nat_is_zero(amount) {
+        return None;
+    }
+
+    let amount_with_gas = nat_subtract(amount, &token.fee()).unwrap_or(nat_zero());
+    match icrc1_transfer(&amount_with_gas, treasury_account, token, None).await {
+        Ok(block_id) => Some(block_id),
+        Err(e) => {
+            error_log(&format!(
+                "Failed to transfer Kong fee of {} for pool_id #{}. {}",
+                token.symbol(),
+                pool_id,
+                e
+            ));
+            // add back with the latest state of the pool
+            match pool_map::get_by_pool_id(pool_id) {
+                Some(pool) => match token_index {
+                    TokenIndex::Token0 => pool_map::update(&StablePool {
+                        kong_fee_0: nat_add(&pool.kong_fee_0, amount),
+                        ..pool
+                    }),
+                    TokenIndex::Token1 => pool_map::update(&StablePool {
+                        kong_fee_1: nat_add(&pool.kong_fee_1, amount),
+                        ..pool
+                    }),
+                },
+                None => error_log(&format!("Failed to return Kong fee of {} to pool_id #{}", token.symbol(), pool_id)),
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::ic_token;
+    use crate::stable_token::ic_token::ICToken;
+
+    #[test]
+    fn test_sweep_amount() {
+        let token = StableToken::IC(ICToken {
+            decimals: 6,
+            fee: Nat::from(10_000_u32),
+            ..ic_token(1, "ckUSDT")
+        });
+        // only swept if more than the gas fee
+        assert_eq!(sweep_amount(&nat_zero(), &token), nat_zero());
+        assert_eq!(sweep_amount(&Nat::from(10_000_u32), &token), nat_zero());
+        assert_eq!(sweep_amount(&Nat::from(10_001_u32), &token), Nat::from(10_001_u32));
+    }
+}
@@ -0,0 +1,62 @@
+use candid::Nat;
+use ic_cdk::query;
+use num::BigRational;
+
+use super::twap_reply::TwapReply;
+
+use crate::helpers::math_helpers::price_rounded;
+use crate::helpers::nat_helpers::{nat_10pow, nat_subtract, nat_to_bigint};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::PRICE_CUMULATIVE_DECIMALS;
+use crate::stable_pool_observation::pool_observation_map;
+
+/// get the time-weighted average prices of a pool
+///
+/// # Arguments
+/// pool: String - pool symbol or address. ie. "ckBTC_ckUSDT"
+/// window_secs: u64 - averaging window in seconds, ending now
+#[query(guard = "not_in_maintenance_mode")]
+fn twap(pool: String, window_secs: u64) -> Result<TwapReply, String> {
+    if window_secs == 0 {
+        return Err("Window must be greater than zero".to_string());
+    }
+    let pool = pool_map::get_by_token(&pool)?;
+    let ts = get_time();
+    let start_ts = ts.saturating_sub(window_secs.saturating_mul(1_000_000_000));
+
+    // latest observation at or before the start of the window
+    let observations = pool_observation_map::get_by_pool_id(pool.pool_id).ok_or("No price observations for pool")?;
+    let observation = observations
+        .get_at_or_before(start_ts)
+        .ok_or("Not enough price observations for window")?;
+    let elapsed = ts - observation.ts;
+    if elapsed == 0 {
+        return Err("Not enough price observations for window".to_string());
+    }
+
+    let (price_0_cumulative, price_1_cumulative) = pool.get_price_cumulative(ts);
+    let price_0 = average_price(&price_0_cumulative, &observation.price_0_cumulative, elapsed).ok_or("Unable to calculate price_0")?;
+    let price_1 = average_price(&price_1_cumulative, &observation.price_1_cumulative, elapsed).ok_or("Unable to calculate price_1")?;
+
+    Ok(TwapReply {
+        symbol: pool.symbol(),
+        chain_0: pool.chain_0(),
+        symbol_0: pool.symbol_0(),
+        chain_1: pool.chain_1(),
+        symbol_1: pool.symbol_1(),
+        price_0,
+        price_1,
+        window_secs: elapsed / 1_000_000_000,
+        start_ts: observation.ts,
+        end_ts: ts,
+    })
+}
+
+/// (end cumulative - start cumulative) / elapsed nanoseconds, removing the PRICE_CUMULATIVE_DECIMALS scaling
+fn average_price(end_cumulative: &Nat, start_cumulative: &Nat, elapsed: u64) -> Option<f64> {
+    let numerator = nat_to_bigint(&nat_subtract(end_cumulative, start_cumulative)?);
+    let denominator = nat_to_bigint(&(nat_10pow(PRICE_CUMULATIVE_DECIMALS) * elapsed));
+    price_rounded(&BigRational::new(numerator, denominator))
+}
@@ -2,8 +2,9 @@ use crate::add_liquidity::add_liquidity_reply_helpers::create_add_liquidity_repl
 use crate::add_pool::add_pool_reply_helpers::create_add_pool_reply;
 use crate::remove_liquidity::remove_liquidity_reply_helpers::create_remove_liquidity_reply;
 use crate::send::send_reply_helpers::create_send_reply;
-use crate::stable_tx::stable_tx::StableTx::{self, AddLiquidity, AddPool, RemoveLiquidity, Send, Swap};
+use crate::stable_tx::stable_tx::StableTx::{self, AddLiquidity, AddPool, RemoveLiquidity, Send, Swap, SweepKongFees};
 use crate::swap::swap_reply_helpers::create_swap_reply;
+use crate::treasury::sweep_kong_fees_reply_helpers::create_sweep_kong_fees_reply;
 
 use super::txs_reply::TxsReply;
 
@@ -14,5 +15,6 @@ pub fn to_txs_reply(tx: &StableTx) -> TxsReply {
         RemoveLiquidity(tx) => TxsReply::RemoveLiquidity(create_remove_liquidity_reply(tx)),
         Swap(tx) => TxsReply::Swap(create_swap_reply(tx)),
         Send(tx) => TxsReply::Send(create_send_reply(tx)),
+        SweepKongFees(tx) => TxsReply::SweepKongFees(create_sweep_kong_fees_reply(tx)),
     }
 }
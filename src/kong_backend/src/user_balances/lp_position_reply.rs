@@ -0,0 +1,19 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
+pub struct LPPositionReply {
+    pub symbol: String,
+    pub position_id: u64,
+    pub price_lower: f64,
+    pub price_upper: f64,
+    pub in_range: bool,
+    pub usd_balance: f64,
+    pub symbol_0: String,
+    pub amount_0: f64, // includes uncollected fees
+    pub usd_amount_0: f64,
+    pub symbol_1: String,
+    pub amount_1: f64, // includes uncollected fees
+    pub usd_amount_1: f64,
+    pub ts: u64,
+}
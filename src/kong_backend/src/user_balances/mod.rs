@@ -1,3 +1,4 @@
+pub mod lp_position_reply;
 pub mod lp_reply;
 pub mod usd_balance;
 #[allow(clippy::module_inception)]
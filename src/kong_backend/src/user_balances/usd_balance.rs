@@ -1,5 +1,5 @@
 use super::user_balances_reply::UserBalancesReply;
-use super::user_balances_reply::UserBalancesReply::LP;
+use super::user_balances_reply::UserBalancesReply::{Position, LP};
 
 pub trait USDBalance {
     fn usd_balance(&self) -> f64;
@@ -9,6 +9,7 @@ impl USDBalance for UserBalancesReply {
     fn usd_balance(&self) -> f64 {
         match self {
             LP(token) => token.usd_balance,
+            Position(position) => position.usd_balance,
         }
     }
 }
@@ -1,12 +1,17 @@
 use ic_cdk::query;
 
+use super::lp_position_reply::LPPositionReply;
 use super::lp_reply::LPReply;
 use super::user_balances_reply::UserBalancesReply;
 
+use crate::helpers::math_helpers::price_rounded;
 use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_multiply, nat_to_decimals_f64, nat_zero};
 use crate::ic::ckusdt::{ckusdt_amount, to_ckusdt_decimals_f64};
 use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode_and_caller_is_not_anonymous};
+use crate::stable_lp_position::{lp_position_map, stable_lp_position::StableLPPosition};
 use crate::stable_lp_token::lp_token_map;
+use crate::stable_pool::concentrated_liquidity::{position_amounts, position_fees};
+use crate::stable_pool::stable_pool::{PoolType, StablePool};
 use crate::stable_token::lp_token::LPToken;
 use crate::stable_token::stable_token::StableToken::{IC, LP};
 use crate::stable_token::token::Token;
@@ -33,6 +38,13 @@ pub async fn user_balances(symbol: Option<String>) -> Result<Vec<UserBalancesRep
             if let Some(reply) = user_balance_lp_token_reply(lp_token, user_id, ts) {
                 user_balances.push(reply);
             }
+            // concentrated liquidity pools have positions instead of LP tokens
+            if let Some(pool) = lp_token.pool_of().filter(|pool| pool.pool_type == PoolType::ConcentratedLiquidity) {
+                lp_position_map::get_by_user_id(user_id, Some(pool.pool_id))
+                    .iter()
+                    .filter_map(|position| user_balance_lp_position_reply(&pool, position, ts))
+                    .for_each(|reply| user_balances.push(reply));
+            }
         }
         IC(_) => (),
     });
@@ -91,3 +103,46 @@ fn user_balance_lp_token_reply(token: &LPToken, user_id: u32, ts: u64) -> Option
         ts,
     }))
 }
+
+fn user_balance_lp_position_reply(pool: &StablePool, position: &StableLPPosition, ts: u64) -> Option<UserBalancesReply> {
+    let (raw_amount_0, raw_amount_1) = position_amounts(pool, position);
+    let (raw_fee_0, raw_fee_1) = position_fees(pool, position);
+
+    let token_0 = pool.token_0();
+    let symbol_0 = token_0.symbol();
+    let raw_amount_0 = nat_add(&raw_amount_0, &raw_fee_0);
+    let amount_0 = nat_to_decimals_f64(token_0.decimals(), &raw_amount_0)?;
+    let usd_amount_0 = ckusdt_amount(&token_0, &raw_amount_0)
+        .and_then(|amount_0| to_ckusdt_decimals_f64(&amount_0).ok_or("Error converting amount 0 to ckUSDT".to_string()))
+        .unwrap_or(0_f64);
+
+    let token_1 = pool.token_1();
+    let symbol_1 = token_1.symbol();
+    let raw_amount_1 = nat_add(&raw_amount_1, &raw_fee_1);
+    let amount_1 = nat_to_decimals_f64(token_1.decimals(), &raw_amount_1)?;
+    let usd_amount_1 = ckusdt_amount(&token_1, &raw_amount_1)
+        .and_then(|amount_1| to_ckusdt_decimals_f64(&amount_1).ok_or("Error converting amount 1 to ckUSDT".to_string()))
+        .unwrap_or(0_f64);
+
+    let usd_balance = usd_amount_0 + usd_amount_1;
+
+    Some(UserBalancesReply::Position(LPPositionReply {
+        symbol: pool.symbol(),
+        position_id: position.position_id,
+        price_lower: pool
+            .get_price_at_tick(position.tick_lower)
+            .and_then(|price| price_rounded(&price))?,
+        price_upper: pool
+            .get_price_at_tick(position.tick_upper)
+            .and_then(|price| price_rounded(&price))?,
+        in_range: position.is_in_range(pool.tick),
+        usd_balance,
+        symbol_0,
+        amount_0,
+        usd_amount_0,
+        symbol_1,
+        amount_1,
+        usd_amount_1,
+        ts,
+    }))
+}
@@ -1,9 +1,11 @@
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
 
+use super::lp_position_reply::LPPositionReply;
 use super::lp_reply::LPReply;
 
 #[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
 pub enum UserBalancesReply {
-    LP(LPReply), // only return LP token balances for now
+    LP(LPReply),
+    Position(LPPositionReply), // concentrated liquidity position
 }
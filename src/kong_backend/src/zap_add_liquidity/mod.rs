@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+pub mod zap_add_liquidity;
+pub mod zap_add_liquidity_args;
+pub mod zap_add_liquidity_reply;
+pub mod zap_add_liquidity_reply_helpers;
+pub mod zap_amounts;
@@ -0,0 +1,419 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::zap_add_liquidity_args::ZapAddLiquidityArgs;
+use super::zap_add_liquidity_reply::ZapAddLiquidityReply;
+use super::zap_add_liquidity_reply_helpers::create_zap_add_liquidity_reply;
+use super::zap_amounts::zap_swap_amount;
+
+use crate::add_liquidity::add_liquidity::TokenIndex;
+use crate::add_liquidity::add_liquidity_reply_helpers::{create_add_liquidity_reply_failed, create_add_liquidity_reply_with_tx_id};
+use crate::add_liquidity::add_liquidity_transfer_from::{return_token, transfer_from_token, update_liquidity_pool};
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::get_time::{get_time, is_expired};
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller_id;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::{PoolType, StablePool};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_tx::{add_liquidity_tx::AddLiquidityTx, stable_tx::StableTx, swap_tx::SwapTx, tx_map};
+use crate::stable_user::user_map;
+use crate::swap::swap_reply::SwapReply;
+use crate::swap::swap_reply_helpers::create_swap_reply_with_tx_id;
+use crate::swap::update_liquidity_pool::update_liquidity_pool_single_pool;
+
+/// Add liquidity to a pool with only token_0 or token_1
+///
+/// - before calling zap_add_liquidity, the user must icrc2_approve pay_amount + gas of pay_token
+///
+/// Arguments: ZapAddLiquidityArgs
+///  token_0, token_1, fee_tier: pool to add liquidity to
+///  pay_token: token_0 or token_1
+///  pay_amount: amount of pay_token to add
+///
+/// Returns: ZapAddLiquidityReply
+///  swap: swap of part of pay_amount for the other token of the pool
+///  add_liquidity: liquidity added with the rest of pay_amount and the swap's receive amount
+///
+/// Steps:
+/// 1. transfer_from_token() - transfer pay_amount of pay_token
+/// 2. zap_swap_amount() - calculate the amount to swap so the rest of pay_amount and the receive amount match the pool ratio after the swap
+/// 3. update_liquidity_pool_single_pool() - swap through the pool itself, without a gas fee as the receive amount stays in the pool
+/// 4. update_liquidity_pool() - add the rest of pay_amount and the receive amount to the pool and mint LP tokens for the user
+/// 5. return_token() - return any dust not used by the pool ratio. dust not enough to pay for the gas stays in the pool
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn zap_add_liquidity(args: ZapAddLiquidityArgs) -> Result<ZapAddLiquidityReply, String> {
+    let (user_id, pool, pay_token_index, pay_token, max_slippage) = check_arguments(&args)?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::ZapAddLiquidity(args.clone()), ts));
+
+    let result = process_zap_add_liquidity(
+        request_id,
+        user_id,
+        &pool,
+        &pay_token_index,
+        &pay_token,
+        &args.pay_amount,
+        max_slippage,
+        args.min_lp_token_amount.as_ref(),
+        args.deadline_ns,
+        ts,
+    )
+    .await
+    .map_or_else(
+        |e| {
+            request_map::update_status(request_id, StatusCode::Failed, Some(&e));
+            Err(e)
+        },
+        |reply| {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        },
+    );
+
+    request_map::get_by_request_and_user_id(Some(request_id), Some(user_id), None)
+        .first()
+        .map(archive_to_kong_data);
+
+    result
+}
+
+fn check_arguments(args: &ZapAddLiquidityArgs) -> Result<(u32, StablePool, TokenIndex, StableToken, f64), String> {
+    if nat_is_zero(&args.pay_amount) {
+        return Err("Invalid zero amount".to_string());
+    }
+
+    // fail early if the deadline has already passed
+    if is_expired(args.deadline_ns) {
+        return Err("Deadline expired".to_string());
+    }
+
+    let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1, args.fee_tier)?;
+    if pool.pool_type == PoolType::ConcentratedLiquidity {
+        return Err(format!("Use add_position for concentrated liquidity pool {}", pool.symbol()));
+    }
+
+    let pay_token = token_map::get_by_token(&args.pay_token)?;
+    let pay_token_index = if pay_token.token_id() == pool.token_id_0 {
+        TokenIndex::Token0
+    } else if pay_token.token_id() == pool.token_id_1 {
+        TokenIndex::Token1
+    } else {
+        return Err(format!("Pay token must be {} or {}", pool.symbol_0(), pool.symbol_1()));
+    };
+
+    if !pay_token.is_icrc2() {
+        return Err("Pay token must support ICRC2".to_string());
+    }
+
+    // use specified max slippage or use default
+    let max_slippage = args.max_slippage.unwrap_or(kong_settings_map::get().default_max_slippage);
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    // make sure the pay amount is enough to zap with the current state
+    let user_fee_level = user_map::get_by_caller().ok().flatten().unwrap_or_default().fee_level;
+    if nat_is_zero(&zap_swap_amount(&pool, pay_token.token_id(), &args.pay_amount, user_fee_level)?) {
+        return Err("Pay amount too small to zap".to_string());
+    }
+
+    Ok((user_id, pool, pay_token_index, pay_token, max_slippage))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_zap_add_liquidity(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    pay_token_index: &TokenIndex,
+    pay_token: &StableToken,
+    pay_amount: &Nat,
+    max_slippage: f64,
+    min_lp_token_amount: Option<&Nat>,
+    deadline_ns: Option<u64>,
+    ts: u64,
+) -> Result<ZapAddLiquidityReply, String> {
+    let caller_id = caller_id();
+    let kong_backend = kong_settings_map::get().kong_backend_account;
+    let mut transfer_ids = Vec::new();
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // transfer_from pay_token. if this fails, nothing to return so just return the error
+    transfer_from_token(
+        request_id,
+        &caller_id,
+        pay_token_index,
+        pay_token,
+        pay_amount,
+        &kong_backend,
+        &mut transfer_ids,
+        ts,
+    )
+    .await
+    .map_err(|e| format!("Pay token transfer_from failed. {}", e))?;
+
+    // swap with the latest pool state
+    let receive_token = match pay_token_index {
+        TokenIndex::Token0 => pool.token_1(),
+        TokenIndex::Token1 => pool.token_0(),
+    };
+    let user_fee_level = user_map::get_by_caller().ok().flatten().unwrap_or_default().fee_level;
+    let swap_result = pool_map::get_by_pool_id(pool.pool_id)
+        .ok_or("Pool not found".to_string())
+        .and_then(|pool| {
+            let swap_amount = zap_swap_amount(&pool, pay_token.token_id(), pay_amount, user_fee_level)?;
+            if nat_is_zero(&swap_amount) {
+                return Err("Pay amount too small to zap".to_string());
+            }
+            update_liquidity_pool_single_pool(
+                request_id,
+                &pool,
+                pay_token,
+                &swap_amount,
+                &receive_token,
+                None,
+                max_slippage,
+                deadline_ns,
+            )
+            .map(|swap| (swap_amount, swap))
+        });
+    let (swap_amount, (receive_amount, mid_price, price, slippage, swaps)) = match swap_result {
+        Ok(result) => result,
+        Err(e) => {
+            // return pay token back to user
+            let (amount_0, amount_1) = match pay_token_index {
+                TokenIndex::Token0 => (Some(pay_amount), None),
+                TokenIndex::Token1 => (None, Some(pay_amount)),
+            };
+            return_tokens(
+                request_id,
+                user_id,
+                &caller_id,
+                pool,
+                pay_token,
+                pay_amount,
+                None,
+                amount_0,
+                amount_1,
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+
+    let swap_tx = SwapTx::new_success(
+        user_id,
+        request_id,
+        pay_token.token_id(),
+        &swap_amount,
+        receive_token.token_id(),
+        &receive_amount,
+        mid_price,
+        price,
+        slippage,
+        &swaps,
+        &[],
+        &[],
+        ts,
+    );
+    let swap_tx_id = tx_map::insert(&StableTx::Swap(swap_tx.clone()));
+    let swap_reply = create_swap_reply_with_tx_id(swap_tx_id, &swap_tx);
+
+    // add liquidity with the rest of pay_amount and the receive amount
+    let remaining_amount = nat_subtract(pay_amount, &swap_amount).unwrap_or(nat_zero());
+    let (add_amount_0, add_amount_1) = match pay_token_index {
+        TokenIndex::Token0 => (remaining_amount, receive_amount),
+        TokenIndex::Token1 => (receive_amount, remaining_amount),
+    };
+    let add_liquidity_result = pool_map::get_by_pool_id(pool.pool_id)
+        .ok_or("Pool not found".to_string())
+        .and_then(|pool| {
+            update_liquidity_pool(
+                request_id,
+                user_id,
+                &pool,
+                &add_amount_0,
+                &add_amount_1,
+                min_lp_token_amount,
+                deadline_ns,
+                ts,
+            )
+        });
+    let (pool, amount_0, amount_1, add_lp_token_amount) = match add_liquidity_result {
+        Ok(result) => result,
+        Err(e) => {
+            // the swap stays done. return both tokens back to user
+            return_tokens(
+                request_id,
+                user_id,
+                &caller_id,
+                pool,
+                pay_token,
+                pay_amount,
+                Some(swap_reply),
+                Some(&add_amount_0),
+                Some(&add_amount_1),
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+
+    // dust not used by the pool ratio is returned. if the dust is not enough to pay for the gas, it stays in the pool
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    let dust_0 = nat_subtract(&add_amount_0, &amount_0).unwrap_or(nat_zero());
+    let dust_1 = nat_subtract(&add_amount_1, &amount_1).unwrap_or(nat_zero());
+    let return_amount_0 = (dust_0 > token_0.fee()).then(|| dust_0.clone());
+    let return_amount_1 = (dust_1 > token_1.fee()).then(|| dust_1.clone());
+    if return_amount_0.is_none() && !nat_is_zero(&dust_0) || return_amount_1.is_none() && !nat_is_zero(&dust_1) {
+        if let Some(pool) = pool_map::get_by_pool_id(pool.pool_id) {
+            let mut pool = pool;
+            if return_amount_0.is_none() {
+                pool.balance_0 = nat_add(&pool.balance_0, &dust_0);
+            }
+            if return_amount_1.is_none() {
+                pool.balance_1 = nat_add(&pool.balance_1, &dust_1);
+            }
+            pool.update_tvl();
+            pool_map::update(&pool);
+        }
+    }
+
+    let mut claim_ids = Vec::new();
+    if let Some(return_amount_0) = return_amount_0 {
+        return_token(
+            request_id,
+            user_id,
+            &caller_id,
+            &TokenIndex::Token0,
+            &token_0,
+            &return_amount_0,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+    if let Some(return_amount_1) = return_amount_1 {
+        return_token(
+            request_id,
+            user_id,
+            &caller_id,
+            &TokenIndex::Token1,
+            &token_1,
+            &return_amount_1,
+            &mut transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+
+    // succcesful, add tx and update request with reply
+    let add_liquidity_tx = AddLiquidityTx::new_success(
+        pool.pool_id,
+        user_id,
+        request_id,
+        &amount_0,
+        &amount_1,
+        &add_lp_token_amount,
+        &transfer_ids,
+        &claim_ids,
+        ts,
+    );
+    let tx_id = tx_map::insert(&StableTx::AddLiquidity(add_liquidity_tx.clone()));
+
+    let reply = create_zap_add_liquidity_reply(
+        request_id,
+        pay_token,
+        pay_amount,
+        Some(swap_reply),
+        create_add_liquidity_reply_with_tx_id(tx_id, &add_liquidity_tx),
+        ts,
+    );
+    request_map::update_reply(request_id, Reply::ZapAddLiquidity(Box::new(reply.clone())));
+
+    Ok(reply)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn return_tokens(
+    request_id: u64,
+    user_id: u32,
+    to_principal_id: &Account,
+    pool: &StablePool,
+    pay_token: &StableToken,
+    pay_amount: &Nat,
+    swap_reply: Option<SwapReply>,
+    amount_0: Option<&Nat>,
+    amount_1: Option<&Nat>,
+    transfer_ids: &mut Vec<u64>,
+    ts: u64,
+) {
+    let mut claim_ids = Vec::new();
+
+    if let Some(amount_0) = amount_0.filter(|amount_0| !nat_is_zero(amount_0)) {
+        return_token(
+            request_id,
+            user_id,
+            to_principal_id,
+            &TokenIndex::Token0,
+            &pool.token_0(),
+            amount_0,
+            transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+
+    if let Some(amount_1) = amount_1.filter(|amount_1| !nat_is_zero(amount_1)) {
+        return_token(
+            request_id,
+            user_id,
+            to_principal_id,
+            &TokenIndex::Token1,
+            &pool.token_1(),
+            amount_1,
+            transfer_ids,
+            &mut claim_ids,
+            ts,
+        )
+        .await;
+    }
+
+    let add_liquidity_reply = create_add_liquidity_reply_failed(pool.pool_id, request_id, transfer_ids, &claim_ids, ts);
+    let reply = create_zap_add_liquidity_reply(request_id, pay_token, pay_amount, swap_reply, add_liquidity_reply, ts);
+    request_map::update_reply(request_id, Reply::ZapAddLiquidity(Box::new(reply)));
+}
+
+pub fn archive_to_kong_data(request: &StableRequest) {
+    match &request.reply {
+        Reply::ZapAddLiquidity(reply) => {
+            let tx_ids: Vec<u64> = reply
+                .swap
+                .as_ref()
+                .map(|swap| swap.tx_id)
+                .into_iter()
+                .chain((reply.add_liquidity.tx_id != 0).then_some(reply.add_liquidity.tx_id))
+                .collect();
+            request_map::archive_request_and_reply_to_kong_data(
+                request.request_id,
+                &reply.add_liquidity.claim_ids,
+                &reply.add_liquidity.transfer_ids,
+                &tx_ids,
+            );
+        }
+        _ => request_map::archive_request_to_kong_data(request.request_id),
+    }
+}
@@ -0,0 +1,16 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `zap_add_liquidity` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ZapAddLiquidityArgs {
+    pub token_0: String,
+    pub token_1: String,
+    pub fee_tier: Option<u32>, // if specified, the pool of the token pair with this LP fee in pips. otherwise the first pool of the token pair
+    pub pay_token: String,     // token_0 or token_1. part of pay_amount is swapped for the other token of the pool
+    pub pay_amount: Nat,
+    pub max_slippage: Option<f64>, // max slippage of the swap. if None, default_max_slippage of the Kong settings
+    pub deadline_ns: Option<u64>,  // if specified, zap fails if the pool is not updated by this time
+    pub min_lp_token_amount: Option<Nat>, // if specified, zap fails if the LP tokens received are less than this
+}
@@ -0,0 +1,19 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
+use crate::swap::swap_reply::SwapReply;
+
+/// Data structure for the reply of the `zap_add_liquidity` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ZapAddLiquidityReply {
+    pub request_id: u64,
+    pub status: String,
+    pub pay_chain: String,
+    pub pay_symbol: String,
+    pub pay_amount: Nat,
+    pub swap: Option<SwapReply>,          // swap of part of pay_amount for the other token of the pool
+    pub add_liquidity: AddLiquidityReply, // transfers and claims of the zap, including any dust returned
+    pub ts: u64,
+}
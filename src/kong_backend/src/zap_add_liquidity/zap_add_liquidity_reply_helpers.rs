@@ -0,0 +1,27 @@
+use candid::Nat;
+
+use super::zap_add_liquidity_reply::ZapAddLiquidityReply;
+
+use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
+use crate::stable_token::{stable_token::StableToken, token::Token};
+use crate::swap::swap_reply::SwapReply;
+
+pub fn create_zap_add_liquidity_reply(
+    request_id: u64,
+    pay_token: &StableToken,
+    pay_amount: &Nat,
+    swap: Option<SwapReply>,
+    add_liquidity: AddLiquidityReply,
+    ts: u64,
+) -> ZapAddLiquidityReply {
+    ZapAddLiquidityReply {
+        request_id,
+        status: add_liquidity.status.clone(),
+        pay_chain: pay_token.chain(),
+        pay_symbol: pay_token.symbol(),
+        pay_amount: pay_amount.clone(),
+        swap,
+        add_liquidity,
+        ts,
+    }
+}
@@ -0,0 +1,132 @@
+use candid::Nat;
+
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
+use crate::stable_pool::stable_pool::StablePool;
+use crate::swap::swap_amounts::swap_amount_single_pool;
+
+/// amount of pay_amount to swap through the pool so the rest of pay_amount and the receive amount are in the ratio of the pool after the swap
+/// found with a binary search as the swap depends on the pool type, the fees and the user's fee level
+pub fn zap_swap_amount(pool: &StablePool, pay_token_id: u32, pay_amount: &Nat, user_fee_level: u8) -> Result<Nat, String> {
+    let reserve_0 = nat_add(&pool.balance_0, &pool.lp_fee_0);
+    let reserve_1 = nat_add(&pool.balance_1, &pool.lp_fee_1);
+    let (reserve_in, reserve_out) = if pay_token_id == pool.token_id_0 {
+        (reserve_0, reserve_1)
+    } else {
+        (reserve_1, reserve_0)
+    };
+    if nat_is_zero(&reserve_in) || nat_is_zero(&reserve_out) {
+        return Err(format!("Pool {} has no liquidity to zap into", pool.symbol()));
+    }
+
+    let one = Nat::from(1_u8);
+    let two = Nat::from(2_u8);
+    let mut low = nat_zero();
+    let mut high = pay_amount.clone();
+    while nat_add(&low, &one) < high {
+        let mid = nat_divide(&nat_add(&low, &high), &two).ok_or("Invalid swap amount")?;
+        match is_below_pool_ratio(pool, pay_token_id, pay_amount, &mid, user_fee_level, &reserve_in, &reserve_out) {
+            Ok(true) => low = mid,
+            // swapping mid is too much or the swap failed
+            _ => high = mid,
+        }
+    }
+
+    Ok(low)
+}
+
+/// true if, after swapping swap_amount, the rest of pay_amount is more than the pool ratio needs for the receive amount
+fn is_below_pool_ratio(
+    pool: &StablePool,
+    pay_token_id: u32,
+    pay_amount: &Nat,
+    swap_amount: &Nat,
+    user_fee_level: u8,
+    reserve_in: &Nat,
+    reserve_out: &Nat,
+) -> Result<bool, String> {
+    let swap = swap_amount_single_pool(pool, pay_token_id, swap_amount, user_fee_level)?;
+    let receive_amount = swap.receive_amount_with_fees_and_gas();
+
+    // reserves after the swap. the LP fee, less Kong's fee, stays in the pool
+    // kong_fee = lp_fee * kong_fee_pips / lp_fee_pips
+    let kong_fee = nat_divide(
+        &nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_pips)),
+        &Nat::from(pool.lp_fee_pips),
+    )
+    .unwrap_or(nat_zero());
+    let reserve_in = nat_add(reserve_in, swap_amount);
+    let reserve_out =
+        nat_subtract(&nat_add(reserve_out, &swap.lp_fee), &nat_add(&swap.receive_amount, &kong_fee)).ok_or("Invalid pool reserves")?;
+
+    // (pay_amount - swap_amount) / reserve_in > receive_amount / reserve_out
+    let remaining_amount = nat_subtract(pay_amount, swap_amount).ok_or("Invalid swap amount")?;
+    Ok(nat_multiply(&remaining_amount, &reserve_out) > nat_multiply(&receive_amount, &reserve_in))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helpers::test_fixtures::{insert_token, new_pool};
+
+    fn pool(balance_0: u64, balance_1: u64, kong_fee_pips: u32) -> StablePool {
+        insert_token(1, "TOKEN_0");
+        insert_token(2, "TOKEN_1");
+        StablePool {
+            kong_fee_pips,
+            ..new_pool(0, 1, 2, balance_0, balance_1)
+        }
+    }
+
+    // the swap amount is the largest amount that keeps the rest of pay_amount at or above the pool ratio after the swap
+    fn assert_pool_ratio(pool: &StablePool, pay_token_id: u32, pay_amount: &Nat, swap_amount: &Nat) {
+        let (reserve_in, reserve_out) = if pay_token_id == pool.token_id_0 {
+            (pool.balance_0.clone(), pool.balance_1.clone())
+        } else {
+            (pool.balance_1.clone(), pool.balance_0.clone())
+        };
+        let next_amount = nat_add(swap_amount, &Nat::from(1_u8));
+        assert!(is_below_pool_ratio(pool, pay_token_id, pay_amount, swap_amount, 0, &reserve_in, &reserve_out).unwrap());
+        assert!(!is_below_pool_ratio(pool, pay_token_id, pay_amount, &next_amount, 0, &reserve_in, &reserve_out).unwrap());
+    }
+
+    #[test]
+    fn test_zap_swap_amount() {
+        let pool = pool(1_000_000_000, 4_000_000_000, 0);
+        let pay_amount = Nat::from(10_000_000_u64);
+        let swap_amount = zap_swap_amount(&pool, 1, &pay_amount, 0).unwrap();
+        assert_pool_ratio(&pool, 1, &pay_amount, &swap_amount);
+        // a little less than half is swapped as the swap moves the price against the rest and pays the LP fee
+        assert!(swap_amount > 4_900_000_u64 && swap_amount < 5_000_000_u64);
+    }
+
+    #[test]
+    fn test_zap_swap_amount_token_1() {
+        let pool = pool(1_000_000_000, 4_000_000_000, 0);
+        let pay_amount = Nat::from(40_000_000_u64);
+        let swap_amount = zap_swap_amount(&pool, 2, &pay_amount, 0).unwrap();
+        assert_pool_ratio(&pool, 2, &pay_amount, &swap_amount);
+        assert!(swap_amount > 19_600_000_u64 && swap_amount < 20_000_000_u64);
+    }
+
+    #[test]
+    fn test_zap_swap_amount_with_kong_fee() {
+        // Kong's share of the LP fee leaves the pool and lowers its reserve of the receive token, so a little less is swapped
+        let pay_amount = Nat::from(100_000_000_u64);
+        let without_kong_fee = zap_swap_amount(&pool(1_000_000_000, 1_000_000_000, 0), 1, &pay_amount, 0).unwrap();
+        let kong_fee_pool = pool(1_000_000_000, 1_000_000_000, 1_000);
+        let with_kong_fee = zap_swap_amount(&kong_fee_pool, 1, &pay_amount, 0).unwrap();
+        assert_pool_ratio(&kong_fee_pool, 1, &pay_amount, &with_kong_fee);
+        assert!(with_kong_fee < without_kong_fee);
+    }
+
+    #[test]
+    fn test_zap_swap_amount_empty_pool() {
+        assert!(zap_swap_amount(&pool(0, 0, 0), 1, &Nat::from(1_000_u64), 0).is_err());
+        // too small to swap
+        assert_eq!(
+            zap_swap_amount(&pool(1_000_000_000, 1_000_000_000, 0), 1, &Nat::from(1_u64), 0).unwrap(),
+            nat_zero()
+        );
+    }
+}
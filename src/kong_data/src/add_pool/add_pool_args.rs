@@ -13,7 +13,9 @@ pub struct AddPoolArgs {
     pub token_1: String,
     pub amount_1: Nat,
     pub tx_id_1: Option<TxId>,
-    pub lp_fee_bps: Option<u8>,
+    pub lp_fee_pips: Option<u32>, // LP fee in pips (1/100 of a basis point)
+    pub kong_fee_pips: Option<u32>,
+    pub lp_fee_bps: Option<u8>, // deprecated. LP fee in basis points if lp_fee_pips is not specified
     pub kong_fee_bps: Option<u8>,
     pub on_kong: Option<bool>,
 }
@@ -1,6 +1,7 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::stable_pool::stable_pool::PIPS_PER_BPS;
 use crate::transfers::transfer_reply::TransferIdReply;
 
 /// Data structure for the reply of the `add_pool` function.
@@ -24,7 +25,10 @@ pub struct AddPoolReply {
     pub amount_1: Nat,
     pub balance_1: Nat,
     pub add_lp_token_amount: Nat,
-    pub lp_fee_bps: u8,
+    #[serde(default)]
+    pub lp_fee_pips: u32,
+    #[serde(default)]
+    pub lp_fee_bps: u8, // deprecated. lp_fee_pips rounded down to basis points
     pub lp_token_symbol: String,
     pub transfer_ids: Vec<TransferIdReply>,
     pub claim_ids: Vec<u64>,
@@ -35,3 +39,16 @@ pub struct AddPoolReply {
 fn empty_string() -> String {
     String::new()
 }
+
+impl AddPoolReply {
+    /// replies stored before fees were in pips only have lp_fee_bps
+    pub fn migrate_fee_bps(self) -> Self {
+        if self.lp_fee_pips != 0 || self.lp_fee_bps == 0 {
+            return self;
+        }
+        Self {
+            lp_fee_pips: self.lp_fee_bps as u32 * PIPS_PER_BPS,
+            ..self
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::helpers::nat_helpers::nat_zero;
 use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::pips_to_bps;
 use crate::stable_token::token::Token;
 use crate::stable_tx::add_pool_tx::AddPoolTx;
 use crate::transfers::transfer_reply_helpers::to_transfer_ids;
@@ -14,7 +15,7 @@ pub fn create_add_pool_reply(add_pool_tx: &AddPoolTx) -> AddPoolReply {
 }
 
 pub fn create_add_pool_reply_with_tx_id(tx_id: u64, add_pool_tx: &AddPoolTx) -> AddPoolReply {
-    let (symbol, chain_0, address_0, symbol_0, balance_0, chain_1, address_1, symbol_1, balance_1, lp_fee_bps, lp_token_symbol) =
+    let (symbol, chain_0, address_0, symbol_0, balance_0, chain_1, address_1, symbol_1, balance_1, lp_fee_pips, lp_token_symbol) =
         pool_map::get_by_pool_id(add_pool_tx.pool_id).map_or_else(
             || {
                 (
@@ -52,7 +53,7 @@ pub fn create_add_pool_reply_with_tx_id(tx_id: u64, add_pool_tx: &AddPoolTx) ->
                     address_1,
                     symbol_1,
                     balance_1,
-                    pool.lp_fee_bps,
+                    pool.lp_fee_pips,
                     pool.lp_token().symbol(),
                 )
             },
@@ -72,7 +73,8 @@ pub fn create_add_pool_reply_with_tx_id(tx_id: u64, add_pool_tx: &AddPoolTx) ->
         symbol_1,
         amount_1: add_pool_tx.amount_1.clone(),
         balance_1,
-        lp_fee_bps,
+        lp_fee_pips,
+        lp_fee_bps: pips_to_bps(lp_fee_pips),
         add_lp_token_amount: add_pool_tx.add_lp_token_amount.clone(),
         lp_token_symbol,
         transfer_ids: to_transfer_ids(&add_pool_tx.transfer_ids),
@@ -0,0 +1,22 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::add_liquidity::add_liquidity_args::AddLiquidityArgs;
+use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
+use crate::swap::swap_args::SwapArgs;
+
+/// Data structure for the arguments of the `batch` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct BatchArgs {
+    pub operations: Vec<BatchOperation>, // run in sequence. outputs of earlier operations are used by later ones
+    pub deadline_ns: Option<u64>,        // if specified, batch fails if the operations are not started by this time
+}
+
+/// An operation of a batch, with the arguments of the Request variant
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub enum BatchOperation {
+    AddLiquidity(AddLiquidityArgs),
+    RemoveLiquidity(RemoveLiquidityArgs),
+    Swap(SwapArgs),
+}
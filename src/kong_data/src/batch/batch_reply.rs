@@ -0,0 +1,26 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
+use crate::remove_liquidity::remove_liquidity_reply::RemoveLiquidityReply;
+use crate::swap::swap_reply::SwapReply;
+use crate::transfers::transfer_reply::TransferIdReply;
+
+/// Data structure for the reply of the `batch` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReply {
+    pub request_id: u64,
+    pub status: String,
+    pub operations: Vec<BatchOperationReply>, // replies of the operations that succeeded, in order
+    pub transfer_ids: Vec<TransferIdReply>,   // tokens transferred from the user and the tokens paid out
+    pub claim_ids: Vec<u64>,                  // payouts that failed, or the refunds if an operation failed
+    pub ts: u64,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub enum BatchOperationReply {
+    AddLiquidity(AddLiquidityReply),
+    RemoveLiquidity(RemoveLiquidityReply),
+    Swap(SwapReply),
+}
@@ -0,0 +1,2 @@
+pub mod batch_args;
+pub mod batch_reply;
@@ -3,10 +3,10 @@ use std::collections::BTreeMap;
 
 use crate::ic::get_time::get_time;
 use crate::ic::guards::{caller_is_kingkong, caller_is_kong_backend};
+use crate::stable_db_update::db_update_map;
+use crate::stable_db_update::stable_db_update::{StableDBUpdate, StableMemory};
 use crate::stable_memory::POOL_MAP;
 use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
-use crate::stable_db_update::stable_db_update::{StableMemory, StableDBUpdate};
-use crate::stable_db_update::db_update_map;
 
 const MAX_POOLS: usize = 1_000;
 
@@ -40,7 +40,7 @@ fn update_pools(tokens: String) -> Result<String, String> {
     POOL_MAP.with(|user_map| {
         let mut map = user_map.borrow_mut();
         for (k, v) in pools {
-            map.insert(k, v);
+            map.insert(k, v.migrate_fee_bps());
         }
     });
 
@@ -49,8 +49,8 @@ fn update_pools(tokens: String) -> Result<String, String> {
 
 #[update(hidden = true, guard = "caller_is_kong_backend")]
 fn update_pool(stable_pool_json: String) -> Result<String, String> {
-    let pool: StablePool = match serde_json::from_str(&stable_pool_json) {
-        Ok(pool) => pool,
+    let pool: StablePool = match serde_json::from_str::<StablePool>(&stable_pool_json) {
+        Ok(pool) => pool.migrate_fee_bps(),
         Err(e) => return Err(format!("Invalid pool: {}", e)),
     };
 
@@ -0,0 +1,2 @@
+pub mod create_farm_args;
+pub mod farm_reply;
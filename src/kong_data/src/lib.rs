@@ -1,12 +1,16 @@
 mod add_liquidity;
 mod add_pool;
+mod batch;
 mod canister;
 mod chains;
 mod claims;
 mod controllers;
+mod farms;
 mod helpers;
 mod ic;
+mod orders;
 mod pools;
+mod positions;
 mod remove_liquidity;
 mod requests;
 mod send;
@@ -25,8 +29,10 @@ mod stable_user;
 mod swap;
 mod tokens;
 mod transfers;
+mod treasury;
 mod txs;
 mod user;
+mod zap_add_liquidity;
 
 pub const APP_NAME: &str = "Kong Data";
 pub const APP_VERSION: &str = "v0.0.13";
@@ -0,0 +1,14 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `limit_order` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LimitOrderArgs {
+    pub pay_token: String,
+    pub pay_amount: Nat,
+    pub receive_token: String,
+    pub limit_price: f64, // minimum price (receive token per pay token) the order will be filled at
+    pub receive_address: Option<String>,
+    pub expires_at: u64, // nanoseconds since the Unix epoch. escrow is returned if the order is not filled by this time
+}
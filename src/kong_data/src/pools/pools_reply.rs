@@ -26,7 +26,8 @@ pub struct PoolReply {
     pub balance_1: Nat,
     pub lp_fee_1: Nat,
     pub price: f64,
-    pub lp_fee_bps: u8,
+    pub lp_fee_pips: u32,
+    pub lp_fee_bps: u8, // deprecated. lp_fee_pips rounded down to basis points
     pub on_kong: bool,
     pub tvl: Nat,
     pub rolling_24h_volume: Nat,
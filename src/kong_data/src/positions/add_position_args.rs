@@ -0,0 +1,15 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `add_position` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct AddPositionArgs {
+    pub token_0: String,
+    pub amount_0: Nat, // maximum amount of token_0. any amount not used by the position is returned
+    pub token_1: String,
+    pub amount_1: Nat, // maximum amount of token_1. any amount not used by the position is returned
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub fee_tier: Option<u32>, // if specified, the pool of the token pair with this LP fee in pips. otherwise the first pool of the token pair
+}
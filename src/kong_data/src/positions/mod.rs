@@ -0,0 +1,4 @@
+pub mod add_position_args;
+pub mod add_position_reply;
+pub mod remove_position_args;
+pub mod remove_position_reply;
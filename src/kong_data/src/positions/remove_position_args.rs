@@ -0,0 +1,10 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `remove_position` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct RemovePositionArgs {
+    pub position_id: u64,
+    pub liquidity: Option<Nat>, // liquidity to remove. defaults to all. 0 only collects the fees
+}
@@ -0,0 +1,26 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use crate::transfers::transfer_reply::TransferIdReply;
+
+/// Data structure for the reply of the `remove_position` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct RemovePositionReply {
+    pub position_id: u64,
+    pub symbol: String,
+    pub request_id: u64,
+    pub status: String,
+    pub chain_0: String,
+    pub symbol_0: String,
+    pub amount_0: Nat,
+    pub fee_0: Nat,
+    pub chain_1: String,
+    pub symbol_1: String,
+    pub amount_1: Nat,
+    pub fee_1: Nat,
+    pub remove_liquidity: Nat,
+    pub transfer_ids: Vec<TransferIdReply>,
+    pub claim_ids: Vec<u64>,
+    pub ts: u64,
+}
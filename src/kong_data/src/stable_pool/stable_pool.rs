@@ -6,6 +6,13 @@ use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 
+pub const PIPS_PER_BPS: u32 = 100;
+
+/// fee in pips rounded down to basis points for the deprecated lp_fee_bps of replies
+pub fn pips_to_bps(pips: u32) -> u8 {
+    (pips / PIPS_PER_BPS).min(u8::MAX as u32) as u8
+}
+
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StablePoolId(pub u32);
 
@@ -31,11 +38,17 @@ pub struct StablePool {
     pub token_id_1: u32,
     pub balance_1: Nat,
     pub lp_fee_1: Nat,
-    pub kong_fee_1: Nat,  // Kong's share of the LP fee
-    pub lp_fee_bps: u8,   // LP's fee in basis points
-    pub kong_fee_bps: u8, // Kong's fee in basis points
+    pub kong_fee_1: Nat, // Kong's share of the LP fee
+    #[serde(default)]
+    pub lp_fee_pips: u32, // LP's fee in pips (1/100 of a basis point). 1_000_000 pips = 100%
+    #[serde(default)]
+    pub kong_fee_pips: u32, // Kong's fee in pips
+    #[serde(default, skip_serializing)]
+    pub lp_fee_bps: u8, // deprecated. LP's fee of pools stored before fees were in pips, migrated to lp_fee_pips
+    #[serde(default, skip_serializing)]
+    pub kong_fee_bps: u8, // deprecated. Kong's fee of pools stored before fees were in pips, migrated to kong_fee_pips
     pub lp_token_id: u32, // token id of the LP token
-    pub on_kong: bool,    // whether the pool is on Kong
+    pub on_kong: bool,   // whether the pool is on Kong
     pub tvl: Nat,
     pub rolling_24h_volume: Nat,
     pub rolling_24h_lp_fee: Nat,
@@ -59,6 +72,20 @@ impl StablePool {
     pub fn lp_token(&self) -> StableToken {
         token_map::get_by_token_id(self.lp_token_id).unwrap()
     }
+
+    /// pools stored before fees were in pips have their fees in lp_fee_bps and kong_fee_bps
+    pub fn migrate_fee_bps(self) -> Self {
+        if self.lp_fee_bps == 0 && self.kong_fee_bps == 0 {
+            return self;
+        }
+        Self {
+            lp_fee_pips: self.lp_fee_bps as u32 * PIPS_PER_BPS,
+            kong_fee_pips: self.kong_fee_bps as u32 * PIPS_PER_BPS,
+            lp_fee_bps: 0,
+            kong_fee_bps: 0,
+            ..self
+        }
+    }
 }
 
 impl Storable for StablePool {
@@ -67,7 +94,7 @@ impl Storable for StablePool {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
+        serde_cbor::from_slice::<StablePool>(&bytes).unwrap().migrate_fee_bps()
     }
 
     const BOUND: Bound = Bound::Unbounded;
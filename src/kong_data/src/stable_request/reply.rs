@@ -3,10 +3,16 @@ use serde::{Deserialize, Serialize};
 
 use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
 use crate::add_pool::add_pool_reply::AddPoolReply;
+use crate::batch::batch_reply::BatchReply;
 use crate::claims::claim_reply::ClaimReply;
+use crate::farms::farm_reply::FarmReply;
+use crate::orders::order_reply::OrderReply;
+use crate::positions::add_position_reply::AddPositionReply;
+use crate::positions::remove_position_reply::RemovePositionReply;
 use crate::remove_liquidity::remove_liquidity_reply::RemoveLiquidityReply;
 use crate::send::send_reply::SendReply;
 use crate::swap::swap_reply::SwapReply;
+use crate::zap_add_liquidity::zap_add_liquidity_reply::ZapAddLiquidityReply;
 
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub enum Reply {
@@ -17,4 +23,10 @@ pub enum Reply {
     Swap(SwapReply),
     Claim(ClaimReply),
     Send(SendReply),
+    Order(OrderReply),
+    AddPosition(AddPositionReply),
+    RemovePosition(RemovePositionReply),
+    ZapAddLiquidity(Box<ZapAddLiquidityReply>), // boxed as it holds both a swap and an add liquidity reply
+    Batch(BatchReply),
+    Farm(FarmReply),
 }
@@ -31,13 +31,26 @@ pub struct StableRequest {
     pub ts: u64,
 }
 
+impl StableRequest {
+    /// add pool replies stored before fees were in pips only have lp_fee_bps
+    pub fn migrate_fee_bps(self) -> Self {
+        match self.reply {
+            Reply::AddPool(reply) => Self {
+                reply: Reply::AddPool(reply.migrate_fee_bps()),
+                ..self
+            },
+            _ => self,
+        }
+    }
+}
+
 impl Storable for StableRequest {
     fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
         serde_cbor::to_vec(self).unwrap().into()
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
+        serde_cbor::from_slice::<StableRequest>(&bytes).unwrap().migrate_fee_bps()
     }
 
     const BOUND: Bound = Bound::Unbounded;
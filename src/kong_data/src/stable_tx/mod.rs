@@ -5,6 +5,7 @@ pub mod send_tx;
 #[allow(clippy::module_inception)]
 pub mod stable_tx;
 pub mod status_tx;
+pub mod sweep_kong_fees_tx;
 pub mod swap_tx;
 pub mod tx;
 pub mod tx_map;
@@ -7,6 +7,7 @@ use super::add_pool_tx::AddPoolTx;
 use super::remove_liquidity_tx::RemoveLiquidityTx;
 use super::send_tx::SendTx;
 use super::swap_tx::SwapTx;
+use super::sweep_kong_fees_tx::SweepKongFeesTx;
 
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StableTxId(pub u64);
@@ -30,6 +31,7 @@ pub enum StableTx {
     RemoveLiquidity(RemoveLiquidityTx),
     Swap(SwapTx),
     Send(SendTx),
+    SweepKongFees(SweepKongFeesTx),
 }
 
 impl Storable for StableTx {
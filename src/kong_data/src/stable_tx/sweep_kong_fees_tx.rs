@@ -0,0 +1,22 @@
+use candid::{CandidType, Nat};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+use super::status_tx::StatusTx;
+
+/// Kong fees of a pool swept to the treasury account
+/// amount_0 and amount_1 are the Kong fees taken from the pool. the treasury receives them less the gas fees
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct SweepKongFeesTx {
+    pub tx_id: u64,
+    pub user_id: u32,
+    pub status: StatusTx,
+    pub pool_id: u32,
+    pub amount_0: Nat,
+    pub tx_id_0: Option<Nat>, // block index of the transfer of token_0 to the treasury
+    pub amount_1: Nat,
+    pub tx_id_1: Option<Nat>, // block index of the transfer of token_1 to the treasury
+    pub treasury_account: Account,
+    pub ts: u64,
+}
+
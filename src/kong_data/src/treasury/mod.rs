@@ -0,0 +1,2 @@
+pub mod sweep_kong_fees_reply;
+pub mod sweep_kong_fees_reply_helpers;
@@ -0,0 +1,17 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct SweepKongFeesReply {
+    pub tx_id: u64,
+    pub status: String,
+    pub symbol: String,
+    pub chain_0: String,
+    pub symbol_0: String,
+    pub amount_0: Nat, // Kong fees of token_0 taken from the pool. the treasury receives amount_0 less gas
+    pub chain_1: String,
+    pub symbol_1: String,
+    pub amount_1: Nat, // Kong fees of token_1 taken from the pool. the treasury receives amount_1 less gas
+    pub treasury_account: String,
+    pub ts: u64,
+}
@@ -0,0 +1,37 @@
+use crate::stable_pool::pool_map;
+use crate::stable_token::token::Token;
+use crate::stable_tx::sweep_kong_fees_tx::SweepKongFeesTx;
+
+use super::sweep_kong_fees_reply::SweepKongFeesReply;
+
+pub fn create_sweep_kong_fees_reply(sweep_kong_fees_tx: &SweepKongFeesTx) -> SweepKongFeesReply {
+    let (symbol, chain_0, symbol_0, chain_1, symbol_1) = pool_map::get_by_pool_id(sweep_kong_fees_tx.pool_id).map_or_else(
+        || {
+            (
+                "Pool symbol not found".to_string(),
+                "Pool chain_0 not found".to_string(),
+                "Pool symbol_0 not found".to_string(),
+                "Pool chain_1 not found".to_string(),
+                "Pool symbol_1 not found".to_string(),
+            )
+        },
+        |pool| {
+            let token_0 = pool.token_0();
+            let token_1 = pool.token_1();
+            (pool.symbol(), token_0.chain(), token_0.symbol(), token_1.chain(), token_1.symbol())
+        },
+    );
+    SweepKongFeesReply {
+        tx_id: sweep_kong_fees_tx.tx_id,
+        status: sweep_kong_fees_tx.status.to_string(),
+        symbol,
+        chain_0,
+        symbol_0,
+        amount_0: sweep_kong_fees_tx.amount_0.clone(),
+        chain_1,
+        symbol_1,
+        amount_1: sweep_kong_fees_tx.amount_1.clone(),
+        treasury_account: sweep_kong_fees_tx.treasury_account.to_string(),
+        ts: sweep_kong_fees_tx.ts,
+    }
+}
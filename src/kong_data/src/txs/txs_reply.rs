@@ -6,6 +6,7 @@ use crate::add_pool::add_pool_reply::AddPoolReply;
 use crate::remove_liquidity::remove_liquidity_reply::RemoveLiquidityReply;
 use crate::send::send_reply::SendReply;
 use crate::swap::swap_reply::SwapReply;
+use crate::treasury::sweep_kong_fees_reply::SweepKongFeesReply;
 
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub enum TxsReply {
@@ -14,4 +15,5 @@ pub enum TxsReply {
     RemoveLiquidity(RemoveLiquidityReply),
     Swap(SwapReply),
     Send(SendReply),
+    SweepKongFees(SweepKongFeesReply),
 }
@@ -0,0 +1,2 @@
+pub mod zap_add_liquidity_args;
+pub mod zap_add_liquidity_reply;
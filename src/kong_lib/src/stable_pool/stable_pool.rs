@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::helpers::nat_helpers::nat_zero;
 
+pub const PIPS_PER_BPS: u32 = 100;
+
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StablePoolId(pub u32);
 
@@ -29,11 +31,17 @@ pub struct StablePool {
     pub token_id_1: u32,
     pub balance_1: Nat,
     pub lp_fee_1: Nat,
-    pub kong_fee_1: Nat,  // Kong's share of the LP fee
-    pub lp_fee_bps: u8,   // LP's fee in basis points
-    pub kong_fee_bps: u8, // Kong's fee in basis points
+    pub kong_fee_1: Nat, // Kong's share of the LP fee
+    #[serde(default)]
+    pub lp_fee_pips: u32, // LP's fee in pips (1/100 of a basis point). 1_000_000 pips = 100%
+    #[serde(default)]
+    pub kong_fee_pips: u32, // Kong's fee in pips
+    #[serde(default, skip_serializing)]
+    pub lp_fee_bps: u8, // deprecated. LP's fee of pools stored before fees were in pips, migrated to lp_fee_pips
+    #[serde(default, skip_serializing)]
+    pub kong_fee_bps: u8, // deprecated. Kong's fee of pools stored before fees were in pips, migrated to kong_fee_pips
     pub lp_token_id: u32, // token id of the LP token
-    pub on_kong: bool,    // whether the pool is on Kong
+    pub on_kong: bool,   // whether the pool is on Kong
     pub tvl: Nat,
     pub rolling_24h_volume: Nat,
     pub rolling_24h_lp_fee: Nat,
@@ -42,7 +50,7 @@ pub struct StablePool {
 }
 
 impl StablePool {
-    pub fn new(token_id_0: u32, token_id_1: u32, lp_fee_bps: u8, kong_fee_bps: u8, lp_token_id: u32, on_kong: bool) -> Self {
+    pub fn new(token_id_0: u32, token_id_1: u32, lp_fee_pips: u32, kong_fee_pips: u32, lp_token_id: u32, on_kong: bool) -> Self {
         Self {
             pool_id: 0,
             token_id_0,
@@ -53,8 +61,10 @@ impl StablePool {
             balance_1: nat_zero(),
             lp_fee_1: nat_zero(),
             kong_fee_1: nat_zero(),
-            lp_fee_bps,
-            kong_fee_bps,
+            lp_fee_pips,
+            kong_fee_pips,
+            lp_fee_bps: 0,
+            kong_fee_bps: 0,
             lp_token_id,
             on_kong,
             tvl: nat_zero(),
@@ -64,6 +74,20 @@ impl StablePool {
             rolling_24h_apy: 0_f64,
         }
     }
+
+    /// pools stored before fees were in pips have their fees in lp_fee_bps and kong_fee_bps
+    pub fn migrate_fee_bps(self) -> Self {
+        if self.lp_fee_bps == 0 && self.kong_fee_bps == 0 {
+            return self;
+        }
+        Self {
+            lp_fee_pips: self.lp_fee_bps as u32 * PIPS_PER_BPS,
+            kong_fee_pips: self.kong_fee_bps as u32 * PIPS_PER_BPS,
+            lp_fee_bps: 0,
+            kong_fee_bps: 0,
+            ..self
+        }
+    }
 }
 
 impl Storable for StablePool {
@@ -72,7 +96,7 @@ impl Storable for StablePool {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
+        serde_cbor::from_slice::<StablePool>(&bytes).unwrap().migrate_fee_bps()
     }
 
     const BOUND: Bound = Bound::Unbounded;
@@ -27,7 +27,8 @@ pub struct PoolReply {
     pub balance_1: Nat,
     pub lp_fee_1: Nat,
     pub price: f64,
-    pub lp_fee_bps: u8,
+    pub lp_fee_pips: u32,
+    pub lp_fee_bps: u8, // deprecated. lp_fee_pips rounded down to basis points
     pub rolling_24h_volume: Nat,
     pub rolling_24h_lp_fee: Nat,
     pub rolling_24h_num_swaps: Nat,